@@ -0,0 +1,62 @@
+use criterion::criterion_main;
+
+// compares one full MtA exchange (message A, message B, alpha) of the Paillier and OT backends
+mod bench {
+    use criterion::{criterion_group, Criterion};
+    use curv::elliptic::curves::p256::FE;
+    use curv::elliptic::curves::traits::*;
+    use multi_party_ecdsa::protocols::threshold_signer::Session;
+    use multi_party_ecdsa::utilities::mta::ot::{OtMta, OtMtaSetup};
+    use multi_party_ecdsa::utilities::mta::{MtaBackend, PaillierMta};
+    use multi_party_ecdsa::utilities::paillier_context::PaillierContext;
+    use paillier::{KeyGeneration, Paillier};
+
+    fn exchange<M: MtaBackend>(alice: &M, bob: &M, a: &FE, b: &FE) -> FE {
+        let m_a = alice.message_a(a);
+        let (m_b, beta) = bob.message_b(0, b, &m_a).expect("bad message a");
+        alice.alpha(1, &m_b, a).expect("bad message b") + beta
+    }
+
+    fn ot_setup() -> (OtMta, OtMta) {
+        let mut alice = OtMtaSetup::new(0, &[0, 1]).unwrap();
+        let mut bob = OtMtaSetup::new(1, &[0, 1]).unwrap();
+        let mut to_bob = alice.proceed(Vec::new()).unwrap();
+        let mut to_alice = bob.proceed(Vec::new()).unwrap();
+        while !alice.is_finished() {
+            let from_bob = bob.proceed(to_bob).unwrap();
+            to_bob = alice.proceed(to_alice).unwrap();
+            to_alice = from_bob;
+        }
+        (alice.pick_output().unwrap(), bob.pick_output().unwrap())
+    }
+
+    pub fn bench_paillier_mta(c: &mut Criterion) {
+        let (ek, dk) = Paillier::keypair().keys();
+        let alice = PaillierMta::new(PaillierContext::with_decryption_key(&dk), &[]);
+        let bob = PaillierMta::new(PaillierContext::new(&ek), &[ek.clone()]);
+        let a: FE = ECScalar::new_random();
+        let b: FE = ECScalar::new_random();
+        c.bench_function("paillier mta", |bench| {
+            bench.iter(|| exchange(&alice, &bob, &a, &b))
+        });
+    }
+
+    pub fn bench_ot_mta(c: &mut Criterion) {
+        c.bench_function("ot mta setup", |bench| bench.iter(ot_setup));
+        let (alice, bob) = ot_setup();
+        let a: FE = ECScalar::new_random();
+        let b: FE = ECScalar::new_random();
+        c.bench_function("ot mta", |bench| {
+            bench.iter(|| exchange(&alice, &bob, &a, &b))
+        });
+    }
+
+    criterion_group! {
+    name = mta;
+    config = Criterion::default().sample_size(10);
+    targets =
+    self::bench_paillier_mta,
+    self::bench_ot_mta}
+}
+
+criterion_main!(bench::mta);
@@ -0,0 +1,157 @@
+use criterion::criterion_main;
+
+// compare `cargo bench --bench gg18_verify` with `cargo bench --bench gg18_verify --features rayon`
+mod bench {
+    use criterion::{criterion_group, BenchmarkId, Criterion};
+    use curv::cryptographic_primitives::proofs::sigma_dlog::DLogProof;
+    use curv::elliptic::curves::p256::{FE, GE};
+    use curv::elliptic::curves::traits::*;
+    use curv::BigInt;
+    use multi_party_ecdsa::protocols::multi_party_ecdsa::gg_2018::party_i::*;
+    use multi_party_ecdsa::utilities::mta::{MessageA, MessageB};
+    use multi_party_ecdsa::utilities::paillier_context::PaillierContext;
+    use multi_party_ecdsa::utilities::parallel;
+    use paillier::{KeyGeneration, Paillier};
+
+    const PARTIES: [u16; 3] = [5, 10, 20];
+
+    pub fn bench_verify_correct_keys(c: &mut Criterion) {
+        let mut group = c.benchmark_group("keygen phase1 verify");
+        for n in PARTIES.iter() {
+            let params = Parameters {
+                threshold: n / 2,
+                share_count: *n,
+            };
+            let party_keys_vec = (0..*n as usize).map(Keys::create).collect::<Vec<Keys>>();
+            let (bc1_vec, decom_vec): (Vec<_>, Vec<_>) = party_keys_vec
+                .iter()
+                .map(|key| key.phase1_broadcast_phase3_proof_of_correct_key())
+                .unzip();
+            group.bench_with_input(BenchmarkId::from_parameter(n), n, |b, _| {
+                b.iter(|| {
+                    party_keys_vec[0]
+                        .phase1_verify_com_phase3_verify_correct_key_phase2_distribute(
+                            &params, &decom_vec, &bc1_vec,
+                        )
+                        .expect("invalid key")
+                })
+            });
+        }
+        group.finish();
+    }
+
+    pub fn bench_verify_dlog_proofs(c: &mut Criterion) {
+        let mut group = c.benchmark_group("keygen dlog proofs verify");
+        for n in PARTIES.iter() {
+            let params = Parameters {
+                threshold: n / 2,
+                share_count: *n,
+            };
+            let dlog_proof_vec = (0..*n)
+                .map(|_| {
+                    let x: FE = ECScalar::new_random();
+                    DLogProof::prove(&x)
+                })
+                .collect::<Vec<DLogProof<GE>>>();
+            let y_vec = dlog_proof_vec.iter().map(|p| p.pk).collect::<Vec<GE>>();
+            group.bench_with_input(BenchmarkId::from_parameter(n), n, |b, _| {
+                b.iter(|| {
+                    Keys::verify_dlog_proofs(&params, &dlog_proof_vec, &y_vec)
+                        .expect("bad dlog proof")
+                })
+            });
+        }
+        group.finish();
+    }
+
+    pub fn bench_verify_phase5(c: &mut Criterion) {
+        let mut group = c.benchmark_group("sign phase5c verify");
+        let g: GE = ECPoint::generator();
+        let big_r = g * FE::new_random();
+        let y = g * FE::new_random();
+        let message = BigInt::from(1234);
+        for n in PARTIES.iter() {
+            let local_sig_vec = (0..*n)
+                .map(|_| {
+                    let k_i: FE = ECScalar::new_random();
+                    let sigma_i: FE = ECScalar::new_random();
+                    LocalSignature::phase5_local_sig(&k_i, &message, &big_r, &sigma_i, &y)
+                })
+                .collect::<Vec<LocalSignature>>();
+            let mut com_vec = Vec::new();
+            let mut decom_vec = Vec::new();
+            let mut elgamal_vec = Vec::new();
+            let mut dlog_vec = Vec::new();
+            for local_sig in &local_sig_vec[1..] {
+                let (com, decom, elgamal, dlog) = local_sig.phase5a_broadcast_5b_zkproof();
+                com_vec.push(com);
+                decom_vec.push(decom);
+                elgamal_vec.push(elgamal);
+                dlog_vec.push(dlog);
+            }
+            let (_, decom, _, _) = local_sig_vec[0].phase5a_broadcast_5b_zkproof();
+            group.bench_with_input(BenchmarkId::from_parameter(n), n, |b, _| {
+                b.iter(|| {
+                    local_sig_vec[0]
+                        .phase5c(
+                            &decom_vec,
+                            &com_vec,
+                            &elgamal_vec,
+                            &dlog_vec,
+                            &decom.V_i,
+                            &big_r,
+                        )
+                        .expect("error phase5")
+                })
+            });
+        }
+        group.finish();
+    }
+
+    // the checks a signer runs on the MtA responses of the other signers
+    pub fn bench_verify_mta(c: &mut Criterion) {
+        let mut group = c.benchmark_group("sign mta verify");
+        let (ek, dk) = Paillier::keypair().keys();
+        let alice = PaillierContext::with_decryption_key(&dk);
+        let k_i: FE = ECScalar::new_random();
+        let (m_a, _) = MessageA::a(&k_i, &ek);
+        for n in PARTIES.iter() {
+            let m_b_vec = (1..*n)
+                .map(|_| {
+                    let w_j: FE = ECScalar::new_random();
+                    MessageB::b(&w_j, &ek, m_a.clone()).0
+                })
+                .collect::<Vec<MessageB>>();
+            let g_w_vec = m_b_vec
+                .iter()
+                .map(|m_b| m_b.b_proof.pk)
+                .collect::<Vec<GE>>();
+            group.bench_with_input(BenchmarkId::from_parameter(n), n, |b, _| {
+                b.iter(|| {
+                    let failure = parallel::first_failure(m_b_vec.len(), |i| {
+                        m_b_vec[i]
+                            .verify_proofs_get_alpha_with_context(&alice, &k_i)
+                            .is_ok()
+                            && MessageB::verify_b_against_public(
+                                &g_w_vec[i],
+                                &m_b_vec[i].b_proof.pk,
+                            )
+                    });
+                    assert_eq!(failure, None, "bad mta response")
+                })
+            });
+        }
+        group.finish();
+    }
+
+    criterion_group! {
+    name = verify;
+    config = Criterion::default().sample_size(10);
+    targets =
+    self::bench_verify_correct_keys,
+    self::bench_verify_dlog_proofs,
+    self::bench_verify_phase5,
+    self::bench_verify_mta}
+}
+
+criterion_main!(bench::verify);
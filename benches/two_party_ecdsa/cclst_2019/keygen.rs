@@ -5,9 +5,15 @@ mod bench {
     use curv::arithmetic::traits::Samplable;
     use curv::elliptic::curves::traits::*;
     use curv::BigInt;
+    use multi_party_ecdsa::protocols::two_party_ecdsa::cclst_2019::setup::ClSetup;
     use multi_party_ecdsa::protocols::two_party_ecdsa::cclst_2019::{party_one, party_two};
 
     pub fn bench_full_keygen_party_one_two(c: &mut Criterion) {
+        // the class group is derived once and shared by all keygens
+        let seed: BigInt = str::parse(
+            "314159265358979323846264338327950288419716939937510582097494459230781640628620899862803482534211706798214808651328230664709384460955058223172535940812848"
+        ).unwrap();
+        let setup = ClSetup::from_seed(&seed);
         c.bench_function("keygen", move |b| {
             b.iter(|| {
 
@@ -32,20 +38,17 @@ mod bench {
                     .expect("failed to verify commitments and DLog proof");
 
                 // init HSMCL keypair:
-                let seed: BigInt = str::parse(
-                    "314159265358979323846264338327950288419716939937510582097494459230781640628620899862803482534211706798214808651328230664709384460955058223172535940812848"
-                ).unwrap();
-                let (hsmcl, hsmcl_public) = party_one::HSMCL::generate_keypair_and_encrypted_share_and_proof(
+                let (hsmcl, hsmcl_public) = party_one::HSMCL::generate_keypair_and_encrypted_share_and_proof_with_setup(
                     &ec_key_pair_party1,
-                    &seed,
+                    &setup,
                 );
 
                 //P1 sends P2 hsmcl_public
                 let _party_one_private = party_one::Party1Private::set_private_key(&ec_key_pair_party1, &hsmcl);
 
-                let _party_two_hsmcl_pub = party_two::Party2Public::verify_setup_and_zkcldl_proof(
+                let _party_two_hsmcl_pub = party_two::Party2Public::verify_zkcldl_proof_with_setup(
                     &hsmcl_public,
-                    &seed,
+                    &setup,
                     &party_one_second_message.comm_witness.public_share,
                 )
                     .expect("proof error");
@@ -3,7 +3,6 @@
 use std::io::Write;
 use std::io::BufRead;
 use std::convert::TryInto;
-use sha256::digest;
 use curv::{
     arithmetic::traits::*,
     cryptographic_primitives::{
@@ -18,7 +17,9 @@ use multi_party_ecdsa::protocols::multi_party_ecdsa::gg_2018::party_i::{
     Keys, LocalSignature, PartyPrivate, Phase5ADecom1, Phase5Com1, Phase5Com2, Phase5DDecom2,
     SharedKeys, SignBroadcastPhase1, SignDecommitPhase1, SignKeys,
 };
+use multi_party_ecdsa::utilities::digest::{signing_intent, MessageDigest};
 use multi_party_ecdsa::utilities::mta::*;
+use multi_party_ecdsa::utilities::parallel;
 
 use paillier::EncryptionKey;
 
@@ -36,12 +37,14 @@ fn main() {
     let indices = env::args().nth(4).unwrap();
     let setup_path = env::args().nth(5).unwrap();
     let signature_path = env::args().nth(6).unwrap();
+    // optional, e.g. a summary of the transaction every signer must have been shown
+    let metadata = env::args().nth(7).unwrap_or_default();
 
-    sign(message, port, ips, indices, setup_path, signature_path);
+    sign(message, port, ips, indices, setup_path, signature_path, metadata);
 
 }
 
-fn sign(message: String, lowest_port : u16, ips: String, indices: String, setup_path: String, signature_path: String) {
+fn sign(message: String, lowest_port : u16, ips: String, indices: String, setup_path: String, signature_path: String, metadata: String) {
 
 
 	let data = fs::read_to_string(setup_path)
@@ -60,6 +63,39 @@ fn sign(message: String, lowest_port : u16, ips: String, indices: String, setup_
     let threshold_index = indices.iter().position(|&x| x == party_id as usize).unwrap();
     let (mut sendvec, mut recvvec) = set_connection(threshold, threshold_index.try_into().unwrap(), lowest_port, ips);
 
+    //////////////////////////////////////////////////////////////////////////////
+    // agree on the message and metadata before any MtA work
+    let intent = signing_intent(
+        &MessageDigest::Sha256.to_message(message.as_bytes()),
+        metadata.as_bytes(),
+    );
+    send_to_all(&mut sendvec, &format!("{}\n", serde_json::to_string(&intent).unwrap()).as_bytes());
+
+    let mut disagreeing: Vec<usize> = Vec::new();
+    let mut j = 0;
+    for i in 0..threshold {
+        if (i as usize) != threshold_index {
+            let mut received = "".to_string();
+            loop {
+                let num_bytes = recvvec[j as usize].read_line(&mut received).unwrap();
+
+                if num_bytes > 0 {
+                    break;
+                }
+            }
+            received.pop();
+            let intent_j: [u8; 32] = serde_json::from_str(&received).unwrap();
+            if intent_j != intent {
+                disagreeing.push(indices[i as usize]);
+            }
+            j += 1;
+        }
+    }
+    if !disagreeing.is_empty() {
+        eprintln!("parties {:?} want to sign another message or metadata, aborting", disagreeing);
+        std::process::exit(1);
+    }
+
     let private = PartyPrivate::set_private(party_keys.clone(), shared_keys);
 
     let sign_keys = SignKeys::create(
@@ -159,33 +195,25 @@ fn sign(message: String, lowest_port : u16, ips: String, indices: String, setup_
         m_b_w_rec_vec.push(m_b_w_i);
         
     }
-    let mut alpha_vec: Vec<FE> = Vec::new();
-    let mut miu_vec: Vec<FE> = Vec::new();
-
-    let mut j = 0;
-    for i in 0..threshold {
-        if (i as usize) != threshold_index {
-            let m_b = m_b_gamma_rec_vec[j].clone();
-
-            let alpha_ij_gamma = m_b
-                .verify_proofs_get_alpha(&party_keys.dk, &sign_keys.k_i)
-                .expect("wrong dlog or m_b");
-            let m_b = m_b_w_rec_vec[j].clone();
-            let alpha_ij_wi = m_b
-                .verify_proofs_get_alpha(&party_keys.dk, &sign_keys.k_i)
-                .expect("wrong dlog or m_b");
-            alpha_vec.push(alpha_ij_gamma.0);
-            miu_vec.push(alpha_ij_wi.0);
-            let g_w_i = Keys::update_commitments_to_xi(
-                &xi_com_vec[indices[i as usize]],
-                &vss_scheme_vec[indices[i as usize]],
-                indices[i as usize],
-                &indices,
-            );
-            assert_eq!(m_b.b_proof.pk, g_w_i);
-            j += 1;
-        }
-    }
+    // verified in parallel when built with the rayon feature
+    let others: Vec<usize> = (0..threshold as usize).filter(|i| *i != threshold_index).collect();
+    let alphas = parallel::try_map(others.len(), |j| {
+        let i = others[j];
+        let (alpha_ij_gamma, _) = m_b_gamma_rec_vec[j]
+            .verify_proofs_get_alpha(&party_keys.dk, &sign_keys.k_i)?;
+        let m_b = &m_b_w_rec_vec[j];
+        let (alpha_ij_wi, _) = m_b.verify_proofs_get_alpha(&party_keys.dk, &sign_keys.k_i)?;
+        let g_w_i = Keys::update_commitments_to_xi(
+            &xi_com_vec[indices[i]],
+            &vss_scheme_vec[indices[i]],
+            indices[i],
+            &indices,
+        );
+        assert_eq!(m_b.b_proof.pk, g_w_i);
+        Ok((alpha_ij_gamma, alpha_ij_wi))
+    })
+    .expect("wrong dlog or m_b");
+    let (alpha_vec, miu_vec): (Vec<FE>, Vec<FE>) = alphas.into_iter().unzip();
 
 
     //////////////////////////////////////////////////////////////////////////////
@@ -260,9 +288,14 @@ fn sign(message: String, lowest_port : u16, ips: String, indices: String, setup_
     let R = R + decomm_i.g_gamma_i * delta_inv;
 
 
-    let message_bn = BigInt::from_bytes(&hex::decode(digest(message.clone())).unwrap());
-    let local_sig =
-        LocalSignature::phase5_local_sig(&sign_keys.k_i, &message_bn, &R, &sigma, &y_sum);
+    let local_sig = LocalSignature::phase5_local_sig_with_digest(
+        &sign_keys.k_i,
+        message.as_bytes(),
+        MessageDigest::Sha256,
+        &R,
+        &sigma,
+        &y_sum,
+    );
 
     let (phase5_com, phase_5a_decom, helgamal_proof, dlog_proof_rho) =
         local_sig.phase5a_broadcast_5b_zkproof();
@@ -94,11 +94,11 @@ pub fn aes_decrypt(key: &[u8], aead_pack: AEAD) -> Vec<u8> {
 
 #[allow(dead_code)]
 pub fn check_sig(r: &FE, s: &FE, msg: String, pk: &GE) {
-    use p256::ecdsa::Signature;
+    use multi_party_ecdsa::utilities::encoding;
     use p256::ecdsa::{VerifyKey, signature::Verifier};
 
     let public_key : VerifyKey = pk.get_element();
-    let signature : Signature = Signature::from_scalars(r.get_element(), s.get_element()).unwrap();
+    let signature = encoding::to_p256(&r.to_big_int(), &s.to_big_int()).unwrap();
 
 
     let is_correct = public_key.verify(msg.as_bytes(), &signature).is_ok();
@@ -0,0 +1,305 @@
+/*
+    Multi-party ECDSA
+
+    Copyright 2018 by Kzen Networks
+
+    This file is part of Multi-party ECDSA library
+    (https://github.com/KZen-networks/multi-party-ecdsa)
+
+    Multi-party ECDSA is free software: you can redistribute
+    it and/or modify it under the terms of the GNU General Public
+    License as published by the Free Software Foundation, either
+    version 3 of the License, or (at your option) any later version.
+
+    @license GPL-3.0+ <https://github.com/KZen-networks/multi-party-ecdsa/blob/master/LICENSE>
+*/
+
+//! GG18 keygen and signing, running the [`Keygen`] and [`Signing`] sessions of
+//! `gg_2018::signer` over a [`Channel`]. Parties are identified by their 0-based index,
+//! which is also the `sender`/`receiver` used in the envelopes.
+
+use curv::elliptic::curves::traits::*;
+use curv::BigInt;
+use futures::{Sink, Stream};
+use serde::{Deserialize, Serialize};
+
+use super::{Channel, DriverError, Envelope};
+use crate::protocols::multi_party_ecdsa::gg_2018::party_i::{self, SignatureRecid};
+pub use crate::protocols::multi_party_ecdsa::gg_2018::signer::LocalKey;
+use crate::protocols::multi_party_ecdsa::gg_2018::signer::{Keygen, Signing};
+use crate::protocols::threshold_signer::Session;
+use crate::utilities::mta::MtaBackend;
+use crate::Error;
+
+pub async fn keygen<St, Si>(
+    channel: &mut Channel<St, Si>,
+    threshold: u16,
+    share_count: u16,
+) -> Result<LocalKey, DriverError>
+where
+    St: Stream<Item = Envelope> + Unpin,
+    Si: Sink<Envelope> + Unpin,
+{
+    let index = channel.party();
+    let keygen = channel
+        .compute(move || Keygen::new(index, threshold, share_count))
+        .await?
+        .map_err(|_| DriverError::InvalidParties)?;
+    channel
+        .run_session(keygen, 0, |keygen| keygen.culprits().to_vec())
+        .await
+}
+
+/// Signs `message` together with the parties in `signers` (0-based indices, at least
+/// `threshold + 1` of them, including the local party).
+pub async fn sign<St, Si>(
+    channel: &mut Channel<St, Si>,
+    key: &LocalKey,
+    signers: &[u16],
+    message: &BigInt,
+) -> Result<SignatureRecid, DriverError>
+where
+    St: Stream<Item = Envelope> + Unpin,
+    Si: Sink<Envelope> + Unpin,
+{
+    sign_with_metadata(channel, key, signers, message, &[]).await
+}
+
+/// Like `sign`, but the signers must also agree on the application `metadata`, such as a
+/// summary of the transaction. The signers first compare the signing intent of the
+/// message and the metadata, and signing fails with `Misbehaviour` naming the signers that
+/// want to sign something else, before any MtA.
+pub async fn sign_with_metadata<St, Si>(
+    channel: &mut Channel<St, Si>,
+    key: &LocalKey,
+    signers: &[u16],
+    message: &BigInt,
+    metadata: &[u8],
+) -> Result<SignatureRecid, DriverError>
+where
+    St: Stream<Item = Envelope> + Unpin,
+    Si: Sink<Envelope> + Unpin,
+{
+    let signing =
+        Signing::new(key.clone(), signers, message).map_err(|_| DriverError::InvalidParties)?;
+    sign_session(channel, signing.with_metadata(metadata)).await
+}
+
+/// Runs a signing session set up by the caller, e.g. with [`Signing::with_mta`] to run the
+/// MtA over another backend.
+pub async fn sign_session<St, Si, M>(
+    channel: &mut Channel<St, Si>,
+    signing: Signing<M>,
+) -> Result<SignatureRecid, DriverError>
+where
+    St: Stream<Item = Envelope> + Unpin,
+    Si: Sink<Envelope> + Unpin,
+    M: MtaBackend + Send + 'static,
+{
+    sign_rounds(channel, signing, 0).await
+}
+
+// runs `signing` with its rounds numbered from `base + 1`
+async fn sign_rounds<St, Si, M>(
+    channel: &mut Channel<St, Si>,
+    signing: Signing<M>,
+    base: u16,
+) -> Result<SignatureRecid, DriverError>
+where
+    St: Stream<Item = Envelope> + Unpin,
+    Si: Sink<Envelope> + Unpin,
+    M: MtaBackend + Send + 'static,
+{
+    if signing.party() != channel.party() {
+        return Err(DriverError::InvalidParties);
+    }
+    let signature = channel
+        .run_session(signing, base, |signing| {
+            if signing.disagreeing().is_empty() {
+                signing.culprits().to_vec()
+            } else {
+                signing.disagreeing().to_vec()
+            }
+        })
+        .await?;
+    Ok(SignatureRecid {
+        r: ECScalar::from(&signature.r),
+        s: ECScalar::from(&signature.s),
+        recid: signature.recid,
+    })
+}
+
+// signing rounds of an attempt, plus the report and echo rounds
+const ATTEMPT_ROUNDS: u16 = 12;
+
+/// What a signer of a failover attempt tells the committee about the attempt: the
+/// signature, or `None` if the attempt failed on its side.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+struct Report {
+    signature: Option<SignatureRecid>,
+}
+
+/// Signs `message` with `threshold + 1` parties of `committee`, retrying with another subset
+/// whenever an attempt fails. Every member of the committee that is still online must call
+/// this with the same arguments, `metadata` is compared as in [`sign_with_metadata`].
+pub async fn sign_with_failover<St, Si>(
+    channel: &mut Channel<St, Si>,
+    key: &LocalKey,
+    committee: &[u16],
+    message: &BigInt,
+    metadata: &[u8],
+) -> Result<SignatureRecid, DriverError>
+where
+    St: Stream<Item = Envelope> + Unpin,
+    Si: Sink<Envelope> + Unpin,
+{
+    sign_with_failover_session(channel, key, committee, message, |signers| {
+        Ok(Signing::new(key.clone(), signers, message)?.with_metadata(metadata))
+    })
+    .await
+}
+
+/// Like `sign_with_failover`, but each attempt runs the session `signing` sets up for the
+/// signers of the attempt, e.g. with [`Signing::with_mta`] to run the MtA over another
+/// backend.
+///
+/// Each attempt signs with the first untried subset of the parties not excluded yet, after
+/// which every signer broadcasts a report with the signature, or without one if the attempt
+/// failed. Every party of the attempt then echoes the reports it got, and the next subset
+/// only depends on all reports echoed. A signer that nobody got a report from, not even
+/// through an echo, is excluded: it did not take part in the attempt. The parties a signer
+/// blames are not excluded, an accusation proves nothing to the others, the failed subset
+/// is only marked as tried. So parties that were delivered different reports still agree on
+/// the next subset, unless a party also hands out different echoes, and a malicious signer
+/// can neither get honest parties excluded nor a wrong signature accepted. Fails with
+/// `NoSigners` once fewer than `threshold + 1` parties are left, every subset has been
+/// tried, or the local party is excluded.
+pub async fn sign_with_failover_session<St, Si, M, F>(
+    channel: &mut Channel<St, Si>,
+    key: &LocalKey,
+    committee: &[u16],
+    message: &BigInt,
+    mut signing: F,
+) -> Result<SignatureRecid, DriverError>
+where
+    St: Stream<Item = Envelope> + Unpin,
+    Si: Sink<Envelope> + Unpin,
+    M: MtaBackend + Send + 'static,
+    F: FnMut(&[u16]) -> Result<Signing<M>, Error>,
+{
+    let index = key.party_index;
+    let mut active = committee.to_vec();
+    active.sort_unstable();
+    active.dedup();
+    if channel.party() != index
+        || active.len() != committee.len()
+        || active.len() <= key.threshold as usize
+        || !active.contains(&index)
+        || active.iter().any(|j| *j >= key.share_count)
+    {
+        return Err(DriverError::InvalidParties);
+    }
+    // a report may come after every round of the attempt has timed out
+    let report_timeout = channel.config.round_timeout * u32::from(ATTEMPT_ROUNDS);
+    let mut tried: Vec<Vec<u16>> = Vec::new();
+    let mut base: u16 = 0;
+
+    loop {
+        if !active.contains(&index) {
+            return Err(DriverError::NoSigners);
+        }
+        let signers = next_subset(&active, key.threshold as usize + 1, &tried)
+            .ok_or(DriverError::NoSigners)?;
+        let echo_round = base
+            .checked_add(ATTEMPT_ROUNDS)
+            .ok_or(DriverError::NoSigners)?;
+        let report_round = echo_round - 1;
+
+        let mut reports: Vec<(u16, Report)> = Vec::new();
+        if signers.contains(&index) {
+            let attempt = signing(&signers).map_err(|_| DriverError::InvalidParties)?;
+            let signature = match sign_rounds(channel, attempt, base).await {
+                Ok(signature) => Some(signature),
+                Err(DriverError::Timeout { .. })
+                | Err(DriverError::Serialization { .. })
+                | Err(DriverError::Misbehaviour { .. })
+                | Err(DriverError::Protocol(_)) => None,
+                Err(e) => return Err(e),
+            };
+            let report = Report { signature };
+            channel.broadcast(report_round, &report).await?;
+            reports.push((index, report));
+        }
+        let others: Vec<u16> = signers.iter().cloned().filter(|j| *j != index).collect();
+        let received: Vec<(u16, Report)> = channel
+            .receive_available(report_round, &others, report_timeout)
+            .await?;
+        reports.extend(received);
+        reports.sort_by_key(|(j, _)| *j);
+
+        channel.broadcast(echo_round, &reports).await?;
+        if let Some(signature) = valid_signature(&reports, key, message) {
+            return Ok(signature);
+        }
+        // a signer that did not report to us is unlikely to echo either
+        let echo_senders: Vec<u16> = active
+            .iter()
+            .cloned()
+            .filter(|j| {
+                *j != index
+                    && (!signers.contains(j) || reports.iter().any(|(sender, _)| sender == j))
+            })
+            .collect();
+        let echoes: Vec<(u16, Vec<(u16, Report)>)> = channel
+            .receive_available(echo_round, &echo_senders, report_timeout)
+            .await?;
+        for (_, echo) in echoes {
+            reports.extend(echo.into_iter().filter(|(j, _)| signers.contains(j)));
+        }
+        if let Some(signature) = valid_signature(&reports, key, message) {
+            return Ok(signature);
+        }
+
+        let absent: Vec<u16> = signers
+            .iter()
+            .cloned()
+            .filter(|j| !reports.iter().any(|(sender, _)| sender == j))
+            .collect();
+        active.retain(|j| !absent.contains(j));
+        tried.push(signers);
+        base = echo_round;
+    }
+}
+
+// the first signature among `reports` that verifies
+fn valid_signature(
+    reports: &[(u16, Report)],
+    key: &LocalKey,
+    message: &BigInt,
+) -> Option<SignatureRecid> {
+    reports
+        .iter()
+        .filter_map(|(_, report)| report.signature.clone())
+        .find(|signature| party_i::verify(signature, &key.y, message).is_ok())
+}
+
+// the first `size`-subset of `parties`, in lexicographic order, that is not in `tried`
+fn next_subset(parties: &[u16], size: usize, tried: &[Vec<u16>]) -> Option<Vec<u16>> {
+    if size > parties.len() {
+        return None;
+    }
+    let n = parties.len();
+    let mut positions: Vec<usize> = (0..size).collect();
+    loop {
+        let subset: Vec<u16> = positions.iter().map(|p| parties[*p]).collect();
+        if !tried.contains(&subset) {
+            return Some(subset);
+        }
+        // advance to the next combination
+        let i = (0..size).rev().find(|i| positions[*i] < n - size + *i)?;
+        positions[i] += 1;
+        for k in i + 1..size {
+            positions[k] = positions[k - 1] + 1;
+        }
+    }
+}
@@ -0,0 +1,236 @@
+/*
+    Multi-party ECDSA
+
+    Copyright 2018 by Kzen Networks
+
+    This file is part of Multi-party ECDSA library
+    (https://github.com/KZen-networks/multi-party-ecdsa)
+
+    Multi-party ECDSA is free software: you can redistribute
+    it and/or modify it under the terms of the GNU General Public
+    License as published by the Free Software Foundation, either
+    version 3 of the License, or (at your option) any later version.
+
+    @license GPL-3.0+ <https://github.com/KZen-networks/multi-party-ecdsa/blob/master/LICENSE>
+*/
+
+//! Lindell 2017 two-party keygen and signing. Party one uses index `PARTY_ONE` and party two
+//! uses index `PARTY_TWO` in the envelopes.
+
+use curv::BigInt;
+use futures::{Sink, Stream};
+
+use super::{Channel, DriverError, Envelope};
+pub use crate::protocols::two_party_ecdsa::lindell_2017::signer::{
+    PaillierKeyMsg, Party1KeyShare, Party2KeyShare, PARTY_ONE, PARTY_TWO,
+};
+use crate::protocols::two_party_ecdsa::lindell_2017::{party_one, party_two};
+use crate::Error::{self, InvalidKey, InvalidSig};
+
+pub async fn keygen_party_one<St, Si>(
+    channel: &mut Channel<St, Si>,
+) -> Result<Party1KeyShare, DriverError>
+where
+    St: Stream<Item = Envelope> + Unpin,
+    Si: Sink<Envelope> + Unpin,
+{
+    let (first_msg, comm_witness, ec_key_pair) = party_one::KeyGenFirstMsg::create_commitments();
+    channel.send_to(1, PARTY_TWO, &first_msg).await?;
+    let party_two_first_msg: party_two::KeyGenFirstMsg = channel.receive_from(2, PARTY_TWO).await?;
+
+    let second_msg = party_one::KeyGenSecondMsg::verify_and_decommit(
+        comm_witness,
+        &party_two_first_msg.d_log_proof,
+    )
+    .map_err(|_| InvalidKey)?;
+
+    let ec = ec_key_pair.clone();
+    let (private, paillier_msg) = channel
+        .compute(move || {
+            let paillier_key_pair =
+                party_one::PaillierKeyPair::generate_keypair_and_encrypted_share(&ec);
+            let private = party_one::Party1Private::set_private_key(&ec, &paillier_key_pair);
+            let correct_key_proof =
+                party_one::PaillierKeyPair::generate_ni_proof_correct_key(&paillier_key_pair);
+            let (pdl_statement, pdl_proof, composite_dlog_proof) =
+                party_one::PaillierKeyPair::pdl_proof(&private, &paillier_key_pair);
+            let paillier_msg = PaillierKeyMsg {
+                ek: paillier_key_pair.ek.clone(),
+                encrypted_share: paillier_key_pair.encrypted_share.clone(),
+                correct_key_proof,
+                pdl_statement,
+                pdl_proof,
+                composite_dlog_proof,
+            };
+            (private, paillier_msg)
+        })
+        .await?;
+
+    channel
+        .send_to(3, PARTY_TWO, &(&second_msg, &paillier_msg))
+        .await?;
+
+    let public_key = party_one::compute_pubkey(&private, &party_two_first_msg.public_share);
+    Ok(Party1KeyShare {
+        private,
+        public_share: ec_key_pair.public_share,
+        public_key,
+    })
+}
+
+pub async fn keygen_party_two<St, Si>(
+    channel: &mut Channel<St, Si>,
+) -> Result<Party2KeyShare, DriverError>
+where
+    St: Stream<Item = Envelope> + Unpin,
+    Si: Sink<Envelope> + Unpin,
+{
+    // party one commits to its share before seeing ours
+    let party_one_first_msg: party_one::KeyGenFirstMsg = channel.receive_from(1, PARTY_ONE).await?;
+    let (first_msg, ec_key_pair) = party_two::KeyGenFirstMsg::create();
+    channel.send_to(2, PARTY_ONE, &first_msg).await?;
+    let (party_one_second_msg, paillier_msg): (party_one::KeyGenSecondMsg, PaillierKeyMsg) =
+        channel.receive_from(3, PARTY_ONE).await?;
+
+    let party_one_public_share = party_one_second_msg.comm_witness.public_share;
+    let paillier_public = channel
+        .compute(move || -> Result<_, Error> {
+            party_two::KeyGenSecondMsg::verify_commitments_and_dlog_proof(
+                &party_one_first_msg,
+                &party_one_second_msg,
+            )
+            .map_err(|_| InvalidKey)?;
+            let paillier_public = party_two::PaillierPublic {
+                ek: paillier_msg.ek,
+                encrypted_secret_share: paillier_msg.encrypted_share,
+            };
+            party_two::PaillierPublic::verify_ni_proof_correct_key(
+                paillier_msg.correct_key_proof,
+                &paillier_public.ek,
+            )
+            .map_err(|_| InvalidKey)?;
+            party_two::PaillierPublic::pdl_verify(
+                &paillier_msg.composite_dlog_proof,
+                &paillier_msg.pdl_statement,
+                &paillier_msg.pdl_proof,
+                &paillier_public,
+                &party_one_public_share,
+            )
+            .map_err(|_| InvalidKey)?;
+            Ok(paillier_public)
+        })
+        .await??;
+
+    let public_key = party_two::compute_pubkey(&ec_key_pair, &party_one_public_share);
+    Ok(Party2KeyShare {
+        private: party_two::Party2Private::set_private_key(&ec_key_pair),
+        paillier_public,
+        public_share: ec_key_pair.public_share,
+        public_key,
+    })
+}
+
+pub async fn sign_party_one<St, Si>(
+    channel: &mut Channel<St, Si>,
+    key: &Party1KeyShare,
+    message: &BigInt,
+) -> Result<party_one::SignatureRecid, DriverError>
+where
+    St: Stream<Item = Envelope> + Unpin,
+    Si: Sink<Envelope> + Unpin,
+{
+    // party two commits to its ephemeral share before seeing ours
+    let party_two_eph_first_msg: party_two::EphKeyGenFirstMsg =
+        channel.receive_from(1, PARTY_TWO).await?;
+    let (eph_first_msg, eph_ec_key_pair) = party_one::EphKeyGenFirstMsg::create();
+    channel.send_to(2, PARTY_TWO, &eph_first_msg).await?;
+    let (party_two_eph_second_msg, partial_sig): (
+        party_two::EphKeyGenSecondMsg,
+        party_two::PartialSig,
+    ) = channel.receive_from(3, PARTY_TWO).await?;
+
+    let (private, public_key, message_c) = (key.private.clone(), key.public_key, message.clone());
+    let signature = channel
+        .compute(move || -> Result<_, Error> {
+            party_one::EphKeyGenSecondMsg::verify_commitments_and_dlog_proof(
+                &party_two_eph_first_msg,
+                &party_two_eph_second_msg,
+            )
+            .map_err(|_| InvalidKey)?;
+            let signature = party_one::Signature::compute_with_recid(
+                &private,
+                &partial_sig.c3,
+                &eph_ec_key_pair,
+                &party_two_eph_second_msg.comm_witness.public_share,
+            );
+            party_one::verify(
+                &party_one::Signature {
+                    r: signature.r.clone(),
+                    s: signature.s.clone(),
+                },
+                &public_key,
+                &message_c,
+            )
+            .map_err(|_| InvalidSig)?;
+            Ok(signature)
+        })
+        .await??;
+
+    channel.send_to(4, PARTY_TWO, &signature).await?;
+    Ok(signature)
+}
+
+pub async fn sign_party_two<St, Si>(
+    channel: &mut Channel<St, Si>,
+    key: &Party2KeyShare,
+    message: &BigInt,
+) -> Result<party_one::SignatureRecid, DriverError>
+where
+    St: Stream<Item = Envelope> + Unpin,
+    Si: Sink<Envelope> + Unpin,
+{
+    let (eph_first_msg, eph_comm_witness, eph_ec_key_pair) =
+        party_two::EphKeyGenFirstMsg::create_commitments();
+    channel.send_to(1, PARTY_ONE, &eph_first_msg).await?;
+    let party_one_eph_first_msg: party_one::EphKeyGenFirstMsg =
+        channel.receive_from(2, PARTY_ONE).await?;
+    let eph_second_msg = party_two::EphKeyGenSecondMsg::verify_and_decommit(
+        eph_comm_witness,
+        &party_one_eph_first_msg,
+    )
+    .map_err(|_| InvalidKey)?;
+
+    let (ek, encrypted_share, private, message_c) = (
+        key.paillier_public.ek.clone(),
+        key.paillier_public.encrypted_secret_share.clone(),
+        key.private.clone(),
+        message.clone(),
+    );
+    let party_one_eph_public_share = party_one_eph_first_msg.public_share;
+    let partial_sig = channel
+        .compute(move || {
+            party_two::PartialSig::compute(
+                &ek,
+                &encrypted_share,
+                &private,
+                &eph_ec_key_pair,
+                &party_one_eph_public_share,
+                &message_c,
+            )
+        })
+        .await?;
+    channel
+        .send_to(3, PARTY_ONE, &(&eph_second_msg, &partial_sig))
+        .await?;
+
+    let signature: party_one::SignatureRecid = channel.receive_from(4, PARTY_ONE).await?;
+    party_one::verify(
+        &party_one::Signature {
+            r: signature.r.clone(),
+            s: signature.s.clone(),
+        },
+        &key.public_key,
+        message,
+    )?;
+    Ok(signature)
+}
@@ -0,0 +1,380 @@
+/*
+    Multi-party ECDSA
+
+    Copyright 2018 by Kzen Networks
+
+    This file is part of Multi-party ECDSA library
+    (https://github.com/KZen-networks/multi-party-ecdsa)
+
+    Multi-party ECDSA is free software: you can redistribute
+    it and/or modify it under the terms of the GNU General Public
+    License as published by the Free Software Foundation, either
+    version 3 of the License, or (at your option) any later version.
+
+    @license GPL-3.0+ <https://github.com/KZen-networks/multi-party-ecdsa/blob/master/LICENSE>
+*/
+
+//! Asynchronous drivers running the protocols of this crate over a message transport.
+//!
+//! A party is connected to the rest of the session through a [`Channel`], which wraps any
+//! `Stream` of incoming and `Sink` of outgoing [`Envelope`]s. Every round is bounded by
+//! `DriverConfig::round_timeout`, the whole session can be aborted through a
+//! `CancellationToken`, and the Paillier heavy lifting is done on tokio's blocking pool so
+//! that a single runtime can host many concurrent sessions.
+//!
+//! The transport is expected to deliver peer-to-peer envelopes over private and
+//! authenticated channels, and broadcast envelopes to every other party of the session.
+
+use std::collections::BTreeMap;
+use std::time::Duration;
+
+use futures::{Sink, SinkExt, Stream, StreamExt};
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use tokio_util::sync::CancellationToken;
+
+use crate::protocols::threshold_signer::{Msg, Session};
+use crate::Error;
+
+pub mod gg18;
+pub mod lindell;
+
+#[cfg(test)]
+mod test;
+
+/// A serialized protocol message together with its routing information.
+/// `receiver` is `None` for broadcast messages.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Envelope {
+    pub sender: u16,
+    pub receiver: Option<u16>,
+    pub round: u16,
+    pub body: String,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum DriverError {
+    /// not all expected messages of `round` arrived in time
+    Timeout { round: u16, missing: Vec<u16> },
+    Cancelled,
+    TransportClosed,
+    TransportError,
+    /// a message of `round` sent by `sender` could not be (de)serialized
+    Serialization { round: u16, sender: u16 },
+    /// the set of parties passed to the driver is inconsistent with the key share
+    InvalidParties,
+    /// a computation offloaded to the blocking pool panicked
+    TaskFailed,
+    /// the proofs `culprits` sent in `round` are invalid, or they want to sign another message
+    Misbehaviour { round: u16, culprits: Vec<u16> },
+    /// failover ran out of signers, or the local party was excluded
+    NoSigners,
+    Protocol(Error),
+}
+
+impl DriverError {
+    /// The parties the error can be blamed on, if any.
+    pub fn culprits(&self) -> Vec<u16> {
+        match self {
+            DriverError::Timeout { missing, .. } => missing.clone(),
+            DriverError::Serialization { sender, .. } => vec![*sender],
+            DriverError::Misbehaviour { culprits, .. } => culprits.clone(),
+            _ => Vec::new(),
+        }
+    }
+}
+
+impl From<Error> for DriverError {
+    fn from(e: Error) -> Self {
+        DriverError::Protocol(e)
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct DriverConfig {
+    pub round_timeout: Duration,
+}
+
+impl Default for DriverConfig {
+    fn default() -> Self {
+        DriverConfig {
+            round_timeout: Duration::from_secs(60),
+        }
+    }
+}
+
+pub struct Channel<St, Si> {
+    party: u16,
+    incoming: St,
+    outgoing: Si,
+    pending: Vec<Envelope>,
+    config: DriverConfig,
+    cancel: CancellationToken,
+}
+
+impl<St, Si> Channel<St, Si>
+where
+    St: Stream<Item = Envelope> + Unpin,
+    Si: Sink<Envelope> + Unpin,
+{
+    pub fn new(
+        party: u16,
+        incoming: St,
+        outgoing: Si,
+        config: DriverConfig,
+        cancel: CancellationToken,
+    ) -> Self {
+        Channel {
+            party,
+            incoming,
+            outgoing,
+            pending: Vec::new(),
+            config,
+            cancel,
+        }
+    }
+
+    pub fn party(&self) -> u16 {
+        self.party
+    }
+
+    pub fn cancellation_token(&self) -> CancellationToken {
+        self.cancel.clone()
+    }
+
+    pub async fn broadcast<T: Serialize>(&mut self, round: u16, body: &T) -> Result<(), DriverError> {
+        self.send(round, None, body).await
+    }
+
+    pub async fn send_to<T: Serialize>(
+        &mut self,
+        round: u16,
+        receiver: u16,
+        body: &T,
+    ) -> Result<(), DriverError> {
+        self.send(round, Some(receiver), body).await
+    }
+
+    async fn send<T: Serialize>(
+        &mut self,
+        round: u16,
+        receiver: Option<u16>,
+        body: &T,
+    ) -> Result<(), DriverError> {
+        let body = serde_json::to_string(body).map_err(|_| DriverError::Serialization {
+            round,
+            sender: self.party,
+        })?;
+        let envelope = Envelope {
+            sender: self.party,
+            receiver,
+            round,
+            body,
+        };
+        let cancel = self.cancel.clone();
+        tokio::select! {
+            _ = cancel.cancelled() => Err(DriverError::Cancelled),
+            res = self.outgoing.send(envelope) => res.map_err(|_| DriverError::TransportError),
+        }
+    }
+
+    /// Waits for exactly one message of `round` from each party in `senders` and returns the
+    /// deserialized bodies in the order of `senders`. Messages of later rounds are buffered,
+    /// stale and duplicate messages are dropped.
+    pub async fn receive<T: DeserializeOwned>(
+        &mut self,
+        round: u16,
+        senders: &[u16],
+    ) -> Result<Vec<T>, DriverError> {
+        let received = self.receive_msgs(round, senders).await?;
+        Ok(received.into_iter().map(|msg| msg.body).collect())
+    }
+
+    /// Like `receive`, but keeps the routing information of the messages.
+    pub async fn receive_msgs<T: DeserializeOwned>(
+        &mut self,
+        round: u16,
+        senders: &[u16],
+    ) -> Result<Vec<Msg<T>>, DriverError> {
+        let round_timeout = self.config.round_timeout;
+        let (received, outcome) = self.gather(round, senders, round_timeout).await;
+        if let Err(DriverError::Timeout { round, .. }) = outcome {
+            let missing = senders
+                .iter()
+                .filter(|j| !received.contains_key(j))
+                .cloned()
+                .collect();
+            return Err(DriverError::Timeout { round, missing });
+        }
+        outcome?;
+
+        senders
+            .iter()
+            .map(|j| {
+                let envelope = &received[j];
+                let body = serde_json::from_str(&envelope.body)
+                    .map_err(|_| DriverError::Serialization { round, sender: *j })?;
+                Ok(Msg {
+                    sender: *j,
+                    receiver: envelope.receiver,
+                    round,
+                    body,
+                })
+            })
+            .collect()
+    }
+
+    /// Like `receive`, but waits up to `timeout` and returns whatever arrived by then, as
+    /// (sender, body). Bodies that do not deserialize are left out.
+    pub async fn receive_available<T: DeserializeOwned>(
+        &mut self,
+        round: u16,
+        senders: &[u16],
+        timeout: Duration,
+    ) -> Result<Vec<(u16, T)>, DriverError> {
+        let (received, outcome) = self.gather(round, senders, timeout).await;
+        match outcome {
+            Ok(()) | Err(DriverError::Timeout { .. }) => (),
+            Err(e) => return Err(e),
+        }
+        Ok(received
+            .into_iter()
+            .filter_map(|(j, envelope)| Some((j, serde_json::from_str(&envelope.body).ok()?)))
+            .collect())
+    }
+
+    // collects one envelope of `round` from each of `senders`, until `timeout`
+    async fn gather(
+        &mut self,
+        round: u16,
+        senders: &[u16],
+        timeout: Duration,
+    ) -> (BTreeMap<u16, Envelope>, Result<(), DriverError>) {
+        let party = self.party;
+        let mut received: BTreeMap<u16, Envelope> = BTreeMap::new();
+        for envelope in std::mem::take(&mut self.pending) {
+            sort_envelope(
+                party,
+                round,
+                senders,
+                envelope,
+                &mut received,
+                &mut self.pending,
+            );
+        }
+
+        let cancel = self.cancel.clone();
+        let outcome = {
+            let incoming = &mut self.incoming;
+            let pending = &mut self.pending;
+            let received = &mut received;
+            let collect = async move {
+                while received.len() < senders.len() {
+                    let envelope = incoming.next().await.ok_or(DriverError::TransportClosed)?;
+                    sort_envelope(party, round, senders, envelope, received, pending);
+                }
+                Ok(())
+            };
+            tokio::select! {
+                _ = cancel.cancelled() => Err(DriverError::Cancelled),
+                res = tokio::time::timeout(timeout, collect) => match res {
+                    Ok(res) => res,
+                    Err(_) => Err(DriverError::Timeout { round, missing: Vec::new() }),
+                },
+            }
+        };
+        (received, outcome)
+    }
+
+    pub async fn receive_from<T: DeserializeOwned>(
+        &mut self,
+        round: u16,
+        sender: u16,
+    ) -> Result<T, DriverError> {
+        let mut received = self.receive(round, &[sender]).await?;
+        Ok(received.remove(0))
+    }
+
+    /// Drives `session` until it has an output, sending and receiving its round `r` as round
+    /// `base + r`. Every round is computed on the blocking thread pool. When `proceed` fails,
+    /// the error is blamed on the parties `culprits` finds in the session, if any.
+    pub async fn run_session<S, F>(
+        &mut self,
+        mut session: S,
+        base: u16,
+        culprits: F,
+    ) -> Result<S::Output, DriverError>
+    where
+        S: Session + Send + 'static,
+        S::Message: Send + 'static,
+        F: Fn(&S) -> Vec<u16>,
+    {
+        loop {
+            let round = session.round();
+            let senders = session.expected_senders();
+            let incoming: Vec<Msg<S::Message>> = if senders.is_empty() {
+                Vec::new()
+            } else {
+                self.receive_msgs(base + round, &senders)
+                    .await?
+                    .into_iter()
+                    .map(|msg| Msg { round, ..msg })
+                    .collect()
+            };
+            let (returned, outgoing) = self
+                .compute(move || {
+                    let outgoing = session.proceed(incoming);
+                    (session, outgoing)
+                })
+                .await?;
+            session = returned;
+            let outgoing = outgoing.map_err(|e| match culprits(&session) {
+                ref culprits if culprits.is_empty() => DriverError::Protocol(e),
+                culprits => DriverError::Misbehaviour {
+                    round: base + round,
+                    culprits,
+                },
+            })?;
+            for msg in outgoing {
+                self.send(base + msg.round, msg.receiver, &msg.body).await?;
+            }
+            if session.is_finished() {
+                return session
+                    .pick_output()
+                    .ok_or(DriverError::Protocol(Error::InvalidMessage));
+            }
+        }
+    }
+
+    /// Runs `f` on the blocking thread pool. The session is aborted as soon as the
+    /// cancellation token fires, even if `f` itself keeps running to completion.
+    pub async fn compute<F, R>(&self, f: F) -> Result<R, DriverError>
+    where
+        F: FnOnce() -> R + Send + 'static,
+        R: Send + 'static,
+    {
+        let cancel = self.cancel.clone();
+        tokio::select! {
+            _ = cancel.cancelled() => Err(DriverError::Cancelled),
+            res = tokio::task::spawn_blocking(f) => res.map_err(|_| DriverError::TaskFailed),
+        }
+    }
+}
+
+fn sort_envelope(
+    party: u16,
+    round: u16,
+    senders: &[u16],
+    envelope: Envelope,
+    received: &mut BTreeMap<u16, Envelope>,
+    pending: &mut Vec<Envelope>,
+) {
+    if envelope.receiver.map_or(false, |r| r != party) || envelope.round < round {
+        return;
+    }
+    if envelope.round > round {
+        pending.push(envelope);
+    } else if senders.contains(&envelope.sender) && !received.contains_key(&envelope.sender) {
+        received.insert(envelope.sender, envelope);
+    }
+}
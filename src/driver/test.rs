@@ -0,0 +1,306 @@
+use std::time::Duration;
+
+use curv::BigInt;
+use futures::channel::mpsc::{unbounded, UnboundedReceiver, UnboundedSender};
+use futures::future::{join, join_all};
+use futures::StreamExt;
+use tokio_util::sync::CancellationToken;
+
+use super::{gg18, lindell, Channel, DriverConfig, DriverError, Envelope};
+use crate::protocols::multi_party_ecdsa::gg_2018::party_i::verify;
+use crate::protocols::multi_party_ecdsa::gg_2018::signer::Signing;
+use crate::protocols::two_party_ecdsa::lindell_2017::party_one;
+use crate::utilities::mta::ot::OtMtaSetup;
+
+type TestChannel = Channel<UnboundedReceiver<Envelope>, UnboundedSender<Envelope>>;
+
+// in-memory transport: a hub task routes every envelope to its receiver(s)
+fn network(n: u16, config: DriverConfig) -> Vec<TestChannel> {
+    lossy_network(n, config, |_, _| false)
+}
+
+// like `network`, but drops the envelopes for which `lost(envelope, receiver)` holds
+fn lossy_network(
+    n: u16,
+    config: DriverConfig,
+    lost: fn(&Envelope, u16) -> bool,
+) -> Vec<TestChannel> {
+    let (hub_tx, mut hub_rx) = unbounded::<Envelope>();
+    let (txs, rxs): (Vec<_>, Vec<_>) = (0..n).map(|_| unbounded::<Envelope>()).unzip();
+    tokio::spawn(async move {
+        while let Some(envelope) = hub_rx.next().await {
+            for (j, tx) in txs.iter().enumerate() {
+                let j = j as u16;
+                if j != envelope.sender
+                    && envelope.receiver.map_or(true, |r| r == j)
+                    && !lost(&envelope, j)
+                {
+                    let _ = tx.unbounded_send(envelope.clone());
+                }
+            }
+        }
+    });
+    rxs.into_iter()
+        .enumerate()
+        .map(|(i, rx)| {
+            Channel::new(
+                i as u16,
+                rx,
+                hub_tx.clone(),
+                config.clone(),
+                CancellationToken::new(),
+            )
+        })
+        .collect()
+}
+
+#[tokio::test]
+async fn test_gg18_keygen_and_sign_t1_n3() {
+    let mut channels = network(3, DriverConfig::default());
+    let keys = join_all(channels.iter_mut().map(|c| gg18::keygen(c, 1, 3)))
+        .await
+        .into_iter()
+        .collect::<Result<Vec<_>, _>>()
+        .expect("keygen failed");
+    assert!(keys.iter().all(|k| k.y == keys[0].y));
+
+    let message = BigInt::from(1234);
+    let signers = [0u16, 2];
+    let mut channels = network(3, DriverConfig::default());
+    let (head, tail) = channels.split_at_mut(2);
+    let (sig0, sig2) = join(
+        gg18::sign(&mut head[0], &keys[0], &signers, &message),
+        gg18::sign(&mut tail[0], &keys[2], &signers, &message),
+    )
+    .await;
+    let sig0 = sig0.expect("signing failed");
+    let sig2 = sig2.expect("signing failed");
+    assert_eq!(sig0.r, sig2.r);
+    assert_eq!(sig0.s, sig2.s);
+    verify(&sig0, &keys[0].y, &message).expect("invalid signature");
+}
+
+#[tokio::test]
+async fn test_gg18_sign_message_mismatch() {
+    let keys = gg18_keys(1, 3).await;
+    let message = BigInt::from(1234);
+    let signers = [0u16, 1, 2];
+
+    // party 2 was shown another transaction
+    let mut channels = network(3, DriverConfig::default());
+    let results = join_all(channels.iter_mut().zip(&keys).map(|(c, key)| {
+        let metadata: &[u8] = if key.party_index == 2 {
+            b"pay 100 to B"
+        } else {
+            b"pay 100 to A"
+        };
+        gg18::sign_with_metadata(c, key, &signers, &message, metadata)
+    }))
+    .await;
+    let culprits: Vec<Vec<u16>> = vec![vec![2], vec![2], vec![0, 1]];
+    for (result, culprits) in results.into_iter().zip(culprits) {
+        assert_eq!(
+            result.err(),
+            Some(DriverError::Misbehaviour { round: 1, culprits })
+        );
+    }
+}
+
+#[tokio::test]
+async fn test_gg18_sign_over_ot_mta() {
+    let keys = gg18_keys(1, 3).await;
+    let message = BigInt::from(1234);
+    let signers = [0u16, 2];
+
+    // the OT setup is a session of its own, which serves any number of signing sessions
+    let mut channels = network(3, DriverConfig::default());
+    let (head, tail) = channels.split_at_mut(2);
+    let (mta0, mta2) = join(
+        head[0].run_session(OtMtaSetup::new(0, &signers).unwrap(), 0, |_| Vec::new()),
+        tail[0].run_session(OtMtaSetup::new(2, &signers).unwrap(), 0, |_| Vec::new()),
+    )
+    .await;
+    let signing0 = Signing::with_mta(keys[0].clone(), &signers, &message, mta0.unwrap()).unwrap();
+    let signing2 = Signing::with_mta(keys[2].clone(), &signers, &message, mta2.unwrap()).unwrap();
+
+    let mut channels = network(3, DriverConfig::default());
+    let (head, tail) = channels.split_at_mut(2);
+    let (sig0, sig2) = join(
+        gg18::sign_session(&mut head[0], signing0),
+        gg18::sign_session(&mut tail[0], signing2),
+    )
+    .await;
+    let sig0 = sig0.expect("signing failed");
+    let sig2 = sig2.expect("signing failed");
+    assert_eq!(sig0.r, sig2.r);
+    assert_eq!(sig0.s, sig2.s);
+    verify(&sig0, &keys[0].y, &message).expect("invalid signature");
+}
+
+#[tokio::test]
+async fn test_lindell_keygen_and_sign() {
+    let mut channels = network(2, DriverConfig::default());
+    let (head, tail) = channels.split_at_mut(1);
+    let (share1, share2) = join(
+        lindell::keygen_party_one(&mut head[0]),
+        lindell::keygen_party_two(&mut tail[0]),
+    )
+    .await;
+    let share1 = share1.expect("party one keygen failed");
+    let share2 = share2.expect("party two keygen failed");
+    assert_eq!(share1.public_key, share2.public_key);
+
+    let message = BigInt::from(1234);
+    let mut channels = network(2, DriverConfig::default());
+    let (head, tail) = channels.split_at_mut(1);
+    let (sig1, sig2) = join(
+        lindell::sign_party_one(&mut head[0], &share1, &message),
+        lindell::sign_party_two(&mut tail[0], &share2, &message),
+    )
+    .await;
+    let sig1 = sig1.expect("party one signing failed");
+    sig2.expect("party two signing failed");
+    let signature = party_one::Signature {
+        r: sig1.r,
+        s: sig1.s,
+    };
+    party_one::verify(&signature, &share1.public_key, &message).expect("invalid signature");
+}
+
+#[tokio::test]
+async fn test_round_timeout_reports_missing_parties() {
+    let config = DriverConfig {
+        round_timeout: Duration::from_millis(200),
+    };
+    let mut channels = network(3, config);
+    let res = gg18::keygen(&mut channels[0], 1, 3).await;
+    assert_eq!(
+        res.err(),
+        Some(DriverError::Timeout {
+            round: 1,
+            missing: vec![1, 2],
+        })
+    );
+}
+
+#[tokio::test]
+async fn test_cancellation() {
+    let mut channels = network(3, DriverConfig::default());
+    channels[0].cancellation_token().cancel();
+    let res = gg18::keygen(&mut channels[0], 1, 3).await;
+    assert_eq!(res.err(), Some(DriverError::Cancelled));
+}
+
+async fn gg18_keys(threshold: u16, share_count: u16) -> Vec<gg18::LocalKey> {
+    let mut channels = network(share_count, DriverConfig::default());
+    join_all(
+        channels
+            .iter_mut()
+            .map(|c| gg18::keygen(c, threshold, share_count)),
+    )
+    .await
+    .into_iter()
+    .collect::<Result<Vec<_>, _>>()
+    .expect("keygen failed")
+}
+
+#[tokio::test]
+async fn test_gg18_failover_excludes_absent_signer() {
+    let keys = gg18_keys(1, 3).await;
+    let message = BigInt::from(1234);
+    let committee = [0u16, 1, 2];
+    let config = DriverConfig {
+        round_timeout: Duration::from_millis(200),
+    };
+
+    // party 0 is in the first subset but never shows up
+    let mut channels = network(3, config);
+    let (head, tail) = channels.split_at_mut(2);
+    let (sig1, sig2) = join(
+        gg18::sign_with_failover(&mut head[1], &keys[1], &committee, &message, &[]),
+        gg18::sign_with_failover(&mut tail[0], &keys[2], &committee, &message, &[]),
+    )
+    .await;
+    let sig1 = sig1.expect("signing failed");
+    let sig2 = sig2.expect("signing failed");
+    assert_eq!(sig1.r, sig2.r);
+    assert_eq!(sig1.s, sig2.s);
+    verify(&sig1, &keys[0].y, &message).expect("invalid signature");
+}
+
+#[tokio::test]
+async fn test_gg18_failover_agrees_on_next_subset() {
+    let keys = gg18_keys(1, 4).await;
+    let message = BigInt::from(1234);
+    let committee = [0u16, 1, 2, 3];
+    let config = DriverConfig {
+        round_timeout: Duration::from_millis(200),
+    };
+
+    // party 0 never shows up, and the report of party 1 about the first attempt does not
+    // reach party 3, which would otherwise exclude party 1 as well
+    let mut channels = lossy_network(4, config, |envelope, to| {
+        envelope.sender == 1 && envelope.round == 11 && to == 3
+    });
+    let signatures = join_all(
+        channels[1..]
+            .iter_mut()
+            .zip(&keys[1..])
+            .map(|(c, key)| gg18::sign_with_failover(c, key, &committee, &message, &[])),
+    )
+    .await;
+    let signatures = signatures
+        .into_iter()
+        .collect::<Result<Vec<_>, _>>()
+        .expect("signing failed");
+    for signature in &signatures {
+        assert_eq!(signature.r, signatures[0].r);
+        assert_eq!(signature.s, signatures[0].s);
+    }
+    verify(&signatures[0], &keys[0].y, &message).expect("invalid signature");
+}
+
+#[tokio::test]
+async fn test_gg18_failover_does_not_exclude_on_accusations() {
+    let keys = gg18_keys(1, 3).await;
+    let message = BigInt::from(1234);
+    let committee = [0u16, 1, 2];
+
+    // party 0 was shown another transaction, so it and its co-signers blame each other in
+    // the first two attempts, which must not get anyone excluded before [1, 2] is tried
+    let mut channels = network(3, DriverConfig::default());
+    let signatures = join_all(channels.iter_mut().zip(&keys).map(|(c, key)| {
+        let metadata: &[u8] = if key.party_index == 0 {
+            b"pay 100 to B"
+        } else {
+            b"pay 100 to A"
+        };
+        gg18::sign_with_failover(c, key, &committee, &message, metadata)
+    }))
+    .await;
+    let signatures = signatures
+        .into_iter()
+        .collect::<Result<Vec<_>, _>>()
+        .expect("signing failed");
+    for signature in &signatures {
+        assert_eq!(signature.r, signatures[0].r);
+        assert_eq!(signature.s, signatures[0].s);
+    }
+    verify(&signatures[0], &keys[0].y, &message).expect("invalid signature");
+}
+
+#[tokio::test]
+async fn test_gg18_failover_runs_out_of_signers() {
+    let keys = gg18_keys(1, 3).await;
+    let message = BigInt::from(1234);
+    let config = DriverConfig {
+        round_timeout: Duration::from_millis(100),
+    };
+
+    let mut channels = network(3, config);
+    let res = gg18::sign_with_failover(&mut channels[1], &keys[1], &[0, 1, 2], &message, &[]).await;
+    assert_eq!(res.err(), Some(DriverError::NoSigners));
+
+    let res = gg18::sign_with_failover(&mut channels[1], &keys[1], &[0, 2], &message, &[]).await;
+    assert_eq!(res.err(), Some(DriverError::InvalidParties));
+}
@@ -14,6 +14,8 @@
     @license GPL-3.0+ <https://github.com/KZen-networks/multi-party-ecdsa/blob/master/LICENSE>
 */
 
+#[cfg(feature = "async-driver")]
+pub mod driver;
 pub mod protocols;
 pub mod utilities;
 #[derive(Copy, PartialEq, Eq, Clone, Debug)]
@@ -24,4 +26,6 @@ pub enum Error {
     InvalidSig,
     Phase5BadSum,
     Phase6Error,
+    InvalidMessage,
+    MessageMismatch,
 }
@@ -0,0 +1,173 @@
+/*
+    Multi-party ECDSA
+
+    Copyright 2018 by Kzen Networks
+
+    This file is part of Multi-party ECDSA library
+    (https://github.com/KZen-networks/multi-party-ecdsa)
+
+    Multi-party ECDSA is free software: you can redistribute
+    it and/or modify it under the terms of the GNU General Public
+    License as published by the Free Software Foundation, either
+    version 3 of the License, or (at your option) any later version.
+
+    @license GPL-3.0+ <https://github.com/KZen-networks/multi-party-ecdsa/blob/master/LICENSE>
+*/
+
+//! Reconstruction of the full private key from its shares, for disaster recovery or to
+//! leave threshold custody.
+//!
+//! Every share holder reveals its share with `Contribution::reveal_gg18` or
+//! `reveal_party_one` / `reveal_party_two`, and sends it over a private and authenticated
+//! channel to whoever reconstructs. Every contribution is checked against the public data
+//! of the key before it is used, and the result against the public key, so a wrong share
+//! is reported instead of exported. Once a key has been exported the sharing no longer
+//! protects it, and it should be retired.
+
+use curv::cryptographic_primitives::secret_sharing::feldman_vss::VerifiableSS;
+use curv::elliptic::curves::p256::{FE, GE};
+use curv::elliptic::curves::traits::*;
+use serde::{Deserialize, Serialize};
+use zeroize::Zeroize;
+
+use crate::protocols::multi_party_ecdsa::gg_2018::party_i::Keys;
+use crate::protocols::multi_party_ecdsa::gg_2018::signer::LocalKey;
+use crate::protocols::two_party_ecdsa::lindell_2017::signer::{
+    Party1KeyShare, Party2KeyShare, PARTY_ONE, PARTY_TWO,
+};
+use crate::utilities::encoding;
+use crate::utilities::recovery::to_bytes_32;
+use crate::Error::{self, InvalidKey, InvalidSS};
+
+/// The secret share of one party. It deliberately implements neither `Clone` nor `Debug`.
+#[derive(Serialize, Deserialize)]
+pub struct Contribution {
+    party: u16,
+    share: FE,
+}
+
+impl Contribution {
+    pub fn reveal_gg18(key: &LocalKey) -> Self {
+        Contribution {
+            party: key.party_index,
+            share: key.shared_keys.x_i,
+        }
+    }
+
+    pub fn reveal_party_one(key: &Party1KeyShare) -> Self {
+        Contribution {
+            party: PARTY_ONE,
+            share: key.private.x1(),
+        }
+    }
+
+    pub fn reveal_party_two(key: &Party2KeyShare) -> Self {
+        Contribution {
+            party: PARTY_TWO,
+            share: key.private.x2(),
+        }
+    }
+
+    pub fn party(&self) -> u16 {
+        self.party
+    }
+}
+
+impl Drop for Contribution {
+    fn drop(&mut self) {
+        self.share.zeroize();
+    }
+}
+
+/// A reconstructed private key, checked against its public key. The secret only leaves it
+/// in one of the standard encodings.
+pub struct ExportedKey {
+    secret: FE,
+    public_key: GE,
+}
+
+impl ExportedKey {
+    /// Reconstructs a GG18 key from the contributions of at least `threshold + 1` distinct
+    /// parties. Only the public parts of `key`, which may belong to any party of the
+    /// sharing, are used.
+    pub fn reconstruct_gg18(key: &LocalKey, contributions: &[Contribution]) -> Result<Self, Error> {
+        let mut parties: Vec<u16> = contributions.iter().map(|c| c.party).collect();
+        parties.sort_unstable();
+        parties.dedup();
+        if parties.len() != contributions.len()
+            || parties.len() <= key.threshold as usize
+            || parties.iter().any(|j| *j >= key.share_count)
+        {
+            return Err(InvalidKey);
+        }
+        let xi_com_vec = Keys::get_commitments_to_xi(&key.vss_scheme_vec);
+        let g = GE::generator();
+        if contributions
+            .iter()
+            .any(|c| g * c.share != xi_com_vec[c.party as usize])
+        {
+            return Err(InvalidSS);
+        }
+
+        let indices: Vec<usize> = contributions.iter().map(|c| c.party as usize).collect();
+        let mut shares: Vec<FE> = contributions.iter().map(|c| c.share).collect();
+        let secret = key.vss_scheme_vec[0].reconstruct(&indices, &shares);
+        shares.iter_mut().for_each(|share| share.zeroize());
+        Self::checked(secret, key.y)
+    }
+
+    /// Reconstructs a Lindell 2017 key. `public_key` must come from a key share, not from
+    /// the sender of either contribution.
+    pub fn reconstruct_two_party(
+        party_one: &Contribution,
+        party_two: &Contribution,
+        public_key: &GE,
+    ) -> Result<Self, Error> {
+        if party_one.party != PARTY_ONE || party_two.party != PARTY_TWO {
+            return Err(InvalidKey);
+        }
+        Self::checked(party_one.share * party_two.share, *public_key)
+    }
+
+    fn checked(mut secret: FE, public_key: GE) -> Result<Self, Error> {
+        if GE::generator() * secret != public_key {
+            secret.zeroize();
+            return Err(InvalidKey);
+        }
+        Ok(ExportedKey { secret, public_key })
+    }
+
+    pub fn public_key(&self) -> GE {
+        self.public_key
+    }
+
+    /// 32 bytes big endian.
+    pub fn to_bytes(&self) -> [u8; 32] {
+        to_bytes_32(&self.secret.to_big_int())
+    }
+
+    pub fn to_sec1_der(&self) -> Vec<u8> {
+        encoding::secret_key_to_sec1_der(&self.secret)
+    }
+
+    pub fn to_pkcs8_der(&self) -> Vec<u8> {
+        encoding::secret_key_to_pkcs8_der(&self.secret)
+    }
+
+    pub fn to_sec1_pem(&self) -> String {
+        encoding::secret_key_to_sec1_pem(&self.secret)
+    }
+
+    pub fn to_pkcs8_pem(&self) -> String {
+        encoding::secret_key_to_pkcs8_pem(&self.secret)
+    }
+}
+
+impl Drop for ExportedKey {
+    fn drop(&mut self) {
+        self.secret.zeroize();
+    }
+}
+
+#[cfg(test)]
+mod test;
@@ -0,0 +1,109 @@
+use curv::arithmetic::traits::*;
+use curv::elliptic::curves::p256::{FE, GE};
+use curv::elliptic::curves::traits::*;
+use curv::BigInt;
+
+use super::{Contribution, ExportedKey};
+use crate::protocols::import::Dealer;
+use crate::protocols::multi_party_ecdsa::gg_2018::import::Import;
+use crate::protocols::multi_party_ecdsa::gg_2018::signer::LocalKey;
+use crate::protocols::threshold_signer::testing::{run, SECRET_KEY};
+use crate::protocols::threshold_signer::KeyShare;
+use crate::protocols::two_party_ecdsa::lindell_2017::signer::{self as lindell, Keygen};
+use crate::utilities::encoding;
+
+fn secret_key() -> FE {
+    ECScalar::from(&BigInt::from_str_radix(SECRET_KEY, 16).unwrap())
+}
+
+// the known key imported into a 1-of-3 sharing, so the export can be compared with it
+fn gg18_keys() -> Vec<LocalKey> {
+    let dealer = Dealer::new(secret_key()).unwrap();
+    let y = dealer.public_key();
+    let imports = dealer
+        .deal_gg18(1, 3)
+        .unwrap()
+        .into_iter()
+        .enumerate()
+        .map(|(i, dealing)| Import::new(i as u16, 1, 3, y, dealing).unwrap())
+        .collect();
+    run(imports).unwrap()
+}
+
+// contributions are sent to whoever reconstructs
+fn send(contribution: Contribution) -> Contribution {
+    serde_json::from_str(&serde_json::to_string(&contribution).unwrap()).unwrap()
+}
+
+#[test]
+fn test_export_gg18() {
+    let keys = gg18_keys();
+    let x = secret_key();
+    for parties in &[vec![0, 2], vec![2, 1, 0]] {
+        let contributions: Vec<Contribution> = parties
+            .iter()
+            .map(|i| send(Contribution::reveal_gg18(&keys[*i])))
+            .collect();
+        let exported = ExportedKey::reconstruct_gg18(&keys[1], &contributions).unwrap();
+        assert_eq!(exported.public_key(), keys[0].y);
+        assert_eq!(
+            BigInt::from_bytes(&exported.to_bytes()),
+            BigInt::from_str_radix(SECRET_KEY, 16).unwrap()
+        );
+        assert_eq!(exported.to_sec1_der(), encoding::secret_key_to_sec1_der(&x));
+        assert_eq!(
+            exported.to_pkcs8_der(),
+            encoding::secret_key_to_pkcs8_der(&x)
+        );
+        assert_eq!(
+            encoding::secret_key_from_pem(&exported.to_sec1_pem()).unwrap(),
+            x
+        );
+        assert_eq!(
+            encoding::secret_key_from_pem(&exported.to_pkcs8_pem()).unwrap(),
+            x
+        );
+    }
+}
+
+#[test]
+fn test_export_gg18_rejects_bad_contributions() {
+    let keys = gg18_keys();
+    let reveal = |i: usize| Contribution::reveal_gg18(&keys[i]);
+
+    // too few, or the same party twice
+    assert!(ExportedKey::reconstruct_gg18(&keys[0], &[reveal(0)]).is_err());
+    assert!(ExportedKey::reconstruct_gg18(&keys[0], &[reveal(0), reveal(0)]).is_err());
+
+    // a share that does not match its commitment
+    let mut wrong = reveal(2);
+    wrong.share = wrong.share + FE::new_random();
+    assert!(ExportedKey::reconstruct_gg18(&keys[0], &[reveal(0), wrong]).is_err());
+
+    // a valid share claimed by another party
+    let mut wrong = reveal(2);
+    wrong.party = 1;
+    assert!(ExportedKey::reconstruct_gg18(&keys[0], &[reveal(0), wrong]).is_err());
+}
+
+#[test]
+fn test_export_two_party() {
+    let keygens = (0..2).map(|i| Keygen::new(i).unwrap()).collect();
+    let keys = run(keygens).unwrap();
+    let y = keys[0].public_key();
+    let (party_one, party_two) = match (&keys[0], &keys[1]) {
+        (lindell::LocalKey::PartyOne(p1), lindell::LocalKey::PartyTwo(p2)) => (
+            send(Contribution::reveal_party_one(p1)),
+            send(Contribution::reveal_party_two(p2)),
+        ),
+        _ => unreachable!(),
+    };
+
+    let exported = ExportedKey::reconstruct_two_party(&party_one, &party_two, &y).unwrap();
+    let x = encoding::secret_key_from_sec1_der(&exported.to_sec1_der()).unwrap();
+    assert_eq!(GE::generator() * x, y);
+
+    assert!(ExportedKey::reconstruct_two_party(&party_two, &party_one, &y).is_err());
+    let other: GE = GE::generator() * FE::new_random();
+    assert!(ExportedKey::reconstruct_two_party(&party_one, &party_two, &other).is_err());
+}
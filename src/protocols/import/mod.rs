@@ -0,0 +1,139 @@
+/*
+    Multi-party ECDSA
+
+    Copyright 2018 by Kzen Networks
+
+    This file is part of Multi-party ECDSA library
+    (https://github.com/KZen-networks/multi-party-ecdsa)
+
+    Multi-party ECDSA is free software: you can redistribute
+    it and/or modify it under the terms of the GNU General Public
+    License as published by the Free Software Foundation, either
+    version 3 of the License, or (at your option) any later version.
+
+    @license GPL-3.0+ <https://github.com/KZen-networks/multi-party-ecdsa/blob/master/LICENSE>
+*/
+
+//! Import of an existing P-256 private key through a trusted dealer.
+//!
+//! The dealer decodes the key and splits it. Dealing consumes the dealer, which wipes its
+//! copy of the secret. Every recipient checks its shares against the public key of the
+//! imported key, which it must learn from the key owner rather than from the dealt shares:
+//!
+//! - `deal_gg18` gives every party the dealings of a GG18 key, one Feldman VSS per slot
+//!   with constant terms that add up to the public key. The parties then run
+//!   `gg_2018::import::Import`, which adds their Paillier keys.
+//! - `deal_two_party` splits the key multiplicatively, x = x1 * x2, for `Keygen::import`
+//!   of Lindell 2017 and CCLST, or the fixed secret share keygen of DKLs. The keygen fails
+//!   for both parties unless x1 x2 G is the public key.
+//!
+//! The shares must reach the parties over private and authenticated channels.
+
+use curv::cryptographic_primitives::secret_sharing::feldman_vss::VerifiableSS;
+use curv::elliptic::curves::p256::{FE, GE};
+use curv::elliptic::curves::traits::*;
+use serde::{Deserialize, Serialize};
+use zeroize::Zeroize;
+
+use crate::utilities::encoding;
+use crate::Error::{self, InvalidKey};
+
+/// The VSS scheme of one slot and the receiver's share of it.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct SlotDealing {
+    pub slot: u16,
+    pub vss_scheme: VerifiableSS<GE>,
+    pub share: FE,
+}
+
+pub struct Dealer {
+    secret: FE,
+    public_key: GE,
+}
+
+impl Dealer {
+    pub fn new(secret: FE) -> Result<Self, Error> {
+        if secret == FE::zero() {
+            return Err(InvalidKey);
+        }
+        Ok(Dealer {
+            secret,
+            public_key: GE::generator() * secret,
+        })
+    }
+
+    /// 32 bytes big endian.
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, Error> {
+        Self::new(encoding::secret_key_from_bytes(bytes)?)
+    }
+
+    pub fn from_sec1_der(bytes: &[u8]) -> Result<Self, Error> {
+        Self::new(encoding::secret_key_from_sec1_der(bytes)?)
+    }
+
+    pub fn from_pkcs8_der(bytes: &[u8]) -> Result<Self, Error> {
+        Self::new(encoding::secret_key_from_pkcs8_der(bytes)?)
+    }
+
+    /// SEC1 or PKCS#8 PEM.
+    pub fn from_pem(pem: &str) -> Result<Self, Error> {
+        Self::new(encoding::secret_key_from_pem(pem)?)
+    }
+
+    pub fn public_key(&self) -> GE {
+        self.public_key
+    }
+
+    /// The dealings of every party, indexed by party, for a key any `threshold + 1` out of
+    /// `share_count` parties can sign with.
+    pub fn deal_gg18(
+        self,
+        threshold: u16,
+        share_count: u16,
+    ) -> Result<Vec<Vec<SlotDealing>>, Error> {
+        if share_count < 2 || threshold >= share_count {
+            return Err(InvalidKey);
+        }
+        let mut dealings = vec![Vec::with_capacity(share_count as usize); share_count as usize];
+        let mut rest = self.secret;
+        for slot in (0..share_count).rev() {
+            let mut secret: FE = if slot == 0 {
+                rest
+            } else {
+                ECScalar::new_random()
+            };
+            rest = rest.sub(&secret.get_element());
+            let (vss_scheme, shares) =
+                VerifiableSS::share(threshold as usize, share_count as usize, &secret);
+            for (j, share) in shares.into_iter().enumerate() {
+                dealings[j].push(SlotDealing {
+                    slot,
+                    vss_scheme: vss_scheme.clone(),
+                    share,
+                });
+            }
+            secret.zeroize();
+        }
+        rest.zeroize();
+        for dealing in dealings.iter_mut() {
+            dealing.reverse();
+        }
+        Ok(dealings)
+    }
+
+    /// (x1, x2) for party one and party two.
+    pub fn deal_two_party(self) -> (FE, FE) {
+        let x1: FE = ECScalar::new_random();
+        let x2 = x1.invert() * self.secret;
+        (x1, x2)
+    }
+}
+
+impl Drop for Dealer {
+    fn drop(&mut self) {
+        self.secret.zeroize();
+    }
+}
+
+#[cfg(test)]
+mod test;
@@ -0,0 +1,95 @@
+use curv::arithmetic::traits::*;
+use curv::elliptic::curves::p256::{FE, GE};
+use curv::elliptic::curves::traits::*;
+use curv::BigInt;
+
+use super::Dealer;
+use crate::protocols::multi_party_ecdsa::gg_2018::import::Import;
+use crate::protocols::multi_party_ecdsa::gg_2018::signer::Gg18;
+use crate::protocols::threshold_signer::testing::{run, SECRET_KEY};
+use crate::protocols::threshold_signer::{KeyShare, ThresholdSigner};
+use crate::protocols::two_party_ecdsa::lindell_2017::signer::{Keygen, Lindell2017};
+
+fn dealer() -> Dealer {
+    let bytes = BigInt::from_str_radix(SECRET_KEY, 16).unwrap().to_bytes();
+    Dealer::from_bytes(&bytes).unwrap()
+}
+
+#[test]
+fn test_import_gg18() {
+    let dealer = dealer();
+    let y = dealer.public_key();
+    let dealings = dealer.deal_gg18(1, 3).unwrap();
+    let imports = dealings
+        .into_iter()
+        .enumerate()
+        .map(|(i, dealing)| Import::new(i as u16, 1, 3, y, dealing).unwrap())
+        .collect();
+    let keys = run(imports).unwrap();
+    for key in &keys {
+        assert_eq!(key.public_key(), y);
+    }
+
+    let message = BigInt::from(1234);
+    let signers = [0, 2];
+    let signings = signers
+        .iter()
+        .map(|i| Gg18::sign(&keys[*i as usize], &signers, &message).unwrap())
+        .collect();
+    for signature in run::<<Gg18 as ThresholdSigner>::Signing>(signings).unwrap() {
+        signature.verify(&y, &message).expect("invalid signature");
+    }
+}
+
+#[test]
+fn test_import_gg18_rejects_bad_dealing() {
+    let dealer = dealer();
+    let y = dealer.public_key();
+    let mut dealings = dealer.deal_gg18(1, 3).unwrap();
+
+    let other: GE = GE::generator() * FE::new_random();
+    assert!(Import::new(0, 1, 3, other, dealings[0].clone()).is_err());
+    assert!(Import::new(0, 2, 3, y, dealings[0].clone()).is_err());
+    assert!(Import::new(1, 1, 3, y, dealings[0].clone()).is_err());
+
+    dealings[2][1].share = dealings[2][1].share + FE::new_random();
+    assert!(Import::new(2, 1, 3, y, dealings[2].clone()).is_err());
+}
+
+#[test]
+fn test_import_two_party() {
+    let dealer = dealer();
+    let y = dealer.public_key();
+    let (x1, x2) = dealer.deal_two_party();
+    let keygens = vec![
+        Keygen::import(0, x1, y).unwrap(),
+        Keygen::import(1, x2, y).unwrap(),
+    ];
+    let keys = run(keygens).unwrap();
+    for key in &keys {
+        assert_eq!(key.public_key(), y);
+    }
+
+    let message = BigInt::from(1234);
+    let signings = keys
+        .iter()
+        .map(|key| Lindell2017::sign(key, &[0, 1], &message).unwrap())
+        .collect();
+    for signature in run::<<Lindell2017 as ThresholdSigner>::Signing>(signings).unwrap() {
+        signature.verify(&y, &message).expect("invalid signature");
+    }
+
+    // a share that does not match the published key fails the keygen
+    let (x1, x2) = Dealer::new(FE::new_random()).unwrap().deal_two_party();
+    let keygens = vec![
+        Keygen::import(0, x1, y).unwrap(),
+        Keygen::import(1, x2, y).unwrap(),
+    ];
+    assert!(run(keygens).is_err());
+}
+
+#[test]
+fn test_dealer_rejects_zero() {
+    assert!(Dealer::new(FE::zero()).is_err());
+    assert!(Dealer::from_bytes(&[0u8; 32]).is_err());
+}
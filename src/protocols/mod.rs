@@ -14,5 +14,8 @@
     @license GPL-3.0+ <https://github.com/KZen-networks/multi-party-ecdsa/blob/master/LICENSE>
 */
 
+pub mod export;
+pub mod import;
 pub mod multi_party_ecdsa;
+pub mod threshold_signer;
 pub mod two_party_ecdsa;
@@ -0,0 +1,304 @@
+/*
+    Multi-party ECDSA
+
+    Copyright 2018 by Kzen Networks
+
+    This file is part of Multi-party ECDSA library
+    (https://github.com/KZen-networks/multi-party-ecdsa)
+
+    Multi-party ECDSA is free software: you can redistribute
+    it and/or modify it under the terms of the GNU General Public
+    License as published by the Free Software Foundation, either
+    version 3 of the License, or (at your option) any later version.
+
+    @license GPL-3.0+ <https://github.com/KZen-networks/multi-party-ecdsa/blob/master/LICENSE>
+*/
+
+//! Generation of the auxiliary info of CGGMP21 as a [`Session`]: every party publishes a
+//! Paillier modulus made of safe primes, ring-Pedersen parameters over the same modulus and
+//! proofs of both.
+//!
+//! ```text
+//! round 1  broadcast  hash commitment to N_i, s_i, t_i, the Π-prm proof and rho_i
+//! round 2  broadcast  decommitment
+//! round 3  broadcast  Π-mod proof for N_i, bound to rho = XOR of all rho_i, and the echo
+//!                     of the commitments of round 1
+//! ```
+//!
+//! A party that hands out different commitments is caught by the echo, the session then
+//! fails with `MessageMismatch` and [`AuxInfoGen::culprits`] names the parties whose echo
+//! differs from ours.
+//!
+//! The session can be run again at any time to replace the Paillier keys. It does not
+//! refresh the key shares.
+
+use curv::arithmetic::traits::*;
+use curv::cryptographic_primitives::commitments::hash_commitment::HashCommitment;
+use curv::cryptographic_primitives::commitments::traits::Commitment;
+use curv::BigInt;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+use super::paillier::{PaillierKey, PaillierPublic, RingPedersen};
+use super::zk::{ModProof, PrmProof};
+use super::{echo, MIN_PAILLIER_BITS};
+use crate::protocols::threshold_signer::{self, broadcast, Msg, Session};
+use crate::utilities::ot::{self, Seed};
+use crate::Error::{self, InvalidCom, InvalidKey, InvalidMessage, MessageMismatch};
+
+const SECURITY: usize = 256;
+
+/// The public parameters of a party.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct PartyAux {
+    pub paillier: PaillierPublic,
+    pub ring_pedersen: RingPedersen,
+}
+
+/// The output of [`AuxInfoGen`], kept next to the [`super::keygen::LocalKey`].
+#[derive(Clone, Serialize, Deserialize)]
+pub struct AuxInfo {
+    pub party_index: u16,
+    pub paillier_key: PaillierKey,
+    pub parties: Vec<PartyAux>,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct AuxDecommitment {
+    pub ring_pedersen: RingPedersen,
+    pub prm_proof: PrmProof,
+    pub rho: Seed,
+    pub blind_factor: BigInt,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub enum AuxInfoMessage {
+    Commitment(BigInt),
+    Decommitment(AuxDecommitment),
+    ModProof(ModProof, Seed),
+}
+
+pub struct AuxInfoGen {
+    party: u16,
+    others: Vec<u16>,
+    round: u16,
+    paillier_key: PaillierKey,
+    decommitment: Option<AuxDecommitment>,
+    commitments: Vec<BigInt>,
+    decommitments: Vec<AuxDecommitment>,
+    rho: Seed,
+    echo: Seed,
+    culprits: Vec<u16>,
+    output: Option<AuxInfo>,
+}
+
+impl AuxInfoGen {
+    /// Generates a fresh Paillier key, which takes a while.
+    pub fn new(party: u16, share_count: u16) -> Result<Self, Error> {
+        Self::with_key(party, share_count, PaillierKey::generate())
+    }
+
+    /// Like `new`, with a Paillier key generated ahead of time from two safe primes.
+    pub fn with_key(
+        party: u16,
+        share_count: u16,
+        paillier_key: PaillierKey,
+    ) -> Result<Self, Error> {
+        if party >= share_count || paillier_key.public().n.bit_length() < MIN_PAILLIER_BITS {
+            return Err(InvalidKey);
+        }
+        let (ring_pedersen, lambda) = RingPedersen::generate(&paillier_key);
+        let prm_proof =
+            PrmProof::prove(&ring_pedersen, &lambda, &paillier_key, &party.to_be_bytes());
+        Ok(AuxInfoGen {
+            party,
+            others: (0..share_count).filter(|j| *j != party).collect(),
+            round: 0,
+            paillier_key,
+            decommitment: Some(AuxDecommitment {
+                ring_pedersen,
+                prm_proof,
+                rho: ot::random_seed(),
+                blind_factor: BigInt::sample(SECURITY),
+            }),
+            commitments: Vec::new(),
+            decommitments: Vec::new(),
+            rho: [0u8; 32],
+            echo: [0u8; 32],
+            culprits: Vec::new(),
+            output: None,
+        })
+    }
+
+    /// The parties whose echo of round 1 differs from ours, once `proceed` has returned
+    /// `MessageMismatch`.
+    pub fn culprits(&self) -> &[u16] {
+        &self.culprits
+    }
+}
+
+fn commit(party: u16, decommitment: &AuxDecommitment) -> BigInt {
+    let rp = &decommitment.ring_pedersen;
+    let proof = &decommitment.prm_proof;
+    let hash = [&rp.n, &rp.s, &rp.t]
+        .iter()
+        .cloned()
+        .chain(proof.a.iter())
+        .chain(proof.z.iter())
+        .fold(Sha256::new().chain(party.to_be_bytes()), |hash, x| {
+            let bytes = x.to_bytes();
+            hash.chain((bytes.len() as u64).to_be_bytes()).chain(bytes)
+        })
+        .chain(decommitment.rho)
+        .finalize();
+    HashCommitment::create_commitment_with_user_defined_randomness(
+        &BigInt::from_bytes(&hash),
+        &decommitment.blind_factor,
+    )
+}
+
+fn mod_aux(rho: &Seed, party: u16) -> Vec<u8> {
+    [&rho[..], &party.to_be_bytes()].concat()
+}
+
+impl Session for AuxInfoGen {
+    type Message = AuxInfoMessage;
+    type Output = AuxInfo;
+
+    fn party(&self) -> u16 {
+        self.party
+    }
+
+    fn round(&self) -> u16 {
+        self.round
+    }
+
+    fn expected_senders(&self) -> Vec<u16> {
+        match self.round {
+            1..=3 => self.others.clone(),
+            _ => Vec::new(),
+        }
+    }
+
+    fn proceed(
+        &mut self,
+        incoming: Vec<Msg<AuxInfoMessage>>,
+    ) -> Result<Vec<Msg<AuxInfoMessage>>, Error> {
+        let party = self.party;
+        let index = party as usize;
+        let outgoing = match self.round {
+            0 => {
+                let com = commit(party, self.decommitment.as_ref().unwrap());
+                vec![broadcast(party, 1, AuxInfoMessage::Commitment(com))]
+            }
+            1 => {
+                self.commitments =
+                    threshold_signer::collect(incoming, self.party(), 1, &self.others, |body| {
+                        match body {
+                            AuxInfoMessage::Commitment(com) => Some(com),
+                            _ => None,
+                        }
+                    })?;
+                let decommitment = self.decommitment.clone().unwrap();
+                vec![broadcast(
+                    party,
+                    2,
+                    AuxInfoMessage::Decommitment(decommitment),
+                )]
+            }
+            2 => {
+                let mut decommitments =
+                    threshold_signer::collect(incoming, self.party(), 2, &self.others, |body| {
+                        match body {
+                            AuxInfoMessage::Decommitment(decommitment) => Some(decommitment),
+                            _ => None,
+                        }
+                    })?;
+                let valid_com = self
+                    .others
+                    .iter()
+                    .zip(&self.commitments)
+                    .zip(&decommitments)
+                    .all(|((j, com), decommitment)| commit(*j, decommitment) == *com);
+                if !valid_com {
+                    return Err(InvalidCom);
+                }
+                for (j, decommitment) in self.others.iter().zip(&decommitments) {
+                    if decommitment.ring_pedersen.n.bit_length() < MIN_PAILLIER_BITS {
+                        return Err(InvalidKey);
+                    }
+                    decommitment
+                        .prm_proof
+                        .verify(&decommitment.ring_pedersen, &j.to_be_bytes())?;
+                }
+                let own = self.decommitment.take().unwrap();
+                let mut commitments = self.commitments.clone();
+                commitments.insert(index, commit(party, &own));
+                decommitments.insert(index, own);
+                let mut rho = [0u8; 32];
+                for decommitment in &decommitments {
+                    for (byte, other) in rho.iter_mut().zip(decommitment.rho.iter()) {
+                        *byte ^= other;
+                    }
+                }
+                let proof = ModProof::prove(&self.paillier_key, &mod_aux(&rho, party));
+                self.rho = rho;
+                self.echo = echo(&commitments);
+                self.decommitments = decommitments;
+                vec![broadcast(
+                    party,
+                    3,
+                    AuxInfoMessage::ModProof(proof, self.echo),
+                )]
+            }
+            3 => {
+                let proofs =
+                    threshold_signer::collect(incoming, self.party(), 3, &self.others, |body| {
+                        match body {
+                            AuxInfoMessage::ModProof(proof, echo) => Some((proof, echo)),
+                            _ => None,
+                        }
+                    })?;
+                self.culprits = self
+                    .others
+                    .iter()
+                    .zip(&proofs)
+                    .filter(|(_, (_, echo))| *echo != self.echo)
+                    .map(|(j, _)| *j)
+                    .collect();
+                if !self.culprits.is_empty() {
+                    return Err(MessageMismatch);
+                }
+                for (j, (proof, _)) in self.others.iter().zip(&proofs) {
+                    let n = &self.decommitments[*j as usize].ring_pedersen.n;
+                    proof.verify(n, &mod_aux(&self.rho, *j))?;
+                }
+                let parties = self
+                    .decommitments
+                    .drain(..)
+                    .map(|decommitment| PartyAux {
+                        paillier: PaillierPublic::new(decommitment.ring_pedersen.n.clone()),
+                        ring_pedersen: decommitment.ring_pedersen,
+                    })
+                    .collect();
+                self.output = Some(AuxInfo {
+                    party_index: party,
+                    paillier_key: self.paillier_key.clone(),
+                    parties,
+                });
+                Vec::new()
+            }
+            _ => return Err(InvalidMessage),
+        };
+        self.round += 1;
+        Ok(outgoing)
+    }
+
+    fn is_finished(&self) -> bool {
+        self.round > 3
+    }
+
+    fn pick_output(&mut self) -> Option<AuxInfo> {
+        self.output.take()
+    }
+}
@@ -0,0 +1,350 @@
+/*
+    Multi-party ECDSA
+
+    Copyright 2018 by Kzen Networks
+
+    This file is part of Multi-party ECDSA library
+    (https://github.com/KZen-networks/multi-party-ecdsa)
+
+    Multi-party ECDSA is free software: you can redistribute
+    it and/or modify it under the terms of the GNU General Public
+    License as published by the Free Software Foundation, either
+    version 3 of the License, or (at your option) any later version.
+
+    @license GPL-3.0+ <https://github.com/KZen-networks/multi-party-ecdsa/blob/master/LICENSE>
+*/
+
+//! CGGMP21 keygen as a [`Session`], a Feldman VSS of a random secret per party with
+//! commitments sent ahead:
+//!
+//! ```text
+//! round 1  broadcast  hash commitment to the VSS commitments, rid_i and A_i
+//! round 2  p2p        decommitment and the share of the receiver
+//! round 3  broadcast  Schnorr proof for the constant term, answering A_i under rid, and
+//!                     the echo of the commitments of round 1
+//! ```
+//!
+//! rid is the XOR of all rid_i. It binds the proofs of this and the later phases to the key.
+//! A party that hands out different commitments is caught by the echo, keygen then fails
+//! with `MessageMismatch` and [`Keygen::culprits`] names the parties whose echo differs
+//! from ours.
+
+use curv::arithmetic::traits::*;
+use curv::cryptographic_primitives::commitments::hash_commitment::HashCommitment;
+use curv::cryptographic_primitives::commitments::traits::Commitment;
+use curv::cryptographic_primitives::secret_sharing::feldman_vss::VerifiableSS;
+use curv::elliptic::curves::p256::{FE, GE};
+use curv::elliptic::curves::traits::*;
+use curv::BigInt;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+use super::echo;
+use super::zk::SchProof;
+use crate::protocols::threshold_signer::{self, broadcast, p2p, KeyShare, Msg, Session};
+use crate::utilities::ot::{self, Seed};
+use crate::Error::{self, InvalidCom, InvalidKey, InvalidMessage, InvalidSS, MessageMismatch};
+
+const SECURITY: usize = 256;
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct LocalKey {
+    pub party_index: u16,
+    pub threshold: u16,
+    pub share_count: u16,
+    pub rid: Seed,
+    pub x_i: FE,
+    pub vss_scheme_vec: Vec<VerifiableSS<GE>>,
+    pub y: GE,
+}
+
+impl LocalKey {
+    /// X_j = x_j G for every party j.
+    pub fn public_shares(&self) -> Vec<GE> {
+        (0..self.share_count as usize)
+            .map(|j| {
+                let (head, tail) = self.vss_scheme_vec.split_at(1);
+                tail.iter()
+                    .fold(head[0].get_point_commitment(j + 1), |acc, vss| {
+                        acc + vss.get_point_commitment(j + 1)
+                    })
+            })
+            .collect()
+    }
+}
+
+impl KeyShare for LocalKey {
+    fn party(&self) -> u16 {
+        self.party_index
+    }
+
+    fn public_key(&self) -> GE {
+        self.y
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct KeygenDecommitment {
+    pub vss_scheme: VerifiableSS<GE>,
+    pub rid: Seed,
+    pub a: GE,
+    pub blind_factor: BigInt,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub enum KeygenMessage {
+    Commitment(BigInt),
+    Decommitment(KeygenDecommitment, FE),
+    Proof(SchProof, Seed),
+}
+
+pub struct Keygen {
+    party: u16,
+    threshold: u16,
+    share_count: u16,
+    others: Vec<u16>,
+    round: u16,
+    u_i: FE,
+    alpha: FE,
+    decommitment: Option<KeygenDecommitment>,
+    secret_shares: Vec<FE>,
+    commitments: Vec<BigInt>,
+    decommitments: Vec<KeygenDecommitment>,
+    x_i: FE,
+    rid: Seed,
+    echo: Seed,
+    culprits: Vec<u16>,
+    output: Option<LocalKey>,
+}
+
+impl Keygen {
+    pub fn new(party: u16, threshold: u16, share_count: u16) -> Result<Self, Error> {
+        if party >= share_count || threshold >= share_count {
+            return Err(InvalidKey);
+        }
+        let u_i: FE = ECScalar::new_random();
+        let (vss_scheme, secret_shares) =
+            VerifiableSS::share(threshold as usize, share_count as usize, &u_i);
+        let (alpha, a) = SchProof::commit();
+        let rid = ot::random_seed();
+        Ok(Keygen {
+            party,
+            threshold,
+            share_count,
+            others: (0..share_count).filter(|j| *j != party).collect(),
+            round: 0,
+            u_i,
+            alpha,
+            decommitment: Some(KeygenDecommitment {
+                vss_scheme,
+                rid,
+                a,
+                blind_factor: BigInt::sample(SECURITY),
+            }),
+            secret_shares,
+            commitments: Vec::new(),
+            decommitments: Vec::new(),
+            x_i: FE::zero(),
+            rid: [0u8; 32],
+            echo: [0u8; 32],
+            culprits: Vec::new(),
+            output: None,
+        })
+    }
+
+    /// The parties whose echo of round 1 differs from ours, once `proceed` has returned
+    /// `MessageMismatch`.
+    pub fn culprits(&self) -> &[u16] {
+        &self.culprits
+    }
+}
+
+fn commit(party: u16, decommitment: &KeygenDecommitment) -> BigInt {
+    let hash = decommitment
+        .vss_scheme
+        .commitments
+        .iter()
+        .fold(Sha256::new().chain(party.to_be_bytes()), |hash, c| {
+            hash.chain(c.bytes_compressed_to_big_int().to_bytes())
+        })
+        .chain(decommitment.rid)
+        .chain(decommitment.a.bytes_compressed_to_big_int().to_bytes())
+        .finalize();
+    HashCommitment::create_commitment_with_user_defined_randomness(
+        &BigInt::from_bytes(&hash),
+        &decommitment.blind_factor,
+    )
+}
+
+fn proof_aux(rid: &Seed, party: u16) -> Vec<u8> {
+    [&rid[..], &party.to_be_bytes()].concat()
+}
+
+impl Session for Keygen {
+    type Message = KeygenMessage;
+    type Output = LocalKey;
+
+    fn party(&self) -> u16 {
+        self.party
+    }
+
+    fn round(&self) -> u16 {
+        self.round
+    }
+
+    fn expected_senders(&self) -> Vec<u16> {
+        match self.round {
+            1..=3 => self.others.clone(),
+            _ => Vec::new(),
+        }
+    }
+
+    fn proceed(
+        &mut self,
+        incoming: Vec<Msg<KeygenMessage>>,
+    ) -> Result<Vec<Msg<KeygenMessage>>, Error> {
+        let party = self.party;
+        let index = party as usize;
+        let outgoing = match self.round {
+            0 => {
+                let com = commit(party, self.decommitment.as_ref().unwrap());
+                vec![broadcast(party, 1, KeygenMessage::Commitment(com))]
+            }
+            1 => {
+                self.commitments =
+                    threshold_signer::collect(incoming, self.party(), 1, &self.others, |body| {
+                        match body {
+                            KeygenMessage::Commitment(com) => Some(com),
+                            _ => None,
+                        }
+                    })?;
+                let decommitment = self.decommitment.clone().unwrap();
+                self.others
+                    .iter()
+                    .map(|j| {
+                        let share = self.secret_shares[*j as usize];
+                        let body = KeygenMessage::Decommitment(decommitment.clone(), share);
+                        p2p(party, *j, 2, body)
+                    })
+                    .collect()
+            }
+            2 => {
+                let received =
+                    threshold_signer::collect(incoming, self.party(), 2, &self.others, |body| {
+                        match body {
+                            KeygenMessage::Decommitment(decommitment, share) => {
+                                Some((decommitment, share))
+                            }
+                            _ => None,
+                        }
+                    })?;
+                let valid_com = self
+                    .others
+                    .iter()
+                    .zip(&self.commitments)
+                    .zip(&received)
+                    .all(|((j, com), (decommitment, _))| commit(*j, decommitment) == *com);
+                if !valid_com {
+                    return Err(InvalidCom);
+                }
+                let valid_ss = received.iter().all(|(decommitment, share)| {
+                    let vss = &decommitment.vss_scheme;
+                    vss.parameters.threshold == self.threshold as usize
+                        && vss.parameters.share_count == self.share_count as usize
+                        && vss.commitments.len() == self.threshold as usize + 1
+                        && vss.validate_share(share, index + 1).is_ok()
+                });
+                if !valid_ss {
+                    return Err(InvalidSS);
+                }
+
+                let own = self.decommitment.take().unwrap();
+                let mut commitments = self.commitments.clone();
+                commitments.insert(index, commit(party, &own));
+                let x_i = received
+                    .iter()
+                    .fold(self.secret_shares[index], |acc, (_, share)| acc + share);
+                let mut decommitments: Vec<KeygenDecommitment> = received
+                    .into_iter()
+                    .map(|(decommitment, _)| decommitment)
+                    .collect();
+                decommitments.insert(index, own);
+                let mut rid = [0u8; 32];
+                for decommitment in &decommitments {
+                    for (byte, other) in rid.iter_mut().zip(decommitment.rid.iter()) {
+                        *byte ^= other;
+                    }
+                }
+                let proof = SchProof::prove(
+                    &self.alpha,
+                    &self.u_i,
+                    &decommitments[index].a,
+                    &proof_aux(&rid, party),
+                );
+                self.x_i = x_i;
+                self.rid = rid;
+                self.echo = echo(&commitments);
+                self.decommitments = decommitments;
+                vec![broadcast(party, 3, KeygenMessage::Proof(proof, self.echo))]
+            }
+            3 => {
+                let proofs =
+                    threshold_signer::collect(incoming, self.party(), 3, &self.others, |body| {
+                        match body {
+                            KeygenMessage::Proof(proof, echo) => Some((proof, echo)),
+                            _ => None,
+                        }
+                    })?;
+                self.culprits = self
+                    .others
+                    .iter()
+                    .zip(&proofs)
+                    .filter(|(_, (_, echo))| *echo != self.echo)
+                    .map(|(j, _)| *j)
+                    .collect();
+                if !self.culprits.is_empty() {
+                    return Err(MessageMismatch);
+                }
+                for (j, (proof, _)) in self.others.iter().zip(&proofs) {
+                    let decommitment = &self.decommitments[*j as usize];
+                    proof.verify(
+                        &decommitment.a,
+                        &decommitment.vss_scheme.commitments[0],
+                        &proof_aux(&self.rid, *j),
+                    )?;
+                }
+
+                let vss_scheme_vec: Vec<VerifiableSS<GE>> = self
+                    .decommitments
+                    .drain(..)
+                    .map(|decommitment| decommitment.vss_scheme)
+                    .collect();
+                let (head, tail) = vss_scheme_vec.split_at(1);
+                let y = tail
+                    .iter()
+                    .fold(head[0].commitments[0], |acc, vss| acc + vss.commitments[0]);
+                self.output = Some(LocalKey {
+                    party_index: party,
+                    threshold: self.threshold,
+                    share_count: self.share_count,
+                    rid: self.rid,
+                    x_i: self.x_i,
+                    vss_scheme_vec,
+                    y,
+                });
+                Vec::new()
+            }
+            _ => return Err(InvalidMessage),
+        };
+        self.round += 1;
+        Ok(outgoing)
+    }
+
+    fn is_finished(&self) -> bool {
+        self.round > 3
+    }
+
+    fn pick_output(&mut self) -> Option<LocalKey> {
+        self.output.take()
+    }
+}
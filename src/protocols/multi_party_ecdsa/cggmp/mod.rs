@@ -0,0 +1,66 @@
+/*
+    Multi-party ECDSA
+
+    Copyright 2018 by Kzen Networks
+
+    This file is part of Multi-party ECDSA library
+    (https://github.com/KZen-networks/multi-party-ecdsa)
+
+    Multi-party ECDSA is free software: you can redistribute
+    it and/or modify it under the terms of the GNU General Public
+    License as published by the Free Software Foundation, either
+    version 3 of the License, or (at your option) any later version.
+
+    @license GPL-3.0+ <https://github.com/KZen-networks/multi-party-ecdsa/blob/master/LICENSE>
+*/
+
+// CGGMP21 (https://eprint.iacr.org/2021/060) threshold ECDSA. Keygen shares the key with a
+// Feldman VSS, and the aux info session publishes a Paillier key and ring-Pedersen
+// parameters per party, proven well formed with Π-mod and Π-prm. Presigning takes three
+// rounds, plus a fourth one that names the cheating signers when the presignature comes
+// out inconsistent. Signing a message with a presignature is then a single message per
+// signer, which needs no interaction beyond it.
+//
+// There is no reliable broadcast channel in this crate. Every session carries a hash of
+// the broadcasts it depends on, as received, in its last round, and gives no output before
+// all hashes agree.
+
+use curv::arithmetic::traits::*;
+use curv::BigInt;
+use sha2::{Digest, Sha256};
+
+use crate::utilities::ot::Seed;
+
+/// Bits of the secrets proven in range, the size of q.
+pub const ELL: usize = 256;
+/// Bits of the additive masks of the MtA.
+pub const ELL_PRIME: usize = 5 * ELL;
+/// Slack of the range proofs.
+pub const EPSILON: usize = 2 * ELL;
+/// Repetitions of the proofs with binary challenges.
+pub const REPETITIONS: usize = 80;
+/// Smallest accepted Paillier modulus.
+pub const MIN_PAILLIER_BITS: usize = 2047;
+/// Bound on the plaintexts of the ciphertexts of delta_i and chi_i, with room for 2^15
+/// signers.
+pub const SUM_BITS: usize = ELL_PRIME + 16;
+
+pub mod aux_info;
+pub mod keygen;
+pub mod paillier;
+pub mod presign;
+pub mod zk;
+
+#[cfg(test)]
+mod test;
+
+// the hash of the commitments of all parties, in the order of their index
+pub(crate) fn echo(commitments: &[BigInt]) -> Seed {
+    let hash = commitments.iter().fold(Sha256::new(), |hash, com| {
+        let bytes = com.to_bytes();
+        hash.chain((bytes.len() as u64).to_be_bytes()).chain(bytes)
+    });
+    let mut echo = [0u8; 32];
+    echo.copy_from_slice(&hash.finalize());
+    echo
+}
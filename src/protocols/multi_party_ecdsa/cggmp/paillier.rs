@@ -0,0 +1,203 @@
+/*
+    Multi-party ECDSA
+
+    Copyright 2018 by Kzen Networks
+
+    This file is part of Multi-party ECDSA library
+    (https://github.com/KZen-networks/multi-party-ecdsa)
+
+    Multi-party ECDSA is free software: you can redistribute
+    it and/or modify it under the terms of the GNU General Public
+    License as published by the Free Software Foundation, either
+    version 3 of the License, or (at your option) any later version.
+
+    @license GPL-3.0+ <https://github.com/KZen-networks/multi-party-ecdsa/blob/master/LICENSE>
+*/
+
+//! Paillier encryption with the randomness exposed, as needed by the proofs of [`super::zk`],
+//! and the ring-Pedersen parameters every party publishes next to its Paillier key. Both use
+//! the same modulus N = pq, with p and q safe primes.
+
+use curv::arithmetic::traits::*;
+use curv::BigInt;
+use paillier::{KeyGeneration, Paillier};
+use serde::{Deserialize, Serialize};
+
+/// The secret key of a party, that is the factorization of its modulus.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct PaillierKey {
+    p: BigInt,
+    q: BigInt,
+    n: BigInt,
+    nn: BigInt,
+    phi: BigInt,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct PaillierPublic {
+    pub n: BigInt,
+    pub nn: BigInt,
+}
+
+/// Commitments s^x t^r mod n, with s in the group generated by t.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct RingPedersen {
+    pub n: BigInt,
+    pub s: BigInt,
+    pub t: BigInt,
+}
+
+impl PaillierKey {
+    /// Generates a modulus from two fresh safe primes, which takes a while.
+    pub fn generate() -> Self {
+        let (_, dk) = Paillier::keypair_safe_primes().keys();
+        Self::from_primes(dk.p, dk.q)
+    }
+
+    /// `p` and `q` must be safe primes, or the proofs of the parameters will not verify.
+    pub fn from_primes(p: BigInt, q: BigInt) -> Self {
+        let n = &p * &q;
+        let nn = &n * &n;
+        let phi = (&p - BigInt::one()) * (&q - BigInt::one());
+        PaillierKey { p, q, n, nn, phi }
+    }
+
+    pub fn public(&self) -> PaillierPublic {
+        PaillierPublic {
+            n: self.n.clone(),
+            nn: self.nn.clone(),
+        }
+    }
+
+    pub(crate) fn primes(&self) -> (&BigInt, &BigInt) {
+        (&self.p, &self.q)
+    }
+
+    pub(crate) fn phi(&self) -> &BigInt {
+        &self.phi
+    }
+
+    /// The plaintext in [0, n).
+    pub fn decrypt(&self, c: &BigInt) -> BigInt {
+        let u = BigInt::mod_pow(c, &self.phi, &self.nn);
+        let l = (u - BigInt::one()).div_floor(&self.n);
+        BigInt::mod_mul(&l, &BigInt::mod_inv(&self.phi, &self.n).unwrap(), &self.n)
+    }
+
+    /// The plaintext in (-n/2, n/2], for ciphertexts of possibly negative values.
+    pub fn decrypt_signed(&self, c: &BigInt) -> BigInt {
+        let m = self.decrypt(c);
+        if m > self.n.div_floor(&BigInt::from(2)) {
+            m - &self.n
+        } else {
+            m
+        }
+    }
+
+    /// The r such that `c` is the encryption of `m` with randomness r.
+    pub fn randomness(&self, c: &BigInt, m: &BigInt) -> BigInt {
+        // (1 + n)^-m = 1 - m n mod n^2, what is left is r^n
+        let one_minus_mn = (BigInt::one() - m.mod_floor(&self.n) * &self.n).mod_floor(&self.nn);
+        let r_n = BigInt::mod_mul(c, &one_minus_mn, &self.nn).mod_floor(&self.n);
+        let n_inv = BigInt::mod_inv(&self.n, &self.phi).unwrap();
+        BigInt::mod_pow(&r_n, &n_inv, &self.n)
+    }
+}
+
+impl PaillierPublic {
+    pub fn new(n: BigInt) -> Self {
+        let nn = &n * &n;
+        PaillierPublic { n, nn }
+    }
+
+    pub fn encrypt(&self, m: &BigInt) -> (BigInt, BigInt) {
+        let r = sample_unit(&self.n);
+        (self.encrypt_with(m, &r), r)
+    }
+
+    /// (1 + n)^m r^n mod n^2, `m` may be negative.
+    pub fn encrypt_with(&self, m: &BigInt, r: &BigInt) -> BigInt {
+        let g_m = BigInt::one() + m.mod_floor(&self.n) * &self.n;
+        BigInt::mod_mul(&g_m, &BigInt::mod_pow(r, &self.n, &self.nn), &self.nn)
+    }
+
+    /// Whether `c` is a unit mod n^2, as every honestly generated ciphertext is.
+    pub fn is_ciphertext(&self, c: &BigInt) -> bool {
+        c > &BigInt::zero() && c < &self.nn && BigInt::mod_inv(c, &self.nn).is_some()
+    }
+
+    pub fn add(&self, c1: &BigInt, c2: &BigInt) -> BigInt {
+        BigInt::mod_mul(c1, c2, &self.nn)
+    }
+
+    /// c^k, the encryption of k times the plaintext of `c`. `None` if `k` is negative and
+    /// `c` is not invertible.
+    pub fn mul(&self, c: &BigInt, k: &BigInt) -> Option<BigInt> {
+        pow_mod(c, k, &self.nn)
+    }
+}
+
+impl RingPedersen {
+    /// Parameters over the modulus of `key`, with the secret lambda such that s = t^lambda.
+    pub fn generate(key: &PaillierKey) -> (Self, BigInt) {
+        let r = sample_unit(&key.n);
+        let t = BigInt::mod_mul(&r, &r, &key.n);
+        let lambda = BigInt::sample_below(&key.phi);
+        let s = BigInt::mod_pow(&t, &lambda, &key.n);
+        (
+            RingPedersen {
+                n: key.n.clone(),
+                s,
+                t,
+            },
+            lambda,
+        )
+    }
+
+    /// s^x t^r mod n. `None` if an exponent is negative and s or t is not invertible.
+    pub fn commit(&self, x: &BigInt, r: &BigInt) -> Option<BigInt> {
+        Some(BigInt::mod_mul(
+            &pow_mod(&self.s, x, &self.n)?,
+            &pow_mod(&self.t, r, &self.n)?,
+            &self.n,
+        ))
+    }
+}
+
+/// base^exp mod modulus, for a possibly negative `exp`.
+pub(crate) fn pow_mod(base: &BigInt, exp: &BigInt, modulus: &BigInt) -> Option<BigInt> {
+    if exp < &BigInt::zero() {
+        let inv = BigInt::mod_inv(&base.mod_floor(modulus), modulus)?;
+        Some(BigInt::mod_pow(&inv, &(-exp), modulus))
+    } else {
+        Some(BigInt::mod_pow(&base.mod_floor(modulus), exp, modulus))
+    }
+}
+
+/// Uniform in the units mod `n`.
+pub(crate) fn sample_unit(n: &BigInt) -> BigInt {
+    loop {
+        let r = BigInt::sample_below(n);
+        if r > BigInt::zero() && BigInt::mod_inv(&r, n).is_some() {
+            return r;
+        }
+    }
+}
+
+/// Uniform in [-2^bits, 2^bits].
+pub(crate) fn sample_signed(bits: usize) -> BigInt {
+    let bound = BigInt::one() << bits;
+    BigInt::sample_below(&(&bound + &bound + BigInt::one())) - bound
+}
+
+/// Uniform in [-2^bits n, 2^bits n].
+pub(crate) fn sample_signed_times(bits: usize, n: &BigInt) -> BigInt {
+    let bound = (BigInt::one() << bits) * n;
+    BigInt::sample_below(&(&bound + &bound + BigInt::one())) - bound
+}
+
+/// Whether |x| <= 2^bits.
+pub(crate) fn in_range(x: &BigInt, bits: usize) -> bool {
+    let bound = BigInt::one() << bits;
+    x <= &bound && x >= &(-&bound)
+}
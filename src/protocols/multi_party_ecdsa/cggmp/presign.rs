@@ -0,0 +1,940 @@
+#![allow(non_snake_case)]
+/*
+    Multi-party ECDSA
+
+    Copyright 2018 by Kzen Networks
+
+    This file is part of Multi-party ECDSA library
+    (https://github.com/KZen-networks/multi-party-ecdsa)
+
+    Multi-party ECDSA is free software: you can redistribute
+    it and/or modify it under the terms of the GNU General Public
+    License as published by the Free Software Foundation, either
+    version 3 of the License, or (at your option) any later version.
+
+    @license GPL-3.0+ <https://github.com/KZen-networks/multi-party-ecdsa/blob/master/LICENSE>
+*/
+
+//! CGGMP21 presigning with identifiable abort as a [`Session`], and the non-interactive
+//! online signing that consumes its output.
+//!
+//! ```text
+//! round 1  broadcast  K_i = enc_i(k_i), G_i = enc_i(gamma_i), a nonce, Π-enc for every
+//!                     verifier
+//! round 2  broadcast  Gamma_i, and towards every j the MtA ciphertexts of gamma_i and w_i
+//!                     with their Π-aff-g proofs and Π-log* for G_i
+//! round 3  broadcast  delta_i, Delta_i = k_i Gamma, S_i = chi_i Gamma, Π-log* for Delta_i,
+//!                     the echo of rounds 1 and 2
+//! round 4  broadcast  only when delta G != sum Delta_j or sum S_j != delta Y, proofs that
+//!                     delta_i and S_i were computed honestly
+//! ```
+//!
+//! A party whose proof fails is reported by [`Presigning::culprits`]. The MtA ciphertexts
+//! of every pair travel in the broadcast of round 2, which lets every party recompute the
+//! ciphertexts of delta_i and chi_i in round 4. There is no reliable broadcast channel in
+//! this crate, so round 3 carries a hash of every broadcast of rounds 1 and 2 as received,
+//! and nothing of them is used further before all hashes agree. A sender that hands out
+//! different broadcasts cannot be told apart from a signer that lies about what it got, so
+//! that abort is not identifiable: it fails with `MessageMismatch` and names the signers
+//! whose hash differs from ours.
+//!
+//! The proofs of round 1 are bound to the nonce of their prover, all later ones to the
+//! session id, the hash of the nonces of all signers.
+//!
+//! Signing then takes a single message per signer, sigma_i = k_i m + r chi_i, which can be
+//! checked on its own against Delta_i and S_i. Like the presignatures of Lindell 2017, a
+//! presignature must never be used twice, and can be kept in a [`PresignatureStore`].
+//!
+//! [`PresignatureStore`]: crate::utilities::presign::PresignatureStore
+
+use curv::arithmetic::traits::*;
+use curv::cryptographic_primitives::hashing::hash_sha256::HSha256;
+use curv::cryptographic_primitives::hashing::traits::Hash;
+use curv::cryptographic_primitives::secret_sharing::feldman_vss::VerifiableSS;
+use curv::elliptic::curves::p256::{FE, GE};
+use curv::elliptic::curves::traits::*;
+use curv::BigInt;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use zeroize::Zeroize;
+
+use super::aux_info::{AuxInfo, PartyAux};
+use super::keygen::LocalKey;
+use super::paillier::{pow_mod, sample_signed, sample_unit};
+use super::zk::{
+    scalar, AffGProof, AffGStatement, AffGWitness, DecProof, EncProof, LogStarProof, MulProof,
+    MulStarProof,
+};
+use super::{ELL, ELL_PRIME, SUM_BITS};
+use crate::protocols::threshold_signer::{self, broadcast, Msg, Session};
+use crate::utilities::ot::{self, Seed};
+use crate::utilities::presign::{self as presign_store, PresignatureId};
+use crate::utilities::{encoding, recovery};
+use crate::Error::{self, InvalidKey, InvalidMessage, InvalidSig, MessageMismatch, Phase5BadSum};
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct PresignRound1 {
+    pub K: BigInt,
+    pub G: BigInt,
+    pub nonce: Seed,
+    pub enc_proofs: Vec<(u16, EncProof)>,
+}
+
+/// The MtA of the sender towards one other signer.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct MtaPair {
+    pub D: BigInt,
+    pub F: BigInt,
+    pub D_hat: BigInt,
+    pub F_hat: BigInt,
+    pub aff_g: AffGProof,
+    pub aff_g_hat: AffGProof,
+    pub log_star: LogStarProof,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct PresignRound2 {
+    pub Gamma: GE,
+    pub mta: Vec<(u16, MtaPair)>,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct PresignRound3 {
+    pub delta: FE,
+    pub Delta: GE,
+    pub S: GE,
+    pub log_star: Vec<(u16, LogStarProof)>,
+    pub echo: Seed,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct BlameProofs {
+    pub mul_star: MulStarProof,
+    pub dec: DecProof,
+    pub log_star: LogStarProof,
+}
+
+/// H_i = enc_i(k_i gamma_i) and H^_i = enc_i(k_i w_i), from which delta_i and chi_i are
+/// recomputed under encryption.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct PresignBlame {
+    pub H: BigInt,
+    pub H_hat: BigInt,
+    pub mul: MulProof,
+    pub proofs: Vec<(u16, BlameProofs)>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub enum PresignMessage {
+    Round1(PresignRound1),
+    Round2(PresignRound2),
+    Round3(PresignRound3),
+    Blame(PresignBlame),
+}
+
+/// The part of a presignature every signer holds alike, enough to check partial
+/// signatures and to combine them.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct PresignaturePublic {
+    pub R: GE,
+    pub Gamma: GE,
+    pub delta: FE,
+    pub Delta: Vec<GE>,
+    pub S: Vec<GE>,
+    pub signers: Vec<u16>,
+    pub y: GE,
+}
+
+/// A signer's share of a presignature. It cannot be cloned, signing consumes it and k_i and
+/// chi_i are zeroized when it is dropped.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Presignature {
+    pub party_index: u16,
+    k_i: FE,
+    chi_i: FE,
+    pub public: PresignaturePublic,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct PartialSignature {
+    pub party: u16,
+    pub id: PresignatureId,
+    pub sigma: FE,
+}
+
+impl Presignature {
+    pub fn sign(self, message: &BigInt) -> PartialSignature {
+        let m: FE = ECScalar::from(&message.mod_floor(&FE::q()));
+        PartialSignature {
+            party: self.party_index,
+            id: self.public.id(),
+            sigma: self.k_i * m + self.public.r() * self.chi_i,
+        }
+    }
+}
+
+impl Drop for Presignature {
+    fn drop(&mut self) {
+        self.k_i.zeroize();
+        self.chi_i.zeroize();
+    }
+}
+
+impl presign_store::Presignature for Presignature {
+    fn id(&self) -> PresignatureId {
+        self.public.id()
+    }
+}
+
+impl PresignaturePublic {
+    pub fn id(&self) -> PresignatureId {
+        HSha256::create_hash(&[
+            &self.R.bytes_compressed_to_big_int(),
+            &self.Gamma.bytes_compressed_to_big_int(),
+        ])
+    }
+
+    fn r(&self) -> FE {
+        ECScalar::from(&self.R.x_coor().unwrap().mod_floor(&FE::q()))
+    }
+
+    /// sigma_j Gamma = m Delta_j + r S_j.
+    pub fn verify_partial(
+        &self,
+        message: &BigInt,
+        partial: &PartialSignature,
+    ) -> Result<(), Error> {
+        let position = self
+            .signers
+            .iter()
+            .position(|j| *j == partial.party)
+            .ok_or(InvalidMessage)?;
+        if partial.id != self.id() {
+            return Err(InvalidMessage);
+        }
+        let m: FE = ECScalar::from(&message.mod_floor(&FE::q()));
+        if self.Gamma * partial.sigma == self.Delta[position] * m + self.S[position] * self.r() {
+            Ok(())
+        } else {
+            Err(InvalidSig)
+        }
+    }
+
+    /// Combines the partial signatures of all signers. Use `verify_partial` to find out
+    /// which signer to blame on `InvalidSig`.
+    pub fn combine(
+        &self,
+        message: &BigInt,
+        partials: &[PartialSignature],
+    ) -> Result<threshold_signer::Signature, Error> {
+        if partials.len() != self.signers.len()
+            || self
+                .signers
+                .iter()
+                .any(|j| partials.iter().filter(|p| p.party == *j).count() != 1)
+        {
+            return Err(InvalidMessage);
+        }
+        for partial in partials {
+            self.verify_partial(message, partial)?;
+        }
+        let s = partials.iter().fold(FE::zero(), |acc, p| acc + p.sigma);
+        let (s, was_high) = encoding::normalize_s(&s.to_big_int());
+        let signature = threshold_signer::Signature {
+            r: self.r().to_big_int(),
+            s,
+            recid: recovery::compute_recid(&self.R, was_high),
+        };
+        signature.verify(&self.y, message)?;
+        Ok(signature)
+    }
+}
+
+pub struct Presigning {
+    key: LocalKey,
+    aux: AuxInfo,
+    signers: Vec<u16>,
+    others: Vec<u16>,
+    position: usize,
+    round: u16,
+    session_id: Seed,
+    w_i: FE,
+    W: Vec<GE>,
+    k_i: FE,
+    gamma_i: FE,
+    rho_i: BigInt,
+    nu_i: BigInt,
+    own_round1: Option<PresignRound1>,
+    round1: Vec<PresignRound1>,
+    round2: Vec<PresignRound2>,
+    round3: Vec<PresignRound3>,
+    betas: Vec<(BigInt, BigInt)>,
+    Gamma: GE,
+    delta_i: FE,
+    chi_i: FE,
+    culprits: Vec<u16>,
+    output: Option<Presignature>,
+}
+
+impl Presigning {
+    /// `signers` are the 0-based indices of at least `threshold + 1` parties, including
+    /// the owner of `key` and `aux`.
+    pub fn new(key: LocalKey, aux: AuxInfo, signers: &[u16]) -> Result<Self, Error> {
+        let index = key.party_index;
+        let position = signers.iter().position(|j| *j == index).ok_or(InvalidKey)?;
+        let mut sorted = signers.to_vec();
+        sorted.sort_unstable();
+        sorted.dedup();
+        if sorted.len() != signers.len()
+            || signers.len() <= key.threshold as usize
+            || signers.iter().any(|j| *j >= key.share_count)
+            || aux.party_index != index
+            || aux.parties.len() != key.share_count as usize
+        {
+            return Err(InvalidKey);
+        }
+
+        let s: Vec<usize> = signers.iter().map(|j| *j as usize).collect();
+        let params = &key.vss_scheme_vec[index as usize].parameters;
+        let lambda = |j: u16| VerifiableSS::<GE>::map_share_to_new_params(params, j as usize, &s);
+        let w_i = lambda(index) * key.x_i;
+        let public_shares = key.public_shares();
+        let W = signers
+            .iter()
+            .map(|j| public_shares[*j as usize] * lambda(*j))
+            .collect();
+
+        let k_i: FE = ECScalar::new_random();
+        let gamma_i: FE = ECScalar::new_random();
+        let own = &aux.parties[index as usize];
+        let (K, rho_i) = own.paillier.encrypt(&k_i.to_big_int());
+        let (G, nu_i) = own.paillier.encrypt(&gamma_i.to_big_int());
+        let nonce = ot::random_seed();
+
+        let mut presigning = Presigning {
+            others: signers.iter().cloned().filter(|j| *j != index).collect(),
+            signers: signers.to_vec(),
+            position,
+            round: 0,
+            // until round 1 agrees on the session id
+            session_id: nonce,
+            w_i,
+            W,
+            k_i,
+            gamma_i,
+            rho_i,
+            nu_i,
+            own_round1: None,
+            round1: Vec::new(),
+            round2: Vec::new(),
+            round3: Vec::new(),
+            betas: Vec::new(),
+            Gamma: GE::generator(),
+            delta_i: FE::zero(),
+            chi_i: FE::zero(),
+            culprits: Vec::new(),
+            output: None,
+            key,
+            aux,
+        };
+        let enc_proofs = presigning
+            .others
+            .iter()
+            .map(|j| {
+                let proof = EncProof::prove(
+                    &presigning.own().paillier,
+                    &presigning.party_aux(*j).ring_pedersen,
+                    &K,
+                    &presigning.k_i.to_big_int(),
+                    &presigning.rho_i,
+                    &presigning.proof_aux(index),
+                );
+                (*j, proof)
+            })
+            .collect();
+        presigning.own_round1 = Some(PresignRound1 {
+            K,
+            G,
+            nonce,
+            enc_proofs,
+        });
+        Ok(presigning)
+    }
+
+    /// The signers whose proofs failed, or after `MessageMismatch` whose view of rounds 1
+    /// and 2 differs, once `proceed` has returned an error.
+    pub fn culprits(&self) -> &[u16] {
+        &self.culprits
+    }
+
+    fn party_aux(&self, j: u16) -> &PartyAux {
+        &self.aux.parties[j as usize]
+    }
+
+    fn own(&self) -> &PartyAux {
+        self.party_aux(self.key.party_index)
+    }
+
+    fn position_of(&self, j: u16) -> usize {
+        self.signers.iter().position(|l| *l == j).unwrap()
+    }
+
+    /// Binds every proof to the key, the signers, the session and the prover.
+    fn proof_aux(&self, prover: u16) -> Vec<u8> {
+        self.session_aux(&self.session_id, prover)
+    }
+
+    fn session_aux(&self, session_id: &Seed, prover: u16) -> Vec<u8> {
+        let signers = self.signers.iter().flat_map(|j| j.to_be_bytes().to_vec());
+        self.key
+            .rid
+            .iter()
+            .cloned()
+            .chain(signers)
+            .chain(session_id.iter().cloned())
+            .chain(prover.to_be_bytes().iter().cloned())
+            .collect()
+    }
+
+    fn record_culprits(
+        &mut self,
+        senders: &[u16],
+        checks: Vec<bool>,
+        error: Error,
+    ) -> Result<(), Error> {
+        self.culprits = senders
+            .iter()
+            .zip(checks)
+            .filter(|(_, ok)| !ok)
+            .map(|(j, _)| *j)
+            .collect();
+        if self.culprits.is_empty() {
+            Ok(())
+        } else {
+            Err(error)
+        }
+    }
+
+    fn mta_towards(&self, j: u16) -> MtaPair {
+        let index = self.key.party_index;
+        let own = &self.own().paillier;
+        let other = self.party_aux(j);
+        let K_j = &self.round1[self.position_of(j)].K;
+        let aux = self.proof_aux(index);
+        let mta = |x: &FE, X: &GE, beta: &BigInt| {
+            let (enc_beta, s) = other.paillier.encrypt(beta);
+            let D = other.paillier.add(
+                &other.paillier.mul(K_j, &x.to_big_int()).unwrap(),
+                &enc_beta,
+            );
+            let (F, r) = own.encrypt(beta);
+            let proof = AffGProof::prove(
+                &AffGStatement {
+                    verifier_key: &other.paillier,
+                    prover_key: own,
+                    c: K_j,
+                    d: &D,
+                    f: &F,
+                    x_public: X,
+                },
+                &AffGWitness {
+                    x: &x.to_big_int(),
+                    y: beta,
+                    rho: &s,
+                    rho_y: &r,
+                },
+                &other.ring_pedersen,
+                &aux,
+            );
+            (D, F, proof)
+        };
+        let (beta, beta_hat) = &self.betas[self.others.iter().position(|l| *l == j).unwrap()];
+        let Gamma_i = GE::generator() * self.gamma_i;
+        let (D, F, aff_g) = mta(&self.gamma_i, &Gamma_i, beta);
+        let (D_hat, F_hat, aff_g_hat) = mta(&self.w_i, &self.W[self.position], beta_hat);
+        let log_star = LogStarProof::prove(
+            own,
+            &other.ring_pedersen,
+            &GE::generator(),
+            &self.round1[self.position].G,
+            &Gamma_i,
+            &self.gamma_i.to_big_int(),
+            &self.nu_i,
+            ELL,
+            &aux,
+        );
+        MtaPair {
+            D,
+            F,
+            D_hat,
+            F_hat,
+            aff_g,
+            aff_g_hat,
+            log_star,
+        }
+    }
+
+    fn check_mta(&self, j: u16, msg: &PresignRound2) -> bool {
+        let index = self.key.party_index;
+        let own = self.own();
+        let sender = &self.party_aux(j).paillier;
+        let K_i = &self.round1[self.position].K;
+        let aux = self.proof_aux(j);
+        let pair = match addressed(&msg.mta, index) {
+            Some(pair) => pair,
+            None => return false,
+        };
+        let statement = |d, f, x_public| AffGStatement {
+            verifier_key: &own.paillier,
+            prover_key: sender,
+            c: K_i,
+            d,
+            f,
+            x_public,
+        };
+        let W_j = &self.W[self.position_of(j)];
+        pair.aff_g
+            .verify(
+                &statement(&pair.D, &pair.F, &msg.Gamma),
+                &own.ring_pedersen,
+                &aux,
+            )
+            .is_ok()
+            && pair
+                .aff_g_hat
+                .verify(
+                    &statement(&pair.D_hat, &pair.F_hat, W_j),
+                    &own.ring_pedersen,
+                    &aux,
+                )
+                .is_ok()
+            && pair
+                .log_star
+                .verify(
+                    sender,
+                    &own.ring_pedersen,
+                    &GE::generator(),
+                    &self.round1[self.position_of(j)].G,
+                    &msg.Gamma,
+                    ELL,
+                    &aux,
+                )
+                .is_ok()
+    }
+
+    /// The ciphertexts of delta_j and chi_j under the key of j: H_j times the D sent to j,
+    /// divided by the F of j, which encrypt the betas of j.
+    fn sum_ciphertexts(&self, j: u16, H: &BigInt, H_hat: &BigInt) -> Option<(BigInt, BigInt)> {
+        let key = &self.party_aux(j).paillier;
+        let own_mta = &self.round2[self.position_of(j)].mta;
+        let mut C = H.clone();
+        let mut C_hat = H_hat.clone();
+        for (l, round2) in self.signers.iter().zip(&self.round2) {
+            if *l == j {
+                continue;
+            }
+            let received = addressed(&round2.mta, j)?;
+            let sent = addressed(own_mta, *l)?;
+            let minus_one = -BigInt::one();
+            C = key.add(
+                &key.add(&C, &received.D),
+                &pow_mod(&sent.F, &minus_one, &key.nn)?,
+            );
+            C_hat = key.add(
+                &key.add(&C_hat, &received.D_hat),
+                &pow_mod(&sent.F_hat, &minus_one, &key.nn)?,
+            );
+        }
+        Some((C, C_hat))
+    }
+
+    fn blame_proofs(&self) -> PresignBlame {
+        let index = self.key.party_index;
+        let own = &self.own().paillier;
+        let paillier_key = &self.aux.paillier_key;
+        let aux = self.proof_aux(index);
+        let round1 = &self.round1[self.position];
+        let k_i = self.k_i.to_big_int();
+        let w_i = self.w_i.to_big_int();
+
+        let r_h = sample_unit(&own.n);
+        let H = own.add(
+            &own.mul(&round1.G, &k_i).unwrap(),
+            &own.encrypt_with(&BigInt::zero(), &r_h),
+        );
+        let mul = MulProof::prove(own, &round1.K, &round1.G, &H, &k_i, &r_h, &self.rho_i, &aux);
+        let r_h_hat = sample_unit(&own.n);
+        let H_hat = own.add(
+            &own.mul(&round1.K, &w_i).unwrap(),
+            &own.encrypt_with(&BigInt::zero(), &r_h_hat),
+        );
+
+        let (C, C_hat) = self.sum_ciphertexts(index, &H, &H_hat).unwrap();
+        let delta = paillier_key.decrypt_signed(&C);
+        let rho_delta = paillier_key.randomness(&C, &delta);
+        let chi = paillier_key.decrypt_signed(&C_hat);
+        let rho_chi = paillier_key.randomness(&C_hat, &chi);
+        let S_i = self.round3[self.position].S;
+        let proofs = self
+            .others
+            .iter()
+            .map(|j| {
+                let rp = &self.party_aux(*j).ring_pedersen;
+                let W_i = &self.W[self.position];
+                let proofs = BlameProofs {
+                    mul_star: MulStarProof::prove(
+                        own, rp, &round1.K, &H_hat, W_i, &w_i, &r_h_hat, &aux,
+                    ),
+                    dec: DecProof::prove(
+                        own,
+                        rp,
+                        &C,
+                        &self.delta_i,
+                        &delta,
+                        &rho_delta,
+                        SUM_BITS,
+                        &aux,
+                    ),
+                    log_star: LogStarProof::prove(
+                        own,
+                        rp,
+                        &self.Gamma,
+                        &C_hat,
+                        &S_i,
+                        &chi,
+                        &rho_chi,
+                        SUM_BITS,
+                        &aux,
+                    ),
+                };
+                (*j, proofs)
+            })
+            .collect();
+        PresignBlame {
+            H,
+            H_hat,
+            mul,
+            proofs,
+        }
+    }
+
+    fn check_blame(&self, j: u16, blame: &PresignBlame) -> bool {
+        let own = self.own();
+        let sender = &self.party_aux(j).paillier;
+        let aux = self.proof_aux(j);
+        let position = self.position_of(j);
+        let round1 = &self.round1[position];
+        let round3 = &self.round3[position];
+        let (C, C_hat, proofs) = match (
+            self.sum_ciphertexts(j, &blame.H, &blame.H_hat),
+            addressed(&blame.proofs, self.key.party_index),
+        ) {
+            (Some((C, C_hat)), Some(proofs)) => (C, C_hat, proofs),
+            _ => return false,
+        };
+        blame
+            .mul
+            .verify(sender, &round1.K, &round1.G, &blame.H, &aux)
+            .is_ok()
+            && proofs
+                .mul_star
+                .verify(
+                    sender,
+                    &own.ring_pedersen,
+                    &round1.K,
+                    &blame.H_hat,
+                    &self.W[position],
+                    &aux,
+                )
+                .is_ok()
+            && proofs
+                .dec
+                .verify(sender, &own.ring_pedersen, &C, &round3.delta, &aux)
+                .is_ok()
+            && proofs
+                .log_star
+                .verify(
+                    sender,
+                    &own.ring_pedersen,
+                    &self.Gamma,
+                    &C_hat,
+                    &round3.S,
+                    SUM_BITS,
+                    &aux,
+                )
+                .is_ok()
+    }
+}
+
+/// Hashes everything of rounds 1 and 2 that the later rounds rely on, in the order of the
+/// signers.
+fn echo(round1: &[PresignRound1], round2: &[PresignRound2]) -> Seed {
+    let hash = round1.iter().fold(Sha256::new(), |hash, msg| {
+        hash.chain(msg.K.to_bytes())
+            .chain(msg.G.to_bytes())
+            .chain(msg.nonce)
+    });
+    let hash = round2.iter().fold(hash, |hash, msg| {
+        msg.mta.iter().fold(
+            hash.chain(msg.Gamma.bytes_compressed_to_big_int().to_bytes()),
+            |hash, (j, pair)| {
+                hash.chain(j.to_be_bytes())
+                    .chain(pair.D.to_bytes())
+                    .chain(pair.F.to_bytes())
+                    .chain(pair.D_hat.to_bytes())
+                    .chain(pair.F_hat.to_bytes())
+            },
+        )
+    });
+    let mut echo = [0u8; 32];
+    echo.copy_from_slice(&hash.finalize());
+    echo
+}
+
+fn addressed<T>(entries: &[(u16, T)], party: u16) -> Option<&T> {
+    entries
+        .iter()
+        .find(|(receiver, _)| *receiver == party)
+        .map(|(_, entry)| entry)
+}
+
+impl Session for Presigning {
+    type Message = PresignMessage;
+    type Output = Presignature;
+
+    fn party(&self) -> u16 {
+        self.key.party_index
+    }
+
+    fn round(&self) -> u16 {
+        self.round
+    }
+
+    fn expected_senders(&self) -> Vec<u16> {
+        match self.round {
+            1..=4 => self.others.clone(),
+            _ => Vec::new(),
+        }
+    }
+
+    fn proceed(
+        &mut self,
+        incoming: Vec<Msg<PresignMessage>>,
+    ) -> Result<Vec<Msg<PresignMessage>>, Error> {
+        let party = self.key.party_index;
+        let others = self.others.clone();
+        let outgoing =
+            match self.round {
+                0 => {
+                    let round1 = self.own_round1.clone().unwrap();
+                    vec![broadcast(party, 1, PresignMessage::Round1(round1))]
+                }
+                1 => {
+                    let mut round1 =
+                        threshold_signer::collect(incoming, self.party(), 1, &others, |body| {
+                            match body {
+                                PresignMessage::Round1(msg) => Some(msg),
+                                _ => None,
+                            }
+                        })?;
+                    let own = self.own();
+                    let checks = others
+                        .iter()
+                        .zip(&round1)
+                        .map(|(j, msg)| {
+                            let sender = &self.party_aux(*j).paillier;
+                            sender.is_ciphertext(&msg.K)
+                                && sender.is_ciphertext(&msg.G)
+                                && addressed(&msg.enc_proofs, party).map_or(false, |proof| {
+                                    proof
+                                        .verify(
+                                            sender,
+                                            &own.ring_pedersen,
+                                            &msg.K,
+                                            &self.session_aux(&msg.nonce, *j),
+                                        )
+                                        .is_ok()
+                                })
+                        })
+                        .collect();
+                    self.record_culprits(&others, checks, InvalidKey)?;
+                    round1.insert(self.position, self.own_round1.take().unwrap());
+                    let hash = round1
+                        .iter()
+                        .fold(Sha256::new(), |hash, msg| hash.chain(msg.nonce))
+                        .finalize();
+                    self.session_id.copy_from_slice(&hash);
+                    self.round1 = round1;
+
+                    self.betas = others
+                        .iter()
+                        .map(|_| (sample_signed(ELL_PRIME), sample_signed(ELL_PRIME)))
+                        .collect();
+                    let mta = others.iter().map(|j| (*j, self.mta_towards(*j))).collect();
+                    let round2 = PresignRound2 {
+                        Gamma: GE::generator() * self.gamma_i,
+                        mta,
+                    };
+                    self.round2 = vec![round2.clone()];
+                    vec![broadcast(party, 2, PresignMessage::Round2(round2))]
+                }
+                2 => {
+                    let mut round2 =
+                        threshold_signer::collect(incoming, self.party(), 2, &others, |body| {
+                            match body {
+                                PresignMessage::Round2(msg) => Some(msg),
+                                _ => None,
+                            }
+                        })?;
+                    let checks = others
+                        .iter()
+                        .zip(&round2)
+                        .map(|(j, msg)| self.check_mta(*j, msg))
+                        .collect();
+                    self.record_culprits(&others, checks, InvalidKey)?;
+
+                    let paillier_key = &self.aux.paillier_key;
+                    let mut delta_i = self.k_i * self.gamma_i;
+                    let mut chi_i = self.k_i * self.w_i;
+                    for (msg, (beta, beta_hat)) in round2.iter().zip(&self.betas) {
+                        let pair = addressed(&msg.mta, party).unwrap();
+                        let alpha = paillier_key.decrypt_signed(&pair.D);
+                        let alpha_hat = paillier_key.decrypt_signed(&pair.D_hat);
+                        delta_i = delta_i + scalar(&(alpha - beta));
+                        chi_i = chi_i + scalar(&(alpha_hat - beta_hat));
+                    }
+                    round2.insert(self.position, self.round2.remove(0));
+                    let (head, tail) = round2.split_at(1);
+                    let Gamma = tail.iter().fold(head[0].Gamma, |acc, msg| acc + msg.Gamma);
+                    let Delta = Gamma * self.k_i;
+                    let own = &self.own().paillier;
+                    let log_star = others
+                        .iter()
+                        .map(|j| {
+                            let proof = LogStarProof::prove(
+                                own,
+                                &self.party_aux(*j).ring_pedersen,
+                                &Gamma,
+                                &self.round1[self.position].K,
+                                &Delta,
+                                &self.k_i.to_big_int(),
+                                &self.rho_i,
+                                ELL,
+                                &self.proof_aux(party),
+                            );
+                            (*j, proof)
+                        })
+                        .collect();
+                    let round3 = PresignRound3 {
+                        delta: delta_i,
+                        Delta,
+                        S: Gamma * chi_i,
+                        log_star,
+                        echo: echo(&self.round1, &round2),
+                    };
+                    self.round2 = round2;
+                    self.Gamma = Gamma;
+                    self.delta_i = delta_i;
+                    self.chi_i = chi_i;
+                    self.round3 = vec![round3.clone()];
+                    vec![broadcast(party, 3, PresignMessage::Round3(round3))]
+                }
+                3 => {
+                    let mut round3 =
+                        threshold_signer::collect(incoming, self.party(), 3, &others, |body| {
+                            match body {
+                                PresignMessage::Round3(msg) => Some(msg),
+                                _ => None,
+                            }
+                        })?;
+                    let own_echo = self.round3[0].echo;
+                    let checks = round3.iter().map(|msg| msg.echo == own_echo).collect();
+                    self.record_culprits(&others, checks, MessageMismatch)?;
+                    let own = self.own();
+                    let checks = others
+                        .iter()
+                        .zip(&round3)
+                        .map(|(j, msg)| {
+                            addressed(&msg.log_star, party).map_or(false, |proof| {
+                                proof
+                                    .verify(
+                                        &self.party_aux(*j).paillier,
+                                        &own.ring_pedersen,
+                                        &self.Gamma,
+                                        &self.round1[self.position_of(*j)].K,
+                                        &msg.Delta,
+                                        ELL,
+                                        &self.proof_aux(*j),
+                                    )
+                                    .is_ok()
+                            })
+                        })
+                        .collect();
+                    self.record_culprits(&others, checks, InvalidKey)?;
+                    round3.insert(self.position, self.round3.remove(0));
+                    self.round3 = round3;
+
+                    let delta = self
+                        .round3
+                        .iter()
+                        .fold(FE::zero(), |acc, msg| acc + msg.delta);
+                    let (head, tail) = self.round3.split_at(1);
+                    let Delta = tail.iter().fold(head[0].Delta, |acc, msg| acc + msg.Delta);
+                    let S = tail.iter().fold(head[0].S, |acc, msg| acc + msg.S);
+                    if delta != FE::zero()
+                        && GE::generator() * delta == Delta
+                        && S == self.key.y * delta
+                    {
+                        self.output = Some(Presignature {
+                            party_index: party,
+                            k_i: self.k_i,
+                            chi_i: self.chi_i,
+                            public: PresignaturePublic {
+                                R: self.Gamma * delta.invert(),
+                                Gamma: self.Gamma,
+                                delta,
+                                Delta: self.round3.iter().map(|msg| msg.Delta).collect(),
+                                S: self.round3.iter().map(|msg| msg.S).collect(),
+                                signers: self.signers.clone(),
+                                y: self.key.y,
+                            },
+                        });
+                        // round 4 is skipped
+                        self.round += 1;
+                        Vec::new()
+                    } else {
+                        let blame = self.blame_proofs();
+                        vec![broadcast(party, 4, PresignMessage::Blame(blame))]
+                    }
+                }
+                4 => {
+                    let blames =
+                        threshold_signer::collect(incoming, self.party(), 4, &others, |body| {
+                            match body {
+                                PresignMessage::Blame(msg) => Some(msg),
+                                _ => None,
+                            }
+                        })?;
+                    let checks = others
+                        .iter()
+                        .zip(&blames)
+                        .map(|(j, blame)| self.check_blame(*j, blame))
+                        .collect();
+                    self.record_culprits(&others, checks, Phase5BadSum)?;
+                    // every signer proved its shares honest, only an invalid key share is left
+                    return Err(Phase5BadSum);
+                }
+                _ => return Err(InvalidMessage),
+            };
+        self.round += 1;
+        Ok(outgoing)
+    }
+
+    fn is_finished(&self) -> bool {
+        self.round > 4
+    }
+
+    fn pick_output(&mut self) -> Option<Presignature> {
+        self.output.take()
+    }
+}
@@ -0,0 +1,177 @@
+use curv::arithmetic::traits::*;
+use curv::elliptic::curves::p256::{FE, GE};
+use curv::elliptic::curves::traits::*;
+use curv::BigInt;
+
+use super::aux_info::{AuxInfo, AuxInfoGen};
+use super::keygen::{Keygen, KeygenMessage, LocalKey};
+use super::paillier::{PaillierKey, RingPedersen};
+use super::presign::{PresignMessage, Presigning};
+use super::zk::{ModProof, PrmProof};
+use crate::protocols::threshold_signer::testing::run_with;
+use crate::Error;
+
+// 1024-bit safe primes, generating them on the fly takes minutes
+const SAFE_PRIMES: [&str; 6] = [
+    "DFF1364CABBF0734E8E2AE780FB15C3F3E19E15D4A68F7E9E7FD3C417EB3DFB41DD7141A07939F24486B017C517CE02285F7CF0A836F849C60B97C5A9A1F4C1EEB914071D184F12BF2A2A10C860259C6932AF54964149B11B144CD80DCB8A834295178A540AD92701AA83EFAD522584523EA100DE0E1E3D73B3863B4CCBAF403",
+    "E19FC1ABF14D139F591A1F66C1166AF09CF8E2E39E06537537384EA3F0A18D8A297ADD311AB584ED7D36D7DBCF5C4A2D61888CA24B60AC5AC5A8EE7C401659B0E69929ECFDD95F9BBE6E14DB5D5E6E2B007BA91754DA584C2E281704F7C4E3E93272F85D12992F46B556B872D76DCC6EC4A5E96BD351FE1DC9CF49479E0ED393",
+    "CF1B176613F3E308713655DD76BFA19F7D2A9D2172FBA1563698D8F49177CBC02701359911A7649263F722574EFF43C56F9F244349C7E81D53C91344B5C39E99CCA4E9785C305460716CA1D1AA1362908030CE427EF75585265E82720B55F19E6E8DB99149A41B3FBF8117D637133CFF3BE2A13086CECD7ADCC5ADA483AD810B",
+    "C08CD9AA5CA5CB05FE470745E4D14EA6AF551F6040C8482080756D06D388D4E3B1F6077922BA5CD0AB75278AB582E44CD6D51612AE4AB36F769CF9B65B29162408F8A62A7B6469EE19A5BB629C6164BEA1201592E5AEDC6F5E4E39D5C062C554EBA11982C91306F0DDCC77046F42ECF9ADBDDE92AD32CF448585A1556919FA13",
+    "D26D9056756AAE010BC6CCC157C17F622F8EC51F86F589621257E786C9BA2CA1E0DCA272DC8D80D1DB648B67C55D4563AAE1F6E8BD23B9DF8589FC8FAE746EA9798E3633824E75144030C6D8D67C38EC7D5F0846DD881F71DDB72DE4A780AFAD67D586829D8C087335872EC54AB8FFFAD2F790B5B473F8F21434CBA244935BF3",
+    "CAAEC01123F045C0ADA4991E762C1CEC206EF6B4B31860DC4AC23A53C3BEED37AEAE24B3162C07E2E3420E4BF09A7692CEDC36D904AFC6BAE76B052884F8E8F8EC487D0E9D69663451B853965C53726F6B9F8F3F89416E89222F0369E43568D729939C08CB5911CE8AF1DEB704E7B66A80163D5A746DF0FFD2440E6A3BACA273",
+];
+
+fn paillier_key(party: usize) -> PaillierKey {
+    let p = BigInt::from_str_radix(SAFE_PRIMES[2 * party], 16).unwrap();
+    let q = BigInt::from_str_radix(SAFE_PRIMES[2 * party + 1], 16).unwrap();
+    PaillierKey::from_primes(p, q)
+}
+
+fn keygen(threshold: u16, share_count: u16) -> Vec<LocalKey> {
+    let mut sessions: Vec<_> = (0..share_count)
+        .map(|i| Keygen::new(i, threshold, share_count).unwrap())
+        .collect();
+    run_with(&mut sessions, |_, _| {})
+        .into_iter()
+        .map(|key| key.expect("keygen failed"))
+        .collect()
+}
+
+fn aux_info(share_count: u16) -> Vec<AuxInfo> {
+    let mut sessions: Vec<_> = (0..share_count)
+        .map(|i| AuxInfoGen::with_key(i, share_count, paillier_key(i as usize)).unwrap())
+        .collect();
+    run_with(&mut sessions, |_, _| {})
+        .into_iter()
+        .map(|aux| aux.expect("aux info failed"))
+        .collect()
+}
+
+fn presigning(keys: &[LocalKey], aux: &[AuxInfo], signers: &[u16]) -> Vec<Presigning> {
+    signers
+        .iter()
+        .map(|i| {
+            let i = *i as usize;
+            Presigning::new(keys[i].clone(), aux[i].clone(), signers).unwrap()
+        })
+        .collect()
+}
+
+#[test]
+fn test_keygen() {
+    let keys = keygen(1, 3);
+    let public_shares = keys[0].public_shares();
+    for key in &keys {
+        assert_eq!(key.y, keys[0].y);
+        assert_eq!(key.rid, keys[0].rid);
+        assert_eq!(
+            public_shares[key.party_index as usize],
+            GE::generator() * key.x_i
+        );
+    }
+}
+
+#[test]
+fn test_keygen_detects_echo_mismatch() {
+    let mut sessions: Vec<_> = (0..3).map(|i| Keygen::new(i, 1, 3).unwrap()).collect();
+    // party 2 is told by party 1 that it got other commitments in round 1
+    let results = run_with(&mut sessions, |to, msg| {
+        if let (1, 2, KeygenMessage::Proof(_, echo)) = (msg.sender, to, &mut msg.body) {
+            echo[0] ^= 1;
+        }
+    });
+    assert!(results[0].is_ok() && results[1].is_ok());
+    assert_eq!(results[2].as_ref().err(), Some(&Error::MessageMismatch));
+    assert_eq!(sessions[2].culprits(), &[1]);
+}
+
+#[test]
+fn test_aux_info_proofs() {
+    let key = paillier_key(0);
+    let proof = ModProof::prove(&key, b"aux");
+    assert!(proof.verify(&key.public().n, b"aux").is_ok());
+    assert!(proof.verify(&key.public().n, b"other").is_err());
+    // a prime passes none of it
+    let p = BigInt::from_str_radix(SAFE_PRIMES[0], 16).unwrap();
+    assert!(proof.verify(&p, b"aux").is_err());
+
+    let (rp, lambda) = RingPedersen::generate(&key);
+    let proof = PrmProof::prove(&rp, &lambda, &key, b"aux");
+    assert!(proof.verify(&rp, b"aux").is_ok());
+    let bad_rp = RingPedersen {
+        s: BigInt::mod_mul(&rp.s, &rp.t, &rp.n),
+        ..rp.clone()
+    };
+    assert!(proof.verify(&bad_rp, b"aux").is_err());
+}
+
+#[test]
+fn test_presign_and_sign() {
+    let keys = keygen(1, 3);
+    let aux = aux_info(3);
+    let signers = [2, 0];
+    let presignatures: Vec<_> = run_with(&mut presigning(&keys, &aux, &signers), |_, _| {})
+        .into_iter()
+        .map(|p| p.expect("presigning failed"))
+        .collect();
+    let public = presignatures[0].public.clone();
+    assert_eq!(public, presignatures[1].public);
+
+    let message = BigInt::from(1234);
+    let partials: Vec<_> = presignatures
+        .into_iter()
+        .map(|p| p.sign(&message))
+        .collect();
+    let signature = public.combine(&message, &partials).unwrap();
+    assert!(signature.verify(&keys[0].y, &message).is_ok());
+
+    let mut bad = partials.clone();
+    bad[1].sigma = bad[1].sigma + bad[0].sigma;
+    assert!(public.verify_partial(&message, &bad[0]).is_ok());
+    assert!(public.verify_partial(&message, &bad[1]).is_err());
+    assert!(public.combine(&message, &bad).is_err());
+    assert!(public.combine(&message, &partials[..1]).is_err());
+}
+
+#[test]
+fn test_presign_identifies_culprit() {
+    let keys = keygen(1, 3);
+    let aux = aux_info(3);
+    let mut sessions = presigning(&keys, &aux, &[0, 1, 2]);
+    let one: FE = ECScalar::from(&BigInt::one());
+    // party 1 lies about delta_1, and gets a wrong delta_0 so that it takes part in the
+    // identification round
+    let results = run_with(&mut sessions, |to, msg| {
+        if let PresignMessage::Round3(round3) = &mut msg.body {
+            if msg.sender == 1 || (msg.sender == 0 && to == 1) {
+                round3.delta = round3.delta + one;
+            }
+        }
+    });
+    assert!(results.iter().all(|result| result.is_err()));
+    assert_eq!(sessions[0].culprits(), &[1]);
+    assert_eq!(sessions[1].culprits(), &[0]);
+    assert_eq!(sessions[2].culprits(), &[1]);
+}
+
+#[test]
+fn test_presign_detects_equivocation() {
+    let keys = keygen(1, 3);
+    let aux = aux_info(3);
+    let mut sessions = presigning(&keys, &aux, &[0, 1, 2]);
+    // party 2 gets a round 2 broadcast of party 1 without the MtA towards party 0, which
+    // party 2 does not check itself
+    let results = run_with(&mut sessions, |to, msg| {
+        if let PresignMessage::Round2(round2) = &mut msg.body {
+            if msg.sender == 1 && to == 2 {
+                round2.mta.retain(|(j, _)| *j == 2);
+            }
+        }
+    });
+    assert!(results
+        .iter()
+        .all(|result| result.as_ref().err() == Some(&Error::MessageMismatch)));
+    assert_eq!(sessions[0].culprits(), &[2]);
+    assert_eq!(sessions[1].culprits(), &[2]);
+    assert_eq!(sessions[2].culprits(), &[0, 1]);
+}
@@ -0,0 +1,946 @@
+/*
+    Multi-party ECDSA
+
+    Copyright 2018 by Kzen Networks
+
+    This file is part of Multi-party ECDSA library
+    (https://github.com/KZen-networks/multi-party-ecdsa)
+
+    Multi-party ECDSA is free software: you can redistribute
+    it and/or modify it under the terms of the GNU General Public
+    License as published by the Free Software Foundation, either
+    version 3 of the License, or (at your option) any later version.
+
+    @license GPL-3.0+ <https://github.com/KZen-networks/multi-party-ecdsa/blob/master/LICENSE>
+*/
+
+//! The zero knowledge proofs of CGGMP21 (https://eprint.iacr.org/2021/060), made non
+//! interactive with Fiat-Shamir. Every proof binds `aux`, which the protocols fill with the
+//! session data and the index of the prover.
+//!
+//! The range proofs are made for a single verifier, under its ring-Pedersen parameters, so
+//! a party proves the same statement once to every other party.
+
+use curv::arithmetic::traits::*;
+use curv::elliptic::curves::p256::{FE, GE};
+use curv::elliptic::curves::traits::*;
+use curv::BigInt;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+use super::paillier::{
+    in_range, pow_mod, sample_signed, sample_signed_times, sample_unit, PaillierKey,
+    PaillierPublic, RingPedersen,
+};
+use super::{ELL, ELL_PRIME, EPSILON, REPETITIONS};
+use crate::utilities::ot::{self, Seed};
+use crate::Error::{self, InvalidKey};
+
+const RP_CHECKED: &str = "ring-Pedersen parameters are checked by the aux info";
+
+struct Transcript(Sha256);
+
+impl Transcript {
+    fn new(label: &[u8], aux: &[u8]) -> Self {
+        Transcript(Sha256::new()).bytes(label).bytes(aux)
+    }
+
+    fn bytes(self, bytes: &[u8]) -> Self {
+        Transcript(
+            self.0
+                .chain((bytes.len() as u64).to_be_bytes())
+                .chain(bytes),
+        )
+    }
+
+    fn int(self, x: &BigInt) -> Self {
+        if x < &BigInt::zero() {
+            self.bytes(&[1]).bytes(&(-x).to_bytes())
+        } else {
+            self.bytes(&[0]).bytes(&x.to_bytes())
+        }
+    }
+
+    fn ints(self, xs: &[&BigInt]) -> Self {
+        xs.iter().fold(self, |transcript, x| transcript.int(x))
+    }
+
+    fn point(self, p: &GE) -> Self {
+        self.int(&p.bytes_compressed_to_big_int())
+    }
+
+    fn ring_pedersen(self, rp: &RingPedersen) -> Self {
+        self.ints(&[&rp.n, &rp.s, &rp.t])
+    }
+
+    fn seed(self) -> Seed {
+        let mut seed = [0u8; 32];
+        seed.copy_from_slice(&self.0.finalize());
+        seed
+    }
+
+    /// Uniform in [-q, q).
+    fn challenge(self) -> BigInt {
+        let q = FE::q();
+        let bytes = ot::expand(&self.seed(), &[0u8; 32], 64);
+        BigInt::from_bytes(&bytes).mod_floor(&(&q + &q)) - q
+    }
+}
+
+fn nonce(i: usize) -> Seed {
+    let mut nonce = [0u8; 32];
+    nonce[..4].copy_from_slice(&(i as u32).to_be_bytes());
+    nonce
+}
+
+pub(crate) fn scalar(x: &BigInt) -> FE {
+    ECScalar::from(&x.mod_floor(&FE::q()))
+}
+
+fn verdict(checks: Option<bool>) -> Result<(), Error> {
+    match checks {
+        Some(true) => Ok(()),
+        _ => Err(InvalidKey),
+    }
+}
+
+/// Schnorr proof of knowledge of x with X = xG (Π-sch), whose commitment A was sent ahead.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct SchProof {
+    pub z: FE,
+}
+
+impl SchProof {
+    /// The secret nonce alpha and the commitment A = alpha G.
+    pub fn commit() -> (FE, GE) {
+        let alpha: FE = ECScalar::new_random();
+        (alpha, GE::generator() * alpha)
+    }
+
+    pub fn prove(alpha: &FE, x: &FE, commitment: &GE, aux: &[u8]) -> Self {
+        let e = sch_challenge(commitment, &(GE::generator() * *x), aux);
+        SchProof { z: *alpha + e * *x }
+    }
+
+    pub fn verify(&self, commitment: &GE, public: &GE, aux: &[u8]) -> Result<(), Error> {
+        let e = sch_challenge(commitment, public, aux);
+        verdict(Some(GE::generator() * self.z == *commitment + *public * e))
+    }
+}
+
+fn sch_challenge(commitment: &GE, public: &GE, aux: &[u8]) -> FE {
+    scalar(
+        &Transcript::new(b"sch", aux)
+            .point(commitment)
+            .point(public)
+            .challenge(),
+    )
+}
+
+/// N is the product of two primes congruent to 3 mod 4 (Π-mod).
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct ModProof {
+    pub w: BigInt,
+    pub x: Vec<BigInt>,
+    pub a: Vec<bool>,
+    pub b: Vec<bool>,
+    pub z: Vec<BigInt>,
+}
+
+impl ModProof {
+    pub fn prove(key: &PaillierKey, aux: &[u8]) -> Self {
+        let n = &key.public().n;
+        let (p, q) = key.primes();
+        let w = loop {
+            let w = sample_unit(n);
+            if jacobi(&w, n) == -1 {
+                break w;
+            }
+        };
+        let minus_one = n - BigInt::one();
+        let n_inv = BigInt::mod_inv(n, key.phi()).unwrap();
+        let mut proof = ModProof {
+            w: w.clone(),
+            x: Vec::with_capacity(REPETITIONS),
+            a: Vec::with_capacity(REPETITIONS),
+            b: Vec::with_capacity(REPETITIONS),
+            z: Vec::with_capacity(REPETITIONS),
+        };
+        for y in mod_challenges(n, &w, aux) {
+            // -1 is a non residue mod p and mod q, w mod exactly one of them, so exactly
+            // one of the four candidates is a quadratic residue mod n
+            let (a, b, y_tag) = [(false, false), (true, false), (false, true), (true, true)]
+                .iter()
+                .map(|(a, b)| (*a, *b, mod_twist(&y, *a, *b, &minus_one, &w, n)))
+                .find(|(_, _, y_tag)| is_qr(y_tag, p) && is_qr(y_tag, q))
+                .expect("y is a unit");
+            proof.x.push(fourth_root(&y_tag, p, q));
+            proof.a.push(a);
+            proof.b.push(b);
+            proof.z.push(BigInt::mod_pow(&y, &n_inv, n));
+        }
+        proof
+    }
+
+    pub fn verify(&self, n: &BigInt, aux: &[u8]) -> Result<(), Error> {
+        if [self.x.len(), self.a.len(), self.b.len(), self.z.len()]
+            .iter()
+            .any(|len| *len != REPETITIONS)
+        {
+            return Err(InvalidKey);
+        }
+        let one = BigInt::one();
+        // odd, and not a prime
+        if n <= &one
+            || !n.test_bit(0)
+            || BigInt::mod_pow(&BigInt::from(2), &(n - &one), n) == one
+            || jacobi(&self.w, n) != -1
+        {
+            return Err(InvalidKey);
+        }
+        let minus_one = n - &one;
+        let four = BigInt::from(4);
+        let ok = mod_challenges(n, &self.w, aux)
+            .iter()
+            .enumerate()
+            .all(|(i, y)| {
+                BigInt::mod_pow(&self.z[i].mod_floor(n), n, n) == *y
+                    && BigInt::mod_pow(&self.x[i].mod_floor(n), &four, n)
+                        == mod_twist(y, self.a[i], self.b[i], &minus_one, &self.w, n)
+            });
+        verdict(Some(ok))
+    }
+}
+
+fn mod_challenges(n: &BigInt, w: &BigInt, aux: &[u8]) -> Vec<BigInt> {
+    let seed = Transcript::new(b"mod", aux).ints(&[n, w]).seed();
+    let len = (n.bit_length() + 7) / 8 + 16;
+    (0..REPETITIONS)
+        .map(|i| BigInt::from_bytes(&ot::expand(&seed, &nonce(i), len)).mod_floor(n))
+        .collect()
+}
+
+// (-1)^a w^b y mod n
+fn mod_twist(y: &BigInt, a: bool, b: bool, minus_one: &BigInt, w: &BigInt, n: &BigInt) -> BigInt {
+    let y = if a {
+        BigInt::mod_mul(y, minus_one, n)
+    } else {
+        y.clone()
+    };
+    if b {
+        BigInt::mod_mul(&y, w, n)
+    } else {
+        y
+    }
+}
+
+fn is_qr(a: &BigInt, p: &BigInt) -> bool {
+    let exp = (p - BigInt::one()).div_floor(&BigInt::from(2));
+    BigInt::mod_pow(&a.mod_floor(p), &exp, p) == BigInt::one()
+}
+
+// the square root a^((p+1)/4) of a residue mod p = 3 mod 4 is itself a residue, so
+// applying it twice gives a fourth root
+fn fourth_root(a: &BigInt, p: &BigInt, q: &BigInt) -> BigInt {
+    let root = |prime: &BigInt| {
+        let exp = (prime + BigInt::one()).div_floor(&BigInt::from(4));
+        let exp = BigInt::mod_mul(&exp, &exp, &(prime - BigInt::one()));
+        BigInt::mod_pow(&a.mod_floor(prime), &exp, prime)
+    };
+    let (x_p, x_q) = (root(p), root(q));
+    let p_inv = BigInt::mod_inv(p, q).unwrap();
+    let h = BigInt::mod_mul(&(x_q - &x_p).mod_floor(q), &p_inv, q);
+    x_p + p * h
+}
+
+/// The Jacobi symbol (a/n) for an odd positive n.
+pub(crate) fn jacobi(a: &BigInt, n: &BigInt) -> i8 {
+    let (two, three, four, five, eight) = (
+        BigInt::from(2),
+        BigInt::from(3),
+        BigInt::from(4),
+        BigInt::from(5),
+        BigInt::from(8),
+    );
+    let mut a = a.mod_floor(n);
+    let mut n = n.clone();
+    let mut t = 1;
+    while a != BigInt::zero() {
+        while !a.test_bit(0) {
+            a = a.div_floor(&two);
+            let r = n.mod_floor(&eight);
+            if r == three || r == five {
+                t = -t;
+            }
+        }
+        std::mem::swap(&mut a, &mut n);
+        if a.mod_floor(&four) == three && n.mod_floor(&four) == three {
+            t = -t;
+        }
+        a = a.mod_floor(&n);
+    }
+    if n == BigInt::one() {
+        t
+    } else {
+        0
+    }
+}
+
+/// s is in the group generated by t, for ring-Pedersen parameters (Π-prm).
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct PrmProof {
+    pub a: Vec<BigInt>,
+    pub z: Vec<BigInt>,
+}
+
+impl PrmProof {
+    pub fn prove(rp: &RingPedersen, lambda: &BigInt, key: &PaillierKey, aux: &[u8]) -> Self {
+        let phi = key.phi();
+        let alphas: Vec<BigInt> = (0..REPETITIONS)
+            .map(|_| BigInt::sample_below(phi))
+            .collect();
+        let a: Vec<BigInt> = alphas
+            .iter()
+            .map(|alpha| BigInt::mod_pow(&rp.t, alpha, &rp.n))
+            .collect();
+        let z = alphas
+            .iter()
+            .zip(prm_challenges(rp, &a, aux))
+            .map(|(alpha, e)| {
+                if e {
+                    (alpha + lambda).mod_floor(phi)
+                } else {
+                    alpha.clone()
+                }
+            })
+            .collect();
+        PrmProof { a, z }
+    }
+
+    pub fn verify(&self, rp: &RingPedersen, aux: &[u8]) -> Result<(), Error> {
+        let n = &rp.n;
+        let unit = |x: &BigInt| x > &BigInt::one() && x < n && BigInt::mod_inv(x, n).is_some();
+        if self.a.len() != REPETITIONS
+            || self.z.len() != REPETITIONS
+            || !unit(&rp.s)
+            || !unit(&rp.t)
+        {
+            return Err(InvalidKey);
+        }
+        let ok = prm_challenges(rp, &self.a, aux)
+            .into_iter()
+            .enumerate()
+            .all(|(i, e)| {
+                let rhs = if e {
+                    BigInt::mod_mul(&self.a[i], &rp.s, n)
+                } else {
+                    self.a[i].mod_floor(n)
+                };
+                pow_mod(&rp.t, &self.z[i], n) == Some(rhs)
+            });
+        verdict(Some(ok))
+    }
+}
+
+fn prm_challenges(rp: &RingPedersen, a: &[BigInt], aux: &[u8]) -> Vec<bool> {
+    let seed = a
+        .iter()
+        .fold(
+            Transcript::new(b"prm", aux).ring_pedersen(rp),
+            |transcript, a_i| transcript.int(a_i),
+        )
+        .seed();
+    let bytes = ot::expand(&seed, &nonce(0), (REPETITIONS + 7) / 8);
+    (0..REPETITIONS)
+        .map(|i| (bytes[i / 8] >> (i % 8)) & 1 == 1)
+        .collect()
+}
+
+/// A Paillier ciphertext of the prover encrypts a value in ±2^ELL (Π-enc).
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct EncProof {
+    pub s: BigInt,
+    pub a: BigInt,
+    pub c: BigInt,
+    pub z1: BigInt,
+    pub z2: BigInt,
+    pub z3: BigInt,
+}
+
+impl EncProof {
+    pub fn prove(
+        key: &PaillierPublic,
+        rp: &RingPedersen,
+        ciphertext: &BigInt,
+        plaintext: &BigInt,
+        rho: &BigInt,
+        aux: &[u8],
+    ) -> Self {
+        let alpha = sample_signed(ELL + EPSILON);
+        let mu = sample_signed_times(ELL, &rp.n);
+        let r = sample_unit(&key.n);
+        let gamma = sample_signed_times(ELL + EPSILON, &rp.n);
+
+        let s = rp.commit(plaintext, &mu).expect(RP_CHECKED);
+        let a = key.encrypt_with(&alpha, &r);
+        let c = rp.commit(&alpha, &gamma).expect(RP_CHECKED);
+        let e = Transcript::new(b"enc", aux)
+            .ring_pedersen(rp)
+            .ints(&[&key.n, ciphertext, &s, &a, &c])
+            .challenge();
+        EncProof {
+            s,
+            a,
+            c,
+            z1: &alpha + &e * plaintext,
+            z2: BigInt::mod_mul(&r, &pow_mod(rho, &e, &key.n).unwrap(), &key.n),
+            z3: gamma + &e * &mu,
+        }
+    }
+
+    pub fn verify(
+        &self,
+        key: &PaillierPublic,
+        rp: &RingPedersen,
+        ciphertext: &BigInt,
+        aux: &[u8],
+    ) -> Result<(), Error> {
+        let e = Transcript::new(b"enc", aux)
+            .ring_pedersen(rp)
+            .ints(&[&key.n, ciphertext, &self.s, &self.a, &self.c])
+            .challenge();
+        verdict(self.check(key, rp, ciphertext, &e))
+    }
+
+    fn check(
+        &self,
+        key: &PaillierPublic,
+        rp: &RingPedersen,
+        ciphertext: &BigInt,
+        e: &BigInt,
+    ) -> Option<bool> {
+        let s_e = pow_mod(&self.s, e, &rp.n)?;
+        Some(
+            in_range(&self.z1, ELL + EPSILON)
+                && key.encrypt_with(&self.z1, &self.z2)
+                    == key.add(&self.a, &key.mul(ciphertext, e)?)
+                && rp.commit(&self.z1, &self.z3)? == BigInt::mod_mul(&self.c, &s_e, &rp.n),
+        )
+    }
+}
+
+/// The public part of a multiplication under the verifier's key: `d` = `c`^x (1 + N0)^y
+/// rho^N0 with N0 the modulus of the verifier, `f` the encryption of y under the
+/// prover's key and `x_public` = x G.
+pub struct AffGStatement<'a> {
+    pub verifier_key: &'a PaillierPublic,
+    pub prover_key: &'a PaillierPublic,
+    pub c: &'a BigInt,
+    pub d: &'a BigInt,
+    pub f: &'a BigInt,
+    pub x_public: &'a GE,
+}
+
+pub struct AffGWitness<'a> {
+    pub x: &'a BigInt,
+    pub y: &'a BigInt,
+    pub rho: &'a BigInt,
+    pub rho_y: &'a BigInt,
+}
+
+/// x is in ±2^ELL and y in ±2^ELL_PRIME for an `AffGStatement` (Π-aff-g).
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct AffGProof {
+    pub a: BigInt,
+    pub b_x: GE,
+    pub b_y: BigInt,
+    pub e_x: BigInt,
+    pub s_x: BigInt,
+    pub e_y: BigInt,
+    pub s_y: BigInt,
+    pub z1: BigInt,
+    pub z2: BigInt,
+    pub z3: BigInt,
+    pub z4: BigInt,
+    pub w: BigInt,
+    pub w_y: BigInt,
+}
+
+impl AffGProof {
+    pub fn prove(
+        statement: &AffGStatement,
+        witness: &AffGWitness,
+        rp: &RingPedersen,
+        aux: &[u8],
+    ) -> Self {
+        let (n0, n1) = (statement.verifier_key, statement.prover_key);
+        let alpha = sample_signed(ELL + EPSILON);
+        let beta = sample_signed(ELL_PRIME + EPSILON);
+        let r = sample_unit(&n0.n);
+        let r_y = sample_unit(&n1.n);
+        let gamma = sample_signed_times(ELL + EPSILON, &rp.n);
+        let m = sample_signed_times(ELL, &rp.n);
+        let delta = sample_signed_times(ELL + EPSILON, &rp.n);
+        let mu = sample_signed_times(ELL, &rp.n);
+
+        let c_alpha = n0
+            .mul(statement.c, &alpha)
+            .expect("ciphertexts are checked on receipt");
+        let mut proof = AffGProof {
+            a: n0.add(&c_alpha, &n0.encrypt_with(&beta, &r)),
+            b_x: GE::generator() * scalar(&alpha),
+            b_y: n1.encrypt_with(&beta, &r_y),
+            e_x: rp.commit(&alpha, &gamma).expect(RP_CHECKED),
+            s_x: rp.commit(witness.x, &m).expect(RP_CHECKED),
+            e_y: rp.commit(&beta, &delta).expect(RP_CHECKED),
+            s_y: rp.commit(witness.y, &mu).expect(RP_CHECKED),
+            z1: BigInt::zero(),
+            z2: BigInt::zero(),
+            z3: BigInt::zero(),
+            z4: BigInt::zero(),
+            w: BigInt::zero(),
+            w_y: BigInt::zero(),
+        };
+        let e = proof.challenge(statement, rp, aux);
+        proof.z1 = &alpha + &e * witness.x;
+        proof.z2 = &beta + &e * witness.y;
+        proof.z3 = &gamma + &e * &m;
+        proof.z4 = &delta + &e * &mu;
+        proof.w = BigInt::mod_mul(&r, &pow_mod(witness.rho, &e, &n0.n).unwrap(), &n0.n);
+        proof.w_y = BigInt::mod_mul(&r_y, &pow_mod(witness.rho_y, &e, &n1.n).unwrap(), &n1.n);
+        proof
+    }
+
+    pub fn verify(
+        &self,
+        statement: &AffGStatement,
+        rp: &RingPedersen,
+        aux: &[u8],
+    ) -> Result<(), Error> {
+        let e = self.challenge(statement, rp, aux);
+        verdict(self.check(statement, rp, &e))
+    }
+
+    fn challenge(&self, statement: &AffGStatement, rp: &RingPedersen, aux: &[u8]) -> BigInt {
+        Transcript::new(b"aff-g", aux)
+            .ring_pedersen(rp)
+            .ints(&[
+                &statement.verifier_key.n,
+                &statement.prover_key.n,
+                statement.c,
+                statement.d,
+                statement.f,
+            ])
+            .point(statement.x_public)
+            .int(&self.a)
+            .point(&self.b_x)
+            .ints(&[&self.b_y, &self.e_x, &self.s_x, &self.e_y, &self.s_y])
+            .challenge()
+    }
+
+    fn check(&self, statement: &AffGStatement, rp: &RingPedersen, e: &BigInt) -> Option<bool> {
+        let (n0, n1) = (statement.verifier_key, statement.prover_key);
+        let lhs_d = n0.add(
+            &n0.mul(statement.c, &self.z1)?,
+            &n0.encrypt_with(&self.z2, &self.w),
+        );
+        let rhs_d = n0.add(&self.a, &n0.mul(statement.d, e)?);
+        let lhs_f = n1.encrypt_with(&self.z2, &self.w_y);
+        let rhs_f = n1.add(&self.b_y, &n1.mul(statement.f, e)?);
+        let rhs_x = BigInt::mod_mul(&self.e_x, &pow_mod(&self.s_x, e, &rp.n)?, &rp.n);
+        let rhs_y = BigInt::mod_mul(&self.e_y, &pow_mod(&self.s_y, e, &rp.n)?, &rp.n);
+        Some(
+            in_range(&self.z1, ELL + EPSILON)
+                && in_range(&self.z2, ELL_PRIME + EPSILON)
+                && lhs_d == rhs_d
+                && GE::generator() * scalar(&self.z1) == self.b_x + *statement.x_public * scalar(e)
+                && lhs_f == rhs_f
+                && rp.commit(&self.z1, &self.z3)? == rhs_x
+                && rp.commit(&self.z2, &self.z4)? == rhs_y,
+        )
+    }
+}
+
+/// A ciphertext of the prover encrypts the discrete log of `x_public` to the base `g`, of
+/// at most `bits` bits (Π-log*).
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct LogStarProof {
+    pub s: BigInt,
+    pub a: BigInt,
+    pub y: GE,
+    pub d: BigInt,
+    pub z1: BigInt,
+    pub z2: BigInt,
+    pub z3: BigInt,
+}
+
+impl LogStarProof {
+    #[allow(clippy::too_many_arguments)]
+    pub fn prove(
+        key: &PaillierPublic,
+        rp: &RingPedersen,
+        g: &GE,
+        ciphertext: &BigInt,
+        x_public: &GE,
+        x: &BigInt,
+        rho: &BigInt,
+        bits: usize,
+        aux: &[u8],
+    ) -> Self {
+        let alpha = sample_signed(bits + EPSILON);
+        let mu = sample_signed_times(bits, &rp.n);
+        let r = sample_unit(&key.n);
+        let gamma = sample_signed_times(bits + EPSILON, &rp.n);
+
+        let mut proof = LogStarProof {
+            s: rp.commit(x, &mu).expect(RP_CHECKED),
+            a: key.encrypt_with(&alpha, &r),
+            y: g * &scalar(&alpha),
+            d: rp.commit(&alpha, &gamma).expect(RP_CHECKED),
+            z1: BigInt::zero(),
+            z2: BigInt::zero(),
+            z3: BigInt::zero(),
+        };
+        let e = proof.challenge(key, rp, g, ciphertext, x_public, aux);
+        proof.z1 = &alpha + &e * x;
+        proof.z2 = BigInt::mod_mul(&r, &pow_mod(rho, &e, &key.n).unwrap(), &key.n);
+        proof.z3 = &gamma + &e * &mu;
+        proof
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    pub fn verify(
+        &self,
+        key: &PaillierPublic,
+        rp: &RingPedersen,
+        g: &GE,
+        ciphertext: &BigInt,
+        x_public: &GE,
+        bits: usize,
+        aux: &[u8],
+    ) -> Result<(), Error> {
+        let e = self.challenge(key, rp, g, ciphertext, x_public, aux);
+        verdict(self.check(key, rp, g, ciphertext, x_public, bits, &e))
+    }
+
+    fn challenge(
+        &self,
+        key: &PaillierPublic,
+        rp: &RingPedersen,
+        g: &GE,
+        ciphertext: &BigInt,
+        x_public: &GE,
+        aux: &[u8],
+    ) -> BigInt {
+        Transcript::new(b"log*", aux)
+            .ring_pedersen(rp)
+            .ints(&[&key.n, ciphertext])
+            .point(g)
+            .point(x_public)
+            .ints(&[&self.s, &self.a])
+            .point(&self.y)
+            .int(&self.d)
+            .challenge()
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn check(
+        &self,
+        key: &PaillierPublic,
+        rp: &RingPedersen,
+        g: &GE,
+        ciphertext: &BigInt,
+        x_public: &GE,
+        bits: usize,
+        e: &BigInt,
+    ) -> Option<bool> {
+        let rhs = BigInt::mod_mul(&self.d, &pow_mod(&self.s, e, &rp.n)?, &rp.n);
+        Some(
+            in_range(&self.z1, bits + EPSILON)
+                && key.encrypt_with(&self.z1, &self.z2)
+                    == key.add(&self.a, &key.mul(ciphertext, e)?)
+                && g * &scalar(&self.z1) == self.y + *x_public * scalar(e)
+                && rp.commit(&self.z1, &self.z3)? == rhs,
+        )
+    }
+}
+
+/// Ciphertexts of the prover with `c` = `y`^x rho^N, x being the plaintext of `x_ct`
+/// (Π-mul). No ring-Pedersen parameters are involved, so one proof serves every verifier.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct MulProof {
+    pub a: BigInt,
+    pub b: BigInt,
+    pub z: BigInt,
+    pub u: BigInt,
+    pub v: BigInt,
+}
+
+impl MulProof {
+    #[allow(clippy::too_many_arguments)]
+    pub fn prove(
+        key: &PaillierPublic,
+        x_ct: &BigInt,
+        y_ct: &BigInt,
+        c: &BigInt,
+        x: &BigInt,
+        rho: &BigInt,
+        rho_x: &BigInt,
+        aux: &[u8],
+    ) -> Self {
+        let alpha = BigInt::sample_below(&key.n);
+        let r = sample_unit(&key.n);
+        let s = sample_unit(&key.n);
+        let a = key.add(
+            &key.mul(y_ct, &alpha).unwrap(),
+            &key.encrypt_with(&BigInt::zero(), &r),
+        );
+        let b = key.encrypt_with(&alpha, &s);
+        let e = mul_challenge(key, x_ct, y_ct, c, &a, &b, aux);
+        MulProof {
+            z: &alpha + &e * x,
+            u: BigInt::mod_mul(&r, &pow_mod(rho, &e, &key.n).unwrap(), &key.n),
+            v: BigInt::mod_mul(&s, &pow_mod(rho_x, &e, &key.n).unwrap(), &key.n),
+            a,
+            b,
+        }
+    }
+
+    pub fn verify(
+        &self,
+        key: &PaillierPublic,
+        x_ct: &BigInt,
+        y_ct: &BigInt,
+        c: &BigInt,
+        aux: &[u8],
+    ) -> Result<(), Error> {
+        let e = mul_challenge(key, x_ct, y_ct, c, &self.a, &self.b, aux);
+        verdict(self.check(key, x_ct, y_ct, c, &e))
+    }
+
+    fn check(
+        &self,
+        key: &PaillierPublic,
+        x_ct: &BigInt,
+        y_ct: &BigInt,
+        c: &BigInt,
+        e: &BigInt,
+    ) -> Option<bool> {
+        let lhs_c = key.add(
+            &key.mul(y_ct, &self.z)?,
+            &key.encrypt_with(&BigInt::zero(), &self.u),
+        );
+        Some(
+            lhs_c == key.add(&self.a, &key.mul(c, e)?)
+                && key.encrypt_with(&self.z, &self.v) == key.add(&self.b, &key.mul(x_ct, e)?),
+        )
+    }
+}
+
+fn mul_challenge(
+    key: &PaillierPublic,
+    x_ct: &BigInt,
+    y_ct: &BigInt,
+    c: &BigInt,
+    a: &BigInt,
+    b: &BigInt,
+    aux: &[u8],
+) -> BigInt {
+    Transcript::new(b"mul", aux)
+        .ints(&[&key.n, x_ct, y_ct, c, a, b])
+        .challenge()
+}
+
+/// A ciphertext of the prover decrypts to a value of at most `bits` bits that is `x` mod q
+/// (Π-dec).
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct DecProof {
+    pub s: BigInt,
+    pub t: BigInt,
+    pub a: BigInt,
+    pub gamma: FE,
+    pub z1: BigInt,
+    pub z2: BigInt,
+    pub w: BigInt,
+}
+
+impl DecProof {
+    #[allow(clippy::too_many_arguments)]
+    pub fn prove(
+        key: &PaillierPublic,
+        rp: &RingPedersen,
+        ciphertext: &BigInt,
+        x: &FE,
+        y: &BigInt,
+        rho: &BigInt,
+        bits: usize,
+        aux: &[u8],
+    ) -> Self {
+        let alpha = sample_signed(bits + EPSILON);
+        let mu = sample_signed_times(bits, &rp.n);
+        let nu = sample_signed_times(bits + EPSILON, &rp.n);
+        let r = sample_unit(&key.n);
+
+        let mut proof = DecProof {
+            s: rp.commit(y, &mu).expect(RP_CHECKED),
+            t: rp.commit(&alpha, &nu).expect(RP_CHECKED),
+            a: key.encrypt_with(&alpha, &r),
+            gamma: scalar(&alpha),
+            z1: BigInt::zero(),
+            z2: BigInt::zero(),
+            w: BigInt::zero(),
+        };
+        let e = proof.challenge(key, rp, ciphertext, x, aux);
+        proof.z1 = &alpha + &e * y;
+        proof.z2 = &nu + &e * &mu;
+        proof.w = BigInt::mod_mul(&r, &pow_mod(rho, &e, &key.n).unwrap(), &key.n);
+        proof
+    }
+
+    pub fn verify(
+        &self,
+        key: &PaillierPublic,
+        rp: &RingPedersen,
+        ciphertext: &BigInt,
+        x: &FE,
+        aux: &[u8],
+    ) -> Result<(), Error> {
+        let e = self.challenge(key, rp, ciphertext, x, aux);
+        verdict(self.check(key, rp, ciphertext, x, &e))
+    }
+
+    fn challenge(
+        &self,
+        key: &PaillierPublic,
+        rp: &RingPedersen,
+        ciphertext: &BigInt,
+        x: &FE,
+        aux: &[u8],
+    ) -> BigInt {
+        Transcript::new(b"dec", aux)
+            .ring_pedersen(rp)
+            .ints(&[&key.n, ciphertext, &x.to_big_int()])
+            .ints(&[&self.s, &self.t, &self.a, &self.gamma.to_big_int()])
+            .challenge()
+    }
+
+    fn check(
+        &self,
+        key: &PaillierPublic,
+        rp: &RingPedersen,
+        ciphertext: &BigInt,
+        x: &FE,
+        e: &BigInt,
+    ) -> Option<bool> {
+        let rhs = BigInt::mod_mul(&self.t, &pow_mod(&self.s, e, &rp.n)?, &rp.n);
+        Some(
+            key.encrypt_with(&self.z1, &self.w) == key.add(&self.a, &key.mul(ciphertext, e)?)
+                && scalar(&self.z1) == self.gamma + scalar(e) * *x
+                && rp.commit(&self.z1, &self.z2)? == rhs,
+        )
+    }
+}
+
+/// Ciphertexts of the prover with `d` = `c`^x rho^N and x G = `x_public`, for x in
+/// ±2^ELL (Π-mul*).
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct MulStarProof {
+    pub a: BigInt,
+    pub b_x: GE,
+    pub e: BigInt,
+    pub s: BigInt,
+    pub z1: BigInt,
+    pub z2: BigInt,
+    pub w: BigInt,
+}
+
+impl MulStarProof {
+    #[allow(clippy::too_many_arguments)]
+    pub fn prove(
+        key: &PaillierPublic,
+        rp: &RingPedersen,
+        c: &BigInt,
+        d: &BigInt,
+        x_public: &GE,
+        x: &BigInt,
+        rho: &BigInt,
+        aux: &[u8],
+    ) -> Self {
+        let alpha = sample_signed(ELL + EPSILON);
+        let r = sample_unit(&key.n);
+        let gamma = sample_signed_times(ELL + EPSILON, &rp.n);
+        let m = sample_signed_times(ELL, &rp.n);
+
+        let mut proof = MulStarProof {
+            a: key.add(
+                &key.mul(c, &alpha).unwrap(),
+                &key.encrypt_with(&BigInt::zero(), &r),
+            ),
+            b_x: GE::generator() * scalar(&alpha),
+            e: rp.commit(&alpha, &gamma).expect(RP_CHECKED),
+            s: rp.commit(x, &m).expect(RP_CHECKED),
+            z1: BigInt::zero(),
+            z2: BigInt::zero(),
+            w: BigInt::zero(),
+        };
+        let e = proof.challenge(key, rp, c, d, x_public, aux);
+        proof.z1 = &alpha + &e * x;
+        proof.z2 = &gamma + &e * &m;
+        proof.w = BigInt::mod_mul(&r, &pow_mod(rho, &e, &key.n).unwrap(), &key.n);
+        proof
+    }
+
+    pub fn verify(
+        &self,
+        key: &PaillierPublic,
+        rp: &RingPedersen,
+        c: &BigInt,
+        d: &BigInt,
+        x_public: &GE,
+        aux: &[u8],
+    ) -> Result<(), Error> {
+        let e = self.challenge(key, rp, c, d, x_public, aux);
+        verdict(self.check(key, rp, c, d, x_public, &e))
+    }
+
+    fn challenge(
+        &self,
+        key: &PaillierPublic,
+        rp: &RingPedersen,
+        c: &BigInt,
+        d: &BigInt,
+        x_public: &GE,
+        aux: &[u8],
+    ) -> BigInt {
+        Transcript::new(b"mul*", aux)
+            .ring_pedersen(rp)
+            .ints(&[&key.n, c, d])
+            .point(x_public)
+            .int(&self.a)
+            .point(&self.b_x)
+            .ints(&[&self.e, &self.s])
+            .challenge()
+    }
+
+    fn check(
+        &self,
+        key: &PaillierPublic,
+        rp: &RingPedersen,
+        c: &BigInt,
+        d: &BigInt,
+        x_public: &GE,
+        e: &BigInt,
+    ) -> Option<bool> {
+        let lhs_d = key.add(
+            &key.mul(c, &self.z1)?,
+            &key.encrypt_with(&BigInt::zero(), &self.w),
+        );
+        let rhs = BigInt::mod_mul(&self.e, &pow_mod(&self.s, e, &rp.n)?, &rp.n);
+        Some(
+            in_range(&self.z1, ELL + EPSILON)
+                && lhs_d == key.add(&self.a, &key.mul(d, e)?)
+                && GE::generator() * scalar(&self.z1) == self.b_x + *x_public * scalar(e)
+                && rp.commit(&self.z1, &self.z2)? == rhs,
+        )
+    }
+}
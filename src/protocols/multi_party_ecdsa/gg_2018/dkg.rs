@@ -0,0 +1,611 @@
+/*
+    Multi-party ECDSA
+
+    Copyright 2018 by Kzen Networks
+
+    This file is part of Multi-party ECDSA library
+    (https://github.com/KZen-networks/multi-party-ecdsa)
+
+    Multi-party ECDSA is free software: you can redistribute
+    it and/or modify it under the terms of the GNU General Public
+    License as published by the Free Software Foundation, either
+    version 3 of the License, or (at your option) any later version.
+
+    @license GPL-3.0+ <https://github.com/KZen-networks/multi-party-ecdsa/blob/master/LICENSE>
+*/
+
+//! Distributed key generation of Gennaro, Jarecki, Krawczyk and Rabin, outputting the same
+//! [`LocalKey`] as the keygen of [`super::signer`]. Every party first deals its secret with
+//! Pedersen VSS, whose commitments hide it perfectly, and only publishes the Feldman
+//! commitments of the same polynomial once the set of dealers is fixed:
+//!
+//! ```text
+//! round 1  broadcast  Pedersen commitments, Paillier key + proof
+//! round 2  p2p        shares (s_ij, s'_ij)
+//! round 3  broadcast  complaints about shares that fail the Pedersen check
+//! round 4  broadcast  the disputed shares, revealed by their dealers (skipped without
+//!                     complaints)
+//! round 5  broadcast  Feldman commitments
+//! round 6  broadcast  complaints about shares that fail the Feldman check
+//! round 7  broadcast  the shares of the dealers found cheating in round 6 (skipped when
+//!                     there are none)
+//! ```
+//!
+//! Up to round 4 keygen fails like the one of [`super::signer`], with `InvalidSS` and the
+//! parties at fault in [`Keygen::culprits`], and nothing is known about y yet. From round 5
+//! on y is decided: the polynomial of a dealer whose Feldman commitments do not match its
+//! shares is reconstructed from the shares of the others, without waiting for the dealer,
+//! and invalid complaints are ignored. So a rushing adversary that sees the other
+//! contributions to y first can no longer bias y by aborting, except by not answering at
+//! all, which any session allows.
+
+use curv::arithmetic::traits::*;
+use curv::cryptographic_primitives::secret_sharing::feldman_vss::{
+    ShamirSecretSharing, VerifiableSS,
+};
+use curv::elliptic::curves::p256::{FE, GE};
+use curv::elliptic::curves::traits::*;
+use curv::BigInt;
+use paillier::EncryptionKey;
+use serde::{Deserialize, Serialize};
+use zeroize::Zeroize;
+use zk_paillier::zkproofs::NICorrectKeyProof;
+
+use super::party_i::{Keys, SharedKeys};
+use super::signer::LocalKey;
+use crate::protocols::threshold_signer::{self, broadcast, p2p, Msg, Session};
+use crate::Error::{self, InvalidKey, InvalidMessage, InvalidSS};
+
+#[derive(Debug, Serialize, Deserialize)]
+pub enum DkgMessage {
+    Commitment(Vec<GE>, EncryptionKey, NICorrectKeyProof),
+    Share(FE, FE),
+    Complaints(Vec<DkgComplaint>),
+    /// The shares revealed to each accuser, as (accuser, s, s').
+    Justification(Vec<(u16, FE, FE)>),
+    VssScheme(VerifiableSS<GE>),
+    /// The shares received from each dealer found cheating, as (dealer, s, s').
+    Reveal(Vec<(u16, FE, FE)>),
+}
+
+/// A complaint against `accused`, with the share (s, s') received from it.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct DkgComplaint {
+    pub accused: u16,
+    pub share: FE,
+    pub blinding: FE,
+}
+
+pub struct Keygen {
+    party: u16,
+    threshold: u16,
+    share_count: u16,
+    others: Vec<u16>,
+    round: u16,
+    party_keys: Keys,
+    coefficients: Vec<FE>,
+    blindings: Vec<FE>,
+    pedersen_vec: Vec<Vec<GE>>,
+    paillier_key_vec: Vec<EncryptionKey>,
+    shares: Vec<(FE, FE)>,
+    complaints: Vec<Vec<DkgComplaint>>,
+    justifications: Vec<Vec<(u16, FE, FE)>>,
+    vss_scheme_vec: Vec<VerifiableSS<GE>>,
+    cheaters: Vec<u16>,
+    culprits: Vec<u16>,
+    output: Option<LocalKey>,
+}
+
+impl Keygen {
+    pub fn new(party: u16, threshold: u16, share_count: u16) -> Result<Self, Error> {
+        if party >= share_count || threshold >= share_count {
+            return Err(InvalidKey);
+        }
+        let party_keys = Keys::create(party as usize);
+        let mut coefficients = vec![party_keys.u_i];
+        coefficients.extend((0..threshold).map(|_| FE::new_random()));
+        let blindings = (0..=threshold).map(|_| FE::new_random()).collect();
+        Ok(Keygen {
+            party,
+            threshold,
+            share_count,
+            others: (0..share_count).filter(|j| *j != party).collect(),
+            round: 0,
+            party_keys,
+            coefficients,
+            blindings,
+            pedersen_vec: Vec::new(),
+            paillier_key_vec: Vec::new(),
+            shares: Vec::new(),
+            complaints: Vec::new(),
+            justifications: Vec::new(),
+            vss_scheme_vec: Vec::new(),
+            cheaters: Vec::new(),
+            culprits: Vec::new(),
+            output: None,
+        })
+    }
+
+    /// The parties found at fault, once `proceed` has returned `InvalidSS`.
+    pub fn culprits(&self) -> &[u16] {
+        &self.culprits
+    }
+
+    fn record_culprits(&mut self, mut culprits: Vec<u16>) -> Result<(), Error> {
+        culprits.sort_unstable();
+        culprits.dedup();
+        self.culprits = culprits;
+        if self.culprits.is_empty() {
+            Ok(())
+        } else {
+            Err(InvalidSS)
+        }
+    }
+
+    // (f(j), f'(j)) of the own polynomials, for the 0-based party j
+    fn own_share(&self, j: u16) -> (FE, FE) {
+        let x = point(j as usize);
+        (
+            evaluate(&self.coefficients, &x),
+            evaluate(&self.blindings, &x),
+        )
+    }
+
+    // whether (s, s') is the share of party `j` of the dealer of `commitments`
+    fn pedersen_valid(commitments: &[GE], j: usize, share: &FE, blinding: &FE) -> bool {
+        let x = point(j);
+        let mut points = commitments.iter().rev();
+        let expected = match points.next() {
+            Some(last) => points.fold(*last, |acc, c| acc * x + *c),
+            None => return false,
+        };
+        GE::generator() * *share + GE::base_point2() * *blinding == expected
+    }
+
+    fn check_complaints(&self, accuser: usize, complaints: &[DkgComplaint]) -> bool {
+        let mut accused: Vec<u16> = complaints.iter().map(|c| c.accused).collect();
+        accused.sort_unstable();
+        accused.dedup();
+        accused.len() == complaints.len()
+            && accused
+                .iter()
+                .all(|j| *j < self.share_count && *j as usize != accuser)
+    }
+
+    fn judge_complaints(&mut self) -> Result<(), Error> {
+        let index = self.party as usize;
+        let mut culprits = Vec::new();
+        for (accuser, complaints) in self.complaints.iter().enumerate() {
+            for complaint in complaints {
+                let dealer = complaint.accused as usize;
+                let commitments = &self.pedersen_vec[dealer];
+                let revealed = self.justifications[dealer]
+                    .iter()
+                    .find(|(j, _, _)| *j as usize == accuser)
+                    .map(|(_, s, s_prime)| (*s, *s_prime))
+                    .filter(|(s, s_prime)| Self::pedersen_valid(commitments, accuser, s, s_prime));
+                let claimed_valid = Self::pedersen_valid(
+                    commitments,
+                    accuser,
+                    &complaint.share,
+                    &complaint.blinding,
+                );
+                match revealed {
+                    None => culprits.push(dealer as u16),
+                    Some(_) if claimed_valid => culprits.push(accuser as u16),
+                    Some(share) if accuser == index => self.shares[dealer] = share,
+                    Some(_) => (),
+                }
+            }
+        }
+        self.record_culprits(culprits)
+    }
+
+    fn pedersen_commitments(&self) -> Vec<GE> {
+        let (g, h) = (GE::generator(), GE::base_point2());
+        self.coefficients
+            .iter()
+            .zip(&self.blindings)
+            .map(|(a, b)| g * *a + h * *b)
+            .collect()
+    }
+
+    fn feldman_commitments(&self) -> VerifiableSS<GE> {
+        let g = GE::generator();
+        VerifiableSS {
+            parameters: ShamirSecretSharing {
+                threshold: self.threshold as usize,
+                share_count: self.share_count as usize,
+            },
+            commitments: self.coefficients.iter().map(|a| g * *a).collect(),
+        }
+    }
+
+    // the dealers whose Feldman commitments are malformed, or do not match a share that
+    // passes the Pedersen check
+    fn find_cheaters(&self, complaints: &[Vec<DkgComplaint>]) -> Vec<u16> {
+        let (threshold, share_count) = (self.threshold as usize, self.share_count as usize);
+        let mut cheaters: Vec<u16> = (0..self.share_count)
+            .filter(|j| {
+                let vss_scheme = &self.vss_scheme_vec[*j as usize];
+                vss_scheme.parameters.threshold != threshold
+                    || vss_scheme.parameters.share_count != share_count
+                    || vss_scheme.commitments.len() != threshold + 1
+            })
+            .collect();
+        for (accuser, complaints) in complaints.iter().enumerate() {
+            for complaint in complaints {
+                let dealer = complaint.accused as usize;
+                if dealer < share_count
+                    && Self::pedersen_valid(
+                        &self.pedersen_vec[dealer],
+                        accuser,
+                        &complaint.share,
+                        &complaint.blinding,
+                    )
+                    && self.vss_scheme_vec[dealer]
+                        .validate_share(&complaint.share, accuser + 1)
+                        .is_err()
+                {
+                    cheaters.push(dealer as u16);
+                }
+            }
+        }
+        cheaters.sort_unstable();
+        cheaters.dedup();
+        cheaters
+    }
+
+    // replaces the Feldman commitments of every cheater by those of its polynomial,
+    // interpolated from the revealed shares that pass the Pedersen check
+    fn reconstruct(&mut self, reveals: &[Vec<(u16, FE, FE)>]) -> Result<(), Error> {
+        let g = GE::generator();
+        for dealer in self.cheaters.clone() {
+            let commitments = &self.pedersen_vec[dealer as usize];
+            let points: Vec<(FE, FE)> = reveals
+                .iter()
+                .enumerate()
+                .filter_map(|(j, reveal)| {
+                    let (_, s, s_prime) = reveal.iter().find(|(d, _, _)| *d == dealer)?;
+                    if Self::pedersen_valid(commitments, j, s, s_prime) {
+                        Some((point(j), *s))
+                    } else {
+                        None
+                    }
+                })
+                .take(self.threshold as usize + 1)
+                .collect();
+            if points.len() <= self.threshold as usize {
+                return Err(InvalidSS);
+            }
+            let vss_scheme = &mut self.vss_scheme_vec[dealer as usize];
+            vss_scheme.commitments = interpolate(&points).iter().map(|a| g * *a).collect();
+        }
+        Ok(())
+    }
+
+    fn own_reveal(&self) -> Vec<(u16, FE, FE)> {
+        self.cheaters
+            .iter()
+            .map(|d| {
+                let (s, s_prime) = self.shares[*d as usize];
+                (*d, s, s_prime)
+            })
+            .collect()
+    }
+
+    fn finish(&mut self) -> Result<(), Error> {
+        let index = self.party as usize;
+        let x_i = self
+            .shares
+            .iter()
+            .fold(FE::zero(), |acc, (share, _)| acc + share);
+        let xi_com_vec = Keys::get_commitments_to_xi(&self.vss_scheme_vec);
+        if GE::generator() * x_i != xi_com_vec[index] {
+            return Err(InvalidSS);
+        }
+        let (head, tail) = self.vss_scheme_vec.split_at(1);
+        let y = tail.iter().fold(head[0].commitments[0], |acc, vss_scheme| {
+            acc + vss_scheme.commitments[0]
+        });
+        self.coefficients.iter_mut().for_each(|a| a.zeroize());
+        self.blindings.iter_mut().for_each(|b| b.zeroize());
+        self.output = Some(LocalKey {
+            party_keys: self.party_keys.clone(),
+            shared_keys: SharedKeys { y, x_i },
+            party_index: self.party,
+            threshold: self.threshold,
+            share_count: self.share_count,
+            vss_scheme_vec: self.vss_scheme_vec.clone(),
+            paillier_key_vec: self.paillier_key_vec.clone(),
+            y,
+        });
+        Ok(())
+    }
+}
+
+impl Session for Keygen {
+    type Message = DkgMessage;
+    type Output = LocalKey;
+
+    fn party(&self) -> u16 {
+        self.party
+    }
+
+    fn round(&self) -> u16 {
+        self.round
+    }
+
+    fn expected_senders(&self) -> Vec<u16> {
+        match self.round {
+            1..=6 => self.others.clone(),
+            // nothing more is needed from the cheaters
+            7 => self
+                .others
+                .iter()
+                .cloned()
+                .filter(|j| !self.cheaters.contains(j))
+                .collect(),
+            _ => Vec::new(),
+        }
+    }
+
+    fn proceed(&mut self, incoming: Vec<Msg<DkgMessage>>) -> Result<Vec<Msg<DkgMessage>>, Error> {
+        let party = self.party;
+        let index = party as usize;
+        let outgoing = match self.round {
+            0 => {
+                let commitments = self.pedersen_commitments();
+                let correct_key_proof = NICorrectKeyProof::proof(&self.party_keys.dk, None);
+                let ek = self.party_keys.ek.clone();
+                let body = DkgMessage::Commitment(commitments, ek, correct_key_proof);
+                vec![broadcast(party, 1, body)]
+            }
+            1 => {
+                let received =
+                    threshold_signer::collect(incoming, self.party(), 1, &self.others, |body| {
+                        match body {
+                            DkgMessage::Commitment(commitments, ek, proof) => {
+                                Some((commitments, ek, proof))
+                            }
+                            _ => None,
+                        }
+                    })?;
+                for (_, ek, proof) in received.iter() {
+                    proof
+                        .verify(ek, zk_paillier::zkproofs::SALT_STRING)
+                        .map_err(|_| InvalidKey)?;
+                }
+                let culprits = self
+                    .others
+                    .iter()
+                    .zip(&received)
+                    .filter(|(_, (commitments, _, _))| {
+                        commitments.len() != self.threshold as usize + 1
+                    })
+                    .map(|(j, _)| *j)
+                    .collect();
+                self.record_culprits(culprits)?;
+
+                let (mut pedersen_vec, mut paillier_key_vec): (Vec<_>, Vec<_>) = received
+                    .into_iter()
+                    .map(|(commitments, ek, _)| (commitments, ek))
+                    .unzip();
+                pedersen_vec.insert(index, self.pedersen_commitments());
+                paillier_key_vec.insert(index, self.party_keys.ek.clone());
+                self.pedersen_vec = pedersen_vec;
+                self.paillier_key_vec = paillier_key_vec;
+                self.others
+                    .iter()
+                    .map(|j| {
+                        let (s, s_prime) = self.own_share(*j);
+                        p2p(party, *j, 2, DkgMessage::Share(s, s_prime))
+                    })
+                    .collect()
+            }
+            2 => {
+                let mut shares =
+                    threshold_signer::collect(incoming, self.party(), 2, &self.others, |body| {
+                        match body {
+                            DkgMessage::Share(s, s_prime) => Some((s, s_prime)),
+                            _ => None,
+                        }
+                    })?;
+                shares.insert(index, self.own_share(party));
+                let complaints: Vec<DkgComplaint> = self
+                    .others
+                    .iter()
+                    .filter(|j| {
+                        let (s, s_prime) = &shares[**j as usize];
+                        !Self::pedersen_valid(&self.pedersen_vec[**j as usize], index, s, s_prime)
+                    })
+                    .map(|j| DkgComplaint {
+                        accused: *j,
+                        share: shares[*j as usize].0,
+                        blinding: shares[*j as usize].1,
+                    })
+                    .collect();
+                self.shares = shares;
+                self.complaints = vec![complaints.clone()];
+                vec![broadcast(party, 3, DkgMessage::Complaints(complaints))]
+            }
+            3 => {
+                let mut complaints =
+                    threshold_signer::collect(incoming, self.party(), 3, &self.others, |body| {
+                        match body {
+                            DkgMessage::Complaints(complaints) => Some(complaints),
+                            _ => None,
+                        }
+                    })?;
+                complaints.insert(index, self.complaints.remove(0));
+                let culprits = (0..self.share_count)
+                    .filter(|j| !self.check_complaints(*j as usize, &complaints[*j as usize]))
+                    .collect();
+                self.record_culprits(culprits)?;
+
+                let accusers: Vec<u16> = (0..self.share_count)
+                    .filter(|j| complaints[*j as usize].iter().any(|c| c.accused == party))
+                    .collect();
+                let nobody_complains = complaints.iter().all(|c| c.is_empty());
+                self.complaints = complaints;
+                if nobody_complains {
+                    // round 4 is skipped
+                    self.round += 1;
+                    let body = DkgMessage::VssScheme(self.feldman_commitments());
+                    vec![broadcast(party, 5, body)]
+                } else {
+                    let justification: Vec<(u16, FE, FE)> = accusers
+                        .iter()
+                        .map(|j| {
+                            let (s, s_prime) = self.own_share(*j);
+                            (*j, s, s_prime)
+                        })
+                        .collect();
+                    self.justifications = vec![justification.clone()];
+                    let body = DkgMessage::Justification(justification);
+                    vec![broadcast(party, 4, body)]
+                }
+            }
+            4 => {
+                let mut justifications =
+                    threshold_signer::collect(incoming, self.party(), 4, &self.others, |body| {
+                        match body {
+                            DkgMessage::Justification(justification) => Some(justification),
+                            _ => None,
+                        }
+                    })?;
+                justifications.insert(index, self.justifications.remove(0));
+                self.justifications = justifications;
+                self.judge_complaints()?;
+                let body = DkgMessage::VssScheme(self.feldman_commitments());
+                vec![broadcast(party, 5, body)]
+            }
+            5 => {
+                let mut vss_scheme_vec =
+                    threshold_signer::collect(incoming, self.party(), 5, &self.others, |body| {
+                        match body {
+                            DkgMessage::VssScheme(vss_scheme) => Some(vss_scheme),
+                            _ => None,
+                        }
+                    })?;
+                vss_scheme_vec.insert(index, self.feldman_commitments());
+                let complaints: Vec<DkgComplaint> = self
+                    .others
+                    .iter()
+                    .filter(|j| {
+                        let vss_scheme = &vss_scheme_vec[**j as usize];
+                        let (s, _) = &self.shares[**j as usize];
+                        vss_scheme.commitments.len() != self.threshold as usize + 1
+                            || vss_scheme.validate_share(s, index + 1).is_err()
+                    })
+                    .map(|j| DkgComplaint {
+                        accused: *j,
+                        share: self.shares[*j as usize].0,
+                        blinding: self.shares[*j as usize].1,
+                    })
+                    .collect();
+                self.vss_scheme_vec = vss_scheme_vec;
+                self.complaints = vec![complaints.clone()];
+                vec![broadcast(party, 6, DkgMessage::Complaints(complaints))]
+            }
+            6 => {
+                let mut complaints =
+                    threshold_signer::collect(incoming, self.party(), 6, &self.others, |body| {
+                        match body {
+                            DkgMessage::Complaints(complaints) => Some(complaints),
+                            _ => None,
+                        }
+                    })?;
+                // everyone has to find the same cheaters, so the own complaints count as well
+                complaints.insert(index, self.complaints.remove(0));
+                self.cheaters = self.find_cheaters(&complaints);
+                if self.cheaters.is_empty() {
+                    // round 7 is skipped
+                    self.round += 1;
+                    self.finish()?;
+                    Vec::new()
+                } else if self.cheaters.contains(&party) {
+                    Vec::new()
+                } else {
+                    let reveal = self.own_reveal();
+                    vec![broadcast(party, 7, DkgMessage::Reveal(reveal))]
+                }
+            }
+            7 => {
+                let senders = self.expected_senders();
+                let received = threshold_signer::collect(
+                    incoming,
+                    self.party(),
+                    7,
+                    &senders,
+                    |body| match body {
+                        DkgMessage::Reveal(reveal) => Some(reveal),
+                        _ => None,
+                    },
+                )?;
+                let mut reveals = vec![Vec::new(); self.share_count as usize];
+                for (j, reveal) in senders.iter().zip(received) {
+                    reveals[*j as usize] = reveal;
+                }
+                if !self.cheaters.contains(&party) {
+                    reveals[index] = self.own_reveal();
+                }
+                self.reconstruct(&reveals)?;
+                self.finish()?;
+                Vec::new()
+            }
+            _ => return Err(InvalidMessage),
+        };
+        self.round += 1;
+        Ok(outgoing)
+    }
+
+    fn is_finished(&self) -> bool {
+        self.round > 7
+    }
+
+    fn pick_output(&mut self) -> Option<LocalKey> {
+        self.output.take()
+    }
+}
+
+// the evaluation point of the 0-based party j
+fn point(j: usize) -> FE {
+    ECScalar::from(&BigInt::from(j as u32 + 1))
+}
+
+// the polynomial with `coefficients`, lowest degree first, at x
+fn evaluate(coefficients: &[FE], x: &FE) -> FE {
+    let mut terms = coefficients.iter().rev();
+    let last = *terms.next().unwrap();
+    terms.fold(last, |acc, a| acc * *x + *a)
+}
+
+// the coefficients, lowest degree first, of the polynomial of degree points.len() - 1
+// through `points`
+fn interpolate(points: &[(FE, FE)]) -> Vec<FE> {
+    let mut coefficients = vec![FE::zero(); points.len()];
+    for (m, (x_m, y_m)) in points.iter().enumerate() {
+        // prod_{l != m} (z - x_l), built up one factor at a time
+        let mut basis = vec![FE::zero(); points.len()];
+        basis[0] = ECScalar::from(&BigInt::one());
+        let mut degree = 0;
+        let mut denominator: FE = ECScalar::from(&BigInt::one());
+        for (l, (x_l, _)) in points.iter().enumerate() {
+            if l == m {
+                continue;
+            }
+            degree += 1;
+            for k in (0..=degree).rev() {
+                let shifted = if k > 0 { basis[k - 1] } else { FE::zero() };
+                basis[k] = shifted.sub(&(basis[k] * *x_l).get_element());
+            }
+            denominator = denominator * x_m.sub(&x_l.get_element());
+        }
+        let scale = *y_m * denominator.invert();
+        for (c, b) in coefficients.iter_mut().zip(&basis) {
+            *c = *c + *b * scale;
+        }
+    }
+    coefficients
+}
@@ -0,0 +1,206 @@
+/*
+    Multi-party ECDSA
+
+    Copyright 2018 by Kzen Networks
+
+    This file is part of Multi-party ECDSA library
+    (https://github.com/KZen-networks/multi-party-ecdsa)
+
+    Multi-party ECDSA is free software: you can redistribute
+    it and/or modify it under the terms of the GNU General Public
+    License as published by the Free Software Foundation, either
+    version 3 of the License, or (at your option) any later version.
+
+    @license GPL-3.0+ <https://github.com/KZen-networks/multi-party-ecdsa/blob/master/LICENSE>
+*/
+
+//! Import of an existing key into a GG18 sharing, from the dealings of
+//! [`crate::protocols::import::Dealer::deal_gg18`]. Each party checks its dealing against
+//! the public key when the session is created, then:
+//!
+//! ```text
+//! round 1  broadcast  fresh Paillier key + proof
+//! round 2  broadcast  proof of knowledge of x_i, which also shows that all parties were
+//!                     dealt the same VSS schemes
+//! ```
+
+use curv::cryptographic_primitives::proofs::sigma_dlog::DLogProof;
+use curv::cryptographic_primitives::secret_sharing::feldman_vss::VerifiableSS;
+use curv::elliptic::curves::p256::{FE, GE};
+use curv::elliptic::curves::traits::*;
+use paillier::EncryptionKey;
+use serde::{Deserialize, Serialize};
+use zk_paillier::zkproofs::NICorrectKeyProof;
+
+use super::party_i::{Keys, SharedKeys};
+use super::signer::LocalKey;
+use crate::protocols::import::SlotDealing;
+use crate::protocols::threshold_signer::{self, broadcast, Msg, Session};
+use crate::Error::{self, InvalidKey, InvalidMessage, InvalidSS};
+
+#[derive(Debug, Serialize, Deserialize)]
+pub enum ImportMessage {
+    PaillierKey(EncryptionKey, NICorrectKeyProof),
+    DLogProof(DLogProof<GE>),
+}
+
+pub struct Import {
+    party: u16,
+    threshold: u16,
+    share_count: u16,
+    others: Vec<u16>,
+    round: u16,
+    public_key: GE,
+    party_keys: Keys,
+    vss_scheme_vec: Vec<VerifiableSS<GE>>,
+    x_i: FE,
+    paillier_key_vec: Vec<EncryptionKey>,
+    output: Option<LocalKey>,
+}
+
+impl Import {
+    /// `dealing` is the dealing of `party`, and `public_key` the public key of the imported
+    /// key as published by its owner.
+    pub fn new(
+        party: u16,
+        threshold: u16,
+        share_count: u16,
+        public_key: GE,
+        dealing: Vec<SlotDealing>,
+    ) -> Result<Self, Error> {
+        if share_count < 2
+            || party >= share_count
+            || threshold >= share_count
+            || dealing.len() != share_count as usize
+        {
+            return Err(InvalidKey);
+        }
+        let index = party as usize;
+        let valid = dealing.iter().enumerate().all(|(slot, s)| {
+            s.slot as usize == slot
+                && s.vss_scheme.parameters.threshold == threshold as usize
+                && s.vss_scheme.parameters.share_count == share_count as usize
+                && s.vss_scheme.validate_share(&s.share, index + 1).is_ok()
+        });
+        let (head, tail) = dealing.split_at(1);
+        let y = tail
+            .iter()
+            .fold(head[0].vss_scheme.commitments[0], |acc, s| {
+                acc + s.vss_scheme.commitments[0]
+            });
+        if !valid || y != public_key {
+            return Err(InvalidSS);
+        }
+
+        let x_i = dealing.iter().fold(FE::zero(), |acc, s| acc + s.share);
+        Ok(Import {
+            party,
+            threshold,
+            share_count,
+            others: (0..share_count).filter(|j| *j != party).collect(),
+            round: 0,
+            public_key,
+            party_keys: Keys::create(index),
+            vss_scheme_vec: dealing.into_iter().map(|s| s.vss_scheme).collect(),
+            x_i,
+            paillier_key_vec: Vec::new(),
+            output: None,
+        })
+    }
+}
+
+impl Session for Import {
+    type Message = ImportMessage;
+    type Output = LocalKey;
+
+    fn party(&self) -> u16 {
+        self.party
+    }
+
+    fn round(&self) -> u16 {
+        self.round
+    }
+
+    fn expected_senders(&self) -> Vec<u16> {
+        match self.round {
+            1 | 2 => self.others.clone(),
+            _ => Vec::new(),
+        }
+    }
+
+    fn proceed(
+        &mut self,
+        incoming: Vec<Msg<ImportMessage>>,
+    ) -> Result<Vec<Msg<ImportMessage>>, Error> {
+        let party = self.party;
+        let index = party as usize;
+        let outgoing = match self.round {
+            0 => {
+                let correct_key_proof = NICorrectKeyProof::proof(&self.party_keys.dk, None);
+                let ek = self.party_keys.ek.clone();
+                let body = ImportMessage::PaillierKey(ek, correct_key_proof);
+                vec![broadcast(party, 1, body)]
+            }
+            1 => {
+                let keys =
+                    threshold_signer::collect(incoming, self.party(), 1, &self.others, |body| {
+                        match body {
+                            ImportMessage::PaillierKey(ek, proof) => Some((ek, proof)),
+                            _ => None,
+                        }
+                    })?;
+                for (ek, proof) in keys.iter() {
+                    proof
+                        .verify(ek, zk_paillier::zkproofs::SALT_STRING)
+                        .map_err(|_| InvalidKey)?;
+                }
+                let mut paillier_key_vec: Vec<EncryptionKey> =
+                    keys.into_iter().map(|(ek, _)| ek).collect();
+                paillier_key_vec.insert(index, self.party_keys.ek.clone());
+                self.paillier_key_vec = paillier_key_vec;
+                let dlog_proof = DLogProof::prove(&self.x_i);
+                vec![broadcast(party, 2, ImportMessage::DLogProof(dlog_proof))]
+            }
+            2 => {
+                let dlog_proofs =
+                    threshold_signer::collect(incoming, self.party(), 2, &self.others, |body| {
+                        match body {
+                            ImportMessage::DLogProof(proof) => Some(proof),
+                            _ => None,
+                        }
+                    })?;
+                let xi_com_vec = Keys::get_commitments_to_xi(&self.vss_scheme_vec);
+                for (j, proof) in self.others.iter().zip(dlog_proofs.iter()) {
+                    if DLogProof::verify(proof).is_err() || proof.pk != xi_com_vec[*j as usize] {
+                        return Err(InvalidKey);
+                    }
+                }
+                self.output = Some(LocalKey {
+                    party_keys: self.party_keys.clone(),
+                    shared_keys: SharedKeys {
+                        y: self.public_key,
+                        x_i: self.x_i,
+                    },
+                    party_index: party,
+                    threshold: self.threshold,
+                    share_count: self.share_count,
+                    vss_scheme_vec: self.vss_scheme_vec.clone(),
+                    paillier_key_vec: self.paillier_key_vec.clone(),
+                    y: self.public_key,
+                });
+                Vec::new()
+            }
+            _ => return Err(InvalidMessage),
+        };
+        self.round += 1;
+        Ok(outgoing)
+    }
+
+    fn is_finished(&self) -> bool {
+        self.round > 2
+    }
+
+    fn pick_output(&mut self) -> Option<LocalKey> {
+        self.output.take()
+    }
+}
@@ -14,7 +14,10 @@
     @license GPL-3.0+ <https://github.com/KZen-networks/multi-party-ecdsa/blob/master/LICENSE>
 */
 
+pub mod dkg;
+pub mod import;
 pub mod party_i;
+pub mod signer;
 
 #[cfg(test)]
 mod test;
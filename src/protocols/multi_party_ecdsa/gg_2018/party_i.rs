@@ -35,10 +35,30 @@ use paillier::{
 use serde::{Deserialize, Serialize};
 use zk_paillier::zkproofs::NICorrectKeyProof;
 
+use crate::utilities::digest::MessageDigest;
+use crate::utilities::encoding;
+use crate::utilities::paillier_context::PaillierContext;
+use crate::utilities::parallel;
+use crate::utilities::recovery;
+use crate::utilities::verification::{self, BatchItem};
 use crate::Error::{self, InvalidCom, InvalidKey, InvalidSS, InvalidSig};
 
 const SECURITY: usize = 256;
 
+/// A failed check on the messages of other parties. `index` is the position, in the vectors
+/// passed to the check, of the first message that failed.
+#[derive(Copy, PartialEq, Eq, Clone, Debug)]
+pub struct PartyError {
+    pub error: Error,
+    pub index: usize,
+}
+
+impl From<PartyError> for Error {
+    fn from(e: PartyError) -> Error {
+        e.error
+    }
+}
+
 #[derive(Debug)]
 pub struct Parameters {
     pub threshold: u16,   //t
@@ -212,32 +232,47 @@ impl Keys {
         decom_vec: &[KeyGenDecommitMessage1],
         bc1_vec: &[KeyGenBroadcastMessage1],
     ) -> Result<(VerifiableSS<GE>, Vec<FE>, usize), Error> {
+        Ok(
+            self.phase1_verify_com_phase3_verify_correct_key_phase2_distribute_blaming(
+                params, decom_vec, bc1_vec,
+            )?,
+        )
+    }
+
+    /// Like `phase1_verify_com_phase3_verify_correct_key_phase2_distribute`, and names the
+    /// first party whose message failed.
+    pub fn phase1_verify_com_phase3_verify_correct_key_phase2_distribute_blaming(
+        &self,
+        params: &Parameters,
+        decom_vec: &[KeyGenDecommitMessage1],
+        bc1_vec: &[KeyGenBroadcastMessage1],
+    ) -> Result<(VerifiableSS<GE>, Vec<FE>, usize), PartyError> {
         // test length:
         assert_eq!(decom_vec.len() as u16, params.share_count);
         assert_eq!(bc1_vec.len() as u16, params.share_count);
         // test paillier correct key and test decommitments
-        let correct_key_correct_decom_all = (0..bc1_vec.len())
-            .map(|i| {
-                HashCommitment::create_commitment_with_user_defined_randomness(
-                    &decom_vec[i].y_i.bytes_compressed_to_big_int(),
-                    &decom_vec[i].blind_factor,
-                ) == bc1_vec[i].com
-                    && bc1_vec[i]
-                        .correct_key_proof
-                        .verify(&bc1_vec[i].e, zk_paillier::zkproofs::SALT_STRING)
-                        .is_ok()
-            })
-            .all(|x| x);
+        let failure = parallel::first_failure(bc1_vec.len(), |i| {
+            HashCommitment::create_commitment_with_user_defined_randomness(
+                &decom_vec[i].y_i.bytes_compressed_to_big_int(),
+                &decom_vec[i].blind_factor,
+            ) == bc1_vec[i].com
+                && bc1_vec[i]
+                    .correct_key_proof
+                    .verify(&bc1_vec[i].e, zk_paillier::zkproofs::SALT_STRING)
+                    .is_ok()
+        });
 
         let (vss_scheme, secret_shares) = VerifiableSS::share(
             params.threshold as usize,
             params.share_count as usize,
             &self.u_i,
         );
-        if correct_key_correct_decom_all {
-            Ok((vss_scheme, secret_shares, self.party_index))
-        } else {
-            Err(InvalidKey)
+        match failure {
+            None => Ok((vss_scheme, secret_shares, self.party_index)),
+            Some(index) => Err(PartyError {
+                error: InvalidKey,
+                index,
+            }),
         }
     }
 
@@ -249,28 +284,51 @@ impl Keys {
         vss_scheme_vec: &[VerifiableSS<GE>],
         index: usize,
     ) -> Result<(SharedKeys, DLogProof<GE>), Error> {
+        Ok(
+            self.phase2_verify_vss_construct_keypair_phase3_pok_dlog_blaming(
+                params,
+                y_vec,
+                secret_shares_vec,
+                vss_scheme_vec,
+                index,
+            )?,
+        )
+    }
+
+    /// Like `phase2_verify_vss_construct_keypair_phase3_pok_dlog`, and names the first
+    /// party whose share or VSS scheme failed.
+    pub fn phase2_verify_vss_construct_keypair_phase3_pok_dlog_blaming(
+        &self,
+        params: &Parameters,
+        y_vec: &[GE],
+        secret_shares_vec: &[FE],
+        vss_scheme_vec: &[VerifiableSS<GE>],
+        index: usize,
+    ) -> Result<(SharedKeys, DLogProof<GE>), PartyError> {
         assert_eq!(y_vec.len() as u16, params.share_count);
         assert_eq!(secret_shares_vec.len() as u16, params.share_count);
         assert_eq!(vss_scheme_vec.len() as u16, params.share_count);
 
-        let correct_ss_verify = (0..y_vec.len())
-            .map(|i| {
-                vss_scheme_vec[i]
-                    .validate_share(&secret_shares_vec[i], index)
-                    .is_ok()
-                    && vss_scheme_vec[i].commitments[0].get_element() == y_vec[i].get_element()
-            })
-            .all(|x| x);
-
-        if correct_ss_verify {
-            let (head, tail) = y_vec.split_at(1);
-            let y = tail.iter().fold(head[0], |acc, x| acc + x);
-
-            let x_i = secret_shares_vec.iter().fold(FE::zero(), |acc, x| acc + x);
-            let dlog_proof = DLogProof::prove(&x_i);
-            Ok((SharedKeys { y, x_i }, dlog_proof))
-        } else {
-            Err(InvalidSS)
+        let failure = parallel::first_failure(y_vec.len(), |i| {
+            vss_scheme_vec[i]
+                .validate_share(&secret_shares_vec[i], index)
+                .is_ok()
+                && vss_scheme_vec[i].commitments[0].get_element() == y_vec[i].get_element()
+        });
+
+        match failure {
+            None => {
+                let (head, tail) = y_vec.split_at(1);
+                let y = tail.iter().fold(head[0], |acc, x| acc + x);
+
+                let x_i = secret_shares_vec.iter().fold(FE::zero(), |acc, x| acc + x);
+                let dlog_proof = DLogProof::prove(&x_i);
+                Ok((SharedKeys { y, x_i }, dlog_proof))
+            }
+            Some(index) => Err(PartyError {
+                error: InvalidSS,
+                index,
+            }),
         }
     }
 
@@ -306,16 +364,31 @@ impl Keys {
         dlog_proofs_vec: &[DLogProof<GE>],
         y_vec: &[GE],
     ) -> Result<(), Error> {
+        Ok(Self::verify_dlog_proofs_blaming(
+            params,
+            dlog_proofs_vec,
+            y_vec,
+        )?)
+    }
+
+    /// Like `verify_dlog_proofs`, and names the first party whose proof failed.
+    pub fn verify_dlog_proofs_blaming(
+        params: &Parameters,
+        dlog_proofs_vec: &[DLogProof<GE>],
+        y_vec: &[GE],
+    ) -> Result<(), PartyError> {
         assert_eq!(y_vec.len() as u16, params.share_count);
         assert_eq!(dlog_proofs_vec.len() as u16, params.share_count);
-        let xi_dlog_verify = (0..y_vec.len())
-            .map(|i| DLogProof::verify(&dlog_proofs_vec[i]).is_ok())
-            .all(|x| x);
-
-        if xi_dlog_verify {
-            Ok(())
-        } else {
-            Err(InvalidKey)
+        let failure = parallel::first_failure(y_vec.len(), |i| {
+            DLogProof::verify(&dlog_proofs_vec[i]).is_ok()
+        });
+
+        match failure {
+            None => Ok(()),
+            Some(index) => Err(PartyError {
+                error: InvalidKey,
+                index,
+            }),
         }
     }
 }
@@ -338,6 +411,11 @@ impl PartyPrivate {
         Paillier::decrypt(&self.dk, &RawCiphertext::from(ciphertext))
     }
 
+    /// Paillier context of the local key, to be kept for the MtA `_with_context` functions.
+    pub fn paillier_context(&self) -> PaillierContext {
+        PaillierContext::with_decryption_key(&self.dk)
+    }
+
     pub fn refresh_private_key(&self, factor: &FE, index: usize) -> Keys {
         let u: FE = self.u_i + factor;
         let y = GE::generator() * u;
@@ -506,6 +584,18 @@ impl LocalSignature {
         }
     }
 
+    /// Like `phase5_local_sig`, but hashes the raw `message` with `digest` first.
+    pub fn phase5_local_sig_with_digest(
+        k_i: &FE,
+        message: &[u8],
+        digest: MessageDigest,
+        R: &GE,
+        sigma_i: &FE,
+        pubkey: &GE,
+    ) -> Self {
+        Self::phase5_local_sig(k_i, &digest.to_message(message), R, sigma_i, pubkey)
+    }
+
     pub fn phase5a_broadcast_5b_zkproof(
         &self,
     ) -> (
@@ -561,33 +651,50 @@ impl LocalSignature {
         v_i: &GE,
         R: &GE,
     ) -> Result<(Phase5Com2, Phase5DDecom2), Error> {
+        Ok(self.phase5c_blaming(decom_vec, com_vec, elgamal_proofs, dlog_proofs_rho, v_i, R)?)
+    }
+
+    /// Like `phase5c`, and names the first party whose commitment or proofs failed.
+    pub fn phase5c_blaming(
+        &self,
+        decom_vec: &[Phase5ADecom1],
+        com_vec: &[Phase5Com1],
+        elgamal_proofs: &[HomoELGamalProof<GE>],
+        dlog_proofs_rho: &[DLogProof<GE>],
+        v_i: &GE,
+        R: &GE,
+    ) -> Result<(Phase5Com2, Phase5DDecom2), PartyError> {
         assert_eq!(decom_vec.len(), com_vec.len());
 
         let g: GE = ECPoint::generator();
-        let test_com_elgamal = (0..com_vec.len())
-            .map(|i| {
-                let delta = HomoElGamalStatement {
-                    G: decom_vec[i].A_i,
-                    H: *R,
-                    Y: g,
-                    D: decom_vec[i].V_i,
-                    E: decom_vec[i].B_i,
-                };
-                let input_hash = HSha256::create_hash_from_ge(&[
-                    &decom_vec[i].V_i,
-                    &decom_vec[i].A_i,
-                    &decom_vec[i].B_i,
-                ])
-                .to_big_int();
-
-                HashCommitment::create_commitment_with_user_defined_randomness(
-                    &input_hash,
-                    &decom_vec[i].blind_factor,
-                ) == com_vec[i].com
-                    && elgamal_proofs[i].verify(&delta).is_ok()
-                    && DLogProof::verify(&dlog_proofs_rho[i]).is_ok()
-            })
-            .all(|x| x);
+        let failure = parallel::first_failure(com_vec.len(), |i| {
+            let delta = HomoElGamalStatement {
+                G: decom_vec[i].A_i,
+                H: *R,
+                Y: g,
+                D: decom_vec[i].V_i,
+                E: decom_vec[i].B_i,
+            };
+            let input_hash = HSha256::create_hash_from_ge(&[
+                &decom_vec[i].V_i,
+                &decom_vec[i].A_i,
+                &decom_vec[i].B_i,
+            ])
+            .to_big_int();
+
+            HashCommitment::create_commitment_with_user_defined_randomness(
+                &input_hash,
+                &decom_vec[i].blind_factor,
+            ) == com_vec[i].com
+                && elgamal_proofs[i].verify(&delta).is_ok()
+                && DLogProof::verify(&dlog_proofs_rho[i]).is_ok()
+        });
+        if let Some(index) = failure {
+            return Err(PartyError {
+                error: InvalidCom,
+                index,
+            });
+        }
 
         let v_vec = (0..com_vec.len())
             .map(|i| &decom_vec[i].V_i)
@@ -618,20 +725,14 @@ impl LocalSignature {
             &blind_factor,
         );
 
-        if test_com_elgamal {
-            Ok({
-                (
-                    Phase5Com2 { com },
-                    Phase5DDecom2 {
-                        u_i,
-                        t_i,
-                        blind_factor,
-                    },
-                )
-            })
-        } else {
-            Err(InvalidCom)
-        }
+        Ok((
+            Phase5Com2 { com },
+            Phase5DDecom2 {
+                u_i,
+                t_i,
+                blind_factor,
+            },
+        ))
     }
 
     pub fn phase5d(
@@ -685,21 +786,20 @@ impl LocalSignature {
         let s_bn = s.to_big_int();
 
         let r: FE = ECScalar::from(&self.R.x_coor().unwrap().mod_floor(&FE::q()));
-        let ry: BigInt = self.R.y_coor().unwrap().mod_floor(&FE::q());
 
         /*
          Calculate recovery id - it is not possible to compute the public key out of the signature
          itself. Recovery id is used to enable extracting the public key uniquely.
          1. id = R.y & 1
          2. if (s > curve.q / 2) id = id ^ 1
+         3. if (R.x >= curve.q) id = id | 2
         */
-        let is_ry_odd = ry.test_bit(0);
-        let mut recid = if is_ry_odd { 1 } else { 0 };
         let s_tag_bn = FE::q() - &s_bn;
-        if s_bn > s_tag_bn {
+        let is_s_high = s_bn > s_tag_bn;
+        if is_s_high {
             s = ECScalar::from(&s_tag_bn);
-            recid = recid ^ 1;
         }
+        let recid = recovery::compute_recid(&self.R, is_s_high);
         let sig = SignatureRecid { r, s, recid };
         let ver = verify(&sig, &self.y, &self.m).is_ok();
         if ver {
@@ -710,20 +810,96 @@ impl LocalSignature {
     }
 }
 
-pub fn verify(sig: &SignatureRecid, y: &GE, message: &BigInt) -> Result<(), Error> {
-    let b = sig.s.invert();
-    let a: FE = ECScalar::from(message);
-    let u1 = a * b;
-    let u2 = sig.r * b;
+impl SignatureRecid {
+    pub fn to_compact(&self) -> Result<[u8; recovery::COMPACT_SIGNATURE_SIZE], Error> {
+        recovery::to_compact(&self.r.to_big_int(), &self.s.to_big_int(), self.recid)
+    }
+
+    pub fn from_compact(bytes: &[u8]) -> Result<Self, Error> {
+        let (r, s, recid) = recovery::from_compact(bytes)?;
+        Ok(SignatureRecid {
+            r: ECScalar::from(&r),
+            s: ECScalar::from(&s),
+            recid,
+        })
+    }
+
+    pub fn to_der(&self) -> Vec<u8> {
+        encoding::to_der(&self.r.to_big_int(), &self.s.to_big_int())
+    }
+
+    pub fn to_fixed(&self) -> Result<[u8; encoding::FIXED_SIGNATURE_SIZE], Error> {
+        encoding::to_fixed(&self.r.to_big_int(), &self.s.to_big_int())
+    }
+
+    pub fn to_p256(&self) -> Result<p256::ecdsa::Signature, Error> {
+        encoding::to_p256(&self.r.to_big_int(), &self.s.to_big_int())
+    }
+
+    /// The encodings below carry no recovery id, it is recomputed from the message and the
+    /// group public key y.
+    pub fn from_der(bytes: &[u8], message: &BigInt, y: &GE) -> Result<Self, Error> {
+        let (r, s) = encoding::from_der(bytes)?;
+        Self::with_recid(r, s, message, y)
+    }
+
+    pub fn from_fixed(bytes: &[u8], message: &BigInt, y: &GE) -> Result<Self, Error> {
+        let (r, s) = encoding::from_fixed(bytes)?;
+        Self::with_recid(r, s, message, y)
+    }
+
+    pub fn from_p256(
+        signature: &p256::ecdsa::Signature,
+        message: &BigInt,
+        y: &GE,
+    ) -> Result<Self, Error> {
+        let (r, s) = encoding::from_p256(signature)?;
+        Self::with_recid(r, s, message, y)
+    }
+
+    fn with_recid(r: BigInt, s: BigInt, message: &BigInt, y: &GE) -> Result<Self, Error> {
+        let recid = encoding::find_recid(&r, &s, message, y)?;
+        Ok(SignatureRecid {
+            r: ECScalar::from(&r),
+            s: ECScalar::from(&s),
+            recid,
+        })
+    }
+}
 
-    let g: GE = ECPoint::generator();
-    let gu1 = g * u1;
-    let yu2 = y * &u2;
-    // can be faster using shamir trick
+pub fn recover_public_key(sig: &SignatureRecid, message: &BigInt) -> Result<GE, Error> {
+    recovery::recover_public_key(&sig.r.to_big_int(), &sig.s.to_big_int(), sig.recid, message)
+}
+
+pub fn verify(sig: &SignatureRecid, y: &GE, message: &BigInt) -> Result<(), Error> {
+    verification::verify(&sig.r.to_big_int(), &sig.s.to_big_int(), message, y)
+}
 
-    if sig.r == ECScalar::from(&(gu1 + yu2).x_coor().unwrap().mod_floor(&FE::q())) {
-        Ok(())
-    } else {
-        Err(InvalidSig)
+/// Verifies `sigs[i]` on `messages[i]` under `ys[i]` for all i at once.
+pub fn batch_verify(sigs: &[SignatureRecid], ys: &[GE], messages: &[BigInt]) -> Result<(), Error> {
+    if sigs.len() != ys.len() || sigs.len() != messages.len() {
+        return Err(InvalidSig);
     }
+    let items: Vec<BatchItem> = sigs
+        .iter()
+        .zip(ys)
+        .zip(messages)
+        .map(|((sig, y), message)| BatchItem {
+            r: sig.r.to_big_int(),
+            s: sig.s.to_big_int(),
+            recid: sig.recid,
+            message: message.clone(),
+            public_key: *y,
+        })
+        .collect();
+    verification::batch_verify(&items)
+}
+
+pub fn verify_with_digest(
+    sig: &SignatureRecid,
+    y: &GE,
+    message: &[u8],
+    digest: MessageDigest,
+) -> Result<(), Error> {
+    verify(sig, y, &digest.to_message(message))
 }
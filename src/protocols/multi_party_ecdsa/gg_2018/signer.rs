@@ -0,0 +1,886 @@
+#![allow(non_snake_case)]
+/*
+    Multi-party ECDSA
+
+    Copyright 2018 by Kzen Networks
+
+    This file is part of Multi-party ECDSA library
+    (https://github.com/KZen-networks/multi-party-ecdsa)
+
+    Multi-party ECDSA is free software: you can redistribute
+    it and/or modify it under the terms of the GNU General Public
+    License as published by the Free Software Foundation, either
+    version 3 of the License, or (at your option) any later version.
+
+    @license GPL-3.0+ <https://github.com/KZen-networks/multi-party-ecdsa/blob/master/LICENSE>
+*/
+
+//! GG18 keygen (7 rounds) and signing (10 rounds) as [`Session`]s.
+//!
+//! A party that receives a share which does not match the VSS scheme of its dealer
+//! broadcasts a complaint against the dealer in round 5, and every accused dealer must
+//! reveal the disputed shares in round 6. The shares travel P2P without a signature of the
+//! dealer, so nobody can check which share the accuser really got. Everyone then applies
+//! the same rule: a dealer whose revealed share is invalid, or missing, is at fault,
+//! otherwise the accuser is, as a false complaint cannot be told apart from a share damaged
+//! on its way. Keygen fails with `InvalidSS` when anyone is at fault, and
+//! [`Keygen::culprits`] names them. A
+//! commitment, key proof or dlog proof that does not verify also names its sender there.
+//! Round 6 is skipped when nobody complains.
+//!
+//! Signing starts with a round in which the signers compare the message and the metadata
+//! they were given, see [`signing_intent`]. If they differ, signing fails with
+//! `MessageMismatch` before any MtA, and [`Signing::disagreeing`] names the other side.
+//! A signer whose MtA answer or phase 5 proofs do not verify is named by
+//! [`Signing::culprits`].
+
+use curv::cryptographic_primitives::proofs::sigma_correct_homomorphic_elgamal_enc::HomoELGamalProof;
+use curv::cryptographic_primitives::proofs::sigma_dlog::DLogProof;
+use curv::cryptographic_primitives::secret_sharing::feldman_vss::VerifiableSS;
+use curv::elliptic::curves::p256::{FE, GE};
+use curv::elliptic::curves::traits::*;
+use curv::BigInt;
+use paillier::EncryptionKey;
+use serde::{Deserialize, Serialize};
+
+use super::party_i::{
+    KeyGenBroadcastMessage1, KeyGenDecommitMessage1, Keys, LocalSignature, Parameters, PartyError,
+    PartyPrivate, Phase5ADecom1, Phase5Com1, Phase5Com2, Phase5DDecom2, SharedKeys,
+    SignBroadcastPhase1, SignDecommitPhase1, SignKeys,
+};
+use crate::protocols::threshold_signer::{
+    self, broadcast, p2p, KeyShare, Msg, Session, ThresholdSigner,
+};
+use crate::utilities::digest::signing_intent;
+use crate::utilities::mta::{MessageA, MessageB, MtaBackend, PaillierMta};
+use crate::utilities::parallel;
+use crate::Error::{self, InvalidKey, InvalidSS, MessageMismatch};
+
+/// Everything a party needs to keep after keygen in order to sign.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct LocalKey {
+    pub party_keys: Keys,
+    pub shared_keys: SharedKeys,
+    pub party_index: u16,
+    pub threshold: u16,
+    pub share_count: u16,
+    pub vss_scheme_vec: Vec<VerifiableSS<GE>>,
+    pub paillier_key_vec: Vec<EncryptionKey>,
+    pub y: GE,
+}
+
+impl KeyShare for LocalKey {
+    fn party(&self) -> u16 {
+        self.party_index
+    }
+
+    fn public_key(&self) -> GE {
+        self.y
+    }
+}
+
+pub struct Gg18;
+
+impl ThresholdSigner for Gg18 {
+    type KeyShare = LocalKey;
+    type Keygen = Keygen;
+    type Signing = Signing;
+
+    fn keygen(party: u16, threshold: u16, share_count: u16) -> Result<Keygen, Error> {
+        Keygen::new(party, threshold, share_count)
+    }
+
+    fn sign(key: &LocalKey, signers: &[u16], message: &BigInt) -> Result<Signing, Error> {
+        Signing::new(key.clone(), signers, message)
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub enum KeygenMessage {
+    Commitment(KeyGenBroadcastMessage1),
+    Decommitment(KeyGenDecommitMessage1),
+    SecretShare(FE),
+    VssScheme(VerifiableSS<GE>),
+    Complaints(Vec<Complaint>),
+    /// The shares revealed to each accuser, as (accuser, share).
+    Justification(Vec<(u16, FE)>),
+    DLogProof(DLogProof<GE>),
+}
+
+/// A complaint against `accused`, whose share failed validation. It carries no evidence,
+/// the accused has to reveal the share publicly.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Complaint {
+    pub accused: u16,
+}
+
+pub struct Keygen {
+    party: u16,
+    threshold: u16,
+    share_count: u16,
+    others: Vec<u16>,
+    round: u16,
+    party_keys: Keys,
+    bc1: Option<KeyGenBroadcastMessage1>,
+    decom1: Option<KeyGenDecommitMessage1>,
+    bc1_vec: Vec<KeyGenBroadcastMessage1>,
+    y_vec: Vec<GE>,
+    secret_shares: Vec<FE>,
+    vss_scheme: Option<VerifiableSS<GE>>,
+    party_shares: Vec<FE>,
+    vss_scheme_vec: Vec<VerifiableSS<GE>>,
+    complaints: Vec<Vec<Complaint>>,
+    justifications: Vec<Vec<(u16, FE)>>,
+    culprits: Vec<u16>,
+    shared_keys: Option<SharedKeys>,
+    dlog_proof: Option<DLogProof<GE>>,
+    output: Option<LocalKey>,
+}
+
+impl Keygen {
+    pub fn new(party: u16, threshold: u16, share_count: u16) -> Result<Self, Error> {
+        if party >= share_count || threshold >= share_count {
+            return Err(InvalidKey);
+        }
+        let party_keys = Keys::create(party as usize);
+        let (bc1, decom1) = party_keys.phase1_broadcast_phase3_proof_of_correct_key();
+        Ok(Keygen {
+            party,
+            threshold,
+            share_count,
+            others: (0..share_count).filter(|j| *j != party).collect(),
+            round: 0,
+            party_keys,
+            bc1: Some(bc1),
+            decom1: Some(decom1),
+            bc1_vec: Vec::new(),
+            y_vec: Vec::new(),
+            secret_shares: Vec::new(),
+            vss_scheme: None,
+            party_shares: Vec::new(),
+            vss_scheme_vec: Vec::new(),
+            complaints: Vec::new(),
+            justifications: Vec::new(),
+            culprits: Vec::new(),
+            shared_keys: None,
+            dlog_proof: None,
+            output: None,
+        })
+    }
+
+    /// The parties found at fault, once `proceed` has returned an error.
+    pub fn culprits(&self) -> &[u16] {
+        &self.culprits
+    }
+
+    fn params(&self) -> Parameters {
+        Parameters {
+            threshold: self.threshold,
+            share_count: self.share_count,
+        }
+    }
+
+    fn record_culprits(&mut self, mut culprits: Vec<u16>) -> Result<(), Error> {
+        culprits.sort_unstable();
+        culprits.dedup();
+        self.culprits = culprits;
+        if self.culprits.is_empty() {
+            Ok(())
+        } else {
+            Err(InvalidSS)
+        }
+    }
+
+    // the vectors checked in keygen are indexed by party, so the failing position is the party
+    fn blame(&mut self, e: PartyError) -> Error {
+        self.culprits = vec![e.index as u16];
+        e.error
+    }
+
+    // a complaint is well formed if it accuses another party, at most once
+    fn check_complaints(&self, accuser: usize, complaints: &[Complaint]) -> bool {
+        let mut accused: Vec<u16> = complaints.iter().map(|c| c.accused).collect();
+        accused.sort_unstable();
+        accused.dedup();
+        accused.len() == complaints.len()
+            && accused
+                .iter()
+                .all(|j| *j < self.share_count && *j as usize != accuser)
+    }
+
+    // every party reaches the same verdict, as it only depends on broadcast messages
+    fn judge_complaints(&mut self) -> Result<(), Error> {
+        let mut culprits = Vec::new();
+        for (accuser, complaints) in self.complaints.iter().enumerate() {
+            for complaint in complaints {
+                let dealer = complaint.accused as usize;
+                let vss_scheme = &self.vss_scheme_vec[dealer];
+                let refuted = self.justifications[dealer]
+                    .iter()
+                    .find(|(j, _)| *j as usize == accuser)
+                    .map_or(false, |(_, share)| {
+                        vss_scheme.validate_share(share, accuser + 1).is_ok()
+                    });
+                if refuted {
+                    culprits.push(accuser as u16);
+                } else {
+                    culprits.push(dealer as u16);
+                }
+            }
+        }
+        self.record_culprits(culprits)
+    }
+
+    fn prove_share(&mut self) -> Result<Vec<Msg<KeygenMessage>>, Error> {
+        let index = self.party as usize;
+        let (shared_keys, dlog_proof) = self
+            .party_keys
+            .phase2_verify_vss_construct_keypair_phase3_pok_dlog_blaming(
+                &self.params(),
+                &self.y_vec,
+                &self.party_shares,
+                &self.vss_scheme_vec,
+                index + 1,
+            )
+            .map_err(|e| self.blame(e))?;
+        self.shared_keys = Some(shared_keys);
+        self.dlog_proof = Some(dlog_proof.clone());
+        Ok(vec![broadcast(
+            self.party,
+            7,
+            KeygenMessage::DLogProof(dlog_proof),
+        )])
+    }
+}
+
+impl Session for Keygen {
+    type Message = KeygenMessage;
+    type Output = LocalKey;
+
+    fn party(&self) -> u16 {
+        self.party
+    }
+
+    fn round(&self) -> u16 {
+        self.round
+    }
+
+    fn expected_senders(&self) -> Vec<u16> {
+        match self.round {
+            1..=7 => self.others.clone(),
+            _ => Vec::new(),
+        }
+    }
+
+    fn proceed(
+        &mut self,
+        incoming: Vec<Msg<KeygenMessage>>,
+    ) -> Result<Vec<Msg<KeygenMessage>>, Error> {
+        let party = self.party;
+        let index = party as usize;
+        let outgoing = match self.round {
+            0 => {
+                let bc1 = self.bc1.clone().unwrap();
+                vec![broadcast(party, 1, KeygenMessage::Commitment(bc1))]
+            }
+            1 => {
+                let mut bc1_vec =
+                    threshold_signer::collect(incoming, self.party(), 1, &self.others, |body| {
+                        match body {
+                            KeygenMessage::Commitment(bc1) => Some(bc1),
+                            _ => None,
+                        }
+                    })?;
+                bc1_vec.insert(index, self.bc1.take().unwrap());
+                self.bc1_vec = bc1_vec;
+                let decom1 = self.decom1.clone().unwrap();
+                vec![broadcast(party, 2, KeygenMessage::Decommitment(decom1))]
+            }
+            2 => {
+                let mut decom_vec =
+                    threshold_signer::collect(incoming, self.party(), 2, &self.others, |body| {
+                        match body {
+                            KeygenMessage::Decommitment(decom) => Some(decom),
+                            _ => None,
+                        }
+                    })?;
+                decom_vec.insert(index, self.decom1.take().unwrap());
+                self.y_vec = decom_vec.iter().map(|d| d.y_i).collect();
+                let (vss_scheme, secret_shares, _) = self
+                    .party_keys
+                    .phase1_verify_com_phase3_verify_correct_key_phase2_distribute_blaming(
+                        &self.params(),
+                        &decom_vec,
+                        &self.bc1_vec,
+                    )
+                    .map_err(|e| self.blame(e))?;
+                self.vss_scheme = Some(vss_scheme);
+                let outgoing = self
+                    .others
+                    .iter()
+                    .map(|j| {
+                        let share = secret_shares[*j as usize];
+                        p2p(party, *j, 3, KeygenMessage::SecretShare(share))
+                    })
+                    .collect();
+                self.secret_shares = secret_shares;
+                outgoing
+            }
+            3 => {
+                let mut party_shares =
+                    threshold_signer::collect(incoming, self.party(), 3, &self.others, |body| {
+                        match body {
+                            KeygenMessage::SecretShare(share) => Some(share),
+                            _ => None,
+                        }
+                    })?;
+                party_shares.insert(index, self.secret_shares[index]);
+                self.party_shares = party_shares;
+                let vss_scheme = self.vss_scheme.clone().unwrap();
+                vec![broadcast(party, 4, KeygenMessage::VssScheme(vss_scheme))]
+            }
+            4 => {
+                let mut vss_scheme_vec =
+                    threshold_signer::collect(incoming, self.party(), 4, &self.others, |body| {
+                        match body {
+                            KeygenMessage::VssScheme(vss_scheme) => Some(vss_scheme),
+                            _ => None,
+                        }
+                    })?;
+                vss_scheme_vec.insert(index, self.vss_scheme.take().unwrap());
+                // a scheme that does not fit its y_i is visible to everyone, no complaint needed
+                let (threshold, share_count) = (self.threshold as usize, self.share_count as usize);
+                let culprits = (0..self.share_count)
+                    .filter(|j| {
+                        let vss_scheme = &vss_scheme_vec[*j as usize];
+                        vss_scheme.parameters.threshold != threshold
+                            || vss_scheme.parameters.share_count != share_count
+                            || vss_scheme.commitments.len() != threshold + 1
+                            || vss_scheme.commitments[0] != self.y_vec[*j as usize]
+                    })
+                    .collect();
+                self.record_culprits(culprits)?;
+
+                let complaints: Vec<Complaint> = self
+                    .others
+                    .iter()
+                    .filter(|j| {
+                        vss_scheme_vec[**j as usize]
+                            .validate_share(&self.party_shares[**j as usize], index + 1)
+                            .is_err()
+                    })
+                    .map(|j| Complaint { accused: *j })
+                    .collect();
+                self.vss_scheme_vec = vss_scheme_vec;
+                self.complaints = vec![complaints.clone()];
+                vec![broadcast(party, 5, KeygenMessage::Complaints(complaints))]
+            }
+            5 => {
+                let mut complaints =
+                    threshold_signer::collect(incoming, self.party(), 5, &self.others, |body| {
+                        match body {
+                            KeygenMessage::Complaints(complaints) => Some(complaints),
+                            _ => None,
+                        }
+                    })?;
+                complaints.insert(index, self.complaints.remove(0));
+                let culprits = (0..self.share_count)
+                    .filter(|j| !self.check_complaints(*j as usize, &complaints[*j as usize]))
+                    .collect();
+                self.record_culprits(culprits)?;
+
+                let accusers: Vec<u16> = (0..self.share_count)
+                    .filter(|j| complaints[*j as usize].iter().any(|c| c.accused == party))
+                    .collect();
+                let nobody_complains = complaints.iter().all(|c| c.is_empty());
+                self.complaints = complaints;
+                if nobody_complains {
+                    // round 6 is skipped
+                    self.round += 1;
+                    self.prove_share()?
+                } else {
+                    let justification: Vec<(u16, FE)> = accusers
+                        .iter()
+                        .map(|j| (*j, self.secret_shares[*j as usize]))
+                        .collect();
+                    self.justifications = vec![justification.clone()];
+                    let body = KeygenMessage::Justification(justification);
+                    vec![broadcast(party, 6, body)]
+                }
+            }
+            6 => {
+                let mut justifications =
+                    threshold_signer::collect(incoming, self.party(), 6, &self.others, |body| {
+                        match body {
+                            KeygenMessage::Justification(justification) => Some(justification),
+                            _ => None,
+                        }
+                    })?;
+                justifications.insert(index, self.justifications.remove(0));
+                self.justifications = justifications;
+                self.judge_complaints()?;
+                self.prove_share()?
+            }
+            7 => {
+                let mut dlog_proof_vec =
+                    threshold_signer::collect(incoming, self.party(), 7, &self.others, |body| {
+                        match body {
+                            KeygenMessage::DLogProof(proof) => Some(proof),
+                            _ => None,
+                        }
+                    })?;
+                dlog_proof_vec.insert(index, self.dlog_proof.take().unwrap());
+                Keys::verify_dlog_proofs_blaming(&self.params(), &dlog_proof_vec, &self.y_vec)
+                    .map_err(|e| self.blame(e))?;
+
+                let (head, tail) = self.y_vec.split_at(1);
+                let y = tail.iter().fold(head[0], |acc, x| acc + x);
+                self.output = Some(LocalKey {
+                    party_keys: self.party_keys.clone(),
+                    shared_keys: self.shared_keys.take().unwrap(),
+                    party_index: party,
+                    threshold: self.threshold,
+                    share_count: self.share_count,
+                    vss_scheme_vec: self.vss_scheme_vec.clone(),
+                    paillier_key_vec: self.bc1_vec.iter().map(|bc1| bc1.e.clone()).collect(),
+                    y,
+                });
+                Vec::new()
+            }
+            _ => return Err(Error::InvalidMessage),
+        };
+        self.round += 1;
+        Ok(outgoing)
+    }
+
+    fn is_finished(&self) -> bool {
+        self.round > 7
+    }
+
+    fn pick_output(&mut self) -> Option<LocalKey> {
+        self.output.take()
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub enum SignMessage<A = MessageA, B = MessageB> {
+    Intent([u8; 32]),
+    Commitment(SignBroadcastPhase1, A),
+    MtA(B, B),
+    Delta(FE),
+    Decommitment(SignDecommitPhase1),
+    Phase5Commitment(Phase5Com1),
+    Phase5Decommitment(Phase5ADecom1, HomoELGamalProof<GE>, DLogProof<GE>),
+    Phase5CCommitment(Phase5Com2),
+    Phase5CDecommitment(Phase5DDecom2),
+    LocalSignature(FE),
+}
+
+/// A GG18 signing session, running its MtA over `M`.
+pub struct Signing<M: MtaBackend = PaillierMta> {
+    key: LocalKey,
+    mta: M,
+    others: Vec<u16>,
+    position: usize,
+    s: Vec<usize>,
+    message: BigInt,
+    metadata: Vec<u8>,
+    disagreeing: Vec<u16>,
+    culprits: Vec<u16>,
+    round: u16,
+    sign_keys: SignKeys,
+    com: Option<SignBroadcastPhase1>,
+    decommit: SignDecommitPhase1,
+    bc1_vec: Vec<SignBroadcastPhase1>,
+    beta_vec: Vec<FE>,
+    ni_vec: Vec<FE>,
+    m_b_gamma_rec_vec: Vec<M::MessageB>,
+    delta_i: Option<FE>,
+    sigma: Option<FE>,
+    delta_inv: Option<FE>,
+    R: Option<GE>,
+    local_sig: Option<LocalSignature>,
+    phase5a: Option<(Phase5ADecom1, HomoELGamalProof<GE>, DLogProof<GE>)>,
+    commit5a_vec: Vec<Phase5Com1>,
+    phase_5a_decomm_vec: Vec<Phase5ADecom1>,
+    phase5c: Option<(Phase5Com2, Phase5DDecom2)>,
+    commit5c_vec: Vec<Phase5Com2>,
+    output: Option<threshold_signer::Signature>,
+}
+
+impl Signing {
+    /// `signers` are the 0-based indices of at least `threshold + 1` parties, including
+    /// the owner of `key`.
+    pub fn new(key: LocalKey, signers: &[u16], message: &BigInt) -> Result<Self, Error> {
+        let private = PartyPrivate::set_private(key.party_keys.clone(), key.shared_keys.clone());
+        let mta = PaillierMta::new(private.paillier_context(), &key.paillier_key_vec);
+        Self::with_mta(key, signers, message, mta)
+    }
+}
+
+impl<M: MtaBackend> Signing<M> {
+    /// Like `new`, with the MtA run over `mta` instead of the Paillier keys of `key`. All
+    /// signers must use the same kind of backend.
+    pub fn with_mta(
+        key: LocalKey,
+        signers: &[u16],
+        message: &BigInt,
+        mta: M,
+    ) -> Result<Self, Error> {
+        let index = key.party_index;
+        let position = signers.iter().position(|j| *j == index).ok_or(InvalidKey)?;
+        let mut sorted = signers.to_vec();
+        sorted.sort_unstable();
+        sorted.dedup();
+        if sorted.len() != signers.len()
+            || signers.len() <= key.threshold as usize
+            || signers.iter().any(|j| *j >= key.share_count)
+        {
+            return Err(InvalidKey);
+        }
+        let s: Vec<usize> = signers.iter().map(|j| *j as usize).collect();
+
+        let private = PartyPrivate::set_private(key.party_keys.clone(), key.shared_keys.clone());
+        let sign_keys = SignKeys::create(
+            &private,
+            &key.vss_scheme_vec[index as usize],
+            index as usize,
+            &s,
+        );
+        let (com, decommit) = sign_keys.phase1_broadcast();
+
+        Ok(Signing {
+            others: signers.iter().cloned().filter(|j| *j != index).collect(),
+            position,
+            s,
+            message: message.clone(),
+            metadata: Vec::new(),
+            disagreeing: Vec::new(),
+            culprits: Vec::new(),
+            round: 0,
+            sign_keys,
+            com: Some(com),
+            decommit,
+            bc1_vec: Vec::new(),
+            beta_vec: Vec::new(),
+            ni_vec: Vec::new(),
+            m_b_gamma_rec_vec: Vec::new(),
+            delta_i: None,
+            sigma: None,
+            delta_inv: None,
+            R: None,
+            local_sig: None,
+            phase5a: None,
+            commit5a_vec: Vec::new(),
+            phase_5a_decomm_vec: Vec::new(),
+            phase5c: None,
+            commit5c_vec: Vec::new(),
+            output: None,
+            key,
+            mta,
+        })
+    }
+
+    /// Application metadata, such as a summary of the transaction, which every signer must
+    /// have been given as well.
+    pub fn with_metadata(mut self, metadata: &[u8]) -> Self {
+        self.metadata = metadata.to_vec();
+        self
+    }
+
+    /// The signers that want to sign another message or metadata, once `proceed` has
+    /// returned `MessageMismatch`.
+    pub fn disagreeing(&self) -> &[u16] {
+        &self.disagreeing
+    }
+
+    /// The signers whose MtA answers or phase 5 proofs failed, once `proceed` has returned
+    /// an error.
+    pub fn culprits(&self) -> &[u16] {
+        &self.culprits
+    }
+}
+
+impl<M: MtaBackend> Session for Signing<M> {
+    type Message = SignMessage<M::MessageA, M::MessageB>;
+    type Output = threshold_signer::Signature;
+
+    fn party(&self) -> u16 {
+        self.key.party_index
+    }
+
+    fn round(&self) -> u16 {
+        self.round
+    }
+
+    fn expected_senders(&self) -> Vec<u16> {
+        match self.round {
+            1..=10 => self.others.clone(),
+            _ => Vec::new(),
+        }
+    }
+
+    fn proceed(
+        &mut self,
+        incoming: Vec<Msg<Self::Message>>,
+    ) -> Result<Vec<Msg<Self::Message>>, Error> {
+        let party = self.key.party_index;
+        let outgoing = match self.round {
+            0 => {
+                let intent = signing_intent(&self.message, &self.metadata);
+                vec![broadcast(party, 1, SignMessage::Intent(intent))]
+            }
+            1 => {
+                let intents =
+                    threshold_signer::collect(incoming, self.party(), 1, &self.others, |body| {
+                        match body {
+                            SignMessage::Intent(intent) => Some(intent),
+                            _ => None,
+                        }
+                    })?;
+                let intent = signing_intent(&self.message, &self.metadata);
+                self.disagreeing = self
+                    .others
+                    .iter()
+                    .zip(intents)
+                    .filter(|(_, other)| *other != intent)
+                    .map(|(j, _)| *j)
+                    .collect();
+                if !self.disagreeing.is_empty() {
+                    return Err(MessageMismatch);
+                }
+                let m_a_k = self.mta.message_a(&self.sign_keys.k_i);
+                let body = SignMessage::Commitment(self.com.take().unwrap(), m_a_k);
+                vec![broadcast(party, 2, body)]
+            }
+            2 => {
+                let round1 =
+                    threshold_signer::collect(incoming, self.party(), 2, &self.others, |body| {
+                        match body {
+                            SignMessage::Commitment(com, m_a) => Some((com, m_a)),
+                            _ => None,
+                        }
+                    })?;
+                let mut outgoing = Vec::new();
+                for (j, (com, m_a)) in self.others.iter().zip(round1) {
+                    let alice = *j as usize;
+                    let (m_b_gamma, beta_gamma) =
+                        self.mta.message_b(alice, &self.sign_keys.gamma_i, &m_a)?;
+                    let (m_b_w, beta_wi) = self.mta.message_b(alice, &self.sign_keys.w_i, &m_a)?;
+                    outgoing.push(p2p(party, *j, 3, SignMessage::MtA(m_b_gamma, m_b_w)));
+                    self.bc1_vec.push(com);
+                    self.beta_vec.push(beta_gamma);
+                    self.ni_vec.push(beta_wi);
+                }
+                outgoing
+            }
+            3 => {
+                let round2 =
+                    threshold_signer::collect(incoming, self.party(), 3, &self.others, |body| {
+                        match body {
+                            SignMessage::MtA(m_b_gamma, m_b_w) => Some((m_b_gamma, m_b_w)),
+                            _ => None,
+                        }
+                    })?;
+                let xi_com_vec = Keys::get_commitments_to_xi(&self.key.vss_scheme_vec);
+                let (mta, k_i) = (&self.mta, &self.sign_keys.k_i);
+                let (vss_scheme_vec, s, others) = (&self.key.vss_scheme_vec, &self.s, &self.others);
+                let alphas = parallel::map(round2.len(), |i| -> Result<_, Error> {
+                    let j = others[i] as usize;
+                    let (m_b_gamma, m_b_w) = &round2[i];
+                    let g_w_j =
+                        Keys::update_commitments_to_xi(&xi_com_vec[j], &vss_scheme_vec[j], j, s);
+                    let alpha_ij_gamma = mta.alpha(j, m_b_gamma, k_i)?;
+                    let alpha_ij_wi = mta.alpha(j, m_b_w, k_i)?;
+                    if !MessageB::verify_b_against_public(&g_w_j, &M::b_proof(m_b_w).pk) {
+                        return Err(InvalidKey);
+                    }
+                    Ok((alpha_ij_gamma, alpha_ij_wi))
+                });
+                self.culprits = others
+                    .iter()
+                    .zip(&alphas)
+                    .filter(|(_, alpha)| alpha.is_err())
+                    .map(|(j, _)| *j)
+                    .collect();
+                let (alpha_vec, miu_vec): (Vec<FE>, Vec<FE>) = alphas
+                    .into_iter()
+                    .collect::<Result<Vec<_>, _>>()?
+                    .into_iter()
+                    .unzip();
+                self.m_b_gamma_rec_vec =
+                    round2.into_iter().map(|(m_b_gamma, _)| m_b_gamma).collect();
+                let delta_i = self.sign_keys.phase2_delta_i(&alpha_vec, &self.beta_vec);
+                self.sigma = Some(self.sign_keys.phase2_sigma_i(&miu_vec, &self.ni_vec));
+                self.delta_i = Some(delta_i);
+                vec![broadcast(party, 4, SignMessage::Delta(delta_i))]
+            }
+            4 => {
+                let mut delta_vec =
+                    threshold_signer::collect(incoming, self.party(), 4, &self.others, |body| {
+                        match body {
+                            SignMessage::Delta(delta) => Some(delta),
+                            _ => None,
+                        }
+                    })?;
+                delta_vec.push(self.delta_i.unwrap());
+                self.delta_inv = Some(SignKeys::phase3_reconstruct_delta(&delta_vec));
+                let decommit = self.decommit.clone();
+                vec![broadcast(party, 5, SignMessage::Decommitment(decommit))]
+            }
+            5 => {
+                let decommit_vec =
+                    threshold_signer::collect(incoming, self.party(), 5, &self.others, |body| {
+                        match body {
+                            SignMessage::Decommitment(decommit) => Some(decommit),
+                            _ => None,
+                        }
+                    })?;
+                let delta_inv = self.delta_inv.unwrap();
+                let b_proof_vec = self
+                    .m_b_gamma_rec_vec
+                    .iter()
+                    .map(M::b_proof)
+                    .collect::<Vec<&DLogProof<GE>>>();
+                let R = SignKeys::phase4(&delta_inv, &b_proof_vec, decommit_vec, &self.bc1_vec)?;
+                // adding local g_gamma_i
+                let R = R + self.decommit.g_gamma_i * delta_inv;
+
+                let local_sig = LocalSignature::phase5_local_sig(
+                    &self.sign_keys.k_i,
+                    &self.message,
+                    &R,
+                    &self.sigma.unwrap(),
+                    &self.key.y,
+                );
+                let (phase5_com, phase_5a_decom, helgamal_proof, dlog_proof_rho) =
+                    local_sig.phase5a_broadcast_5b_zkproof();
+                self.R = Some(R);
+                self.local_sig = Some(local_sig);
+                self.phase5a = Some((phase_5a_decom, helgamal_proof, dlog_proof_rho));
+                vec![broadcast(
+                    party,
+                    6,
+                    SignMessage::Phase5Commitment(phase5_com),
+                )]
+            }
+            6 => {
+                self.commit5a_vec =
+                    threshold_signer::collect(incoming, self.party(), 6, &self.others, |body| {
+                        match body {
+                            SignMessage::Phase5Commitment(com) => Some(com),
+                            _ => None,
+                        }
+                    })?;
+                let (decom, elgamal, dlog) = self.phase5a.clone().unwrap();
+                vec![broadcast(
+                    party,
+                    7,
+                    SignMessage::Phase5Decommitment(decom, elgamal, dlog),
+                )]
+            }
+            7 => {
+                let round6 =
+                    threshold_signer::collect(incoming, self.party(), 7, &self.others, |body| {
+                        match body {
+                            SignMessage::Phase5Decommitment(decom, elgamal, dlog) => {
+                                Some((decom, elgamal, dlog))
+                            }
+                            _ => None,
+                        }
+                    })?;
+                let mut phase_5a_elgamal_vec = Vec::new();
+                let mut phase_5a_dlog_vec = Vec::new();
+                for (decom, elgamal, dlog) in round6 {
+                    self.phase_5a_decomm_vec.push(decom);
+                    phase_5a_elgamal_vec.push(elgamal);
+                    phase_5a_dlog_vec.push(dlog);
+                }
+                let phase5c = self.local_sig.as_ref().unwrap().phase5c_blaming(
+                    &self.phase_5a_decomm_vec,
+                    &self.commit5a_vec,
+                    &phase_5a_elgamal_vec,
+                    &phase_5a_dlog_vec,
+                    &self.phase5a.as_ref().unwrap().0.V_i,
+                    &self.R.unwrap(),
+                );
+                let (phase5_com2, phase_5d_decom2) = match phase5c {
+                    Ok(phase5c) => phase5c,
+                    Err(e) => {
+                        self.culprits = vec![self.others[e.index]];
+                        return Err(e.error);
+                    }
+                };
+                self.phase5c = Some((phase5_com2.clone(), phase_5d_decom2));
+                vec![broadcast(
+                    party,
+                    8,
+                    SignMessage::Phase5CCommitment(phase5_com2),
+                )]
+            }
+            8 => {
+                let mut commit5c_vec =
+                    threshold_signer::collect(incoming, self.party(), 8, &self.others, |body| {
+                        match body {
+                            SignMessage::Phase5CCommitment(com) => Some(com),
+                            _ => None,
+                        }
+                    })?;
+                let (phase5_com2, phase_5d_decom2) = self.phase5c.clone().unwrap();
+                commit5c_vec.insert(self.position, phase5_com2);
+                self.commit5c_vec = commit5c_vec;
+                vec![broadcast(
+                    party,
+                    9,
+                    SignMessage::Phase5CDecommitment(phase_5d_decom2),
+                )]
+            }
+            9 => {
+                let mut decommit5d_vec =
+                    threshold_signer::collect(incoming, self.party(), 9, &self.others, |body| {
+                        match body {
+                            SignMessage::Phase5CDecommitment(decom) => Some(decom),
+                            _ => None,
+                        }
+                    })?;
+                decommit5d_vec.insert(self.position, self.phase5c.take().unwrap().1);
+                let phase_5a_decom = self.phase5a.take().unwrap().0;
+                self.phase_5a_decomm_vec
+                    .insert(self.position, phase_5a_decom);
+                let s_i = self.local_sig.as_ref().unwrap().phase5d(
+                    &decommit5d_vec,
+                    &self.commit5c_vec,
+                    &self.phase_5a_decomm_vec,
+                )?;
+                vec![broadcast(party, 10, SignMessage::LocalSignature(s_i))]
+            }
+            10 => {
+                let s_vec =
+                    threshold_signer::collect(incoming, self.party(), 10, &self.others, |body| {
+                        match body {
+                            SignMessage::LocalSignature(s_i) => Some(s_i),
+                            _ => None,
+                        }
+                    })?;
+                let sig = self.local_sig.take().unwrap().output_signature(&s_vec)?;
+                self.output = Some(threshold_signer::Signature {
+                    r: sig.r.to_big_int(),
+                    s: sig.s.to_big_int(),
+                    recid: sig.recid,
+                });
+                Vec::new()
+            }
+            _ => return Err(Error::InvalidMessage),
+        };
+        self.round += 1;
+        Ok(outgoing)
+    }
+
+    fn is_finished(&self) -> bool {
+        self.round > 10
+    }
+
+    fn pick_output(&mut self) -> Option<threshold_signer::Signature> {
+        self.output.take()
+    }
+}
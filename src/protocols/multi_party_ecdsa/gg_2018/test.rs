@@ -16,11 +16,18 @@
     @license GPL-3.0+ <https://github.com/KZen-networks/multi-party-ecdsa/blob/master/LICENSE>
 */
 
+use crate::protocols::multi_party_ecdsa::gg_2018::dkg::{self, DkgMessage};
 use crate::protocols::multi_party_ecdsa::gg_2018::party_i::{
     verify, KeyGenBroadcastMessage1, KeyGenDecommitMessage1, Keys, LocalSignature, Parameters,
     PartyPrivate, Phase5ADecom1, Phase5Com1, SharedKeys, SignKeys,
 };
+use crate::protocols::multi_party_ecdsa::gg_2018::signer::{
+    Complaint, Keygen, KeygenMessage, LocalKey, Signing,
+};
+use crate::protocols::threshold_signer::testing::run_with;
+use crate::protocols::threshold_signer::{Msg, Session};
 use crate::utilities::mta::{MessageA, MessageB};
+use crate::Error;
 
 use curv::arithmetic::traits::Converter;
 use curv::cryptographic_primitives::hashing::hash_sha256::HSha256;
@@ -29,7 +36,9 @@ use curv::cryptographic_primitives::proofs::sigma_dlog::DLogProof;
 use curv::cryptographic_primitives::secret_sharing::feldman_vss::VerifiableSS;
 use curv::elliptic::curves::p256::{FE, GE};
 use curv::elliptic::curves::traits::*;
+use curv::BigInt;
 use paillier::*;
+use std::cell::Cell;
 
 #[test]
 fn test_keygen_t1_n2() {
@@ -435,3 +444,246 @@ fn test_serialize_deserialize() {
     assert_eq!(decommit.y_i, decoded.y_i);
 // FAILED
 }
+
+// runs the sessions and hands them back with their outputs, `tamper` may change a message
+// before it reaches `to`
+fn run_lockstep<S, F>(mut sessions: Vec<S>, tamper: F) -> Vec<(S, Result<S::Output, Error>)>
+where
+    S: Session,
+    F: Fn(u16, &mut Msg<S::Message>),
+{
+    let results = run_with(&mut sessions, tamper);
+    sessions.into_iter().zip(results).collect()
+}
+
+fn run_keygen<F>(t: u16, n: u16, tamper: F) -> Vec<(Keygen, Result<LocalKey, Error>)>
+where
+    F: Fn(u16, &mut Msg<KeygenMessage>),
+{
+    let sessions = (0..n).map(|i| Keygen::new(i, t, n).unwrap()).collect();
+    run_lockstep(sessions, tamper)
+}
+
+// corrupts the share party 0 deals to party 2, and remembers the original and the bad share
+fn corrupt_share(msg: &mut Msg<KeygenMessage>, to: u16, shares: &Cell<Option<(FE, FE)>>) {
+    if let (0, 2, KeygenMessage::SecretShare(share)) = (msg.sender, to, &mut msg.body) {
+        let bad = *share + FE::new_random();
+        shares.set(Some((*share, bad)));
+        *share = bad;
+    }
+}
+
+#[test]
+fn test_keygen_complaint_answered() {
+    // the share was damaged on its way and the dealer reveals the right one, which nobody
+    // can tell from a false complaint, so the accuser is at fault
+    let shares = Cell::new(None);
+    let results = run_keygen(1, 3, |to, msg| corrupt_share(msg, to, &shares));
+    for (session, result) in &results {
+        assert_eq!(result.as_ref().err(), Some(&Error::InvalidSS));
+        assert_eq!(session.culprits(), &[2]);
+    }
+}
+
+#[test]
+fn test_keygen_complaint_against_dealer() {
+    // party 0 stands by the bad share when it has to reveal it
+    let shares = Cell::new(None);
+    let results = run_keygen(1, 3, |to, msg| {
+        corrupt_share(msg, to, &shares);
+        if let (0, KeygenMessage::Justification(justification)) = (msg.sender, &mut msg.body) {
+            let (_, bad) = shares.get().unwrap();
+            *justification = vec![(2, bad)];
+        }
+    });
+    for (session, result) in &results[1..] {
+        assert!(result.is_err());
+        assert_eq!(session.culprits(), &[0]);
+    }
+}
+
+#[test]
+fn test_keygen_false_complaint() {
+    // party 1 complains about the valid share of party 0, which party 0 then reveals
+    let results = run_keygen(1, 3, |_, msg| {
+        if let (1, KeygenMessage::Complaints(complaints)) = (msg.sender, &mut msg.body) {
+            *complaints = vec![Complaint { accused: 0 }];
+        }
+    });
+    for (session, result) in &results {
+        assert_eq!(result.as_ref().err(), Some(&Error::InvalidSS));
+        assert_eq!(session.culprits(), &[1]);
+    }
+}
+
+#[test]
+fn test_keygen_bad_decommitment() {
+    // party 1 opens its commitment with the wrong blinding factor
+    let results = run_keygen(1, 3, |_, msg| {
+        if let (1, KeygenMessage::Decommitment(decom)) = (msg.sender, &mut msg.body) {
+            decom.blind_factor = decom.blind_factor.clone() + BigInt::one();
+        }
+    });
+    for (session, result) in [&results[0], &results[2]].iter() {
+        assert_eq!(result.as_ref().err(), Some(&Error::InvalidKey));
+        assert_eq!(session.culprits(), &[1]);
+    }
+}
+
+fn run_dkg<F>(t: u16, n: u16, tamper: F) -> Vec<(dkg::Keygen, Result<LocalKey, Error>)>
+where
+    F: Fn(u16, &mut Msg<DkgMessage>),
+{
+    let sessions = (0..n).map(|i| dkg::Keygen::new(i, t, n).unwrap()).collect();
+    run_lockstep(sessions, tamper)
+}
+
+// checks that the keys fit together and sign with `signers`
+fn check_keys_and_sign(keys: &[LocalKey], signers: &[u16]) {
+    let y = keys[0].y;
+    for key in keys {
+        assert_eq!(key.y, y);
+        let xi_com_vec = Keys::get_commitments_to_xi(&key.vss_scheme_vec);
+        assert_eq!(
+            GE::generator() * key.shared_keys.x_i,
+            xi_com_vec[key.party_index as usize]
+        );
+    }
+    let message = BigInt::from(1234);
+    let signings = signers
+        .iter()
+        .map(|i| Signing::new(keys[*i as usize].clone(), signers, &message).unwrap())
+        .collect();
+    for (_, signature) in run_lockstep(signings, |_, _| ()) {
+        signature
+            .expect("signing failed")
+            .verify(&y, &message)
+            .expect("invalid signature");
+    }
+}
+
+#[test]
+fn test_dkg() {
+    let keys: Vec<LocalKey> = run_dkg(1, 3, |_, _| ())
+        .into_iter()
+        .map(|(_, key)| key.expect("keygen failed"))
+        .collect();
+    check_keys_and_sign(&keys, &[0, 2]);
+}
+
+#[test]
+fn test_dkg_complaint_against_dealer() {
+    // party 0 deals party 2 a share that fails the Pedersen check, and stands by it
+    let bad = Cell::new(None);
+    let results = run_dkg(1, 3, |to, msg| match (msg.sender, to, &mut msg.body) {
+        (0, 2, DkgMessage::Share(s, _)) => {
+            *s = *s + FE::new_random();
+            bad.set(Some(*s));
+        }
+        (0, _, DkgMessage::Justification(justification)) => {
+            for (_, s, _) in justification.iter_mut() {
+                *s = bad.get().unwrap();
+            }
+        }
+        _ => (),
+    });
+    for (session, result) in &results[1..] {
+        assert!(result.is_err());
+        assert_eq!(session.culprits(), &[0]);
+    }
+}
+
+#[test]
+fn test_dkg_feldman_cheater_is_reconstructed() {
+    // party 0 publishes Feldman commitments to another constant term, which would shift y
+    let results = run_dkg(1, 3, |_, msg| {
+        if let (0, DkgMessage::VssScheme(vss_scheme)) = (msg.sender, &mut msg.body) {
+            vss_scheme.commitments[0] = vss_scheme.commitments[0] + GE::generator();
+        }
+    });
+    let keys: Vec<LocalKey> = results
+        .into_iter()
+        .map(|(_, key)| key.expect("keygen failed"))
+        .collect();
+    check_keys_and_sign(&keys, &[1, 2]);
+}
+
+#[test]
+fn test_dkg_feldman_cheater_against_one_receiver() {
+    // party 0 publishes the Feldman commitments of f(x) + (x - 2), which still fit the
+    // share of party 1 at x = 2, so that only party 2 complains
+    let results = run_dkg(1, 3, |_, msg| {
+        if let (0, DkgMessage::VssScheme(vss_scheme)) = (msg.sender, &mut msg.body) {
+            let g = GE::generator();
+            let two: FE = ECScalar::from(&BigInt::from(2));
+            vss_scheme.commitments[0] =
+                vss_scheme.commitments[0].sub_point(&(g * two).get_element());
+            vss_scheme.commitments[1] = vss_scheme.commitments[1] + g;
+        }
+    });
+    let keys: Vec<LocalKey> = results
+        .into_iter()
+        .map(|(_, key)| key.expect("keygen failed"))
+        .collect();
+    check_keys_and_sign(&keys, &[1, 2]);
+}
+
+#[test]
+fn test_sign_message_mismatch() {
+    let keys: Vec<LocalKey> = run_keygen(1, 3, |_, _| ())
+        .into_iter()
+        .map(|(_, key)| key.expect("keygen failed"))
+        .collect();
+    let signers = [0u16, 1, 2];
+
+    // party 2 was asked to sign another message
+    let signings = signers
+        .iter()
+        .map(|i| {
+            let message = BigInt::from(if *i == 2 { 4321 } else { 1234 });
+            Signing::new(keys[*i as usize].clone(), &signers, &message).unwrap()
+        })
+        .collect();
+    let results = run_lockstep(signings, |_, _| ());
+    assert_eq!(results[0].0.disagreeing(), &[2]);
+    assert_eq!(results[1].0.disagreeing(), &[2]);
+    assert_eq!(results[2].0.disagreeing(), &[0, 1]);
+    for (_, result) in &results {
+        assert_eq!(result.as_ref().err(), Some(&Error::MessageMismatch));
+    }
+
+    // same message, but party 1 was shown another transaction
+    let message = BigInt::from(1234);
+    let signings = signers
+        .iter()
+        .map(|i| {
+            let metadata: &[u8] = if *i == 1 {
+                b"pay 100 to B"
+            } else {
+                b"pay 100 to A"
+            };
+            Signing::new(keys[*i as usize].clone(), &signers, &message)
+                .unwrap()
+                .with_metadata(metadata)
+        })
+        .collect();
+    let results = run_lockstep(signings, |_, _| ());
+    assert_eq!(results[0].0.disagreeing(), &[1]);
+    assert_eq!(results[1].0.disagreeing(), &[0, 2]);
+    assert!(results.iter().all(|(_, result)| result.is_err()));
+
+    let signings = signers
+        .iter()
+        .map(|i| {
+            Signing::new(keys[*i as usize].clone(), &signers, &message)
+                .unwrap()
+                .with_metadata(b"pay 100 to A")
+        })
+        .collect();
+    for (_, signature) in run_lockstep(signings, |_, _| ()) {
+        signature
+            .expect("signing failed")
+            .verify(&keys[0].y, &message)
+            .expect("invalid signature");
+    }
+}
@@ -14,4 +14,5 @@
     @license GPL-3.0+ <https://github.com/KZen-networks/multi-party-ecdsa/blob/master/LICENSE>
 */
 
+pub mod cggmp;
 pub mod gg_2018;
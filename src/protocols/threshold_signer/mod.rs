@@ -0,0 +1,187 @@
+/*
+    Multi-party ECDSA
+
+    Copyright 2018 by Kzen Networks
+
+    This file is part of Multi-party ECDSA library
+    (https://github.com/KZen-networks/multi-party-ecdsa)
+
+    Multi-party ECDSA is free software: you can redistribute
+    it and/or modify it under the terms of the GNU General Public
+    License as published by the Free Software Foundation, either
+    version 3 of the License, or (at your option) any later version.
+
+    @license GPL-3.0+ <https://github.com/KZen-networks/multi-party-ecdsa/blob/master/LICENSE>
+*/
+
+//! Protocol independent interface to the signing schemes of this crate.
+//!
+//! Keygen and signing are exposed as round based [`Session`]s: the caller delivers all the
+//! messages of the round a session waits for, and sends whatever the session returns. The
+//! session never does I/O itself, so the same transport and the same application code can
+//! drive GG18, Lindell 2017 or CCLST by picking a different [`ThresholdSigner`].
+//!
+//! Parties are identified by their 0-based index. In the two-party protocols party one has
+//! index 0 and party two index 1.
+
+use curv::elliptic::curves::p256::GE;
+use curv::BigInt;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+
+use crate::utilities::digest::MessageDigest;
+use crate::utilities::encoding;
+use crate::utilities::recovery;
+use crate::Error::{self, InvalidMessage};
+
+pub mod rustcrypto;
+
+#[cfg(test)]
+mod test;
+#[cfg(test)]
+pub(crate) mod testing;
+
+/// A protocol message together with its routing information. `receiver` is `None` for
+/// messages broadcast to every other party of the session.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Msg<M> {
+    pub sender: u16,
+    pub receiver: Option<u16>,
+    pub round: u16,
+    pub body: M,
+}
+
+/// A signature produced by any of the protocols, with s normalized to the lower half.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Signature {
+    pub r: BigInt,
+    pub s: BigInt,
+    pub recid: u8,
+}
+
+impl Signature {
+    pub fn verify(&self, public_key: &GE, message: &BigInt) -> Result<(), Error> {
+        // recovery only yields the public key back if sR = mG + rY
+        match recovery::recover_public_key(&self.r, &self.s, self.recid, message) {
+            Ok(ref recovered) if recovered == public_key => Ok(()),
+            _ => Err(Error::InvalidSig),
+        }
+    }
+
+    pub fn verify_with_digest(
+        &self,
+        public_key: &GE,
+        message: &[u8],
+        digest: MessageDigest,
+    ) -> Result<(), Error> {
+        self.verify(public_key, &digest.to_message(message))
+    }
+
+    pub fn to_der(&self) -> Vec<u8> {
+        encoding::to_der(&self.r, &self.s)
+    }
+
+    pub fn to_p256(&self) -> Result<p256::ecdsa::Signature, Error> {
+        encoding::to_p256(&self.r, &self.s)
+    }
+}
+
+/// Round based state machine of a single party.
+///
+/// A new session waits for the messages of `round()` from `expected_senders()`, which may
+/// be empty when the party speaks first. `proceed` consumes them and returns the messages
+/// of the next round. Once `is_finished()` the output can be taken.
+pub trait Session {
+    type Message: Serialize + DeserializeOwned;
+    type Output;
+
+    fn party(&self) -> u16;
+
+    fn round(&self) -> u16;
+
+    fn expected_senders(&self) -> Vec<u16>;
+
+    fn proceed(
+        &mut self,
+        incoming: Vec<Msg<Self::Message>>,
+    ) -> Result<Vec<Msg<Self::Message>>, Error>;
+
+    fn is_finished(&self) -> bool;
+
+    fn pick_output(&mut self) -> Option<Self::Output>;
+}
+
+/// The result of a keygen, as stored by a party between signing sessions.
+pub trait KeyShare {
+    fn party(&self) -> u16;
+
+    /// The joint public key the signatures verify under.
+    fn public_key(&self) -> GE;
+}
+
+pub trait ThresholdSigner {
+    type KeyShare: KeyShare + Clone + Serialize + DeserializeOwned;
+    type Keygen: Session<Output = Self::KeyShare>;
+    type Signing: Session<Output = Signature>;
+
+    /// Starts a keygen where any `threshold + 1` out of `share_count` parties can sign.
+    /// The two-party protocols only accept `threshold = 1` and `share_count = 2`.
+    fn keygen(party: u16, threshold: u16, share_count: u16) -> Result<Self::Keygen, Error>;
+
+    /// Starts signing `message` with the parties in `signers`, which must include the
+    /// owner of `key`.
+    fn sign(
+        key: &Self::KeyShare,
+        signers: &[u16],
+        message: &BigInt,
+    ) -> Result<Self::Signing, Error>;
+}
+
+/// Checks that `incoming` holds exactly one message of `round` from each of `senders`, each
+/// broadcast or sent to `party`, and extracts the bodies, ordered as `senders`.
+pub(crate) fn collect<M, T, F>(
+    mut incoming: Vec<Msg<M>>,
+    party: u16,
+    round: u16,
+    senders: &[u16],
+    extract: F,
+) -> Result<Vec<T>, Error>
+where
+    F: Fn(M) -> Option<T>,
+{
+    if incoming.len() != senders.len()
+        || incoming
+            .iter()
+            .any(|msg| msg.receiver.map_or(false, |receiver| receiver != party))
+    {
+        return Err(InvalidMessage);
+    }
+    let mut bodies = Vec::with_capacity(senders.len());
+    for sender in senders {
+        let position = incoming
+            .iter()
+            .position(|msg| msg.sender == *sender && msg.round == round)
+            .ok_or(InvalidMessage)?;
+        let msg = incoming.swap_remove(position);
+        bodies.push(extract(msg.body).ok_or(InvalidMessage)?);
+    }
+    Ok(bodies)
+}
+
+pub(crate) fn broadcast<M>(sender: u16, round: u16, body: M) -> Msg<M> {
+    Msg {
+        sender,
+        receiver: None,
+        round,
+        body,
+    }
+}
+
+pub(crate) fn p2p<M>(sender: u16, receiver: u16, round: u16, body: M) -> Msg<M> {
+    Msg {
+        sender,
+        receiver: Some(receiver),
+        round,
+        body,
+    }
+}
@@ -0,0 +1,117 @@
+/*
+    Multi-party ECDSA
+
+    Copyright 2018 by Kzen Networks
+
+    This file is part of Multi-party ECDSA library
+    (https://github.com/KZen-networks/multi-party-ecdsa)
+
+    Multi-party ECDSA is free software: you can redistribute
+    it and/or modify it under the terms of the GNU General Public
+    License as published by the Free Software Foundation, either
+    version 3 of the License, or (at your option) any later version.
+
+    @license GPL-3.0+ <https://github.com/KZen-networks/multi-party-ecdsa/blob/master/LICENSE>
+*/
+
+//! [`signature::Signer`](p256::ecdsa::signature::Signer) implementation on top of a
+//! [`ThresholdSigner`], so that threshold keys can be used by code written against the
+//! RustCrypto traits.
+//!
+//! Every call to `try_sign` runs a whole signing session through the [`Transport`]. All the
+//! parties in `signers` have to sign the same message at the same time.
+
+use std::sync::Mutex;
+
+use curv::elliptic::curves::traits::*;
+use curv::BigInt;
+use p256::ecdsa::signature::digest::Digest;
+use p256::ecdsa::signature::{self, DigestSigner, Signer};
+use p256::ecdsa::VerifyKey;
+
+use super::{KeyShare, Msg, Session, Signature, ThresholdSigner};
+use crate::utilities::digest::MessageDigest;
+use crate::Error::{self, InvalidMessage};
+
+type SigningMessage<P> = <<P as ThresholdSigner>::Signing as Session>::Message;
+
+/// Delivers the messages of a session to the other parties. Broadcast messages have no
+/// receiver set.
+pub trait Transport<M> {
+    fn send(&mut self, outgoing: Vec<Msg<M>>) -> Result<(), Error>;
+
+    /// Blocks until one message of `round` arrived from each of `senders`.
+    fn receive(&mut self, round: u16, senders: &[u16]) -> Result<Vec<Msg<M>>, Error>;
+}
+
+pub struct SignerAdapter<P: ThresholdSigner, T> {
+    key: P::KeyShare,
+    signers: Vec<u16>,
+    transport: Mutex<T>,
+}
+
+impl<P, T> SignerAdapter<P, T>
+where
+    P: ThresholdSigner,
+    T: Transport<SigningMessage<P>>,
+{
+    pub fn new(key: P::KeyShare, signers: &[u16], transport: T) -> Self {
+        SignerAdapter {
+            key,
+            signers: signers.to_vec(),
+            transport: Mutex::new(transport),
+        }
+    }
+
+    /// The group key, as used by RustCrypto verifiers.
+    pub fn verify_key(&self) -> VerifyKey {
+        self.key.public_key().get_element()
+    }
+
+    /// Runs a signing session for an already hashed and reduced `message`.
+    pub fn sign_message(&self, message: &BigInt) -> Result<Signature, Error> {
+        let mut transport = self.transport.lock().map_err(|_| InvalidMessage)?;
+        let mut session = P::sign(&self.key, &self.signers, message)?;
+        while !session.is_finished() {
+            let senders = session.expected_senders();
+            let incoming = if senders.is_empty() {
+                Vec::new()
+            } else {
+                transport.receive(session.round(), &senders)?
+            };
+            let outgoing = session.proceed(incoming)?;
+            if !outgoing.is_empty() {
+                transport.send(outgoing)?;
+            }
+        }
+        session.pick_output().ok_or(InvalidMessage)
+    }
+
+    fn sign_p256(&self, message: &BigInt) -> Result<p256::ecdsa::Signature, signature::Error> {
+        self.sign_message(message)
+            .and_then(|signature| signature.to_p256())
+            .map_err(|_| signature::Error::new())
+    }
+}
+
+/// Signs the SHA-256 digest of the message, as `p256::ecdsa::VerifyKey` expects.
+impl<P, T> Signer<p256::ecdsa::Signature> for SignerAdapter<P, T>
+where
+    P: ThresholdSigner,
+    T: Transport<SigningMessage<P>>,
+{
+    fn try_sign(&self, msg: &[u8]) -> Result<p256::ecdsa::Signature, signature::Error> {
+        self.sign_p256(&MessageDigest::Sha256.to_message(msg))
+    }
+}
+
+impl<P, T, D> DigestSigner<D, p256::ecdsa::Signature> for SignerAdapter<P, T>
+where
+    P: ThresholdSigner,
+    T: Transport<SigningMessage<P>>,
+    D: Digest,
+{
+    fn try_sign_digest(&self, digest: D) -> Result<p256::ecdsa::Signature, signature::Error> {
+        self.sign_p256(&MessageDigest::Prehashed.to_message(&digest.finalize()))
+    }
+}
@@ -0,0 +1,382 @@
+use std::sync::mpsc::{channel, Receiver, Sender};
+use std::thread;
+
+use curv::BigInt;
+use p256::ecdsa::signature::{Signer, Verifier};
+
+use super::rustcrypto::{SignerAdapter, Transport};
+use super::testing::run;
+use super::*;
+use crate::protocols::multi_party_ecdsa::gg_2018::signer::{self as gg18, Gg18};
+#[cfg(feature = "cclst")]
+use crate::protocols::two_party_ecdsa::cclst_2019::signer::Cclst2019;
+use crate::protocols::two_party_ecdsa::lindell_2017::conversion::Conversion;
+use crate::protocols::two_party_ecdsa::lindell_2017::signer::{self as lindell, Lindell2017};
+use crate::utilities::mta::ot::OtMtaSetup;
+use crate::utilities::mta::MtaBackend;
+
+fn keygen_and_sign<P: ThresholdSigner>(threshold: u16, share_count: u16, signers: &[u16]) {
+    let keygens = (0..share_count)
+        .map(|i| P::keygen(i, threshold, share_count).unwrap())
+        .collect();
+    let keys = run::<P::Keygen>(keygens).unwrap();
+    let y = keys[0].public_key();
+    for (i, key) in keys.iter().enumerate() {
+        assert_eq!(key.party(), i as u16);
+        assert_eq!(key.public_key(), y);
+    }
+
+    // key shares are stored between keygen and signing
+    let keys: Vec<P::KeyShare> = keys
+        .iter()
+        .map(|key| serde_json::from_str(&serde_json::to_string(key).unwrap()).unwrap())
+        .collect();
+
+    let message = BigInt::from(1234);
+    let signings = signers
+        .iter()
+        .map(|i| P::sign(&keys[*i as usize], signers, &message).unwrap())
+        .collect();
+    let signatures = run::<P::Signing>(signings).unwrap();
+    for signature in &signatures {
+        assert_eq!(signature, &signatures[0]);
+        signature.verify(&y, &message).expect("invalid signature");
+    }
+    assert!(signatures[0].verify(&y, &BigInt::from(4321)).is_err());
+}
+
+#[test]
+fn test_gg18() {
+    keygen_and_sign::<Gg18>(1, 3, &[0, 2]);
+}
+
+#[test]
+fn test_lindell_2017() {
+    keygen_and_sign::<Lindell2017>(1, 2, &[0, 1]);
+}
+
+#[test]
+fn test_lindell_2017_party_two_recomputes_recid() {
+    let keygens = (0..2)
+        .map(|i| Lindell2017::keygen(i, 1, 2).unwrap())
+        .collect();
+    let keys = run::<<Lindell2017 as ThresholdSigner>::Keygen>(keygens).unwrap();
+    let y = keys[0].public_key();
+    let message = BigInt::from(1234);
+    let mut party_one = Lindell2017::sign(&keys[0], &[0, 1], &message).unwrap();
+    let mut party_two = Lindell2017::sign(&keys[1], &[0, 1], &message).unwrap();
+
+    let round1 = party_two.proceed(Vec::new()).unwrap();
+    let round2 = party_one.proceed(round1).unwrap();
+    let round3 = party_two.proceed(round2).unwrap();
+    let mut round4 = party_one.proceed(round3).unwrap();
+    // party one hands out a wrong recovery id
+    if let lindell::SignMessage::Signature(signature) = &mut round4[0].body {
+        signature.recid ^= 1;
+    }
+    party_two.proceed(round4).unwrap();
+    let signature = party_two.pick_output().unwrap();
+    assert_eq!(signature, party_one.pick_output().unwrap());
+    signature.verify(&y, &message).expect("invalid signature");
+}
+
+#[cfg(feature = "cclst")]
+#[test]
+fn test_cclst_2019() {
+    keygen_and_sign::<Cclst2019>(1, 2, &[0, 1]);
+}
+
+fn gg18_keys(threshold: u16, share_count: u16) -> Vec<gg18::LocalKey> {
+    let keygens = (0..share_count)
+        .map(|i| Gg18::keygen(i, threshold, share_count).unwrap())
+        .collect();
+    run::<<Gg18 as ThresholdSigner>::Keygen>(keygens).unwrap()
+}
+
+// GG18 signing with party `signers[i]` running its MtA over `mtas[i]`
+fn sign_gg18_with_mta<M: MtaBackend>(
+    keys: &[gg18::LocalKey],
+    signers: &[u16],
+    mtas: Vec<M>,
+    message: &BigInt,
+) -> Vec<Signature> {
+    let signings: Vec<_> = signers
+        .iter()
+        .zip(mtas)
+        .map(|(i, mta)| {
+            let key = keys[*i as usize].clone();
+            gg18::Signing::with_mta(key, signers, message, mta).unwrap()
+        })
+        .collect();
+    run(signings).unwrap()
+}
+
+#[cfg(feature = "cclst")]
+#[test]
+fn test_gg18_with_cl_mta() {
+    use crate::protocols::two_party_ecdsa::cclst_2019::setup::ClSetup;
+    use crate::utilities::mta::cl::ClMta;
+
+    let keys = gg18_keys(1, 3);
+    let y = keys[0].public_key();
+    let setup = ClSetup::generate();
+    let signers = [0, 2];
+    let mtas = signers.iter().map(|_| ClMta::new(&setup)).collect();
+    let message = BigInt::from(1234);
+    for signature in sign_gg18_with_mta(&keys, &signers, mtas, &message) {
+        signature.verify(&y, &message).expect("invalid signature");
+    }
+}
+
+#[test]
+fn test_gg18_with_ot_mta() {
+    let keys = gg18_keys(1, 3);
+    let y = keys[0].public_key();
+    let signers = [0, 2];
+    let setups: Vec<_> = signers
+        .iter()
+        .map(|i| OtMtaSetup::new(*i, &signers).unwrap())
+        .collect();
+    let mtas = run(setups).unwrap();
+    // one setup serves any number of signing sessions
+    for message in &[BigInt::from(1234), BigInt::from(4321)] {
+        for signature in sign_gg18_with_mta(&keys, &signers, mtas.clone(), message) {
+            signature.verify(&y, message).expect("invalid signature");
+        }
+    }
+}
+
+fn lindell_keys() -> (lindell::Party1KeyShare, lindell::Party2KeyShare) {
+    let keygens = (0..2)
+        .map(|i| Lindell2017::keygen(i, 1, 2).unwrap())
+        .collect();
+    let keys = run::<<Lindell2017 as ThresholdSigner>::Keygen>(keygens).unwrap();
+    let mut keys = keys.into_iter();
+    match (keys.next(), keys.next()) {
+        (Some(lindell::LocalKey::PartyOne(p1)), Some(lindell::LocalKey::PartyTwo(p2))) => (p1, p2),
+        _ => unreachable!(),
+    }
+}
+
+// runs the conversions of party one and two along with the new cosigners, then signs
+fn convert_and_sign<M: MtaBackend>(
+    mut conversions: Vec<Conversion<M>>,
+    y: GE,
+    threshold: u16,
+    share_count: u16,
+    signers: &[u16],
+) {
+    for i in 2..share_count {
+        conversions.push(Conversion::cosigner(i, y, threshold, share_count).unwrap());
+    }
+    let keys = run(conversions).unwrap();
+    for key in &keys {
+        assert_eq!(key.public_key(), y);
+    }
+
+    let message = BigInt::from(1234);
+    let signings = signers
+        .iter()
+        .map(|i| Gg18::sign(&keys[*i as usize], signers, &message).unwrap())
+        .collect();
+    for signature in run::<<Gg18 as ThresholdSigner>::Signing>(signings).unwrap() {
+        signature.verify(&y, &message).expect("invalid signature");
+    }
+}
+
+fn convert_lindell_to_gg18(threshold: u16, share_count: u16, signers: &[u16]) {
+    let (party_one, party_two) = lindell_keys();
+    let y = party_one.public_key;
+    let conversions = vec![
+        Conversion::party_one(party_one, threshold, share_count).unwrap(),
+        Conversion::party_two(party_two, threshold, share_count).unwrap(),
+    ];
+    convert_and_sign(conversions, y, threshold, share_count, signers);
+}
+
+#[test]
+fn test_convert_lindell_to_gg18_two_of_two() {
+    convert_lindell_to_gg18(1, 2, &[0, 1]);
+}
+
+#[test]
+fn test_convert_lindell_to_gg18_reshare() {
+    // the original parties are no longer needed to sign
+    convert_lindell_to_gg18(1, 3, &[1, 2]);
+    convert_lindell_to_gg18(1, 3, &[0, 2]);
+}
+
+#[test]
+fn test_convert_lindell_to_gg18_over_ot() {
+    let (party_one, party_two) = lindell_keys();
+    let y = party_one.public_key;
+    let setups: Vec<_> = (0..2)
+        .map(|i| OtMtaSetup::new(i, &[0, 1]).unwrap())
+        .collect();
+    let mut mtas = run(setups).unwrap().into_iter();
+    let conversions = vec![
+        Conversion::party_one_with_mta(party_one, 1, 3, mtas.next().unwrap()).unwrap(),
+        Conversion::party_two_with_mta(party_two, 1, 3, mtas.next().unwrap()).unwrap(),
+    ];
+    convert_and_sign(conversions, y, 1, 3, &[0, 2]);
+}
+
+#[test]
+fn test_invalid_parameters() {
+    assert!(Lindell2017::keygen(0, 2, 3).is_err());
+    assert!(Lindell2017::keygen(2, 1, 2).is_err());
+    assert!(Gg18::keygen(3, 1, 3).is_err());
+}
+
+#[test]
+fn test_unexpected_message() {
+    let mut session = Lindell2017::keygen(0, 1, 2).unwrap();
+    let outgoing = session.proceed(Vec::new()).unwrap();
+    // party one receiving its own commitment instead of party two's dlog proof
+    let msg = outgoing.into_iter().next().unwrap();
+    let msg = Msg {
+        sender: 1,
+        receiver: Some(0),
+        round: 2,
+        body: msg.body,
+    };
+    assert_eq!(session.proceed(vec![msg]).err(), Some(Error::InvalidMessage));
+}
+
+#[test]
+fn test_misaddressed_message() {
+    let mut sessions: Vec<_> = (0..3).map(|i| Gg18::keygen(i, 1, 3).unwrap()).collect();
+    let mut commitments: Vec<_> = sessions[1..]
+        .iter_mut()
+        .flat_map(|session| session.proceed(Vec::new()).unwrap())
+        .collect();
+    sessions[0].proceed(Vec::new()).unwrap();
+    // party 0 must not accept what party 2 sent to party 1 only
+    commitments[1].receiver = Some(1);
+    assert_eq!(
+        sessions[0].proceed(commitments).err(),
+        Some(Error::InvalidMessage)
+    );
+}
+
+#[test]
+fn test_party_two_waits_for_commitment() {
+    // party two must not reveal its share before party one is bound to its own
+    let mut party_two = Lindell2017::keygen(1, 1, 2).unwrap();
+    assert!(party_two.proceed(Vec::new()).unwrap().is_empty());
+    assert_eq!(party_two.expected_senders(), vec![0]);
+
+    let mut party_one = Lindell2017::keygen(0, 1, 2).unwrap();
+    let commitment = party_one.proceed(Vec::new()).unwrap();
+    let answer = party_two.proceed(commitment).unwrap();
+    assert_eq!(answer.len(), 1);
+    assert_eq!(answer[0].round, 2);
+}
+
+// one party's end of an in-memory network, messages travel serialized as they would
+// over a socket
+struct ChannelTransport {
+    party: u16,
+    peers: Vec<(u16, Sender<Msg<String>>)>,
+    inbox: Receiver<Msg<String>>,
+    pending: Vec<Msg<String>>,
+}
+
+impl<M: Serialize + DeserializeOwned> Transport<M> for ChannelTransport {
+    fn send(&mut self, outgoing: Vec<Msg<M>>) -> Result<(), Error> {
+        for msg in outgoing {
+            let body = serde_json::to_string(&msg.body).unwrap();
+            for (peer, sender) in &self.peers {
+                if msg.receiver.map_or(*peer != self.party, |to| to == *peer) {
+                    let msg = Msg {
+                        sender: msg.sender,
+                        receiver: msg.receiver,
+                        round: msg.round,
+                        body: body.clone(),
+                    };
+                    sender.send(msg).map_err(|_| Error::InvalidMessage)?;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn receive(&mut self, round: u16, senders: &[u16]) -> Result<Vec<Msg<M>>, Error> {
+        let arrived = |pending: &Vec<Msg<String>>| {
+            senders
+                .iter()
+                .all(|s| pending.iter().any(|m| m.sender == *s && m.round == round))
+        };
+        while !arrived(&self.pending) {
+            let msg = self.inbox.recv().map_err(|_| Error::InvalidMessage)?;
+            self.pending.push(msg);
+        }
+        let (mine, rest): (Vec<_>, Vec<_>) = self.pending.drain(..).partition(|m| m.round == round);
+        self.pending = rest;
+        mine.into_iter()
+            .map(|msg| {
+                Ok(Msg {
+                    sender: msg.sender,
+                    receiver: msg.receiver,
+                    round: msg.round,
+                    body: serde_json::from_str(&msg.body).map_err(|_| Error::InvalidMessage)?,
+                })
+            })
+            .collect()
+    }
+}
+
+fn channel_transports(parties: &[u16]) -> Vec<ChannelTransport> {
+    let (senders, inboxes): (Vec<_>, Vec<_>) = parties.iter().map(|_| channel()).unzip();
+    let peers: Vec<_> = parties.iter().cloned().zip(senders).collect();
+    parties
+        .iter()
+        .zip(inboxes)
+        .map(|(party, inbox)| ChannelTransport {
+            party: *party,
+            peers: peers.clone(),
+            inbox,
+            pending: Vec::new(),
+        })
+        .collect()
+}
+
+fn sign_with_adapter<P>(threshold: u16, share_count: u16, signers: &[u16])
+where
+    P: ThresholdSigner + 'static,
+    P::KeyShare: Send + 'static,
+{
+    let keygens = (0..share_count)
+        .map(|i| P::keygen(i, threshold, share_count).unwrap())
+        .collect();
+    let keys = run::<P::Keygen>(keygens).unwrap();
+
+    let message = b"message to sign";
+    let handles: Vec<_> = channel_transports(signers)
+        .into_iter()
+        .map(|transport| {
+            let key = keys[transport.party as usize].clone();
+            let signers = signers.to_vec();
+            thread::spawn(move || {
+                let signer = SignerAdapter::<P, _>::new(key, &signers, transport);
+                let signature: p256::ecdsa::Signature = signer.try_sign(message).unwrap();
+                (signer.verify_key(), signature)
+            })
+        })
+        .collect();
+    for handle in handles {
+        let (verify_key, signature) = handle.join().unwrap();
+        assert!(verify_key.verify(message, &signature).is_ok());
+        assert!(verify_key.verify(b"other message", &signature).is_err());
+    }
+}
+
+#[test]
+fn test_signer_adapter_gg18() {
+    sign_with_adapter::<Gg18>(1, 3, &[0, 2]);
+}
+
+#[test]
+fn test_signer_adapter_lindell_2017() {
+    sign_with_adapter::<Lindell2017>(1, 2, &[0, 1]);
+}
@@ -0,0 +1,82 @@
+//! Runs whole sessions in memory for the tests of every protocol.
+
+use super::{Msg, Session};
+use crate::Error;
+
+/// The P-256 key the import, export and encoding tests work with, generated with
+/// `openssl ecparam -name prime256v1 -genkey`.
+pub(crate) const SECRET_KEY: &str =
+    "b338d17daf1ee7e27bf8c4071845b9733887c0075b3ebbc8dd30a529888221be";
+
+/// Runs `sessions` to the end, or to their first error.
+pub(crate) fn run<S: Session>(mut sessions: Vec<S>) -> Result<Vec<S::Output>, Error> {
+    run_with(&mut sessions, |_, _| ()).into_iter().collect()
+}
+
+// delivers every message through serde_json and through `tamper` with the receiver. A
+// session proceeds once all the messages of its round are in, or with what it got when no
+// session can, which happens after a sender failed
+pub(crate) fn run_with<S: Session>(
+    sessions: &mut [S],
+    mut tamper: impl FnMut(u16, &mut Msg<S::Message>),
+) -> Vec<Result<S::Output, Error>> {
+    let parties: Vec<u16> = sessions.iter().map(|s| s.party()).collect();
+    let mut results: Vec<Option<Result<S::Output, Error>>> =
+        sessions.iter().map(|_| None).collect();
+    let mut in_flight: Vec<(u16, Msg<S::Message>)> = Vec::new();
+    while results.iter().any(|r| r.is_none()) {
+        let ready: Vec<bool> = sessions
+            .iter()
+            .map(|session| {
+                let (party, round) = (session.party(), session.round());
+                session.expected_senders().iter().all(|sender| {
+                    in_flight.iter().any(|(to, msg)| {
+                        *to == party && msg.sender == *sender && msg.round == round
+                    })
+                })
+            })
+            .collect();
+        let stuck = !ready
+            .iter()
+            .zip(&results)
+            .any(|(ready, result)| *ready && result.is_none());
+        for (i, session) in sessions.iter_mut().enumerate() {
+            if results[i].is_some() || !(ready[i] || stuck) {
+                continue;
+            }
+            let (party, round) = (session.party(), session.round());
+            let (mine, rest): (Vec<_>, Vec<_>) = in_flight
+                .into_iter()
+                .partition(|(to, msg)| *to == party && msg.round == round);
+            in_flight = rest;
+            let outgoing = match session.proceed(mine.into_iter().map(|(_, msg)| msg).collect()) {
+                Ok(outgoing) => outgoing,
+                Err(e) => {
+                    results[i] = Some(Err(e));
+                    continue;
+                }
+            };
+            if session.is_finished() {
+                results[i] = Some(Ok(session.pick_output().unwrap()));
+            }
+            for msg in outgoing {
+                let body = serde_json::to_string(&msg.body).unwrap();
+                let receivers = match msg.receiver {
+                    Some(receiver) => vec![receiver],
+                    None => parties.iter().cloned().filter(|j| *j != party).collect(),
+                };
+                for to in receivers {
+                    let mut msg = Msg {
+                        sender: msg.sender,
+                        receiver: msg.receiver,
+                        round: msg.round,
+                        body: serde_json::from_str(&body).unwrap(),
+                    };
+                    tamper(to, &mut msg);
+                    in_flight.push((to, msg));
+                }
+            }
+        }
+    }
+    results.into_iter().map(Option::unwrap).collect()
+}
@@ -18,6 +18,9 @@ const SECURITY_BITS: usize = 256;
 
 pub mod party_one;
 pub mod party_two;
+pub mod rotation;
+pub mod setup;
+pub mod signer;
 
 #[cfg(test)]
 mod test;
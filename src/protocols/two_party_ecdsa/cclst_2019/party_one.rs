@@ -31,11 +31,15 @@ use curv::elliptic::curves::p256::{FE, GE};
 use curv::elliptic::curves::traits::*;
 use curv::BigInt;
 use serde::{Deserialize, Serialize};
-use subtle::ConstantTimeEq;
 
 use super::party_two::EphKeyGenFirstMsg as Party2EphKeyGenFirstMessage;
 use super::party_two::EphKeyGenSecondMsg as Party2EphKeyGenSecondMessage;
+use super::setup::{with_pari, ClSetup, CL_GROUP_BITS};
 use super::SECURITY_BITS;
+use crate::utilities::digest::MessageDigest;
+use crate::utilities::encoding;
+use crate::utilities::recovery;
+use crate::utilities::verification::{self, BatchItem};
 use crate::Error::{self, InvalidSig};
 
 //****************** Begin: Party One structs ******************//
@@ -64,7 +68,7 @@ pub struct KeyGenSecondMsg {
     pub comm_witness: CommWitness,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct HSMCL {
     pub public: PK,
     pub secret: SK,
@@ -243,6 +247,25 @@ impl Party1Private {
             hsmcl_priv: hsmcl.secret.clone(),
         }
     }
+
+    /// Multiplies x1 by `factor` and encrypts the result under a fresh key of the same
+    /// class group, with a new proof that it encrypts the discrete log of the new public
+    /// share.
+    pub fn refresh_private_key(
+        party_one_private: &Party1Private,
+        hsmcl: &HSMCL,
+        factor: &BigInt,
+    ) -> (HSMCL, HSMCLPublic, Party1Private) {
+        let factor_fe: FE = ECScalar::from(factor);
+        let x1_new = party_one_private.x1 * factor_fe;
+        let ec_key_pair = EcKeyPair {
+            public_share: GE::generator() * x1_new,
+            secret_share: x1_new,
+        };
+        let (hsmcl_new, hsmcl_public) = HSMCL::encrypt_share(hsmcl.cl_group.clone(), &ec_key_pair);
+        let party_one_private_new = Party1Private::set_private_key(&ec_key_pair, &hsmcl_new);
+        (hsmcl_new, hsmcl_public, party_one_private_new)
+    }
 }
 
 impl HSMCL {
@@ -250,13 +273,29 @@ impl HSMCL {
         keygen: &EcKeyPair,
         seed: &BigInt,
     ) -> (HSMCL, HSMCLPublic) {
-        let cl_group = CLGroup::new_from_setup(&1348, &seed);
-        let (secret_key, public_key) = cl_group.keygen();
-        let (ciphertext, proof) = verifiably_encrypt(
-            &cl_group,
-            &public_key,
-            (&keygen.secret_share, &keygen.public_share),
-        );
+        let cl_group = with_pari(|| CLGroup::new_from_setup(&CL_GROUP_BITS, &seed));
+        Self::encrypt_share(cl_group, keygen)
+    }
+
+    /// Like `generate_keypair_and_encrypted_share_and_proof`, in a group derived ahead of
+    /// time.
+    pub fn generate_keypair_and_encrypted_share_and_proof_with_setup(
+        keygen: &EcKeyPair,
+        setup: &ClSetup,
+    ) -> (HSMCL, HSMCLPublic) {
+        Self::encrypt_share(setup.group().clone(), keygen)
+    }
+
+    fn encrypt_share(cl_group: CLGroup, keygen: &EcKeyPair) -> (HSMCL, HSMCLPublic) {
+        let (secret_key, public_key, ciphertext, proof) = with_pari(|| {
+            let (secret_key, public_key) = cl_group.keygen();
+            let (ciphertext, proof) = verifiably_encrypt(
+                &cl_group,
+                &public_key,
+                (&keygen.secret_share, &keygen.public_share),
+            );
+            (secret_key, public_key, ciphertext, proof)
+        });
 
         (
             HSMCL {
@@ -372,35 +411,174 @@ impl Signature {
             .to_big_int()
             .invert(&FE::q())
             .unwrap();
-        let s_tag = decrypt(
-            &hsmcl.cl_group,
-            &party_one_private.hsmcl_priv,
-            &partial_sig_c3,
-        );
+        let s_tag = with_pari(|| {
+            decrypt(
+                &hsmcl.cl_group,
+                &party_one_private.hsmcl_priv,
+                &partial_sig_c3,
+            )
+        });
         let s_tag_tag = BigInt::mod_mul(&k1_inv, &s_tag.to_big_int(), &FE::q());
         let s = cmp::min(s_tag_tag.clone(), FE::q().clone() - s_tag_tag.clone());
         Signature { s, r: rx }
     }
+
+    pub fn compute_with_recid(
+        hsmcl: &HSMCL,
+        party_one_private: &Party1Private,
+        partial_sig_c3: CLCiphertext,
+        ephemeral_local_share: &EphEcKeyPair,
+        ephemeral_other_public_share: &GE,
+    ) -> SignatureRecid {
+        //compute r = k2* R1
+        let r = ephemeral_other_public_share
+            .scalar_mul(&ephemeral_local_share.secret_share.get_element());
+
+        let rx = r.x_coor().unwrap().mod_floor(&FE::q());
+        let k1_inv = &ephemeral_local_share
+            .secret_share
+            .to_big_int()
+            .invert(&FE::q())
+            .unwrap();
+        let s_tag = with_pari(|| {
+            decrypt(
+                &hsmcl.cl_group,
+                &party_one_private.hsmcl_priv,
+                &partial_sig_c3,
+            )
+        });
+        let s_tag_tag = BigInt::mod_mul(&k1_inv, &s_tag.to_big_int(), &FE::q());
+        let s = cmp::min(s_tag_tag.clone(), FE::q() - &s_tag_tag);
+
+        // see the Lindell 2017 party one for how the recovery id is derived
+        let is_s_high = s_tag_tag.clone() > FE::q() - s_tag_tag.clone();
+        let recid = recovery::compute_recid(&r, is_s_high);
+
+        SignatureRecid { s, r: rx, recid }
+    }
 }
 
-pub fn verify(signature: &Signature, pubkey: &GE, message: &BigInt) -> Result<(), Error> {
-    let s_fe: FE = ECScalar::from(&signature.s);
-    let rx_fe: FE = ECScalar::from(&signature.r);
+impl SignatureRecid {
+    pub fn to_compact(&self) -> Result<[u8; recovery::COMPACT_SIGNATURE_SIZE], Error> {
+        recovery::to_compact(&self.r, &self.s, self.recid)
+    }
 
-    let s_inv_fe = s_fe.invert();
-    let e_fe: FE = ECScalar::from(&message.mod_floor(&FE::q()));
-    let u1 = GE::generator() * e_fe * s_inv_fe;
-    let u2 = *pubkey * rx_fe * s_inv_fe;
+    pub fn from_compact(bytes: &[u8]) -> Result<Self, Error> {
+        let (r, s, recid) = recovery::from_compact(bytes)?;
+        Ok(SignatureRecid { s, r, recid })
+    }
 
-    // second condition is against malleability
-    let rx_bytes = &BigInt::to_bytes(&signature.r)[..];
-    let u1_plus_u2_bytes = &BigInt::to_bytes(&(u1 + u2).x_coor().unwrap())[..];
+    pub fn to_der(&self) -> Vec<u8> {
+        encoding::to_der(&self.r, &self.s)
+    }
+
+    pub fn to_fixed(&self) -> Result<[u8; encoding::FIXED_SIGNATURE_SIZE], Error> {
+        encoding::to_fixed(&self.r, &self.s)
+    }
+
+    pub fn to_p256(&self) -> Result<p256::ecdsa::Signature, Error> {
+        encoding::to_p256(&self.r, &self.s)
+    }
+
+    /// The encodings below carry no recovery id, it is recomputed from the message and the
+    /// public key the signature verifies under.
+    pub fn from_der(bytes: &[u8], message: &BigInt, pubkey: &GE) -> Result<Self, Error> {
+        let (r, s) = encoding::from_der(bytes)?;
+        let recid = encoding::find_recid(&r, &s, message, pubkey)?;
+        Ok(SignatureRecid { s, r, recid })
+    }
+
+    pub fn from_fixed(bytes: &[u8], message: &BigInt, pubkey: &GE) -> Result<Self, Error> {
+        let (r, s) = encoding::from_fixed(bytes)?;
+        let recid = encoding::find_recid(&r, &s, message, pubkey)?;
+        Ok(SignatureRecid { s, r, recid })
+    }
+
+    pub fn from_p256(
+        signature: &p256::ecdsa::Signature,
+        message: &BigInt,
+        pubkey: &GE,
+    ) -> Result<Self, Error> {
+        let (r, s) = encoding::from_p256(signature)?;
+        let recid = encoding::find_recid(&r, &s, message, pubkey)?;
+        Ok(SignatureRecid { s, r, recid })
+    }
+}
+
+impl Signature {
+    pub fn to_der(&self) -> Vec<u8> {
+        encoding::to_der(&self.r, &self.s)
+    }
+
+    pub fn from_der(bytes: &[u8]) -> Result<Self, Error> {
+        let (r, s) = encoding::from_der(bytes)?;
+        Ok(Signature { s, r })
+    }
+
+    pub fn to_fixed(&self) -> Result<[u8; encoding::FIXED_SIGNATURE_SIZE], Error> {
+        encoding::to_fixed(&self.r, &self.s)
+    }
+
+    pub fn from_fixed(bytes: &[u8]) -> Result<Self, Error> {
+        let (r, s) = encoding::from_fixed(bytes)?;
+        Ok(Signature { s, r })
+    }
+
+    pub fn to_p256(&self) -> Result<p256::ecdsa::Signature, Error> {
+        encoding::to_p256(&self.r, &self.s)
+    }
+
+    pub fn from_p256(signature: &p256::ecdsa::Signature) -> Result<Self, Error> {
+        let (r, s) = encoding::from_p256(signature)?;
+        Ok(Signature { s, r })
+    }
+}
+
+pub fn recover_public_key(signature: &SignatureRecid, message: &BigInt) -> Result<GE, Error> {
+    recovery::recover_public_key(&signature.r, &signature.s, signature.recid, message)
+}
 
-    if rx_bytes.ct_eq(&u1_plus_u2_bytes).unwrap_u8() == 1
-        && signature.s < FE::q() - signature.s.clone()
-    {
+pub fn verify(signature: &Signature, pubkey: &GE, message: &BigInt) -> Result<(), Error> {
+    verification::verify(&signature.r, &signature.s, message, pubkey)?;
+    // against malleability
+    if signature.s < FE::q() - &signature.s {
         Ok(())
     } else {
         Err(InvalidSig)
     }
 }
+
+/// Verifies `signatures[i]` on `messages[i]` under `pubkeys[i]` for all i at once.
+pub fn batch_verify(
+    signatures: &[SignatureRecid],
+    pubkeys: &[GE],
+    messages: &[BigInt],
+) -> Result<(), Error> {
+    if signatures.len() != pubkeys.len() || signatures.len() != messages.len() {
+        return Err(InvalidSig);
+    }
+    let q = FE::q();
+    let mut items = Vec::with_capacity(signatures.len());
+    for ((signature, pubkey), message) in signatures.iter().zip(pubkeys).zip(messages) {
+        if signature.s >= &q - &signature.s {
+            return Err(InvalidSig);
+        }
+        items.push(BatchItem {
+            r: signature.r.clone(),
+            s: signature.s.clone(),
+            recid: signature.recid,
+            message: message.clone(),
+            public_key: *pubkey,
+        });
+    }
+    verification::batch_verify(&items)
+}
+
+pub fn verify_with_digest(
+    signature: &Signature,
+    pubkey: &GE,
+    message: &[u8],
+    digest: MessageDigest,
+) -> Result<(), Error> {
+    verify(signature, pubkey, &digest.to_message(message))
+}
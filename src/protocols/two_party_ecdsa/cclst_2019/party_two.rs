@@ -34,7 +34,9 @@ use serde::{Deserialize, Serialize};
 use super::party_one::EphKeyGenFirstMsg as Party1EphKeyGenFirstMsg;
 use super::party_one::KeyGenFirstMsg as Party1KeyGenFirstMessage;
 use super::party_one::KeyGenSecondMsg as Party1KeyGenSecondMessage;
+use super::setup::{with_pari, ClSetup};
 use super::SECURITY_BITS;
+use crate::utilities::digest::MessageDigest;
 
 //****************** Begin: Party Two structs ******************//
 
@@ -53,7 +55,7 @@ pub struct KeyGenFirstMsg {
 #[derive(Debug, Serialize, Deserialize)]
 pub struct KeyGenSecondMsg {}
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct Party2Public {
     pub group: CLGroup,
     pub ek: HSMCLPK,
@@ -65,7 +67,7 @@ pub struct PartialSig {
     pub c3: CLCiphertext,
 }
 
-#[derive(Serialize, Deserialize)]
+#[derive(Serialize, Deserialize, Clone)]
 pub struct Party2Private {
     x2: FE,
 }
@@ -212,6 +214,13 @@ impl Party2Private {
             x2: ec_key.secret_share.clone(),
         }
     }
+
+    pub fn update_private_key(party_two_private: &Party2Private, factor: &BigInt) -> Party2Private {
+        let factor_fe: FE = ECScalar::from(factor);
+        Party2Private {
+            x2: party_two_private.x2 * factor_fe,
+        }
+    }
 }
 
 impl Party2Public {
@@ -220,14 +229,17 @@ impl Party2Public {
         seed: &BigInt,
         party1_ec_pubkey: &GE,
     ) -> Result<Self, ()> {
-        let setup_verify = hsmcl_public.cl_group.setup_verify(seed);
-
-        let proof_verify = hsmcl_public.proof.verify(
-            &hsmcl_public.cl_group,
-            &hsmcl_public.cl_pub_key,
-            &hsmcl_public.encrypted_share,
-            party1_ec_pubkey,
-        );
+        let (setup_verify, proof_verify) = with_pari(|| {
+            let setup_verify = hsmcl_public.cl_group.setup_verify(seed);
+
+            let proof_verify = hsmcl_public.proof.verify(
+                &hsmcl_public.cl_group,
+                &hsmcl_public.cl_pub_key,
+                &hsmcl_public.encrypted_share,
+                party1_ec_pubkey,
+            );
+            (setup_verify, proof_verify)
+        });
         match proof_verify.is_ok() && setup_verify.is_ok() {
             true => Ok(Party2Public {
                 group: hsmcl_public.cl_group.clone(),
@@ -237,6 +249,29 @@ impl Party2Public {
             false => Err(()),
         }
     }
+
+    /// Like `verify_setup_and_zkcldl_proof`, with the proof checked in the group of
+    /// `setup` instead of the one party one sent along.
+    pub fn verify_zkcldl_proof_with_setup(
+        hsmcl_public: &HSMCLPublic,
+        setup: &ClSetup,
+        party1_ec_pubkey: &GE,
+    ) -> Result<Self, ()> {
+        with_pari(|| {
+            hsmcl_public.proof.verify(
+                setup.group(),
+                &hsmcl_public.cl_pub_key,
+                &hsmcl_public.encrypted_share,
+                party1_ec_pubkey,
+            )
+        })
+        .map_err(|_| ())?;
+        Ok(Party2Public {
+            group: setup.group().clone(),
+            ek: hsmcl_public.cl_pub_key.clone(),
+            encrypted_secret_share: hsmcl_public.encrypted_share.clone(),
+        })
+    }
 }
 
 impl EphKeyGenFirstMsg {
@@ -331,14 +366,34 @@ impl PartialSig {
             .unwrap();
         let k2_inv_m = BigInt::mod_mul(&k2_inv, message, &q);
         let k2_inv_m_fe: FE = ECScalar::from(&k2_inv_m);
-        let c1 = encrypt(&party_two_public.group, &party_two_public.ek, &k2_inv_m_fe);
         let v = BigInt::mod_mul(&k2_inv, &local_share.x2.to_big_int(), &q);
         let v = BigInt::mod_mul(&v, &rx, &q);
 
-        let c2 = eval_scal(&party_two_public.encrypted_secret_share, &v);
-        let c3 = eval_sum(&c1.0, &c2);
+        let c3 = with_pari(|| {
+            let c1 = encrypt(&party_two_public.group, &party_two_public.ek, &k2_inv_m_fe);
+            let c2 = eval_scal(&party_two_public.encrypted_secret_share, &v);
+            eval_sum(&c1.0, &c2)
+        });
 
         //c3:
         PartialSig { c3 }
     }
+
+    /// Like `compute`, but hashes the raw `message` with `digest` first.
+    pub fn compute_with_digest(
+        party_two_public: Party2Public,
+        local_share: &Party2Private,
+        ephemeral_local_share: &EphEcKeyPair,
+        ephemeral_other_public_share: &GE,
+        message: &[u8],
+        digest: MessageDigest,
+    ) -> PartialSig {
+        Self::compute(
+            party_two_public,
+            local_share,
+            ephemeral_local_share,
+            ephemeral_other_public_share,
+            &digest.to_message(message),
+        )
+    }
 }
@@ -0,0 +1,95 @@
+/*
+    Multi-party ECDSA
+
+    Copyright 2018 by Kzen Networks
+
+    This file is part of Multi-party ECDSA library
+    (https://github.com/KZen-networks/multi-party-ecdsa)
+
+    Multi-party ECDSA is free software: you can redistribute
+    it and/or modify it under the terms of the GNU General Public
+    License as published by the Free Software Foundation, either
+    version 3 of the License, or (at your option) any later version.
+
+    @license GPL-3.0+ <https://github.com/KZen-networks/multi-party-ecdsa/blob/master/LICENSE>
+*/
+
+//! Key rotation for CCLST, following the Lindell 2017 one: x1' = x1 * f and x2' = x2 / f
+//! for a factor f that both parties contribute to. The coin flip is the same, only the
+//! last message differs: party one sends x1' encrypted under a fresh HSMCL key of the
+//! same class group, with a `CLDLProof` for the new public share.
+//!
+//! ```text
+//! P1 -> P2  RotationFirstMsg        commitment to seed1
+//! P2 -> P1  Party2RotationFirstMsg  seed2
+//! P1 -> P2  RotationSecondMsg       seed1, new HSMCL key, proof
+//! ```
+
+use curv::elliptic::curves::p256::{FE, GE};
+use curv::elliptic::curves::traits::*;
+use curv::BigInt;
+use serde::{Deserialize, Serialize};
+
+use super::party_one::{HSMCLPublic, Party1Private, HSMCL};
+use super::party_two::{Party2Private, Party2Public};
+use super::setup::ClSetup;
+use crate::Error::{self, InvalidKey};
+
+pub use crate::protocols::two_party_ecdsa::lindell_2017::rotation::{
+    Party2RotationFirstMsg, RotationFirstMsg, RotationWitness,
+};
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct RotationSecondMsg {
+    pub seed: FE,
+    pub blind_factor: BigInt,
+    pub hsmcl_public: HSMCLPublic,
+}
+
+impl RotationSecondMsg {
+    /// Party one's last step, returning the message for party two together with the
+    /// rotated share and HSMCL key.
+    pub fn rotate(
+        witness: RotationWitness,
+        party_two_first_msg: &Party2RotationFirstMsg,
+        party_one_private: &Party1Private,
+        hsmcl: &HSMCL,
+    ) -> Result<(RotationSecondMsg, Party1Private, HSMCL), Error> {
+        let factor = witness.factor(party_two_first_msg)?;
+        let (seed, blind_factor) = witness.into_opening();
+        let (hsmcl, hsmcl_public, party_one_private) =
+            Party1Private::refresh_private_key(party_one_private, hsmcl, &factor.to_big_int());
+        let msg = RotationSecondMsg {
+            seed,
+            blind_factor,
+            hsmcl_public,
+        };
+        Ok((msg, party_one_private, hsmcl))
+    }
+
+    /// Party two's last step. Opens the commitment, checks in the group of `setup` that the
+    /// new ciphertext encrypts the discrete log of `party_one_public_share * factor`, and
+    /// returns the rotated share together with the new public data and party one's new
+    /// public share.
+    pub fn verify_and_rotate(
+        &self,
+        party_one_first_msg: &RotationFirstMsg,
+        party_two_first_msg: &Party2RotationFirstMsg,
+        party_two_private: &Party2Private,
+        party_one_public_share: &GE,
+        setup: &ClSetup,
+    ) -> Result<(Party2Private, Party2Public, GE), Error> {
+        let factor =
+            party_one_first_msg.open(&self.seed, &self.blind_factor, party_two_first_msg)?;
+        let party_one_public_share = party_one_public_share * &factor;
+        let party_two_public = Party2Public::verify_zkcldl_proof_with_setup(
+            &self.hsmcl_public,
+            setup,
+            &party_one_public_share,
+        )
+        .map_err(|_| InvalidKey)?;
+        let party_two_private =
+            Party2Private::update_private_key(party_two_private, &factor.invert().to_big_int());
+        Ok((party_two_private, party_two_public, party_one_public_share))
+    }
+}
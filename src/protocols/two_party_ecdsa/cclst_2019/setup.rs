@@ -0,0 +1,111 @@
+/*
+    Multi-party ECDSA
+
+    Copyright 2018 by Kzen Networks
+
+    This file is part of Multi-party ECDSA library
+    (https://github.com/KZen-networks/multi-party-ecdsa)
+
+    Multi-party ECDSA is free software: you can redistribute
+    it and/or modify it under the terms of the GNU General Public
+    License as published by the Free Software Foundation, either
+    version 3 of the License, or (at your option) any later version.
+
+    @license GPL-3.0+ <https://github.com/KZen-networks/multi-party-ecdsa/blob/master/LICENSE>
+*/
+
+//! The class group both parties work in. Deriving it from a seed is by far the most
+//! expensive step of a CCLST keygen, so a `ClSetup` is meant to be derived once,
+//! persisted, and shared by every keygen and rotation that uses the same seed.
+//!
+//! Since anyone can recompute the group from the seed, a setup is checked against its
+//! seed whenever it is deserialized, and the protocol functions taking a `ClSetup` skip the
+//! per-keygen setup check.
+//!
+//! The class group arithmetic runs on PARI, whose stack is global and not safe to use from
+//! several threads at once, so this crate makes every class group call under one
+//! process-wide lock, see [`with_pari`]. Parallel callers and tests are serialized instead
+//! of corrupting each other.
+//!
+//! Precomputed powers of the generator `gq` are not cached yet: `class_group` v0.5.1
+//! computes its exponentiations inside `verifiably_encrypt`, `encrypt` and the proof
+//! verification, and takes no table from the caller. Caching them needs a fork of that
+//! crate and is left to a follow-up.
+
+use std::convert::TryFrom;
+use std::sync::Mutex;
+
+use class_group::primitives::cl_dl_public_setup::CLGroup;
+use curv::arithmetic::traits::*;
+use curv::BigInt;
+use serde::{Deserialize, Serialize};
+
+use crate::Error::{self, InvalidKey};
+
+/// Security parameter of the class group, as used by the original keygen.
+pub const CL_GROUP_BITS: usize = 1348;
+
+const SEED_BITS: usize = 512;
+
+static PARI: Mutex<()> = Mutex::new(());
+
+/// Runs `f`, which may call into `class_group`, while no other thread does.
+pub(crate) fn with_pari<T, F: FnOnce() -> T>(f: F) -> T {
+    // a panic of another holder must not lock everyone out of the class group
+    let _guard = PARI.lock().unwrap_or_else(|e| e.into_inner());
+    f()
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(try_from = "UncheckedClSetup")]
+pub struct ClSetup {
+    seed: BigInt,
+    group: CLGroup,
+}
+
+#[derive(Deserialize)]
+struct UncheckedClSetup {
+    seed: BigInt,
+    group: CLGroup,
+}
+
+impl ClSetup {
+    /// Derives the group from a public seed, every party doing so gets the same group.
+    pub fn from_seed(seed: &BigInt) -> ClSetup {
+        ClSetup {
+            seed: seed.clone(),
+            group: with_pari(|| CLGroup::new_from_setup(&CL_GROUP_BITS, seed)),
+        }
+    }
+
+    /// Derives the group from a fresh random seed.
+    pub fn generate() -> ClSetup {
+        Self::from_seed(&BigInt::sample(SEED_BITS))
+    }
+
+    /// Accepts a group received from elsewhere only if it is the one derived from `seed`.
+    pub fn from_parts(seed: &BigInt, group: CLGroup) -> Result<ClSetup, Error> {
+        with_pari(|| group.setup_verify(seed)).map_err(|_| InvalidKey)?;
+        Ok(ClSetup {
+            seed: seed.clone(),
+            group,
+        })
+    }
+
+    pub fn seed(&self) -> &BigInt {
+        &self.seed
+    }
+
+    pub fn group(&self) -> &CLGroup {
+        &self.group
+    }
+}
+
+impl TryFrom<UncheckedClSetup> for ClSetup {
+    type Error = &'static str;
+
+    fn try_from(setup: UncheckedClSetup) -> Result<Self, Self::Error> {
+        ClSetup::from_parts(&setup.seed, setup.group)
+            .map_err(|_| "class group does not match its seed")
+    }
+}
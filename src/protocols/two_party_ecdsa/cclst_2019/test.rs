@@ -1,6 +1,7 @@
 // For integration tests, please add your tests in /tests instead
 
 use super::*;
+use curv::arithmetic::traits::*;
 use curv::elliptic::curves::traits::*;
 use curv::BigInt;
 
@@ -137,3 +138,184 @@ fn test_two_party_sign() {
         party_one::compute_pubkey(&party1_private, &party_two_private_share_gen.public_share);
     party_one::verify(&signature, &pubkey, &message).expect("Invalid signature")
 }
+
+fn sign(
+    hsmcl: &party_one::HSMCL,
+    party1_private: &party_one::Party1Private,
+    party2_private: &party_two::Party2Private,
+    party_two_public: &party_two::Party2Public,
+    message: &BigInt,
+) -> party_one::SignatureRecid {
+    let (eph_party_two_first_message, eph_comm_witness, eph_ec_key_pair_party2) =
+        party_two::EphKeyGenFirstMsg::create_commitments();
+    let (eph_party_one_first_message, eph_ec_key_pair_party1) =
+        party_one::EphKeyGenFirstMsg::create();
+    let eph_party_two_second_message = party_two::EphKeyGenSecondMsg::verify_and_decommit(
+        eph_comm_witness,
+        &eph_party_one_first_message,
+    )
+    .expect("party1 DLog proof failed");
+    party_one::EphKeyGenSecondMsg::verify_commitments_and_dlog_proof(
+        &eph_party_two_first_message,
+        &eph_party_two_second_message,
+    )
+    .expect("failed to verify commitments and DLog proof");
+    let partial_sig = party_two::PartialSig::compute(
+        party_two_public.clone(),
+        party2_private,
+        &eph_ec_key_pair_party2,
+        &eph_party_one_first_message.public_share,
+        message,
+    );
+    party_one::Signature::compute_with_recid(
+        hsmcl,
+        party1_private,
+        partial_sig.c3,
+        &eph_ec_key_pair_party1,
+        &eph_party_two_second_message.comm_witness.public_share,
+    )
+}
+
+#[test]
+fn test_key_rotation_and_recid() {
+    let (_party_one_private_share_gen, comm_witness, ec_key_pair_party1) =
+        party_one::KeyGenFirstMsg::create_commitments();
+    let (party_two_private_share_gen, ec_key_pair_party2) = party_two::KeyGenFirstMsg::create();
+    let seed: BigInt = str::parse(
+        "314159265358979323846264338327950288419716939937510582097494459230781640628620899862803482534211706798214808651328230664709384460955058223172535940812848"
+    ).unwrap();
+    let (hsmcl, hsmcl_public) = party_one::HSMCL::generate_keypair_and_encrypted_share_and_proof(
+        &ec_key_pair_party1,
+        &seed,
+    );
+    let party1_private = party_one::Party1Private::set_private_key(&ec_key_pair_party1, &hsmcl);
+    let party2_private = party_two::Party2Private::set_private_key(&ec_key_pair_party2);
+    let party_two_public = party_two::Party2Public::verify_setup_and_zkcldl_proof(
+        &hsmcl_public,
+        &seed,
+        &comm_witness.public_share,
+    )
+    .expect("proof error");
+    let pubkey =
+        party_one::compute_pubkey(&party1_private, &party_two_private_share_gen.public_share);
+
+    let message = BigInt::from(1234);
+    let signature = sign(
+        &hsmcl,
+        &party1_private,
+        &party2_private,
+        &party_two_public,
+        &message,
+    );
+    assert_eq!(
+        party_one::recover_public_key(&signature, &message).unwrap(),
+        pubkey
+    );
+
+    // rotation checks the new proof in the group party two accepted at keygen
+    let setup = setup::ClSetup::from_parts(&seed, party_two_public.group.clone())
+        .expect("group does not match the seed");
+    let (rotation_first_msg, witness) = rotation::RotationFirstMsg::commit();
+    let party_two_rotation_first_msg = rotation::Party2RotationFirstMsg::create();
+    let (rotation_second_msg, party1_private_new, hsmcl_new) = rotation::RotationSecondMsg::rotate(
+        witness,
+        &party_two_rotation_first_msg,
+        &party1_private,
+        &hsmcl,
+    )
+    .expect("rotation failed");
+    let (party2_private_new, party_two_public_new, party_one_public_share_new) =
+        rotation_second_msg
+            .verify_and_rotate(
+                &rotation_first_msg,
+                &party_two_rotation_first_msg,
+                &party2_private,
+                &comm_witness.public_share,
+                &setup,
+            )
+            .expect("invalid rotation message");
+    assert_ne!(party_one_public_share_new, comm_witness.public_share);
+
+    let signature = sign(
+        &hsmcl_new,
+        &party1_private_new,
+        &party2_private_new,
+        &party_two_public_new,
+        &message,
+    );
+    assert_eq!(
+        party_one::recover_public_key(&signature, &message).unwrap(),
+        pubkey
+    );
+
+    // a stale share of party two does not combine with the rotated share of party one
+    let signature = sign(
+        &hsmcl_new,
+        &party1_private_new,
+        &party2_private,
+        &party_two_public_new,
+        &message,
+    );
+    let signature = party_one::Signature {
+        r: signature.r,
+        s: signature.s,
+    };
+    assert!(party_one::verify(&signature, &pubkey, &message).is_err());
+}
+
+#[test]
+fn test_keygens_share_setup() {
+    let seed: BigInt = str::parse(
+        "314159265358979323846264338327950288419716939937510582097494459230781640628620899862803482534211706798214808651328230664709384460955058223172535940812848"
+    ).unwrap();
+    let setup = setup::ClSetup::from_seed(&seed);
+    let message = BigInt::from(1234);
+    for _ in 0..2 {
+        let (_party_one_private_share_gen, comm_witness, ec_key_pair_party1) =
+            party_one::KeyGenFirstMsg::create_commitments();
+        let (party_two_private_share_gen, ec_key_pair_party2) = party_two::KeyGenFirstMsg::create();
+        let (hsmcl, hsmcl_public) =
+            party_one::HSMCL::generate_keypair_and_encrypted_share_and_proof_with_setup(
+                &ec_key_pair_party1,
+                &setup,
+            );
+        let party1_private = party_one::Party1Private::set_private_key(&ec_key_pair_party1, &hsmcl);
+        let party2_private = party_two::Party2Private::set_private_key(&ec_key_pair_party2);
+        let party_two_public = party_two::Party2Public::verify_zkcldl_proof_with_setup(
+            &hsmcl_public,
+            &setup,
+            &comm_witness.public_share,
+        )
+        .expect("proof error");
+        let pubkey =
+            party_one::compute_pubkey(&party1_private, &party_two_private_share_gen.public_share);
+
+        let signature = sign(
+            &hsmcl,
+            &party1_private,
+            &party2_private,
+            &party_two_public,
+            &message,
+        );
+        assert_eq!(
+            party_one::recover_public_key(&signature, &message).unwrap(),
+            pubkey
+        );
+    }
+}
+
+#[test]
+fn test_setup_is_checked_on_load() {
+    let setup = setup::ClSetup::generate();
+    let json = serde_json::to_string(&setup).unwrap();
+    let loaded: setup::ClSetup = serde_json::from_str(&json).unwrap();
+    assert_eq!(loaded.seed(), setup.seed());
+
+    // the same group claimed for another seed is refused
+    let mut value: serde_json::Value = serde_json::from_str(&json).unwrap();
+    value["seed"] = serde_json::to_value(setup.seed() + BigInt::one()).unwrap();
+    assert!(serde_json::from_value::<setup::ClSetup>(value).is_err());
+    assert!(
+        setup::ClSetup::from_parts(&(setup.seed() + BigInt::one()), setup.group().clone()).is_err()
+    );
+}
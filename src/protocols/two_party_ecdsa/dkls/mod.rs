@@ -0,0 +1,37 @@
+/*
+    Multi-party ECDSA
+
+    Copyright 2018 by Kzen Networks
+
+    This file is part of Multi-party ECDSA library
+    (https://github.com/KZen-networks/multi-party-ecdsa)
+
+    Multi-party ECDSA is free software: you can redistribute
+    it and/or modify it under the terms of the GNU General Public
+    License as published by the Free Software Foundation, either
+    version 3 of the License, or (at your option) any later version.
+
+    @license GPL-3.0+ <https://github.com/KZen-networks/multi-party-ecdsa/blob/master/LICENSE>
+*/
+
+// Keygen takes three messages: party one commits to its share, party two sends its share,
+// party one decommits. Alongside, each party runs base OTs with the other, which
+// `utilities::mta::ot` then extends for every signature.
+//
+// Signing takes three messages as well, with x = x1 * x2 and k = k1 * k2. Party two
+// commits to R2 and asks for the two multiplications 1/k1 * 1/k2 and x1/k1 * x2/k2, party
+// one sends R1 and answers them, and party two decommits with its share of
+// s = m/k + r x/k. Party one proves with two DLEQ proofs that its MtA inputs are the 1/k1
+// of R1 and the x1/k1 of X1, so party two never releases s2 for another nonce. Only party
+// one learns the signature, and checks it.
+
+const SECURITY_BITS: usize = 256;
+
+pub(crate) const PARTY_ONE: u16 = 0;
+pub(crate) const PARTY_TWO: u16 = 1;
+
+pub mod party_one;
+pub mod party_two;
+
+#[cfg(test)]
+mod test;
@@ -0,0 +1,355 @@
+/*
+    Multi-party ECDSA
+
+    Copyright 2018 by Kzen Networks
+
+    This file is part of Multi-party ECDSA library
+    (https://github.com/KZen-networks/multi-party-ecdsa)
+
+    Multi-party ECDSA is free software: you can redistribute
+    it and/or modify it under the terms of the GNU General Public
+    License as published by the Free Software Foundation, either
+    version 3 of the License, or (at your option) any later version.
+
+    @license GPL-3.0+ <https://github.com/KZen-networks/multi-party-ecdsa/blob/master/LICENSE>
+*/
+use std::cmp;
+
+use curv::arithmetic::traits::*;
+use curv::cryptographic_primitives::commitments::hash_commitment::HashCommitment;
+use curv::cryptographic_primitives::commitments::traits::Commitment;
+use curv::cryptographic_primitives::proofs::sigma_dlog::*;
+use curv::cryptographic_primitives::proofs::sigma_ec_ddh::*;
+use curv::elliptic::curves::p256::{FE, GE};
+use curv::elliptic::curves::traits::*;
+use curv::BigInt;
+use serde::{Deserialize, Serialize};
+use zeroize::Zeroize;
+
+use super::party_two::EphKeyGenFirstMsg as Party2EphKeyGenFirstMsg;
+use super::party_two::EphKeyGenSecondMsg as Party2EphKeyGenSecondMsg;
+use super::party_two::KeyGenFirstMsg as Party2KeyGenFirstMsg;
+use super::party_two::{MtaRequest, PartialSig};
+use super::{PARTY_ONE, PARTY_TWO, SECURITY_BITS};
+use crate::utilities::mta::ot::{OtMessageB, OtMta};
+use crate::utilities::mta::MtaBackend;
+use crate::utilities::ot::{self, BaseOtReceiverMsg, BaseOtSender, BaseOtSenderMsg};
+use crate::utilities::{recovery, verification};
+use crate::Error::{self, InvalidCom, InvalidKey};
+
+// the signatures are plain ECDSA, the same as with Lindell 2017
+pub use crate::protocols::two_party_ecdsa::lindell_2017::party_one::{
+    recover_public_key, verify, Signature, SignatureRecid,
+};
+
+//****************** Begin: Party One structs ******************//
+#[derive(Clone, Serialize, Deserialize)]
+pub struct EcKeyPair {
+    pub public_share: GE,
+    secret_share: FE,
+    base_ot: BaseOtSender,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct CommWitness {
+    pub pk_commitment_blind_factor: BigInt,
+    pub zk_pok_blind_factor: BigInt,
+    pub public_share: GE,
+    pub d_log_proof: DLogProof<GE>,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct KeyGenFirstMsg {
+    pub pk_commitment: BigInt,
+    pub zk_pok_commitment: BigInt,
+    pub base_ot: BaseOtSenderMsg,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct KeyGenSecondMsg {
+    pub comm_witness: CommWitness,
+    pub base_ot_reply: BaseOtReceiverMsg,
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct Party1Private {
+    x1: FE,
+    mta: OtMta,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct EphEcKeyPair {
+    pub public_share: GE,
+    secret_share: FE,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct EphKeyGenFirstMsg {
+    pub d_log_proof: DLogProof<GE>,
+    pub public_share: GE,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct EphKeyGenSecondMsg {}
+
+/// Answers to the multiplications of `MtaRequest`, with 1/k1 and x1/k1 as inputs. The
+/// answers commit to B = 1/k1 * G and B' = x1/k1 * G, and the proofs show that k1 is the
+/// discrete log of R1 as well as of G to the base B and of X1 to the base B'.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct MtaResponse {
+    pub k_inv: OtMessageB,
+    pub x_k_inv: OtMessageB,
+    pub k_inv_proof: ECDDHProof<GE>,
+    pub x_k_inv_proof: ECDDHProof<GE>,
+}
+
+/// Our additive shares of 1/k and x/k.
+#[derive(Serialize, Deserialize)]
+pub struct MtaShares {
+    k_inv: FE,
+    x_k_inv: FE,
+}
+
+//****************** End: Party One structs ******************//
+
+impl KeyGenFirstMsg {
+    pub fn create_commitments() -> (KeyGenFirstMsg, CommWitness, EcKeyPair) {
+        Self::create_commitments_with_fixed_secret_share(ECScalar::new_random())
+    }
+
+    pub fn create_commitments_with_fixed_secret_share(
+        secret_share: FE,
+    ) -> (KeyGenFirstMsg, CommWitness, EcKeyPair) {
+        let public_share = GE::generator() * secret_share;
+        let d_log_proof = DLogProof::prove(&secret_share);
+
+        let pk_commitment_blind_factor = BigInt::sample(SECURITY_BITS);
+        let pk_commitment = HashCommitment::create_commitment_with_user_defined_randomness(
+            &public_share.bytes_compressed_to_big_int(),
+            &pk_commitment_blind_factor,
+        );
+
+        let zk_pok_blind_factor = BigInt::sample(SECURITY_BITS);
+        let zk_pok_commitment = HashCommitment::create_commitment_with_user_defined_randomness(
+            &d_log_proof
+                .pk_t_rand_commitment
+                .bytes_compressed_to_big_int(),
+            &zk_pok_blind_factor,
+        );
+
+        let base_ot = BaseOtSender::new();
+        (
+            KeyGenFirstMsg {
+                pk_commitment,
+                zk_pok_commitment,
+                base_ot: base_ot.msg.clone(),
+            },
+            CommWitness {
+                pk_commitment_blind_factor,
+                zk_pok_blind_factor,
+                public_share,
+                d_log_proof,
+            },
+            EcKeyPair {
+                public_share,
+                secret_share,
+                base_ot,
+            },
+        )
+    }
+}
+
+impl KeyGenSecondMsg {
+    /// Checks the share of party two, then completes our base OTs with its answer and
+    /// answers its own. Our private key is ready once this succeeds.
+    pub fn verify_and_decommit(
+        comm_witness: CommWitness,
+        ec_key_pair: &EcKeyPair,
+        party_two_first_message: &Party2KeyGenFirstMsg,
+    ) -> Result<(KeyGenSecondMsg, Party1Private), Error> {
+        let d_log_proof = &party_two_first_message.d_log_proof;
+        if DLogProof::verify(d_log_proof).is_err()
+            || d_log_proof.pk != party_two_first_message.public_share
+        {
+            return Err(InvalidKey);
+        }
+        let receiver = ec_key_pair.base_ot.finalize(
+            &party_two_first_message.base_ot_reply,
+            PARTY_ONE,
+            PARTY_TWO,
+        )?;
+        let (base_ot_reply, sender) =
+            ot::base_ot_receive(&party_two_first_message.base_ot, PARTY_TWO, PARTY_ONE)?;
+        let party_one_private = Party1Private {
+            x1: ec_key_pair.secret_share,
+            mta: OtMta::with_peer(PARTY_ONE, PARTY_TWO, receiver, sender),
+        };
+        Ok((
+            KeyGenSecondMsg {
+                comm_witness,
+                base_ot_reply,
+            },
+            party_one_private,
+        ))
+    }
+}
+
+pub fn compute_pubkey(party_one_private: &Party1Private, other_share_public_share: &GE) -> GE {
+    other_share_public_share * &party_one_private.x1
+}
+
+impl EphKeyGenFirstMsg {
+    pub fn create() -> (EphKeyGenFirstMsg, EphEcKeyPair) {
+        let secret_share: FE = ECScalar::new_random();
+        let d_log_proof = DLogProof::prove(&secret_share);
+        let public_share = d_log_proof.pk;
+        (
+            EphKeyGenFirstMsg {
+                d_log_proof,
+                public_share,
+            },
+            EphEcKeyPair {
+                public_share,
+                secret_share,
+            },
+        )
+    }
+}
+
+impl EphKeyGenSecondMsg {
+    pub fn verify_commitments_and_dlog_proof(
+        party_two_first_message: &Party2EphKeyGenFirstMsg,
+        party_two_second_message: &Party2EphKeyGenSecondMsg,
+    ) -> Result<EphKeyGenSecondMsg, Error> {
+        let comm_witness = &party_two_second_message.comm_witness;
+        let pk_commitment = HashCommitment::create_commitment_with_user_defined_randomness(
+            &comm_witness.public_share.bytes_compressed_to_big_int(),
+            &comm_witness.pk_commitment_blind_factor,
+        );
+        let zk_pok_commitment = HashCommitment::create_commitment_with_user_defined_randomness(
+            &comm_witness
+                .d_log_proof
+                .pk_t_rand_commitment
+                .bytes_compressed_to_big_int(),
+            &comm_witness.zk_pok_blind_factor,
+        );
+        if pk_commitment != party_two_first_message.pk_commitment
+            || zk_pok_commitment != party_two_first_message.zk_pok_commitment
+        {
+            return Err(InvalidCom);
+        }
+        if DLogProof::verify(&comm_witness.d_log_proof).is_err()
+            || comm_witness.d_log_proof.pk != comm_witness.public_share
+        {
+            return Err(InvalidKey);
+        }
+        Ok(EphKeyGenSecondMsg {})
+    }
+}
+
+impl MtaResponse {
+    pub fn create(
+        party_one_private: &Party1Private,
+        ephemeral_local_share: &EphEcKeyPair,
+        mta_request: &MtaRequest,
+    ) -> Result<(MtaResponse, MtaShares), Error> {
+        let mta = &party_one_private.mta;
+        let mut k1_inv = ephemeral_local_share.secret_share.invert();
+        let mut x1_k1_inv = party_one_private.x1 * k1_inv;
+        let answers = mta
+            .message_b(PARTY_TWO as usize, &k1_inv, &mta_request.k_inv)
+            .and_then(|k_inv| {
+                let x_k_inv =
+                    mta.message_b(PARTY_TWO as usize, &x1_k1_inv, &mta_request.x_k_inv)?;
+                Ok((k_inv, x_k_inv))
+            });
+        k1_inv.zeroize();
+        x1_k1_inv.zeroize();
+        let ((k_inv, beta_k_inv), (x_k_inv, beta_x_k_inv)) = answers?;
+
+        let g = GE::generator();
+        let w = ECDDHWitness {
+            x: ephemeral_local_share.secret_share,
+        };
+        let k_inv_proof = ECDDHProof::prove(
+            &w,
+            &ECDDHStatement {
+                g1: g,
+                h1: ephemeral_local_share.public_share,
+                g2: k_inv.b_proof.pk,
+                h2: g,
+            },
+        );
+        let x_k_inv_proof = ECDDHProof::prove(
+            &w,
+            &ECDDHStatement {
+                g1: g,
+                h1: ephemeral_local_share.public_share,
+                g2: x_k_inv.b_proof.pk,
+                h2: g * party_one_private.x1,
+            },
+        );
+        Ok((
+            MtaResponse {
+                k_inv,
+                x_k_inv,
+                k_inv_proof,
+                x_k_inv_proof,
+            },
+            MtaShares {
+                k_inv: beta_k_inv,
+                x_k_inv: beta_x_k_inv,
+            },
+        ))
+    }
+}
+
+/// Adds the partial signature of party two to our shares. Party two could send anything,
+/// so the signature is verified under `pubkey` before it is returned.
+pub fn compute_signature(
+    mta_shares: &MtaShares,
+    partial_sig: &PartialSig,
+    ephemeral_local_share: &EphEcKeyPair,
+    ephemeral_other_public_share: &GE,
+    message: &BigInt,
+    pubkey: &GE,
+) -> Result<Signature, Error> {
+    let signature = compute_signature_with_recid(
+        mta_shares,
+        partial_sig,
+        ephemeral_local_share,
+        ephemeral_other_public_share,
+        message,
+        pubkey,
+    )?;
+    Ok(Signature {
+        s: signature.s,
+        r: signature.r,
+    })
+}
+
+pub fn compute_signature_with_recid(
+    mta_shares: &MtaShares,
+    partial_sig: &PartialSig,
+    ephemeral_local_share: &EphEcKeyPair,
+    ephemeral_other_public_share: &GE,
+    message: &BigInt,
+    pubkey: &GE,
+) -> Result<SignatureRecid, Error> {
+    let q = FE::q();
+    //compute r = k1 * R2
+    let r = ephemeral_other_public_share * &ephemeral_local_share.secret_share;
+    let rx = r.x_coor().unwrap().mod_floor(&q);
+    let rx_fe: FE = ECScalar::from(&rx);
+    let m_fe: FE = ECScalar::from(&message.mod_floor(&q));
+
+    let s_fe = m_fe * mta_shares.k_inv + rx_fe * mta_shares.x_k_inv + partial_sig.s2;
+    let s_bn = s_fe.to_big_int();
+    let s = cmp::min(s_bn.clone(), &q - &s_bn);
+    verification::verify(&rx, &s, message, pubkey)?;
+
+    // see the Lindell 2017 party one for how the recovery id is derived
+    let is_s_high = s_bn > &q - &s_bn;
+    let recid = recovery::compute_recid(&r, is_s_high);
+    Ok(SignatureRecid { s, r: rx, recid })
+}
@@ -0,0 +1,319 @@
+/*
+    Multi-party ECDSA
+
+    Copyright 2018 by Kzen Networks
+
+    This file is part of Multi-party ECDSA library
+    (https://github.com/KZen-networks/multi-party-ecdsa)
+
+    Multi-party ECDSA is free software: you can redistribute
+    it and/or modify it under the terms of the GNU General Public
+    License as published by the Free Software Foundation, either
+    version 3 of the License, or (at your option) any later version.
+
+    @license GPL-3.0+ <https://github.com/KZen-networks/multi-party-ecdsa/blob/master/LICENSE>
+*/
+use curv::arithmetic::traits::*;
+use curv::cryptographic_primitives::commitments::hash_commitment::HashCommitment;
+use curv::cryptographic_primitives::commitments::traits::Commitment;
+use curv::cryptographic_primitives::proofs::sigma_dlog::*;
+use curv::cryptographic_primitives::proofs::sigma_ec_ddh::*;
+use curv::elliptic::curves::p256::{FE, GE};
+use curv::elliptic::curves::traits::*;
+use curv::BigInt;
+use serde::{Deserialize, Serialize};
+use zeroize::Zeroize;
+
+use super::party_one::EphKeyGenFirstMsg as Party1EphKeyGenFirstMsg;
+use super::party_one::KeyGenFirstMsg as Party1KeyGenFirstMsg;
+use super::party_one::KeyGenSecondMsg as Party1KeyGenSecondMsg;
+use super::party_one::MtaResponse;
+use super::{PARTY_ONE, PARTY_TWO, SECURITY_BITS};
+use crate::utilities::mta::ot::{OtMessageA, OtMta};
+use crate::utilities::mta::MtaBackend;
+use crate::utilities::ot::{
+    self, BaseOtReceiverMsg, BaseOtSender, BaseOtSenderMsg, ExtensionSender,
+};
+use crate::Error::{self, InvalidCom, InvalidKey, InvalidMessage};
+
+//****************** Begin: Party Two structs ******************//
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct EcKeyPair {
+    pub public_share: GE,
+    secret_share: FE,
+    base_ot: BaseOtSender,
+    extension_sender: ExtensionSender,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct KeyGenFirstMsg {
+    pub d_log_proof: DLogProof<GE>,
+    pub public_share: GE,
+    pub base_ot: BaseOtSenderMsg,
+    pub base_ot_reply: BaseOtReceiverMsg,
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct Party2Private {
+    x2: FE,
+    party_one_public_share: GE,
+    mta: OtMta,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct EphEcKeyPair {
+    pub public_share: GE,
+    secret_share: FE,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct EphCommWitness {
+    pub pk_commitment_blind_factor: BigInt,
+    pub zk_pok_blind_factor: BigInt,
+    pub public_share: GE,
+    pub d_log_proof: DLogProof<GE>,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct EphKeyGenFirstMsg {
+    pub pk_commitment: BigInt,
+    pub zk_pok_commitment: BigInt,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct EphKeyGenSecondMsg {
+    pub comm_witness: EphCommWitness,
+}
+
+/// The multiplications of a signature, with 1/k2 and x2/k2 as our inputs. Sent along with
+/// `EphKeyGenFirstMsg`, and kept to check party one's answers against.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct MtaRequest {
+    pub k_inv: OtMessageA,
+    pub x_k_inv: OtMessageA,
+}
+
+/// Our share of s, sent along with `EphKeyGenSecondMsg`.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct PartialSig {
+    pub s2: FE,
+}
+
+//****************** End: Party Two structs ******************//
+
+impl KeyGenFirstMsg {
+    /// Answers the base OTs of party one and starts ours.
+    pub fn create(
+        party_one_first_message: &Party1KeyGenFirstMsg,
+    ) -> Result<(KeyGenFirstMsg, EcKeyPair), Error> {
+        Self::create_with_fixed_secret_share(ECScalar::new_random(), party_one_first_message)
+    }
+
+    pub fn create_with_fixed_secret_share(
+        secret_share: FE,
+        party_one_first_message: &Party1KeyGenFirstMsg,
+    ) -> Result<(KeyGenFirstMsg, EcKeyPair), Error> {
+        let d_log_proof = DLogProof::prove(&secret_share);
+        let public_share = d_log_proof.pk;
+        let (base_ot_reply, extension_sender) =
+            ot::base_ot_receive(&party_one_first_message.base_ot, PARTY_ONE, PARTY_TWO)?;
+        let base_ot = BaseOtSender::new();
+        Ok((
+            KeyGenFirstMsg {
+                d_log_proof,
+                public_share,
+                base_ot: base_ot.msg.clone(),
+                base_ot_reply,
+            },
+            EcKeyPair {
+                public_share,
+                secret_share,
+                base_ot,
+                extension_sender,
+            },
+        ))
+    }
+}
+
+pub fn compute_pubkey(party_two_private: &Party2Private, other_share_public_share: &GE) -> GE {
+    other_share_public_share * &party_two_private.x2
+}
+
+impl Party2Private {
+    /// Checks the decommitment of party one and completes our base OTs with its answer.
+    pub fn set_private_key(
+        ec_key: &EcKeyPair,
+        party_one_first_message: &Party1KeyGenFirstMsg,
+        party_one_second_message: &Party1KeyGenSecondMsg,
+    ) -> Result<Party2Private, Error> {
+        let comm_witness = &party_one_second_message.comm_witness;
+        let pk_commitment = HashCommitment::create_commitment_with_user_defined_randomness(
+            &comm_witness.public_share.bytes_compressed_to_big_int(),
+            &comm_witness.pk_commitment_blind_factor,
+        );
+        let zk_pok_commitment = HashCommitment::create_commitment_with_user_defined_randomness(
+            &comm_witness
+                .d_log_proof
+                .pk_t_rand_commitment
+                .bytes_compressed_to_big_int(),
+            &comm_witness.zk_pok_blind_factor,
+        );
+        if pk_commitment != party_one_first_message.pk_commitment
+            || zk_pok_commitment != party_one_first_message.zk_pok_commitment
+        {
+            return Err(InvalidCom);
+        }
+        if DLogProof::verify(&comm_witness.d_log_proof).is_err()
+            || comm_witness.d_log_proof.pk != comm_witness.public_share
+        {
+            return Err(InvalidKey);
+        }
+        let receiver = ec_key.base_ot.finalize(
+            &party_one_second_message.base_ot_reply,
+            PARTY_TWO,
+            PARTY_ONE,
+        )?;
+        Ok(Party2Private {
+            x2: ec_key.secret_share,
+            party_one_public_share: comm_witness.public_share,
+            mta: OtMta::with_peer(
+                PARTY_TWO,
+                PARTY_ONE,
+                receiver,
+                ec_key.extension_sender.clone(),
+            ),
+        })
+    }
+}
+
+impl EphKeyGenFirstMsg {
+    pub fn create_commitments() -> (EphKeyGenFirstMsg, EphCommWitness, EphEcKeyPair) {
+        let secret_share: FE = ECScalar::new_random();
+        let d_log_proof = DLogProof::prove(&secret_share);
+        let public_share = d_log_proof.pk;
+
+        let pk_commitment_blind_factor = BigInt::sample(SECURITY_BITS);
+        let pk_commitment = HashCommitment::create_commitment_with_user_defined_randomness(
+            &public_share.bytes_compressed_to_big_int(),
+            &pk_commitment_blind_factor,
+        );
+
+        let zk_pok_blind_factor = BigInt::sample(SECURITY_BITS);
+        let zk_pok_commitment = HashCommitment::create_commitment_with_user_defined_randomness(
+            &d_log_proof
+                .pk_t_rand_commitment
+                .bytes_compressed_to_big_int(),
+            &zk_pok_blind_factor,
+        );
+
+        (
+            EphKeyGenFirstMsg {
+                pk_commitment,
+                zk_pok_commitment,
+            },
+            EphCommWitness {
+                pk_commitment_blind_factor,
+                zk_pok_blind_factor,
+                public_share,
+                d_log_proof,
+            },
+            EphEcKeyPair {
+                public_share,
+                secret_share,
+            },
+        )
+    }
+}
+
+impl EphKeyGenSecondMsg {
+    pub fn verify_and_decommit(
+        comm_witness: EphCommWitness,
+        party_one_first_message: &Party1EphKeyGenFirstMsg,
+    ) -> Result<EphKeyGenSecondMsg, Error> {
+        let d_log_proof = &party_one_first_message.d_log_proof;
+        if DLogProof::verify(d_log_proof).is_err()
+            || d_log_proof.pk != party_one_first_message.public_share
+        {
+            return Err(InvalidKey);
+        }
+        Ok(EphKeyGenSecondMsg { comm_witness })
+    }
+}
+
+impl MtaRequest {
+    pub fn create(party_two_private: &Party2Private, ephemeral_local_share: &EphEcKeyPair) -> Self {
+        let mta = &party_two_private.mta;
+        let mut k2_inv = ephemeral_local_share.secret_share.invert();
+        let mut x2_k2_inv = party_two_private.x2 * k2_inv;
+        let request = MtaRequest {
+            k_inv: mta.message_a(&k2_inv),
+            x_k_inv: mta.message_a(&x2_k2_inv),
+        };
+        k2_inv.zeroize();
+        x2_k2_inv.zeroize();
+        request
+    }
+}
+
+impl PartialSig {
+    /// Fails if the answers of party one do not belong to `mta_request`, if its inputs are
+    /// not 1/k1 and x1/k1 for the k1 of `ephemeral_other_public_share`, or if they do not
+    /// pass the consistency check of the MtA.
+    pub fn compute(
+        party_two_private: &Party2Private,
+        ephemeral_local_share: &EphEcKeyPair,
+        ephemeral_other_public_share: &GE,
+        mta_request: &MtaRequest,
+        mta_response: &MtaResponse,
+        message: &BigInt,
+    ) -> Result<PartialSig, Error> {
+        if mta_response.k_inv.nonce != mta_request.k_inv.nonce
+            || mta_response.x_k_inv.nonce != mta_request.x_k_inv.nonce
+        {
+            return Err(InvalidMessage);
+        }
+        let g = GE::generator();
+        let k_inv_statement = ECDDHStatement {
+            g1: g,
+            h1: *ephemeral_other_public_share,
+            g2: mta_response.k_inv.b_proof.pk,
+            h2: g,
+        };
+        let x_k_inv_statement = ECDDHStatement {
+            g1: g,
+            h1: *ephemeral_other_public_share,
+            g2: mta_response.x_k_inv.b_proof.pk,
+            h2: party_two_private.party_one_public_share,
+        };
+        if mta_response.k_inv_proof.verify(&k_inv_statement).is_err()
+            || mta_response
+                .x_k_inv_proof
+                .verify(&x_k_inv_statement)
+                .is_err()
+        {
+            return Err(InvalidKey);
+        }
+        let q = FE::q();
+        let mta = &party_two_private.mta;
+        let mut k2_inv = ephemeral_local_share.secret_share.invert();
+        let mut x2_k2_inv = party_two_private.x2 * k2_inv;
+        let alphas = mta
+            .alpha(PARTY_ONE as usize, &mta_response.k_inv, &k2_inv)
+            .and_then(|k_inv| {
+                let x_k_inv = mta.alpha(PARTY_ONE as usize, &mta_response.x_k_inv, &x2_k2_inv)?;
+                Ok((k_inv, x_k_inv))
+            });
+        k2_inv.zeroize();
+        x2_k2_inv.zeroize();
+        let (alpha_k_inv, alpha_x_k_inv) = alphas?;
+
+        //compute r = k2 * R1
+        let r = ephemeral_other_public_share * &ephemeral_local_share.secret_share;
+        let rx: FE = ECScalar::from(&r.x_coor().unwrap().mod_floor(&q));
+        let m: FE = ECScalar::from(&message.mod_floor(&q));
+        Ok(PartialSig {
+            s2: m * alpha_k_inv + rx * alpha_x_k_inv,
+        })
+    }
+}
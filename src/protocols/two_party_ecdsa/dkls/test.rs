@@ -0,0 +1,192 @@
+// For integration tests, please add your tests in /tests instead
+
+use crate::protocols::two_party_ecdsa::dkls::{party_one, party_two};
+use crate::Error;
+use curv::arithmetic::traits::Samplable;
+use curv::elliptic::curves::p256::GE;
+use curv::BigInt;
+
+fn keygen() -> (party_one::Party1Private, party_two::Party2Private, GE) {
+    let (party_one_first_message, comm_witness, ec_key_pair_party1) =
+        party_one::KeyGenFirstMsg::create_commitments();
+    let (party_two_first_message, ec_key_pair_party2) =
+        party_two::KeyGenFirstMsg::create(&party_one_first_message).expect("bad base OT message");
+    let (party_one_second_message, party_one_private) =
+        party_one::KeyGenSecondMsg::verify_and_decommit(
+            comm_witness,
+            &ec_key_pair_party1,
+            &party_two_first_message,
+        )
+        .expect("failed to verify and decommit");
+    let party_two_private = party_two::Party2Private::set_private_key(
+        &ec_key_pair_party2,
+        &party_one_first_message,
+        &party_one_second_message,
+    )
+    .expect("failed to verify commitments and DLog proof");
+
+    let pubkey =
+        party_one::compute_pubkey(&party_one_private, &party_two_first_message.public_share);
+    assert_eq!(
+        pubkey,
+        party_two::compute_pubkey(
+            &party_two_private,
+            &party_one_second_message.comm_witness.public_share
+        )
+    );
+    (party_one_private, party_two_private, pubkey)
+}
+
+#[test]
+fn test_two_party_sign() {
+    let (party_one_private, party_two_private, pubkey) = keygen();
+
+    // the base OTs of keygen are reused by every signature
+    for _ in 0..2 {
+        let message = BigInt::sample(256);
+        let (eph_party_two_first_message, eph_comm_witness, eph_ec_key_pair_party2) =
+            party_two::EphKeyGenFirstMsg::create_commitments();
+        let mta_request =
+            party_two::MtaRequest::create(&party_two_private, &eph_ec_key_pair_party2);
+
+        let (eph_party_one_first_message, eph_ec_key_pair_party1) =
+            party_one::EphKeyGenFirstMsg::create();
+        let (mta_response, mta_shares) = party_one::MtaResponse::create(
+            &party_one_private,
+            &eph_ec_key_pair_party1,
+            &mta_request,
+        )
+        .expect("bad MtA request");
+
+        let eph_party_two_second_message = party_two::EphKeyGenSecondMsg::verify_and_decommit(
+            eph_comm_witness,
+            &eph_party_one_first_message,
+        )
+        .expect("party1 DLog proof failed");
+        let partial_sig = party_two::PartialSig::compute(
+            &party_two_private,
+            &eph_ec_key_pair_party2,
+            &eph_party_one_first_message.public_share,
+            &mta_request,
+            &mta_response,
+            &message,
+        )
+        .expect("bad MtA response");
+
+        party_one::EphKeyGenSecondMsg::verify_commitments_and_dlog_proof(
+            &eph_party_two_first_message,
+            &eph_party_two_second_message,
+        )
+        .expect("failed to verify commitments and DLog proof");
+        let signature = party_one::compute_signature_with_recid(
+            &mta_shares,
+            &partial_sig,
+            &eph_ec_key_pair_party1,
+            &eph_party_two_second_message.comm_witness.public_share,
+            &message,
+            &pubkey,
+        )
+        .expect("invalid signature");
+        let signature = party_one::Signature {
+            r: signature.r,
+            s: signature.s,
+        };
+        party_one::verify(&signature, &pubkey, &message).expect("invalid signature");
+    }
+}
+
+#[test]
+fn test_two_party_sign_rejects_bad_partial_sig() {
+    let (party_one_private, party_two_private, pubkey) = keygen();
+    let message = BigInt::sample(256);
+
+    let (_, _, eph_ec_key_pair_party2) = party_two::EphKeyGenFirstMsg::create_commitments();
+    let mta_request = party_two::MtaRequest::create(&party_two_private, &eph_ec_key_pair_party2);
+    let (_, eph_ec_key_pair_party1) = party_one::EphKeyGenFirstMsg::create();
+    let (mta_response, mta_shares) =
+        party_one::MtaResponse::create(&party_one_private, &eph_ec_key_pair_party1, &mta_request)
+            .expect("bad MtA request");
+    let mut partial_sig = party_two::PartialSig::compute(
+        &party_two_private,
+        &eph_ec_key_pair_party2,
+        &eph_ec_key_pair_party1.public_share,
+        &mta_request,
+        &mta_response,
+        &message,
+    )
+    .expect("bad MtA response");
+
+    // a partial signature on another message
+    let other_message = BigInt::sample(256);
+    assert!(party_one::compute_signature(
+        &mta_shares,
+        &partial_sig,
+        &eph_ec_key_pair_party1,
+        &eph_ec_key_pair_party2.public_share,
+        &other_message,
+        &pubkey,
+    )
+    .is_err());
+
+    partial_sig.s2 = partial_sig.s2 + partial_sig.s2;
+    assert!(party_one::compute_signature(
+        &mta_shares,
+        &partial_sig,
+        &eph_ec_key_pair_party1,
+        &eph_ec_key_pair_party2.public_share,
+        &message,
+        &pubkey,
+    )
+    .is_err());
+}
+
+#[test]
+fn test_two_party_sign_rejects_stale_mta_response() {
+    let (party_one_private, party_two_private, _) = keygen();
+    let (_, _, eph_ec_key_pair_party2) = party_two::EphKeyGenFirstMsg::create_commitments();
+    let (_, eph_ec_key_pair_party1) = party_one::EphKeyGenFirstMsg::create();
+    let stale_request = party_two::MtaRequest::create(&party_two_private, &eph_ec_key_pair_party2);
+    let (stale_response, _) =
+        party_one::MtaResponse::create(&party_one_private, &eph_ec_key_pair_party1, &stale_request)
+            .expect("bad MtA request");
+
+    let mta_request = party_two::MtaRequest::create(&party_two_private, &eph_ec_key_pair_party2);
+    assert!(party_two::PartialSig::compute(
+        &party_two_private,
+        &eph_ec_key_pair_party2,
+        &eph_ec_key_pair_party1.public_share,
+        &mta_request,
+        &stale_response,
+        &BigInt::sample(256),
+    )
+    .is_err());
+}
+
+#[test]
+fn test_two_party_sign_rejects_inputs_not_matching_r1() {
+    // party one answers with the inverse of a nonce other than the one of R1
+    let (party_one_private, party_two_private, _) = keygen();
+    let (_, _, eph_ec_key_pair_party2) = party_two::EphKeyGenFirstMsg::create_commitments();
+    let (_, eph_ec_key_pair_party1) = party_one::EphKeyGenFirstMsg::create();
+    let (_, other_eph_ec_key_pair_party1) = party_one::EphKeyGenFirstMsg::create();
+    let mta_request = party_two::MtaRequest::create(&party_two_private, &eph_ec_key_pair_party2);
+    let (mta_response, _) = party_one::MtaResponse::create(
+        &party_one_private,
+        &other_eph_ec_key_pair_party1,
+        &mta_request,
+    )
+    .expect("bad MtA request");
+
+    assert_eq!(
+        party_two::PartialSig::compute(
+            &party_two_private,
+            &eph_ec_key_pair_party2,
+            &eph_ec_key_pair_party1.public_share,
+            &mta_request,
+            &mta_response,
+            &BigInt::sample(256),
+        )
+        .err(),
+        Some(Error::InvalidKey)
+    );
+}
@@ -0,0 +1,131 @@
+/*
+    Multi-party ECDSA
+
+    Copyright 2018 by Kzen Networks
+
+    This file is part of Multi-party ECDSA library
+    (https://github.com/KZen-networks/multi-party-ecdsa)
+
+    Multi-party ECDSA is free software: you can redistribute
+    it and/or modify it under the terms of the GNU General Public
+    License as published by the Free Software Foundation, either
+    version 3 of the License, or (at your option) any later version.
+
+    @license GPL-3.0+ <https://github.com/KZen-networks/multi-party-ecdsa/blob/master/LICENSE>
+*/
+
+//! Two-party ECDSA adaptor signatures.
+//!
+//! Both ephemeral keygens are run with the adaptor point Y = y * G in place of the second
+//! generator, so that besides R1 and R2 the parties learn k1 * Y and k2 * Y with DLEQ
+//! proofs. The signature is computed on R = k1 * k2 * Y, which gives a pre-signature
+//! s' = k^-1 (m + r x) with k = k1 * k2 and r the x coordinate of R. Whoever knows y turns
+//! it into the signature s = s' / y, and anyone holding the pre-signature and the
+//! signature learns y = s' / s.
+//!
+//! ```text
+//! P2 -> P1  party_two::EphKeyGenFirstMsg::create_commitments_with_adaptor(Y)
+//! P1 -> P2  party_one::EphKeyGenFirstMsg::create_with_adaptor(Y)
+//! P2 -> P1  party_two::EphKeyGenSecondMsg::verify_and_decommit_with_adaptor(.., Y)
+//!           party_two::PartialSig::compute_adaptor
+//! P1        party_one::Signature::compute_adaptor -> PreSignature
+//! ```
+
+use curv::arithmetic::traits::*;
+use curv::cryptographic_primitives::proofs::sigma_ec_ddh::*;
+use curv::elliptic::curves::p256::{FE, GE};
+use curv::elliptic::curves::traits::*;
+use curv::BigInt;
+use serde::{Deserialize, Serialize};
+
+use super::party_one::Signature;
+use crate::utilities::encoding;
+use crate::utilities::verification;
+use crate::Error::{self, InvalidKey, InvalidSig};
+
+/// A pre-signature on the nonce R = k * Y, with R' = k * G and the proofs that both have
+/// the same discrete log: k2 for (G, R2, Y, R2_Y) and then k1 for (R2, R', R2_Y, R).
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct PreSignature {
+    pub r: BigInt,
+    pub s: BigInt,
+    pub r_point: GE,
+    pub r_prime: GE,
+    pub party_two_public_share: GE,
+    pub party_two_adaptor_share: GE,
+    pub party_two_proof: ECDDHProof<GE>,
+    pub party_one_proof: ECDDHProof<GE>,
+}
+
+impl PreSignature {
+    /// Checks that adapting the pre-signature with the discrete log of `adaptor_point`
+    /// yields a signature on `message` under `pubkey`.
+    pub fn verify(&self, pubkey: &GE, message: &BigInt, adaptor_point: &GE) -> Result<(), Error> {
+        let q = FE::q();
+        let party_two_statement = ECDDHStatement {
+            g1: GE::generator(),
+            h1: self.party_two_public_share,
+            g2: *adaptor_point,
+            h2: self.party_two_adaptor_share,
+        };
+        let party_one_statement = ECDDHStatement {
+            g1: self.party_two_public_share,
+            h1: self.r_prime,
+            g2: self.party_two_adaptor_share,
+            h2: self.r_point,
+        };
+        self.party_two_proof
+            .verify(&party_two_statement)
+            .map_err(|_| InvalidSig)?;
+        self.party_one_proof
+            .verify(&party_one_statement)
+            .map_err(|_| InvalidSig)?;
+        if self.r != self.r_point.x_coor().unwrap().mod_floor(&q) {
+            return Err(InvalidSig);
+        }
+        if self.r == BigInt::zero() || self.s <= BigInt::zero() || self.s >= q {
+            return Err(InvalidSig);
+        }
+
+        // s'^-1 (m G + r X) must be +-R', the sign of s' is only fixed once adapted
+        let s_fe: FE = ECScalar::from(&self.s);
+        let s_inv = s_fe.invert();
+        let m: FE = ECScalar::from(&message.mod_floor(&q));
+        let r: FE = ECScalar::from(&self.r);
+        let g: GE = ECPoint::generator();
+        match verification::multi_scalar_mul(&[m * s_inv, r * s_inv], &[g, *pubkey]) {
+            Some(point) if point.x_coor() == self.r_prime.x_coor() => Ok(()),
+            _ => Err(InvalidSig),
+        }
+    }
+
+    /// Completes the pre-signature with the discrete log of the adaptor point.
+    pub fn adapt(&self, adaptor_secret: &FE) -> Signature {
+        let s: FE = ECScalar::from(&self.s);
+        let s = s * adaptor_secret.invert();
+        Signature {
+            r: self.r.clone(),
+            s: encoding::normalize_s(&s.to_big_int()).0,
+        }
+    }
+
+    /// Recovers the discrete log of `adaptor_point` from the completed `signature`.
+    pub fn extract(&self, signature: &Signature, adaptor_point: &GE) -> Result<FE, Error> {
+        if signature.r != self.r {
+            return Err(InvalidSig);
+        }
+        let s_pre: FE = ECScalar::from(&self.s);
+        let s: FE = ECScalar::from(&signature.s);
+        let y = s_pre * s.invert();
+        if &(GE::generator() * y) == adaptor_point {
+            return Ok(y);
+        }
+        // s was normalized to the lower half, which flips the sign of y
+        let y = FE::zero().sub(&y.get_element());
+        if &(GE::generator() * y) == adaptor_point {
+            Ok(y)
+        } else {
+            Err(InvalidKey)
+        }
+    }
+}
@@ -0,0 +1,487 @@
+/*
+    Multi-party ECDSA
+
+    Copyright 2018 by Kzen Networks
+
+    This file is part of Multi-party ECDSA library
+    (https://github.com/KZen-networks/multi-party-ecdsa)
+
+    Multi-party ECDSA is free software: you can redistribute
+    it and/or modify it under the terms of the GNU General Public
+    License as published by the Free Software Foundation, either
+    version 3 of the License, or (at your option) any later version.
+
+    @license GPL-3.0+ <https://github.com/KZen-networks/multi-party-ecdsa/blob/master/LICENSE>
+*/
+
+//! Conversion of a Lindell 2017 key into a GG18 key, 2-of-2 or resharing to more parties.
+//!
+//! The multiplicative shares x = x1 * x2 are turned into additive shares alpha + beta
+//! with a single MtA. By default party two answers it on the Paillier encryption of x1 it
+//! already holds, [`LindellMta`]; `party_one_with_mta` and `party_two_with_mta` run it
+//! over any other backend, such as an `OtMta` set up between the two parties. Party one
+//! splits alpha over the slots of every party but party two, party two deals beta in its
+//! own slot, and each slot is Feldman-shared so the result has the layout of a GG18
+//! keygen: one `VerifiableSS` per party whose constant terms add up to the public key.
+//!
+//! ```text
+//! round 1  all -> all   fresh Paillier key + proof, party one adds the MtA MessageA
+//! round 2  P2 -> P1     the MtA MessageB
+//! round 3  P1, P2 -> j  the VSS schemes of the dealer's slots and j's shares
+//! round 4  all -> all   proof of knowledge of the new share x_j
+//! ```
+//!
+//! Party one and party two keep their indexes, new cosigners join as parties `2..n`. The
+//! key shares of the original parties can be dropped once every party has its output.
+
+use curv::cryptographic_primitives::proofs::sigma_dlog::DLogProof;
+use curv::cryptographic_primitives::secret_sharing::feldman_vss::VerifiableSS;
+use curv::elliptic::curves::p256::{FE, GE};
+use curv::elliptic::curves::traits::*;
+use paillier::EncryptionKey;
+use serde::{Deserialize, Serialize};
+use zk_paillier::zkproofs::NICorrectKeyProof;
+
+use super::party_one::Party1Private;
+use super::party_two::{PaillierPublic, Party2Private};
+use super::signer::{Party1KeyShare, Party2KeyShare, PARTY_ONE, PARTY_TWO};
+pub use crate::protocols::import::SlotDealing;
+use crate::protocols::multi_party_ecdsa::gg_2018::party_i::{Keys, SharedKeys};
+use crate::protocols::multi_party_ecdsa::gg_2018::signer::LocalKey;
+use crate::protocols::threshold_signer::{self, broadcast, p2p, Msg, Session};
+use crate::utilities::mta::{MessageB, MtaBackend};
+use crate::Error::{self, InvalidKey, InvalidMessage, InvalidSS};
+
+#[derive(Debug, Serialize, Deserialize)]
+pub enum ConversionMessage<A = LindellMtaRequest, B = MessageB> {
+    PaillierKey(EncryptionKey, NICorrectKeyProof, Option<A>),
+    MtA(B),
+    Dealing(Vec<SlotDealing>),
+    DLogProof(DLogProof<GE>),
+}
+
+/// The Paillier MtA of the conversion. Party two answers on the encryption of x1 from
+/// keygen, so party one's request is empty.
+pub enum LindellMta {
+    PartyOne(Party1Private),
+    PartyTwo(Party2Private, PaillierPublic),
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct LindellMtaRequest {}
+
+impl MtaBackend for LindellMta {
+    type MessageA = LindellMtaRequest;
+    type MessageB = MessageB;
+
+    fn message_a(&self, _a: &FE) -> LindellMtaRequest {
+        LindellMtaRequest {}
+    }
+
+    fn message_b(
+        &self,
+        _alice: usize,
+        _b: &FE,
+        _m_a: &LindellMtaRequest,
+    ) -> Result<(MessageB, FE), Error> {
+        match self {
+            LindellMta::PartyTwo(private, paillier_public) => Ok(private
+                .to_mta_message_b(&paillier_public.ek, &paillier_public.encrypted_secret_share)),
+            LindellMta::PartyOne(_) => Err(InvalidKey),
+        }
+    }
+
+    fn alpha(&self, _bob: usize, m_b: &MessageB, _a: &FE) -> Result<FE, Error> {
+        match self {
+            LindellMta::PartyOne(private) => Ok(private.to_mta_message_b(m_b.clone())?.0),
+            LindellMta::PartyTwo(..) => Err(InvalidKey),
+        }
+    }
+
+    fn b_proof(m_b: &MessageB) -> &DLogProof<GE> {
+        &m_b.b_proof
+    }
+}
+
+enum Role {
+    PartyOne(Party1KeyShare),
+    PartyTwo(Party2KeyShare),
+    Cosigner,
+}
+
+pub struct Conversion<M: MtaBackend = LindellMta> {
+    party: u16,
+    threshold: u16,
+    share_count: u16,
+    others: Vec<u16>,
+    round: u16,
+    role: Role,
+    mta: Option<M>,
+    public_key: GE,
+    party_keys: Keys,
+    beta: Option<FE>,
+    paillier_key_vec: Vec<EncryptionKey>,
+    own_dealing: Vec<SlotDealing>,
+    vss_scheme_vec: Vec<VerifiableSS<GE>>,
+    shared_keys: Option<SharedKeys>,
+    output: Option<LocalKey>,
+}
+
+impl Conversion {
+    pub fn party_one(key: Party1KeyShare, threshold: u16, share_count: u16) -> Result<Self, Error> {
+        let mta = LindellMta::PartyOne(key.private.clone());
+        Self::party_one_with_mta(key, threshold, share_count, mta)
+    }
+
+    pub fn party_two(key: Party2KeyShare, threshold: u16, share_count: u16) -> Result<Self, Error> {
+        let mta = LindellMta::PartyTwo(key.private.clone(), key.paillier_public.clone());
+        Self::party_two_with_mta(key, threshold, share_count, mta)
+    }
+}
+
+impl<M: MtaBackend> Conversion<M> {
+    /// Like `party_one`, with the MtA run over `mta`, which party two must match.
+    pub fn party_one_with_mta(
+        key: Party1KeyShare,
+        threshold: u16,
+        share_count: u16,
+        mta: M,
+    ) -> Result<Self, Error> {
+        let public_key = key.public_key;
+        let role = Role::PartyOne(key);
+        Self::new(
+            PARTY_ONE,
+            role,
+            Some(mta),
+            public_key,
+            threshold,
+            share_count,
+        )
+    }
+
+    pub fn party_two_with_mta(
+        key: Party2KeyShare,
+        threshold: u16,
+        share_count: u16,
+        mta: M,
+    ) -> Result<Self, Error> {
+        let public_key = key.public_key;
+        let role = Role::PartyTwo(key);
+        Self::new(
+            PARTY_TWO,
+            role,
+            Some(mta),
+            public_key,
+            threshold,
+            share_count,
+        )
+    }
+
+    /// A party joining the key, `party` must be at least 2.
+    pub fn cosigner(
+        party: u16,
+        public_key: GE,
+        threshold: u16,
+        share_count: u16,
+    ) -> Result<Self, Error> {
+        if party <= PARTY_TWO {
+            return Err(InvalidKey);
+        }
+        Self::new(
+            party,
+            Role::Cosigner,
+            None,
+            public_key,
+            threshold,
+            share_count,
+        )
+    }
+
+    fn new(
+        party: u16,
+        role: Role,
+        mta: Option<M>,
+        public_key: GE,
+        threshold: u16,
+        share_count: u16,
+    ) -> Result<Self, Error> {
+        if share_count < 2 || party >= share_count || threshold >= share_count {
+            return Err(InvalidKey);
+        }
+        Ok(Conversion {
+            party,
+            threshold,
+            share_count,
+            others: (0..share_count).filter(|j| *j != party).collect(),
+            round: 0,
+            role,
+            mta,
+            public_key,
+            party_keys: Keys::create(party as usize),
+            beta: None,
+            paillier_key_vec: Vec::new(),
+            own_dealing: Vec::new(),
+            vss_scheme_vec: Vec::new(),
+            shared_keys: None,
+            output: None,
+        })
+    }
+
+    fn dealers(&self) -> Vec<u16> {
+        [PARTY_ONE, PARTY_TWO]
+            .iter()
+            .cloned()
+            .filter(|j| *j != self.party)
+            .collect()
+    }
+
+    fn slot_owner(slot: u16) -> u16 {
+        if slot == PARTY_TWO {
+            PARTY_TWO
+        } else {
+            PARTY_ONE
+        }
+    }
+
+    // the dealings of every slot the party owns, one list per receiver
+    fn deal(&self, secrets: Vec<(u16, FE)>) -> Vec<Vec<SlotDealing>> {
+        let mut dealings = vec![Vec::new(); self.share_count as usize];
+        for (slot, secret) in secrets {
+            let (vss_scheme, shares) =
+                VerifiableSS::share(self.threshold as usize, self.share_count as usize, &secret);
+            for (j, share) in shares.into_iter().enumerate() {
+                dealings[j].push(SlotDealing {
+                    slot,
+                    vss_scheme: vss_scheme.clone(),
+                    share,
+                });
+            }
+        }
+        dealings
+    }
+
+    // party two's answer to the MtA, which only party one receives
+    fn mta_answer(
+        incoming: Vec<Msg<ConversionMessage<M::MessageA, M::MessageB>>>,
+    ) -> Result<M::MessageB, Error> {
+        let mut answer =
+            threshold_signer::collect(incoming, PARTY_ONE, 2, &[PARTY_TWO], |body| match body {
+                ConversionMessage::MtA(message_b) => Some(message_b),
+                _ => None,
+            })?;
+        Ok(answer.remove(0))
+    }
+
+    // party one splits alpha at random over its slots, party two deals beta
+    fn slot_secrets(&self, alpha: Option<FE>) -> Vec<(u16, FE)> {
+        match self.role {
+            Role::PartyOne(_) => {
+                let slots: Vec<u16> = (0..self.share_count).filter(|j| *j != PARTY_TWO).collect();
+                let mut rest = alpha.unwrap();
+                let mut secrets = Vec::with_capacity(slots.len());
+                for slot in &slots[1..] {
+                    let secret: FE = ECScalar::new_random();
+                    rest = rest.sub(&secret.get_element());
+                    secrets.push((*slot, secret));
+                }
+                secrets.insert(0, (slots[0], rest));
+                secrets
+            }
+            Role::PartyTwo(_) => vec![(PARTY_TWO, self.beta.unwrap())],
+            Role::Cosigner => Vec::new(),
+        }
+    }
+}
+
+impl<M: MtaBackend> Session for Conversion<M> {
+    type Message = ConversionMessage<M::MessageA, M::MessageB>;
+    type Output = LocalKey;
+
+    fn party(&self) -> u16 {
+        self.party
+    }
+
+    fn round(&self) -> u16 {
+        self.round
+    }
+
+    fn expected_senders(&self) -> Vec<u16> {
+        match (self.round, &self.role) {
+            (1, _) | (4, _) => self.others.clone(),
+            (2, Role::PartyOne(_)) => vec![PARTY_TWO],
+            (3, _) => self.dealers(),
+            _ => Vec::new(),
+        }
+    }
+
+    fn proceed(
+        &mut self,
+        incoming: Vec<Msg<Self::Message>>,
+    ) -> Result<Vec<Msg<Self::Message>>, Error> {
+        let party = self.party;
+        let index = party as usize;
+        let outgoing = match self.round {
+            0 => {
+                let correct_key_proof = NICorrectKeyProof::proof(&self.party_keys.dk, None);
+                let message_a = match (&self.role, &self.mta) {
+                    (Role::PartyOne(key), Some(mta)) => Some(mta.message_a(&key.private.x1())),
+                    _ => None,
+                };
+                let ek = self.party_keys.ek.clone();
+                let body = ConversionMessage::PaillierKey(ek, correct_key_proof, message_a);
+                vec![broadcast(party, 1, body)]
+            }
+            1 => {
+                let mut keys =
+                    threshold_signer::collect(incoming, self.party(), 1, &self.others, |body| {
+                        match body {
+                            ConversionMessage::PaillierKey(ek, proof, message_a) => {
+                                Some((ek, proof, message_a))
+                            }
+                            _ => None,
+                        }
+                    })?;
+                let mut outgoing = Vec::new();
+                for (j, (ek, proof, message_a)) in self.others.iter().zip(keys.iter_mut()) {
+                    proof
+                        .verify(ek, zk_paillier::zkproofs::SALT_STRING)
+                        .map_err(|_| InvalidKey)?;
+                    // only party one starts the MtA, and only party two answers it
+                    if message_a.is_some() != (*j == PARTY_ONE) {
+                        return Err(InvalidMessage);
+                    }
+                    if let (Role::PartyTwo(key), Some(mta), Some(message_a)) =
+                        (&self.role, &self.mta, message_a.take())
+                    {
+                        let x2 = key.private.x2();
+                        let (message_b, beta) =
+                            mta.message_b(PARTY_ONE as usize, &x2, &message_a)?;
+                        self.beta = Some(beta);
+                        let body = ConversionMessage::MtA(message_b);
+                        outgoing.push(p2p(party, PARTY_ONE, 2, body));
+                    }
+                }
+                let mut paillier_key_vec: Vec<EncryptionKey> =
+                    keys.into_iter().map(|(ek, _, _)| ek).collect();
+                paillier_key_vec.insert(index, self.party_keys.ek.clone());
+                self.paillier_key_vec = paillier_key_vec;
+                outgoing
+            }
+            2 => {
+                let alpha = match (&self.role, &self.mta) {
+                    (Role::PartyOne(key), Some(mta)) => {
+                        let message_b = Self::mta_answer(incoming)?;
+                        Some(mta.alpha(PARTY_TWO as usize, &message_b, &key.private.x1())?)
+                    }
+                    _ => None,
+                };
+                let mut dealings = self.deal(self.slot_secrets(alpha));
+                self.own_dealing = std::mem::take(&mut dealings[index]);
+                match self.role {
+                    Role::Cosigner => Vec::new(),
+                    _ => self
+                        .others
+                        .iter()
+                        .map(|j| {
+                            let dealing = dealings[*j as usize].clone();
+                            p2p(party, *j, 3, ConversionMessage::Dealing(dealing))
+                        })
+                        .collect(),
+                }
+            }
+            3 => {
+                let dealers = self.dealers();
+                let received = threshold_signer::collect(
+                    incoming,
+                    self.party(),
+                    3,
+                    &dealers,
+                    |body| match body {
+                        ConversionMessage::Dealing(dealing) => Some(dealing),
+                        _ => None,
+                    },
+                )?;
+                let mut slots: Vec<Option<SlotDealing>> = vec![None; self.share_count as usize];
+                let own = (party, std::mem::take(&mut self.own_dealing));
+                for (dealer, dealing) in dealers.into_iter().zip(received).chain(Some(own)) {
+                    for slot_dealing in dealing {
+                        let slot = slot_dealing.slot;
+                        if slot >= self.share_count
+                            || Self::slot_owner(slot) != dealer
+                            || slots[slot as usize].is_some()
+                        {
+                            return Err(InvalidMessage);
+                        }
+                        slots[slot as usize] = Some(slot_dealing);
+                    }
+                }
+                let slots = slots
+                    .into_iter()
+                    .collect::<Option<Vec<SlotDealing>>>()
+                    .ok_or(InvalidMessage)?;
+
+                let valid = slots.iter().all(|s| {
+                    s.vss_scheme.parameters.threshold == self.threshold as usize
+                        && s.vss_scheme.parameters.share_count == self.share_count as usize
+                        && s.vss_scheme.validate_share(&s.share, index + 1).is_ok()
+                });
+                let (head, tail) = slots.split_at(1);
+                let y = tail
+                    .iter()
+                    .fold(head[0].vss_scheme.commitments[0], |acc, s| {
+                        acc + s.vss_scheme.commitments[0]
+                    });
+                if !valid || y != self.public_key {
+                    return Err(InvalidSS);
+                }
+
+                let x_i = slots.iter().fold(FE::zero(), |acc, s| acc + s.share);
+                let dlog_proof = DLogProof::prove(&x_i);
+                self.vss_scheme_vec = slots.into_iter().map(|s| s.vss_scheme).collect();
+                self.shared_keys = Some(SharedKeys { y, x_i });
+                vec![broadcast(
+                    party,
+                    4,
+                    ConversionMessage::DLogProof(dlog_proof),
+                )]
+            }
+            4 => {
+                let dlog_proofs =
+                    threshold_signer::collect(incoming, self.party(), 4, &self.others, |body| {
+                        match body {
+                            ConversionMessage::DLogProof(proof) => Some(proof),
+                            _ => None,
+                        }
+                    })?;
+                let xi_com_vec = Keys::get_commitments_to_xi(&self.vss_scheme_vec);
+                for (j, proof) in self.others.iter().zip(dlog_proofs.iter()) {
+                    if DLogProof::verify(proof).is_err() || proof.pk != xi_com_vec[*j as usize] {
+                        return Err(InvalidKey);
+                    }
+                }
+                self.output = Some(LocalKey {
+                    party_keys: self.party_keys.clone(),
+                    shared_keys: self.shared_keys.take().unwrap(),
+                    party_index: party,
+                    threshold: self.threshold,
+                    share_count: self.share_count,
+                    vss_scheme_vec: self.vss_scheme_vec.clone(),
+                    paillier_key_vec: self.paillier_key_vec.clone(),
+                    y: self.public_key,
+                });
+                Vec::new()
+            }
+            _ => return Err(InvalidMessage),
+        };
+        self.round += 1;
+        Ok(outgoing)
+    }
+
+    fn is_finished(&self) -> bool {
+        self.round > 4
+    }
+
+    fn pick_output(&mut self) -> Option<LocalKey> {
+        self.output.take()
+    }
+}
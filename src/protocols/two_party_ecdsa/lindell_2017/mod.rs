@@ -16,8 +16,13 @@
 
 const SECURITY_BITS: usize = 256;
 
+pub mod adaptor;
+pub mod conversion;
 pub mod party_one;
 pub mod party_two;
+pub mod presign;
+pub mod rotation;
+pub mod signer;
 
 #[cfg(test)]
 mod test;
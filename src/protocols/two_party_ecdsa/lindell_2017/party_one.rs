@@ -32,15 +32,19 @@ use paillier::Paillier;
 use paillier::{Decrypt, EncryptWithChosenRandomness, KeyGeneration};
 use paillier::{DecryptionKey, EncryptionKey, Randomness, RawCiphertext, RawPlaintext};
 use serde::{Deserialize, Serialize};
-use subtle::ConstantTimeEq;
 use zeroize::Zeroize;
 use zk_paillier::zkproofs::NICorrectKeyProof;
 
+use super::adaptor::PreSignature;
 use super::party_two::EphKeyGenFirstMsg as Party2EphKeyGenFirstMessage;
 use super::party_two::EphKeyGenSecondMsg as Party2EphKeyGenSecondMessage;
 use super::SECURITY_BITS;
 
+use crate::utilities::digest::MessageDigest;
+use crate::utilities::encoding;
 use crate::utilities::mta::MessageB;
+use crate::utilities::recovery;
+use crate::utilities::verification::{self, BatchItem};
 use crate::Error;
 
 use crate::utilities::zk_pdl_with_slack::PDLwSlackProof;
@@ -311,6 +315,11 @@ impl Party1Private {
     pub fn to_mta_message_b(&self, message_b: MessageB) -> Result<(FE, BigInt), Error> {
         message_b.verify_proofs_get_alpha(&self.paillier_priv, &self.x1)
     }
+
+    // the input of party one to an MtA of the conversion other than the Paillier one
+    pub(crate) fn x1(&self) -> FE {
+        self.x1
+    }
 }
 
 impl PaillierKeyPair {
@@ -401,10 +410,16 @@ impl PaillierKeyPair {
 
 impl EphKeyGenFirstMsg {
     pub fn create() -> (EphKeyGenFirstMsg, EphEcKeyPair) {
+        Self::create_with_adaptor(&GE::base_point2())
+    }
+
+    /// The ephemeral keygen of an adaptor signature: `c` is k1 * Y for the adaptor point Y
+    /// instead of k1 * H.
+    pub fn create_with_adaptor(adaptor_point: &GE) -> (EphKeyGenFirstMsg, EphEcKeyPair) {
         let base: GE = ECPoint::generator();
         let mut secret_share: FE = ECScalar::new_random();
         let public_share = &base * &secret_share;
-        let h: GE = GE::base_point2();
+        let h: GE = *adaptor_point;
 
         let c = &h * &secret_share;
         let mut x = secret_share;
@@ -437,6 +452,18 @@ impl EphKeyGenSecondMsg {
     pub fn verify_commitments_and_dlog_proof(
         party_two_first_message: &Party2EphKeyGenFirstMessage,
         party_two_second_message: &Party2EphKeyGenSecondMessage,
+    ) -> Result<EphKeyGenSecondMsg, ProofError> {
+        Self::verify_commitments_and_dlog_proof_with_adaptor(
+            party_two_first_message,
+            party_two_second_message,
+            &GE::base_point2(),
+        )
+    }
+
+    pub fn verify_commitments_and_dlog_proof_with_adaptor(
+        party_two_first_message: &Party2EphKeyGenFirstMessage,
+        party_two_second_message: &Party2EphKeyGenSecondMessage,
+        adaptor_point: &GE,
     ) -> Result<EphKeyGenSecondMsg, ProofError> {
         let party_two_pk_commitment = &party_two_first_message.pk_commitment;
         let party_two_zk_pok_commitment = &party_two_first_message.zk_pok_commitment;
@@ -476,7 +503,7 @@ impl EphKeyGenSecondMsg {
         let delta = ECDDHStatement {
             g1: GE::generator(),
             h1: *party_two_public_share,
-            g2: GE::base_point2(),
+            g2: *adaptor_point,
             h2: party_two_second_message.comm_witness.c,
         };
         party_two_d_log_proof.verify(&delta)?;
@@ -515,6 +542,52 @@ impl Signature {
         Signature { s, r: rx }
     }
 
+    /// The adaptor variant of `compute`, for ephemeral keys generated with an adaptor point
+    /// Y. The result is a pre-signature on the nonce R = k1 * k2 * Y, s is neither final
+    /// nor normalized until the pre-signature is adapted with the discrete log of Y.
+    pub fn compute_adaptor(
+        party_one_private: &Party1Private,
+        partial_sig_c3: &BigInt,
+        ephemeral_local_share: &EphEcKeyPair,
+        party_two_second_message: &Party2EphKeyGenSecondMessage,
+    ) -> PreSignature {
+        let witness = &party_two_second_message.comm_witness;
+        let k1 = &ephemeral_local_share.secret_share;
+        // R' = k1 * k2 * G and R = k1 * k2 * Y
+        let r_prime = &witness.public_share * k1;
+        let r_point = &witness.c * k1;
+        let rx = r_point.x_coor().unwrap().mod_floor(&FE::q());
+
+        let mut k1_inv = k1.invert();
+        let s_tag = Paillier::decrypt(
+            &party_one_private.paillier_priv,
+            &RawCiphertext::from(partial_sig_c3),
+        )
+        .0;
+        let mut s_tag_fe: FE = ECScalar::from(&s_tag);
+        let s = (s_tag_fe * k1_inv).to_big_int();
+        k1_inv.zeroize();
+        s_tag_fe.zeroize();
+
+        let delta = ECDDHStatement {
+            g1: witness.public_share,
+            h1: r_prime,
+            g2: witness.c,
+            h2: r_point,
+        };
+        let party_one_proof = ECDDHProof::prove(&ECDDHWitness { x: *k1 }, &delta);
+        PreSignature {
+            r: rx,
+            s,
+            r_point,
+            r_prime,
+            party_two_public_share: witness.public_share,
+            party_two_adaptor_share: witness.c,
+            party_two_proof: witness.d_log_proof.clone(),
+            party_one_proof,
+        }
+    }
+
     pub fn compute_with_recid(
         party_one_private: &Party1Private,
         partial_sig_c3: &BigInt,
@@ -526,7 +599,6 @@ impl Signature {
             .scalar_mul(&ephemeral_local_share.secret_share.get_element());
 
         let rx = r.x_coor().unwrap().mod_floor(&FE::q());
-        let ry = r.y_coor().unwrap().mod_floor(&FE::q());
         let mut k1_inv = ephemeral_local_share.secret_share.invert();
 
         let s_tag = Paillier::decrypt(
@@ -546,39 +618,140 @@ impl Signature {
          itself. Recovery id is used to enable extracting the public key uniquely.
          1. id = R.y & 1
          2. if (s > curve.q / 2) id = id ^ 1
+         3. if (R.x >= curve.q) id = id | 2
         */
-        let is_ry_odd = ry.test_bit(0);
-        let mut recid = if is_ry_odd { 1 } else { 0 };
-        if s_tag_tag_bn.clone() > FE::q() - s_tag_tag_bn.clone() {
-            recid = recid ^ 1;
-        }
+        let is_s_high = s_tag_tag_bn.clone() > FE::q() - s_tag_tag_bn.clone();
+        let recid = recovery::compute_recid(&r, is_s_high);
 
         SignatureRecid { s, r: rx, recid }
     }
 }
 
-pub fn verify(signature: &Signature, pubkey: &GE, message: &BigInt) -> Result<(), Error> {
-    let s_fe: FE = ECScalar::from(&signature.s);
-    let rx_fe: FE = ECScalar::from(&signature.r);
+impl SignatureRecid {
+    pub fn to_compact(&self) -> Result<[u8; recovery::COMPACT_SIGNATURE_SIZE], Error> {
+        recovery::to_compact(&self.r, &self.s, self.recid)
+    }
+
+    pub fn from_compact(bytes: &[u8]) -> Result<Self, Error> {
+        let (r, s, recid) = recovery::from_compact(bytes)?;
+        Ok(SignatureRecid { s, r, recid })
+    }
+
+    pub fn to_der(&self) -> Vec<u8> {
+        encoding::to_der(&self.r, &self.s)
+    }
+
+    pub fn to_fixed(&self) -> Result<[u8; encoding::FIXED_SIGNATURE_SIZE], Error> {
+        encoding::to_fixed(&self.r, &self.s)
+    }
+
+    pub fn to_p256(&self) -> Result<p256::ecdsa::Signature, Error> {
+        encoding::to_p256(&self.r, &self.s)
+    }
+
+    /// The encodings below carry no recovery id, it is recomputed from the message and the
+    /// public key the signature verifies under.
+    pub fn from_der(bytes: &[u8], message: &BigInt, pubkey: &GE) -> Result<Self, Error> {
+        let (r, s) = encoding::from_der(bytes)?;
+        let recid = encoding::find_recid(&r, &s, message, pubkey)?;
+        Ok(SignatureRecid { s, r, recid })
+    }
+
+    pub fn from_fixed(bytes: &[u8], message: &BigInt, pubkey: &GE) -> Result<Self, Error> {
+        let (r, s) = encoding::from_fixed(bytes)?;
+        let recid = encoding::find_recid(&r, &s, message, pubkey)?;
+        Ok(SignatureRecid { s, r, recid })
+    }
+
+    pub fn from_p256(
+        signature: &p256::ecdsa::Signature,
+        message: &BigInt,
+        pubkey: &GE,
+    ) -> Result<Self, Error> {
+        let (r, s) = encoding::from_p256(signature)?;
+        let recid = encoding::find_recid(&r, &s, message, pubkey)?;
+        Ok(SignatureRecid { s, r, recid })
+    }
+}
+
+impl Signature {
+    pub fn to_der(&self) -> Vec<u8> {
+        encoding::to_der(&self.r, &self.s)
+    }
+
+    pub fn from_der(bytes: &[u8]) -> Result<Self, Error> {
+        let (r, s) = encoding::from_der(bytes)?;
+        Ok(Signature { s, r })
+    }
 
-    let s_inv_fe = s_fe.invert();
-    let e_fe: FE = ECScalar::from(&message.mod_floor(&FE::q()));
-    let u1 = GE::generator() * e_fe * s_inv_fe;
-    let u2 = *pubkey * rx_fe * s_inv_fe;
+    pub fn to_fixed(&self) -> Result<[u8; encoding::FIXED_SIGNATURE_SIZE], Error> {
+        encoding::to_fixed(&self.r, &self.s)
+    }
+
+    pub fn from_fixed(bytes: &[u8]) -> Result<Self, Error> {
+        let (r, s) = encoding::from_fixed(bytes)?;
+        Ok(Signature { s, r })
+    }
+
+    pub fn to_p256(&self) -> Result<p256::ecdsa::Signature, Error> {
+        encoding::to_p256(&self.r, &self.s)
+    }
 
-    // second condition is against malleability
-    let rx_bytes = &BigInt::to_bytes(&signature.r)[..];
-    let u1_plus_u2_bytes = &BigInt::to_bytes(&(u1 + u2).x_coor().unwrap())[..];
+    pub fn from_p256(signature: &p256::ecdsa::Signature) -> Result<Self, Error> {
+        let (r, s) = encoding::from_p256(signature)?;
+        Ok(Signature { s, r })
+    }
+}
+
+pub fn recover_public_key(signature: &SignatureRecid, message: &BigInt) -> Result<GE, Error> {
+    recovery::recover_public_key(&signature.r, &signature.s, signature.recid, message)
+}
 
-    if rx_bytes.ct_eq(&u1_plus_u2_bytes).unwrap_u8() == 1
-        && signature.s < FE::q() - signature.s.clone()
-    {
+pub fn verify(signature: &Signature, pubkey: &GE, message: &BigInt) -> Result<(), Error> {
+    verification::verify(&signature.r, &signature.s, message, pubkey)?;
+    // against malleability
+    if signature.s < FE::q() - &signature.s {
         Ok(())
     } else {
         Err(Error::InvalidSig)
     }
 }
 
+/// Verifies `signatures[i]` on `messages[i]` under `pubkeys[i]` for all i at once.
+pub fn batch_verify(
+    signatures: &[SignatureRecid],
+    pubkeys: &[GE],
+    messages: &[BigInt],
+) -> Result<(), Error> {
+    if signatures.len() != pubkeys.len() || signatures.len() != messages.len() {
+        return Err(Error::InvalidSig);
+    }
+    let q = FE::q();
+    let mut items = Vec::with_capacity(signatures.len());
+    for ((signature, pubkey), message) in signatures.iter().zip(pubkeys).zip(messages) {
+        if signature.s >= &q - &signature.s {
+            return Err(Error::InvalidSig);
+        }
+        items.push(BatchItem {
+            r: signature.r.clone(),
+            s: signature.s.clone(),
+            recid: signature.recid,
+            message: message.clone(),
+            public_key: *pubkey,
+        });
+    }
+    verification::batch_verify(&items)
+}
+
+pub fn verify_with_digest(
+    signature: &Signature,
+    pubkey: &GE,
+    message: &[u8],
+    digest: MessageDigest,
+) -> Result<(), Error> {
+    verify(signature, pubkey, &digest.to_message(message))
+}
+
 pub fn generate_h1_h2_n_tilde() -> (BigInt, BigInt, BigInt, BigInt) {
     //note, should be safe primes:
     // let (ek_tilde, dk_tilde) = Paillier::keypair_safe_primes().keys();;
@@ -27,9 +27,7 @@ use curv::cryptographic_primitives::proofs::ProofError;
 use curv::elliptic::curves::p256::{FE, GE};
 use curv::elliptic::curves::traits::*;
 use curv::BigInt;
-use paillier::Paillier;
-use paillier::{Add, Encrypt, Mul};
-use paillier::{EncryptionKey, RawCiphertext, RawPlaintext};
+use paillier::EncryptionKey;
 use serde::{Deserialize, Serialize};
 use zk_paillier::zkproofs::{CorrectKeyProofError, NICorrectKeyProof};
 
@@ -37,7 +35,9 @@ use super::party_one::EphKeyGenFirstMsg as Party1EphKeyGenFirstMsg;
 use super::party_one::KeyGenFirstMsg as Party1KeyGenFirstMessage;
 use super::party_one::KeyGenSecondMsg as Party1KeyGenSecondMessage;
 use super::SECURITY_BITS;
+use crate::utilities::digest::MessageDigest;
 use crate::utilities::mta::{MessageA, MessageB};
+use crate::utilities::paillier_context::PaillierContext;
 
 use crate::utilities::zk_pdl_with_slack::PDLwSlackProof;
 use crate::utilities::zk_pdl_with_slack::PDLwSlackStatement;
@@ -62,7 +62,7 @@ pub struct KeyGenFirstMsg {
 #[derive(Debug, Serialize, Deserialize)]
 pub struct KeyGenSecondMsg {}
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct PaillierPublic {
     pub ek: EncryptionKey,
     pub encrypted_secret_share: BigInt,
@@ -73,7 +73,17 @@ pub struct PartialSig {
     pub c3: BigInt,
 }
 
-#[derive(Serialize, Deserialize)]
+/// Everything of a `PartialSig` that does not depend on the message: the nonce point R and
+/// the encryption of rho * q + k2^-1 * r * x1 * x2. It cannot be cloned, and k2^-1 is
+/// zeroized when it is dropped.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct PartialSigPrecomputation {
+    pub r: GE,
+    pub c: BigInt,
+    k2_inv: FE,
+}
+
+#[derive(Serialize, Deserialize, Clone)]
 pub struct Party2Private {
     x2: FE,
 }
@@ -253,6 +263,11 @@ impl Party2Private {
         let (a, b, _, _) = MessageB::b(&self.x2, &ek, message_a);
         (a, b)
     }
+
+    // the input of party two to an MtA of the conversion other than the Paillier one
+    pub(crate) fn x2(&self) -> FE {
+        self.x2
+    }
 }
 
 impl PaillierPublic {
@@ -297,13 +312,21 @@ impl PaillierPublic {
 
 impl EphKeyGenFirstMsg {
     pub fn create_commitments() -> (EphKeyGenFirstMsg, EphCommWitness, EphEcKeyPair) {
+        Self::create_commitments_with_adaptor(&GE::base_point2())
+    }
+
+    /// The ephemeral keygen of an adaptor signature: `c` is k2 * Y for the adaptor point Y
+    /// instead of k2 * H.
+    pub fn create_commitments_with_adaptor(
+        adaptor_point: &GE,
+    ) -> (EphKeyGenFirstMsg, EphCommWitness, EphEcKeyPair) {
         let base: GE = ECPoint::generator();
 
         let mut secret_share: FE = ECScalar::new_random();
 
         let public_share = base.scalar_mul(&secret_share.get_element());
 
-        let h: GE = GE::base_point2();
+        let h: GE = *adaptor_point;
 
         let c = &h * &secret_share;
         let mut x = secret_share;
@@ -356,11 +379,23 @@ impl EphKeyGenSecondMsg {
     pub fn verify_and_decommit(
         comm_witness: EphCommWitness,
         party_one_first_message: &Party1EphKeyGenFirstMsg,
+    ) -> Result<EphKeyGenSecondMsg, ProofError> {
+        Self::verify_and_decommit_with_adaptor(
+            comm_witness,
+            party_one_first_message,
+            &GE::base_point2(),
+        )
+    }
+
+    pub fn verify_and_decommit_with_adaptor(
+        comm_witness: EphCommWitness,
+        party_one_first_message: &Party1EphKeyGenFirstMsg,
+        adaptor_point: &GE,
     ) -> Result<EphKeyGenSecondMsg, ProofError> {
         let delta = ECDDHStatement {
             g1: GE::generator(),
             h1: party_one_first_message.public_share,
-            g2: GE::base_point2(),
+            g2: *adaptor_point,
             h2: party_one_first_message.c,
         };
         party_one_first_message.d_log_proof.verify(&delta)?;
@@ -377,6 +412,85 @@ impl PartialSig {
         ephemeral_other_public_share: &GE,
         message: &BigInt,
     ) -> PartialSig {
+        Self::compute_with_context(
+            &PaillierContext::new(ek),
+            encrypted_secret_share,
+            local_share,
+            ephemeral_local_share,
+            ephemeral_other_public_share,
+            message,
+        )
+    }
+
+    /// Like `compute`, but hashes the raw `message` with `digest` first.
+    pub fn compute_with_digest(
+        ek: &EncryptionKey,
+        encrypted_secret_share: &BigInt,
+        local_share: &Party2Private,
+        ephemeral_local_share: &EphEcKeyPair,
+        ephemeral_other_public_share: &GE,
+        message: &[u8],
+        digest: MessageDigest,
+    ) -> PartialSig {
+        Self::compute(
+            ek,
+            encrypted_secret_share,
+            local_share,
+            ephemeral_local_share,
+            ephemeral_other_public_share,
+            &digest.to_message(message),
+        )
+    }
+
+    /// Like `compute`, with `party_one` a context for party one's encryption key whose pool
+    /// can be filled ahead of signing.
+    pub fn compute_with_context(
+        party_one: &PaillierContext,
+        encrypted_secret_share: &BigInt,
+        local_share: &Party2Private,
+        ephemeral_local_share: &EphEcKeyPair,
+        ephemeral_other_public_share: &GE,
+        message: &BigInt,
+    ) -> PartialSig {
+        Self::precompute(
+            party_one,
+            encrypted_secret_share,
+            local_share,
+            ephemeral_local_share,
+            ephemeral_other_public_share,
+        )
+        .finalize(party_one, message)
+    }
+
+    /// The adaptor variant of `compute`, for ephemeral keys generated with an adaptor point
+    /// Y: the nonce is taken from k2 * (k1 * Y) rather than k2 * R1.
+    pub fn compute_adaptor(
+        ek: &EncryptionKey,
+        encrypted_secret_share: &BigInt,
+        local_share: &Party2Private,
+        ephemeral_local_share: &EphEcKeyPair,
+        party_one_first_message: &Party1EphKeyGenFirstMsg,
+        message: &BigInt,
+    ) -> PartialSig {
+        Self::compute(
+            ek,
+            encrypted_secret_share,
+            local_share,
+            ephemeral_local_share,
+            &party_one_first_message.c,
+            message,
+        )
+    }
+
+    /// The message independent part of `compute_with_context`, which can be done before
+    /// the message is known. `finalize` consumes the precomputation, so a nonce is used once.
+    pub fn precompute(
+        party_one: &PaillierContext,
+        encrypted_secret_share: &BigInt,
+        local_share: &Party2Private,
+        ephemeral_local_share: &EphEcKeyPair,
+        ephemeral_other_public_share: &GE,
+    ) -> PartialSigPrecomputation {
         let q = FE::q();
         //compute r = k2* R1
         let r = ephemeral_other_public_share
@@ -384,25 +498,39 @@ impl PartialSig {
 
         let rx = r.x_coor().unwrap().mod_floor(&q);
         let rho = BigInt::sample_below(&q.pow(2));
-        let mut k2_inv =
-            BigInt::mod_inv(&ephemeral_local_share.secret_share.to_big_int(), &q).unwrap();
-        let partial_sig = rho * &q + BigInt::mod_mul(&k2_inv, message, &q);
+        let k2_inv = ephemeral_local_share.secret_share.invert();
 
-        let c1 = Paillier::encrypt(ek, RawPlaintext::from(partial_sig));
+        let (c1, mut r1) = party_one.encrypt(&(rho * &q));
+        r1.zeroize();
         let v = BigInt::mod_mul(
-            &k2_inv,
+            &k2_inv.to_big_int(),
             &BigInt::mod_mul(&rx, &local_share.x2.to_big_int(), &q),
             &q,
         );
-        k2_inv.zeroize();
-        let c2 = Paillier::mul(
-            ek,
-            RawCiphertext::from(encrypted_secret_share.clone()),
-            RawPlaintext::from(v),
-        );
-        //c3:
+        let c2 = party_one.mul(encrypted_secret_share, &v);
+        PartialSigPrecomputation {
+            r,
+            c: party_one.add(&c2, &c1),
+            k2_inv,
+        }
+    }
+}
+
+impl PartialSigPrecomputation {
+    /// Adds k2^-1 * m to the precomputed ciphertext, which is already randomized.
+    pub fn finalize(self, party_one: &PaillierContext, message: &BigInt) -> PartialSig {
+        let q = FE::q();
+        let mut partial_sig = BigInt::mod_mul(&self.k2_inv.to_big_int(), message, &q);
+        let c1 = party_one.encrypt_with_chosen_randomness(&partial_sig, &BigInt::one());
+        partial_sig.zeroize();
         PartialSig {
-            c3: Paillier::add(ek, c2, c1).0.into_owned(),
+            c3: party_one.add(&self.c, &c1),
         }
     }
 }
+
+impl Drop for PartialSigPrecomputation {
+    fn drop(&mut self) {
+        self.k2_inv.zeroize();
+    }
+}
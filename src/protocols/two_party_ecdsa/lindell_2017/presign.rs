@@ -0,0 +1,180 @@
+/*
+    Multi-party ECDSA
+
+    Copyright 2018 by Kzen Networks
+
+    This file is part of Multi-party ECDSA library
+    (https://github.com/KZen-networks/multi-party-ecdsa)
+
+    Multi-party ECDSA is free software: you can redistribute
+    it and/or modify it under the terms of the GNU General Public
+    License as published by the Free Software Foundation, either
+    version 3 of the License, or (at your option) any later version.
+
+    @license GPL-3.0+ <https://github.com/KZen-networks/multi-party-ecdsa/blob/master/LICENSE>
+*/
+
+//! Presigning: the ephemeral key exchange and the message independent part of party two's
+//! Paillier computation are done ahead of time, so that signing takes a single message
+//! from party two and a single decryption by party one.
+//!
+//! ```text
+//! offline  P2 -> P1  party_two::EphKeyGenFirstMsg   commitment to R2
+//!          P1 -> P2  party_one::EphKeyGenFirstMsg   R1
+//!          P2 -> P1  party_two::EphKeyGenSecondMsg  decommitment, P2 stores a presignature
+//!                                                   P1 checks it and stores its own
+//! online   P2 -> P1  PresignedPartialSig            id and c3, P1 decrypts and outputs s
+//! ```
+//!
+//! A presignature must never be used twice, two signatures with the same nonce give away
+//! the key. Both parties keep theirs in a [`PresignatureStore`], which hands out each
+//! presignature at most once and remembers the ids it has handed out. The id of a
+//! presignature is the hash of R1 and R2.
+//!
+//! [`PresignatureStore`]: crate::utilities::presign::PresignatureStore
+
+use curv::cryptographic_primitives::hashing::hash_sha256::HSha256;
+use curv::cryptographic_primitives::hashing::traits::Hash;
+use curv::elliptic::curves::p256::GE;
+use curv::elliptic::curves::traits::*;
+use curv::BigInt;
+use serde::{Deserialize, Serialize};
+
+use super::party_one::{self, Party1Private, SignatureRecid};
+use super::party_two::{self, PaillierPublic, PartialSig, PartialSigPrecomputation, Party2Private};
+use crate::utilities::paillier_context::PaillierContext;
+use crate::utilities::presign::{Presignature, PresignatureId};
+use crate::Error::{self, InvalidKey, InvalidMessage};
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Party1Presignature {
+    eph_ec_key_pair: party_one::EphEcKeyPair,
+    party_two_public_share: GE,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Party2Presignature {
+    id: PresignatureId,
+    precomputation: PartialSigPrecomputation,
+}
+
+/// Party two's message of the online step.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct PresignedPartialSig {
+    pub id: PresignatureId,
+    pub partial_sig: PartialSig,
+}
+
+fn presignature_id(party_one_public_share: &GE, party_two_public_share: &GE) -> PresignatureId {
+    HSha256::create_hash(&[
+        &party_one_public_share.bytes_compressed_to_big_int(),
+        &party_two_public_share.bytes_compressed_to_big_int(),
+    ])
+}
+
+impl Party1Presignature {
+    /// Party one's offline step, once party two opened its commitment.
+    pub fn create(
+        eph_ec_key_pair: party_one::EphEcKeyPair,
+        party_two_first_msg: &party_two::EphKeyGenFirstMsg,
+        party_two_second_msg: &party_two::EphKeyGenSecondMsg,
+    ) -> Result<Party1Presignature, Error> {
+        party_one::EphKeyGenSecondMsg::verify_commitments_and_dlog_proof(
+            party_two_first_msg,
+            party_two_second_msg,
+        )
+        .map_err(|_| InvalidKey)?;
+        Ok(Party1Presignature {
+            eph_ec_key_pair,
+            party_two_public_share: party_two_second_msg.comm_witness.public_share,
+        })
+    }
+
+    /// Decrypts party two's partial signature and checks the result, so a wrong message
+    /// or a wrong share of party two is caught here.
+    pub fn sign(
+        self,
+        party_one_private: &Party1Private,
+        public_key: &GE,
+        message: &BigInt,
+        partial_sig: &PresignedPartialSig,
+    ) -> Result<SignatureRecid, Error> {
+        if partial_sig.id != self.id() {
+            return Err(InvalidMessage);
+        }
+        let signature = party_one::Signature::compute_with_recid(
+            party_one_private,
+            &partial_sig.partial_sig.c3,
+            &self.eph_ec_key_pair,
+            &self.party_two_public_share,
+        );
+        party_one::verify(
+            &party_one::Signature {
+                r: signature.r.clone(),
+                s: signature.s.clone(),
+            },
+            public_key,
+            message,
+        )?;
+        Ok(signature)
+    }
+}
+
+impl Presignature for Party1Presignature {
+    fn id(&self) -> PresignatureId {
+        presignature_id(
+            &self.eph_ec_key_pair.public_share,
+            &self.party_two_public_share,
+        )
+    }
+}
+
+impl Party2Presignature {
+    /// Party two's offline step. Returns the decommitment for party one together with the
+    /// presignature to store.
+    pub fn create(
+        eph_comm_witness: party_two::EphCommWitness,
+        eph_ec_key_pair: &party_two::EphEcKeyPair,
+        party_one_first_msg: &party_one::EphKeyGenFirstMsg,
+        party_two_private: &Party2Private,
+        paillier_public: &PaillierPublic,
+    ) -> Result<(party_two::EphKeyGenSecondMsg, Party2Presignature), Error> {
+        let second_msg = party_two::EphKeyGenSecondMsg::verify_and_decommit(
+            eph_comm_witness,
+            party_one_first_msg,
+        )
+        .map_err(|_| InvalidKey)?;
+        let precomputation = PartialSig::precompute(
+            &PaillierContext::new(&paillier_public.ek),
+            &paillier_public.encrypted_secret_share,
+            party_two_private,
+            eph_ec_key_pair,
+            &party_one_first_msg.public_share,
+        );
+        let presignature = Party2Presignature {
+            id: presignature_id(
+                &party_one_first_msg.public_share,
+                &eph_ec_key_pair.public_share,
+            ),
+            precomputation,
+        };
+        Ok((second_msg, presignature))
+    }
+
+    /// The online step, consuming the presignature.
+    pub fn sign(self, paillier_public: &PaillierPublic, message: &BigInt) -> PresignedPartialSig {
+        let partial_sig = self
+            .precomputation
+            .finalize(&PaillierContext::new(&paillier_public.ek), message);
+        PresignedPartialSig {
+            id: self.id,
+            partial_sig,
+        }
+    }
+}
+
+impl Presignature for Party2Presignature {
+    fn id(&self) -> PresignatureId {
+        self.id.clone()
+    }
+}
@@ -0,0 +1,192 @@
+/*
+    Multi-party ECDSA
+
+    Copyright 2018 by Kzen Networks
+
+    This file is part of Multi-party ECDSA library
+    (https://github.com/KZen-networks/multi-party-ecdsa)
+
+    Multi-party ECDSA is free software: you can redistribute
+    it and/or modify it under the terms of the GNU General Public
+    License as published by the Free Software Foundation, either
+    version 3 of the License, or (at your option) any later version.
+
+    @license GPL-3.0+ <https://github.com/KZen-networks/multi-party-ecdsa/blob/master/LICENSE>
+*/
+
+//! Key rotation: both shares are multiplied by a jointly chosen factor, x1' = x1 * f and
+//! x2' = x2 / f, so the public key stays the same while old shares become useless.
+//!
+//! The factor comes out of a coin flip: party one commits to a seed, party two answers
+//! with its own seed and party one opens. Party one then sends a fresh Paillier key with
+//! the encryption of x1', proven correct as in keygen.
+//!
+//! ```text
+//! P1 -> P2  RotationFirstMsg        commitment to seed1
+//! P2 -> P1  Party2RotationFirstMsg  seed2
+//! P1 -> P2  RotationSecondMsg       seed1, new Paillier key, proofs
+//! ```
+
+use curv::arithmetic::traits::*;
+use curv::cryptographic_primitives::commitments::hash_commitment::HashCommitment;
+use curv::cryptographic_primitives::commitments::traits::Commitment;
+use curv::elliptic::curves::p256::{FE, GE};
+use curv::elliptic::curves::traits::*;
+use curv::BigInt;
+use serde::{Deserialize, Serialize};
+
+use super::party_one::Party1Private;
+use super::party_two::{PaillierPublic, Party2Private};
+use super::signer::PaillierKeyMsg;
+use super::SECURITY_BITS;
+use crate::Error::{self, InvalidCom, InvalidKey};
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct RotationFirstMsg {
+    pub seed_commitment: BigInt,
+}
+
+#[derive(Debug)]
+pub struct RotationWitness {
+    seed: FE,
+    blind_factor: BigInt,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Party2RotationFirstMsg {
+    pub seed: FE,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct RotationSecondMsg {
+    pub seed: FE,
+    pub blind_factor: BigInt,
+    pub paillier_key: PaillierKeyMsg,
+}
+
+impl RotationFirstMsg {
+    pub fn commit() -> (RotationFirstMsg, RotationWitness) {
+        let seed: FE = ECScalar::new_random();
+        let blind_factor = BigInt::sample(SECURITY_BITS);
+        let seed_commitment = HashCommitment::create_commitment_with_user_defined_randomness(
+            &seed.to_big_int(),
+            &blind_factor,
+        );
+        (
+            RotationFirstMsg { seed_commitment },
+            RotationWitness { seed, blind_factor },
+        )
+    }
+
+    /// Opens the commitment to party one's seed and returns the rotation factor.
+    pub(crate) fn open(
+        &self,
+        seed: &FE,
+        blind_factor: &BigInt,
+        party_two_first_msg: &Party2RotationFirstMsg,
+    ) -> Result<FE, Error> {
+        let seed_commitment = HashCommitment::create_commitment_with_user_defined_randomness(
+            &seed.to_big_int(),
+            blind_factor,
+        );
+        if seed_commitment != self.seed_commitment {
+            return Err(InvalidCom);
+        }
+        rotation_factor(seed, &party_two_first_msg.seed)
+    }
+}
+
+impl RotationWitness {
+    pub(crate) fn factor(&self, party_two_first_msg: &Party2RotationFirstMsg) -> Result<FE, Error> {
+        rotation_factor(&self.seed, &party_two_first_msg.seed)
+    }
+
+    pub(crate) fn into_opening(self) -> (FE, BigInt) {
+        (self.seed, self.blind_factor)
+    }
+}
+
+impl Party2RotationFirstMsg {
+    pub fn create() -> Party2RotationFirstMsg {
+        Party2RotationFirstMsg {
+            seed: ECScalar::new_random(),
+        }
+    }
+}
+
+impl RotationSecondMsg {
+    /// Party one's last step, returning the message for party two and the rotated share.
+    pub fn rotate(
+        witness: RotationWitness,
+        party_two_first_msg: &Party2RotationFirstMsg,
+        party_one_private: &Party1Private,
+    ) -> Result<(RotationSecondMsg, Party1Private), Error> {
+        let factor = witness.factor(party_two_first_msg)?;
+        let (seed, blind_factor) = witness.into_opening();
+        let (
+            ek,
+            encrypted_share,
+            party_one_private,
+            correct_key_proof,
+            pdl_statement,
+            pdl_proof,
+            composite_dlog_proof,
+        ) = Party1Private::refresh_private_key(party_one_private, &factor.to_big_int());
+        let msg = RotationSecondMsg {
+            seed,
+            blind_factor,
+            paillier_key: PaillierKeyMsg {
+                ek,
+                encrypted_share,
+                correct_key_proof,
+                pdl_statement,
+                pdl_proof,
+                composite_dlog_proof,
+            },
+        };
+        Ok((msg, party_one_private))
+    }
+
+    /// Party two's last step. Opens the commitment, checks the new Paillier key and that it
+    /// encrypts the discrete log of `party_one_public_share * factor`, and returns the
+    /// rotated share together with party one's new public share.
+    pub fn verify_and_rotate(
+        &self,
+        party_one_first_msg: &RotationFirstMsg,
+        party_two_first_msg: &Party2RotationFirstMsg,
+        party_two_private: &Party2Private,
+        party_one_public_share: &GE,
+    ) -> Result<(Party2Private, PaillierPublic, GE), Error> {
+        let factor =
+            party_one_first_msg.open(&self.seed, &self.blind_factor, party_two_first_msg)?;
+        let party_one_public_share = party_one_public_share * &factor;
+
+        let key = &self.paillier_key;
+        PaillierPublic::verify_ni_proof_correct_key(key.correct_key_proof.clone(), &key.ek)
+            .map_err(|_| InvalidKey)?;
+        let paillier_public = PaillierPublic {
+            ek: key.ek.clone(),
+            encrypted_secret_share: key.encrypted_share.clone(),
+        };
+        PaillierPublic::pdl_verify(
+            &key.composite_dlog_proof,
+            &key.pdl_statement,
+            &key.pdl_proof,
+            &paillier_public,
+            &party_one_public_share,
+        )
+        .map_err(|_| InvalidKey)?;
+
+        let party_two_private =
+            Party2Private::update_private_key(party_two_private, &factor.invert().to_big_int());
+        Ok((party_two_private, paillier_public, party_one_public_share))
+    }
+}
+
+fn rotation_factor(seed1: &FE, seed2: &FE) -> Result<FE, Error> {
+    let factor = *seed1 + *seed2;
+    if factor.to_big_int() == BigInt::zero() {
+        return Err(InvalidKey);
+    }
+    Ok(factor)
+}
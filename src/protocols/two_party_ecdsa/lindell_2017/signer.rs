@@ -0,0 +1,558 @@
+/*
+    Multi-party ECDSA
+
+    Copyright 2018 by Kzen Networks
+
+    This file is part of Multi-party ECDSA library
+    (https://github.com/KZen-networks/multi-party-ecdsa)
+
+    Multi-party ECDSA is free software: you can redistribute
+    it and/or modify it under the terms of the GNU General Public
+    License as published by the Free Software Foundation, either
+    version 3 of the License, or (at your option) any later version.
+
+    @license GPL-3.0+ <https://github.com/KZen-networks/multi-party-ecdsa/blob/master/LICENSE>
+*/
+
+//! Lindell 2017 keygen and signing as [`Session`]s. Party one has index `PARTY_ONE` and
+//! party two `PARTY_TWO`. Party one sends the final signature to party two, so both
+//! parties output it.
+
+use curv::elliptic::curves::p256::{FE, GE};
+use curv::BigInt;
+use paillier::EncryptionKey;
+use serde::{Deserialize, Serialize};
+use zk_paillier::zkproofs::{CompositeDLogProof, NICorrectKeyProof};
+
+use super::{party_one, party_two};
+use crate::protocols::threshold_signer::{
+    self, p2p, KeyShare, Msg, Session, Signature, ThresholdSigner,
+};
+use crate::utilities::encoding;
+use crate::utilities::zk_pdl_with_slack::{PDLwSlackProof, PDLwSlackStatement};
+use crate::Error::{self, InvalidKey, InvalidMessage};
+
+pub const PARTY_ONE: u16 = 0;
+pub const PARTY_TWO: u16 = 1;
+
+/// Party one's Paillier key together with the proofs that it is well formed and that it
+/// encrypts the secret share behind party one's public share.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct PaillierKeyMsg {
+    pub ek: EncryptionKey,
+    pub encrypted_share: BigInt,
+    pub correct_key_proof: NICorrectKeyProof,
+    pub pdl_statement: PDLwSlackStatement,
+    pub pdl_proof: PDLwSlackProof,
+    pub composite_dlog_proof: CompositeDLogProof,
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct Party1KeyShare {
+    pub private: party_one::Party1Private,
+    pub public_share: GE,
+    pub public_key: GE,
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct Party2KeyShare {
+    pub private: party_two::Party2Private,
+    pub paillier_public: party_two::PaillierPublic,
+    pub public_share: GE,
+    pub public_key: GE,
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+pub enum LocalKey {
+    PartyOne(Party1KeyShare),
+    PartyTwo(Party2KeyShare),
+}
+
+impl KeyShare for LocalKey {
+    fn party(&self) -> u16 {
+        match self {
+            LocalKey::PartyOne(_) => PARTY_ONE,
+            LocalKey::PartyTwo(_) => PARTY_TWO,
+        }
+    }
+
+    fn public_key(&self) -> GE {
+        match self {
+            LocalKey::PartyOne(key) => key.public_key,
+            LocalKey::PartyTwo(key) => key.public_key,
+        }
+    }
+}
+
+pub struct Lindell2017;
+
+impl ThresholdSigner for Lindell2017 {
+    type KeyShare = LocalKey;
+    type Keygen = Keygen;
+    type Signing = Signing;
+
+    fn keygen(party: u16, threshold: u16, share_count: u16) -> Result<Keygen, Error> {
+        if threshold != 1 || share_count != 2 {
+            return Err(InvalidKey);
+        }
+        Keygen::new(party)
+    }
+
+    fn sign(key: &LocalKey, signers: &[u16], message: &BigInt) -> Result<Signing, Error> {
+        let mut sorted = signers.to_vec();
+        sorted.sort_unstable();
+        if sorted != [PARTY_ONE, PARTY_TWO] {
+            return Err(InvalidKey);
+        }
+        Ok(Signing::new(key.clone(), message))
+    }
+}
+
+fn other(party: u16) -> u16 {
+    if party == PARTY_ONE {
+        PARTY_TWO
+    } else {
+        PARTY_ONE
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub enum KeygenMessage {
+    Party1Commitment(party_one::KeyGenFirstMsg),
+    Party2DLogProof(party_two::KeyGenFirstMsg),
+    Party1Decommitment(party_one::KeyGenSecondMsg, PaillierKeyMsg),
+}
+
+/// Party one commits to its share in round 1, party two answers with its share and dlog
+/// proof in round 2 and party one decommits and hands over its Paillier key in round 3. Party
+/// two only sends once it holds the commitment, so party one cannot choose its share
+/// depending on party two's.
+pub struct Keygen {
+    party: u16,
+    round: u16,
+    party_one_state: Option<(
+        party_one::KeyGenFirstMsg,
+        party_one::CommWitness,
+        party_one::EcKeyPair,
+    )>,
+    party_two_state: Option<(party_two::KeyGenFirstMsg, party_two::EcKeyPair)>,
+    party_one_first_msg: Option<party_one::KeyGenFirstMsg>,
+    expected_public_key: Option<GE>,
+    output: Option<LocalKey>,
+}
+
+impl Keygen {
+    pub fn new(party: u16) -> Result<Self, Error> {
+        let (party_one_state, party_two_state) = match party {
+            PARTY_ONE => (Some(party_one::KeyGenFirstMsg::create_commitments()), None),
+            PARTY_TWO => (None, Some(party_two::KeyGenFirstMsg::create())),
+            _ => return Err(InvalidKey),
+        };
+        Ok(Keygen {
+            party,
+            round: 0,
+            party_one_state,
+            party_two_state,
+            party_one_first_msg: None,
+            expected_public_key: None,
+            output: None,
+        })
+    }
+
+    /// Keygen over a share dealt by [`crate::protocols::import::Dealer::deal_two_party`]
+    /// instead of a random one. Fails with `InvalidKey` if the joint key is not `public_key`.
+    pub fn import(party: u16, share: FE, public_key: GE) -> Result<Self, Error> {
+        let (party_one_state, party_two_state) = match party {
+            PARTY_ONE => (
+                Some(party_one::KeyGenFirstMsg::create_commitments_with_fixed_secret_share(share)),
+                None,
+            ),
+            PARTY_TWO => (
+                None,
+                Some(party_two::KeyGenFirstMsg::create_with_fixed_secret_share(
+                    share,
+                )),
+            ),
+            _ => return Err(InvalidKey),
+        };
+        Ok(Keygen {
+            party,
+            round: 0,
+            party_one_state,
+            party_two_state,
+            party_one_first_msg: None,
+            expected_public_key: Some(public_key),
+            output: None,
+        })
+    }
+
+    fn check_public_key(&self, public_key: &GE) -> Result<(), Error> {
+        match self.expected_public_key {
+            Some(expected) if expected != *public_key => Err(InvalidKey),
+            _ => Ok(()),
+        }
+    }
+
+    fn party_one_finish(
+        &mut self,
+        party_two_first_msg: party_two::KeyGenFirstMsg,
+    ) -> Result<Vec<Msg<KeygenMessage>>, Error> {
+        let (_, comm_witness, ec_key_pair) = self.party_one_state.take().unwrap();
+        let second_msg = party_one::KeyGenSecondMsg::verify_and_decommit(
+            comm_witness,
+            &party_two_first_msg.d_log_proof,
+        )
+        .map_err(|_| InvalidKey)?;
+
+        let paillier_key_pair =
+            party_one::PaillierKeyPair::generate_keypair_and_encrypted_share(&ec_key_pair);
+        let private = party_one::Party1Private::set_private_key(&ec_key_pair, &paillier_key_pair);
+        let correct_key_proof =
+            party_one::PaillierKeyPair::generate_ni_proof_correct_key(&paillier_key_pair);
+        let (pdl_statement, pdl_proof, composite_dlog_proof) =
+            party_one::PaillierKeyPair::pdl_proof(&private, &paillier_key_pair);
+        let paillier_msg = PaillierKeyMsg {
+            ek: paillier_key_pair.ek.clone(),
+            encrypted_share: paillier_key_pair.encrypted_share.clone(),
+            correct_key_proof,
+            pdl_statement,
+            pdl_proof,
+            composite_dlog_proof,
+        };
+
+        let public_key = party_one::compute_pubkey(&private, &party_two_first_msg.public_share);
+        self.check_public_key(&public_key)?;
+        self.output = Some(LocalKey::PartyOne(Party1KeyShare {
+            private,
+            public_share: ec_key_pair.public_share,
+            public_key,
+        }));
+        let body = KeygenMessage::Party1Decommitment(second_msg, paillier_msg);
+        Ok(vec![p2p(PARTY_ONE, PARTY_TWO, 3, body)])
+    }
+
+    fn party_two_finish(
+        &mut self,
+        party_one_second_msg: party_one::KeyGenSecondMsg,
+        paillier_msg: PaillierKeyMsg,
+    ) -> Result<(), Error> {
+        let party_one_first_msg = self.party_one_first_msg.take().unwrap();
+        let (_, ec_key_pair) = self.party_two_state.take().unwrap();
+        party_two::KeyGenSecondMsg::verify_commitments_and_dlog_proof(
+            &party_one_first_msg,
+            &party_one_second_msg,
+        )
+        .map_err(|_| InvalidKey)?;
+
+        let party_one_public_share = party_one_second_msg.comm_witness.public_share;
+        let paillier_public = party_two::PaillierPublic {
+            ek: paillier_msg.ek,
+            encrypted_secret_share: paillier_msg.encrypted_share,
+        };
+        party_two::PaillierPublic::verify_ni_proof_correct_key(
+            paillier_msg.correct_key_proof,
+            &paillier_public.ek,
+        )
+        .map_err(|_| InvalidKey)?;
+        party_two::PaillierPublic::pdl_verify(
+            &paillier_msg.composite_dlog_proof,
+            &paillier_msg.pdl_statement,
+            &paillier_msg.pdl_proof,
+            &paillier_public,
+            &party_one_public_share,
+        )
+        .map_err(|_| InvalidKey)?;
+
+        let public_key = party_two::compute_pubkey(&ec_key_pair, &party_one_public_share);
+        self.check_public_key(&public_key)?;
+        self.output = Some(LocalKey::PartyTwo(Party2KeyShare {
+            private: party_two::Party2Private::set_private_key(&ec_key_pair),
+            paillier_public,
+            public_share: ec_key_pair.public_share,
+            public_key,
+        }));
+        Ok(())
+    }
+}
+
+impl Session for Keygen {
+    type Message = KeygenMessage;
+    type Output = LocalKey;
+
+    fn party(&self) -> u16 {
+        self.party
+    }
+
+    fn round(&self) -> u16 {
+        self.round
+    }
+
+    fn expected_senders(&self) -> Vec<u16> {
+        match self.round {
+            1..=3 => vec![other(self.party)],
+            _ => Vec::new(),
+        }
+    }
+
+    fn proceed(
+        &mut self,
+        incoming: Vec<Msg<KeygenMessage>>,
+    ) -> Result<Vec<Msg<KeygenMessage>>, Error> {
+        let senders = self.expected_senders();
+        let (outgoing, next_round) =
+            match (self.party, self.round) {
+                (PARTY_ONE, 0) => {
+                    let first_msg = self.party_one_state.as_ref().unwrap().0.clone();
+                    let body = KeygenMessage::Party1Commitment(first_msg);
+                    (vec![p2p(PARTY_ONE, PARTY_TWO, 1, body)], 2)
+                }
+                // party two waits for the commitment before sending anything
+                (PARTY_TWO, 0) => (Vec::new(), 1),
+                (PARTY_TWO, 1) => {
+                    let mut round1 =
+                        threshold_signer::collect(incoming, self.party(), 1, &senders, |body| {
+                            match body {
+                                KeygenMessage::Party1Commitment(msg) => Some(msg),
+                                _ => None,
+                            }
+                        })?;
+                    self.party_one_first_msg = Some(round1.remove(0));
+                    let first_msg = self.party_two_state.as_ref().unwrap().0.clone();
+                    let body = KeygenMessage::Party2DLogProof(first_msg);
+                    (vec![p2p(PARTY_TWO, PARTY_ONE, 2, body)], 3)
+                }
+                (PARTY_ONE, 2) => {
+                    let mut round2 =
+                        threshold_signer::collect(incoming, self.party(), 2, &senders, |body| {
+                            match body {
+                                KeygenMessage::Party2DLogProof(msg) => Some(msg),
+                                _ => None,
+                            }
+                        })?;
+                    let outgoing = self.party_one_finish(round2.remove(0))?;
+                    // party one is done once it sent round 3
+                    (outgoing, 4)
+                }
+                (PARTY_TWO, 3) => {
+                    let mut round3 =
+                        threshold_signer::collect(incoming, self.party(), 3, &senders, |body| {
+                            match body {
+                                KeygenMessage::Party1Decommitment(msg, paillier_msg) => {
+                                    Some((msg, paillier_msg))
+                                }
+                                _ => None,
+                            }
+                        })?;
+                    let (second_msg, paillier_msg) = round3.remove(0);
+                    self.party_two_finish(second_msg, paillier_msg)?;
+                    (Vec::new(), 4)
+                }
+                _ => return Err(InvalidMessage),
+            };
+        self.round = next_round;
+        Ok(outgoing)
+    }
+
+    fn is_finished(&self) -> bool {
+        self.round > 3
+    }
+
+    fn pick_output(&mut self) -> Option<LocalKey> {
+        self.output.take()
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub enum SignMessage {
+    EphCommitment(party_two::EphKeyGenFirstMsg),
+    EphKeyShare(party_one::EphKeyGenFirstMsg),
+    PartialSignature(party_two::EphKeyGenSecondMsg, party_two::PartialSig),
+    Signature(party_one::SignatureRecid),
+}
+
+/// Party two commits to its ephemeral share in round 1, party one answers with its own in
+/// round 2, party two decommits and sends its partial signature in round 3 and party one
+/// returns the signature in round 4. `round()` is always the round whose message the party
+/// waits for next.
+pub struct Signing {
+    key: LocalKey,
+    message: BigInt,
+    round: u16,
+    party_two_eph_first_msg: Option<party_two::EphKeyGenFirstMsg>,
+    party_one_eph_ec_key_pair: Option<party_one::EphEcKeyPair>,
+    party_two_eph_state: Option<(party_two::EphCommWitness, party_two::EphEcKeyPair)>,
+    output: Option<Signature>,
+}
+
+impl Signing {
+    pub fn new(key: LocalKey, message: &BigInt) -> Self {
+        let round = match key {
+            LocalKey::PartyOne(_) => 1,
+            LocalKey::PartyTwo(_) => 0,
+        };
+        Signing {
+            key,
+            message: message.clone(),
+            round,
+            party_two_eph_first_msg: None,
+            party_one_eph_ec_key_pair: None,
+            party_two_eph_state: None,
+            output: None,
+        }
+    }
+
+    fn verify(
+        signature: &party_one::SignatureRecid,
+        public_key: &GE,
+        message: &BigInt,
+    ) -> Result<(), Error> {
+        party_one::verify(
+            &party_one::Signature {
+                r: signature.r.clone(),
+                s: signature.s.clone(),
+            },
+            public_key,
+            message,
+        )
+    }
+}
+
+impl Session for Signing {
+    type Message = SignMessage;
+    type Output = Signature;
+
+    fn party(&self) -> u16 {
+        self.key.party()
+    }
+
+    fn round(&self) -> u16 {
+        self.round
+    }
+
+    fn expected_senders(&self) -> Vec<u16> {
+        match self.round {
+            1..=4 => vec![other(self.party())],
+            _ => Vec::new(),
+        }
+    }
+
+    fn proceed(&mut self, incoming: Vec<Msg<SignMessage>>) -> Result<Vec<Msg<SignMessage>>, Error> {
+        let senders = self.expected_senders();
+        let (outgoing, next_round) =
+            match (&self.key, self.round) {
+                (LocalKey::PartyTwo(_), 0) => {
+                    let (eph_first_msg, eph_comm_witness, eph_ec_key_pair) =
+                        party_two::EphKeyGenFirstMsg::create_commitments();
+                    self.party_two_eph_state = Some((eph_comm_witness, eph_ec_key_pair));
+                    let body = SignMessage::EphCommitment(eph_first_msg);
+                    (vec![p2p(PARTY_TWO, PARTY_ONE, 1, body)], 2)
+                }
+                (LocalKey::PartyOne(_), 1) => {
+                    let mut round1 =
+                        threshold_signer::collect(incoming, self.party(), 1, &senders, |body| {
+                            match body {
+                                SignMessage::EphCommitment(msg) => Some(msg),
+                                _ => None,
+                            }
+                        })?;
+                    // party two committed to its ephemeral share before seeing ours
+                    self.party_two_eph_first_msg = Some(round1.remove(0));
+                    let (eph_first_msg, eph_ec_key_pair) = party_one::EphKeyGenFirstMsg::create();
+                    self.party_one_eph_ec_key_pair = Some(eph_ec_key_pair);
+                    let body = SignMessage::EphKeyShare(eph_first_msg);
+                    (vec![p2p(PARTY_ONE, PARTY_TWO, 2, body)], 3)
+                }
+                (LocalKey::PartyTwo(key), 2) => {
+                    let mut round2 =
+                        threshold_signer::collect(incoming, self.party(), 2, &senders, |body| {
+                            match body {
+                                SignMessage::EphKeyShare(msg) => Some(msg),
+                                _ => None,
+                            }
+                        })?;
+                    let party_one_eph_first_msg = round2.remove(0);
+                    let (eph_comm_witness, eph_ec_key_pair) =
+                        self.party_two_eph_state.take().unwrap();
+                    let eph_second_msg = party_two::EphKeyGenSecondMsg::verify_and_decommit(
+                        eph_comm_witness,
+                        &party_one_eph_first_msg,
+                    )
+                    .map_err(|_| InvalidKey)?;
+                    let partial_sig = party_two::PartialSig::compute(
+                        &key.paillier_public.ek,
+                        &key.paillier_public.encrypted_secret_share,
+                        &key.private,
+                        &eph_ec_key_pair,
+                        &party_one_eph_first_msg.public_share,
+                        &self.message,
+                    );
+                    let body = SignMessage::PartialSignature(eph_second_msg, partial_sig);
+                    (vec![p2p(PARTY_TWO, PARTY_ONE, 3, body)], 4)
+                }
+                (LocalKey::PartyOne(key), 3) => {
+                    let mut round3 =
+                        threshold_signer::collect(incoming, self.party(), 3, &senders, |body| {
+                            match body {
+                                SignMessage::PartialSignature(msg, partial_sig) => {
+                                    Some((msg, partial_sig))
+                                }
+                                _ => None,
+                            }
+                        })?;
+                    let (party_two_eph_second_msg, partial_sig) = round3.remove(0);
+                    party_one::EphKeyGenSecondMsg::verify_commitments_and_dlog_proof(
+                        &self.party_two_eph_first_msg.take().unwrap(),
+                        &party_two_eph_second_msg,
+                    )
+                    .map_err(|_| InvalidKey)?;
+                    let signature = party_one::Signature::compute_with_recid(
+                        &key.private,
+                        &partial_sig.c3,
+                        &self.party_one_eph_ec_key_pair.take().unwrap(),
+                        &party_two_eph_second_msg.comm_witness.public_share,
+                    );
+                    Self::verify(&signature, &key.public_key, &self.message)?;
+                    self.output = Some(Signature {
+                        r: signature.r.clone(),
+                        s: signature.s.clone(),
+                        recid: signature.recid,
+                    });
+                    // party one is done once it sent round 4
+                    let body = SignMessage::Signature(signature);
+                    (vec![p2p(PARTY_ONE, PARTY_TWO, 4, body)], 5)
+                }
+                (LocalKey::PartyTwo(key), 4) => {
+                    let mut round4 =
+                        threshold_signer::collect(incoming, self.party(), 4, &senders, |body| {
+                            match body {
+                                SignMessage::Signature(signature) => Some(signature),
+                                _ => None,
+                            }
+                        })?;
+                    let signature = round4.remove(0);
+                    Self::verify(&signature, &key.public_key, &self.message)?;
+                    // the recid is party one's word, recompute it
+                    let (s, _) = encoding::normalize_s(&signature.s);
+                    let recid =
+                        encoding::find_recid(&signature.r, &s, &self.message, &key.public_key)?;
+                    self.output = Some(Signature {
+                        r: signature.r,
+                        s,
+                        recid,
+                    });
+                    (Vec::new(), 5)
+                }
+                _ => return Err(InvalidMessage),
+            };
+        self.round = next_round;
+        Ok(outgoing)
+    }
+
+    fn is_finished(&self) -> bool {
+        self.round > 4
+    }
+
+    fn pick_output(&mut self) -> Option<Signature> {
+        self.output.take()
+    }
+}
@@ -1,6 +1,7 @@
 // For integration tests, please add your tests in /tests instead
 
-use crate::protocols::two_party_ecdsa::lindell_2017::{party_one, party_two};
+use crate::protocols::two_party_ecdsa::lindell_2017::{party_one, party_two, presign, rotation};
+use crate::utilities::digest::MessageDigest;
 use curv::arithmetic::traits::Samplable;
 use curv::elliptic::curves::traits::*;
 use curv::BigInt;
@@ -135,3 +136,415 @@ fn test_two_party_sign() {
         party_one::compute_pubkey(&party1_private, &party_two_private_share_gen.public_share);
     party_one::verify(&signature, &pubkey, &message).expect("Invalid signature")
 }
+
+#[test]
+fn test_two_party_sign_with_digest() {
+    let (_, _, ec_key_pair_party1) = party_one::KeyGenFirstMsg::create_commitments();
+    let (party_two_private_share_gen, ec_key_pair_party2) = party_two::KeyGenFirstMsg::create();
+    let keypair =
+        party_one::PaillierKeyPair::generate_keypair_and_encrypted_share(&ec_key_pair_party1);
+    let (_, _, eph_ec_key_pair_party2) = party_two::EphKeyGenFirstMsg::create_commitments();
+    let (eph_party_one_first_message, eph_ec_key_pair_party1) =
+        party_one::EphKeyGenFirstMsg::create();
+
+    let party2_private = party_two::Party2Private::set_private_key(&ec_key_pair_party2);
+    let message = b"a raw message";
+    let partial_sig = party_two::PartialSig::compute_with_digest(
+        &keypair.ek,
+        &keypair.encrypted_share,
+        &party2_private,
+        &eph_ec_key_pair_party2,
+        &eph_party_one_first_message.public_share,
+        message,
+        MessageDigest::Sha256,
+    );
+
+    let party1_private = party_one::Party1Private::set_private_key(&ec_key_pair_party1, &keypair);
+    let signature = party_one::Signature::compute(
+        &party1_private,
+        &partial_sig.c3,
+        &eph_ec_key_pair_party1,
+        &eph_ec_key_pair_party2.public_share,
+    );
+    let pubkey =
+        party_one::compute_pubkey(&party1_private, &party_two_private_share_gen.public_share);
+    party_one::verify_with_digest(&signature, &pubkey, message, MessageDigest::Sha256)
+        .expect("invalid signature");
+    assert!(
+        party_one::verify_with_digest(&signature, &pubkey, message, MessageDigest::Sha384).is_err()
+    );
+}
+
+#[test]
+fn test_two_party_sign_recover_public_key() {
+    let (_party_one_private_share_gen, _comm_witness, ec_key_pair_party1) =
+        party_one::KeyGenFirstMsg::create_commitments();
+    let (party_two_private_share_gen, ec_key_pair_party2) = party_two::KeyGenFirstMsg::create();
+
+    let keypair =
+        party_one::PaillierKeyPair::generate_keypair_and_encrypted_share(&ec_key_pair_party1);
+
+    let (eph_party_two_first_message, eph_comm_witness, eph_ec_key_pair_party2) =
+        party_two::EphKeyGenFirstMsg::create_commitments();
+    let (eph_party_one_first_message, eph_ec_key_pair_party1) =
+        party_one::EphKeyGenFirstMsg::create();
+    let eph_party_two_second_message = party_two::EphKeyGenSecondMsg::verify_and_decommit(
+        eph_comm_witness,
+        &eph_party_one_first_message,
+    )
+    .expect("party1 DLog proof failed");
+
+    let _eph_party_one_second_message =
+        party_one::EphKeyGenSecondMsg::verify_commitments_and_dlog_proof(
+            &eph_party_two_first_message,
+            &eph_party_two_second_message,
+        )
+        .expect("failed to verify commitments and DLog proof");
+    let party2_private = party_two::Party2Private::set_private_key(&ec_key_pair_party2);
+    let message = BigInt::from(1234);
+    let partial_sig = party_two::PartialSig::compute(
+        &keypair.ek,
+        &keypair.encrypted_share,
+        &party2_private,
+        &eph_ec_key_pair_party2,
+        &eph_party_one_first_message.public_share,
+        &message,
+    );
+
+    let party1_private = party_one::Party1Private::set_private_key(&ec_key_pair_party1, &keypair);
+
+    let signature = party_one::Signature::compute_with_recid(
+        &party1_private,
+        &partial_sig.c3,
+        &eph_ec_key_pair_party1,
+        &eph_party_two_second_message.comm_witness.public_share,
+    );
+
+    let pubkey =
+        party_one::compute_pubkey(&party1_private, &party_two_private_share_gen.public_share);
+    let recovered =
+        party_one::recover_public_key(&signature, &message).expect("recovery failed");
+    assert_eq!(recovered, pubkey);
+
+    let compact = signature.to_compact().expect("bad signature");
+    let decoded = party_one::SignatureRecid::from_compact(&compact).expect("bad compact encoding");
+    assert_eq!(decoded.r, signature.r);
+    assert_eq!(decoded.s, signature.s);
+    assert_eq!(decoded.recid, signature.recid);
+}
+
+#[test]
+fn test_two_party_batch_verify() {
+    let (_party_one_private_share_gen, _comm_witness, ec_key_pair_party1) =
+        party_one::KeyGenFirstMsg::create_commitments();
+    let (party_two_private_share_gen, ec_key_pair_party2) = party_two::KeyGenFirstMsg::create();
+
+    let keypair =
+        party_one::PaillierKeyPair::generate_keypair_and_encrypted_share(&ec_key_pair_party1);
+    let party1_private = party_one::Party1Private::set_private_key(&ec_key_pair_party1, &keypair);
+    let party2_private = party_two::Party2Private::set_private_key(&ec_key_pair_party2);
+    let pubkey =
+        party_one::compute_pubkey(&party1_private, &party_two_private_share_gen.public_share);
+
+    let messages: Vec<BigInt> = (0..4).map(BigInt::from).collect();
+    let signatures: Vec<party_one::SignatureRecid> = messages
+        .iter()
+        .map(|message| {
+            let (eph_party_two_first_message, eph_comm_witness, eph_ec_key_pair_party2) =
+                party_two::EphKeyGenFirstMsg::create_commitments();
+            let (eph_party_one_first_message, eph_ec_key_pair_party1) =
+                party_one::EphKeyGenFirstMsg::create();
+            let eph_party_two_second_message = party_two::EphKeyGenSecondMsg::verify_and_decommit(
+                eph_comm_witness,
+                &eph_party_one_first_message,
+            )
+            .expect("party1 DLog proof failed");
+            party_one::EphKeyGenSecondMsg::verify_commitments_and_dlog_proof(
+                &eph_party_two_first_message,
+                &eph_party_two_second_message,
+            )
+            .expect("failed to verify commitments and DLog proof");
+            let partial_sig = party_two::PartialSig::compute(
+                &keypair.ek,
+                &keypair.encrypted_share,
+                &party2_private,
+                &eph_ec_key_pair_party2,
+                &eph_party_one_first_message.public_share,
+                message,
+            );
+            party_one::Signature::compute_with_recid(
+                &party1_private,
+                &partial_sig.c3,
+                &eph_ec_key_pair_party1,
+                &eph_party_two_second_message.comm_witness.public_share,
+            )
+        })
+        .collect();
+
+    let pubkeys = vec![pubkey; signatures.len()];
+    party_one::batch_verify(&signatures, &pubkeys, &messages).expect("invalid batch");
+
+    let mut wrong_messages = messages.clone();
+    wrong_messages.swap(0, 1);
+    assert!(party_one::batch_verify(&signatures, &pubkeys, &wrong_messages).is_err());
+    assert!(party_one::batch_verify(&signatures[1..], &pubkeys, &messages).is_err());
+}
+
+fn sign(
+    party1_private: &party_one::Party1Private,
+    party2_private: &party_two::Party2Private,
+    paillier_public: &party_two::PaillierPublic,
+    message: &BigInt,
+) -> party_one::Signature {
+    let (eph_party_two_first_message, eph_comm_witness, eph_ec_key_pair_party2) =
+        party_two::EphKeyGenFirstMsg::create_commitments();
+    let (eph_party_one_first_message, eph_ec_key_pair_party1) =
+        party_one::EphKeyGenFirstMsg::create();
+    let eph_party_two_second_message = party_two::EphKeyGenSecondMsg::verify_and_decommit(
+        eph_comm_witness,
+        &eph_party_one_first_message,
+    )
+    .expect("party1 DLog proof failed");
+    party_one::EphKeyGenSecondMsg::verify_commitments_and_dlog_proof(
+        &eph_party_two_first_message,
+        &eph_party_two_second_message,
+    )
+    .expect("failed to verify commitments and DLog proof");
+    let partial_sig = party_two::PartialSig::compute(
+        &paillier_public.ek,
+        &paillier_public.encrypted_secret_share,
+        party2_private,
+        &eph_ec_key_pair_party2,
+        &eph_party_one_first_message.public_share,
+        message,
+    );
+    party_one::Signature::compute(
+        party1_private,
+        &partial_sig.c3,
+        &eph_ec_key_pair_party1,
+        &eph_party_two_second_message.comm_witness.public_share,
+    )
+}
+
+#[test]
+fn test_key_rotation() {
+    let (_party_one_private_share_gen, _comm_witness, ec_key_pair_party1) =
+        party_one::KeyGenFirstMsg::create_commitments();
+    let (party_two_private_share_gen, ec_key_pair_party2) = party_two::KeyGenFirstMsg::create();
+    let keypair =
+        party_one::PaillierKeyPair::generate_keypair_and_encrypted_share(&ec_key_pair_party1);
+    let party1_private = party_one::Party1Private::set_private_key(&ec_key_pair_party1, &keypair);
+    let party2_private = party_two::Party2Private::set_private_key(&ec_key_pair_party2);
+    let pubkey =
+        party_one::compute_pubkey(&party1_private, &party_two_private_share_gen.public_share);
+
+    let (rotation_first_msg, witness) = rotation::RotationFirstMsg::commit();
+    let party_two_rotation_first_msg = rotation::Party2RotationFirstMsg::create();
+    let (rotation_second_msg, party1_private_new) = rotation::RotationSecondMsg::rotate(
+        witness,
+        &party_two_rotation_first_msg,
+        &party1_private,
+    )
+    .expect("rotation failed");
+    let (party2_private_new, paillier_public_new, party_one_public_share_new) = rotation_second_msg
+        .verify_and_rotate(
+            &rotation_first_msg,
+            &party_two_rotation_first_msg,
+            &party2_private,
+            &ec_key_pair_party1.public_share,
+        )
+        .expect("invalid rotation message");
+    assert_ne!(party_one_public_share_new, ec_key_pair_party1.public_share);
+
+    let message = BigInt::from(1234);
+    let signature = sign(
+        &party1_private_new,
+        &party2_private_new,
+        &paillier_public_new,
+        &message,
+    );
+    party_one::verify(&signature, &pubkey, &message).expect("Invalid signature");
+
+    // a stale share of party two does not combine with the rotated share of party one
+    let signature = sign(
+        &party1_private_new,
+        &party2_private,
+        &paillier_public_new,
+        &message,
+    );
+    assert!(party_one::verify(&signature, &pubkey, &message).is_err());
+
+    // party one cannot bias the factor after seeing party two's seed
+    let (mut forged, _) = rotation::RotationSecondMsg::rotate(
+        rotation::RotationFirstMsg::commit().1,
+        &party_two_rotation_first_msg,
+        &party1_private,
+    )
+    .expect("rotation failed");
+    forged.blind_factor = rotation_second_msg.blind_factor.clone();
+    assert_eq!(
+        forged
+            .verify_and_rotate(
+                &rotation_first_msg,
+                &party_two_rotation_first_msg,
+                &party2_private,
+                &ec_key_pair_party1.public_share,
+            )
+            .err(),
+        Some(crate::Error::InvalidCom)
+    );
+}
+
+#[test]
+fn test_presigning() {
+    use crate::utilities::presign::{Presignature, PresignatureStore};
+    use presign::{Party1Presignature, Party2Presignature};
+
+    let (_party_one_private_share_gen, _comm_witness, ec_key_pair_party1) =
+        party_one::KeyGenFirstMsg::create_commitments();
+    let (party_two_private_share_gen, ec_key_pair_party2) = party_two::KeyGenFirstMsg::create();
+    let keypair =
+        party_one::PaillierKeyPair::generate_keypair_and_encrypted_share(&ec_key_pair_party1);
+    let party1_private = party_one::Party1Private::set_private_key(&ec_key_pair_party1, &keypair);
+    let party2_private = party_two::Party2Private::set_private_key(&ec_key_pair_party2);
+    let paillier_public = party_two::PaillierPublic {
+        ek: keypair.ek.clone(),
+        encrypted_secret_share: keypair.encrypted_share.clone(),
+    };
+    let pubkey =
+        party_one::compute_pubkey(&party1_private, &party_two_private_share_gen.public_share);
+
+    let mut party1_store = PresignatureStore::new();
+    let mut party2_store = PresignatureStore::new();
+    for _ in 0..2 {
+        let (eph_party_two_first_message, eph_comm_witness, eph_ec_key_pair_party2) =
+            party_two::EphKeyGenFirstMsg::create_commitments();
+        let (eph_party_one_first_message, eph_ec_key_pair_party1) =
+            party_one::EphKeyGenFirstMsg::create();
+        let (eph_party_two_second_message, party2_presignature) = Party2Presignature::create(
+            eph_comm_witness,
+            &eph_ec_key_pair_party2,
+            &eph_party_one_first_message,
+            &party2_private,
+            &paillier_public,
+        )
+        .expect("party1 DLog proof failed");
+        let party1_presignature = Party1Presignature::create(
+            eph_ec_key_pair_party1,
+            &eph_party_two_first_message,
+            &eph_party_two_second_message,
+        )
+        .expect("failed to verify commitments and DLog proof");
+        assert_eq!(party1_presignature.id(), party2_presignature.id());
+        party1_store.insert(party1_presignature).unwrap();
+        party2_store.insert(party2_presignature).unwrap();
+    }
+
+    // the stores survive being persisted while the parties are offline
+    let mut party1_store: PresignatureStore<Party1Presignature> =
+        serde_json::from_str(&serde_json::to_string(&party1_store).unwrap()).unwrap();
+    let mut party2_store: PresignatureStore<Party2Presignature> =
+        serde_json::from_str(&serde_json::to_string(&party2_store).unwrap()).unwrap();
+    let mut backup = party1_store.clone();
+
+    for i in 0..2 {
+        let message = BigInt::from(1234 + i);
+        let party2_presignature = party2_store.take_next().unwrap();
+        let partial_sig = party2_presignature.sign(&paillier_public, &message);
+        let party1_presignature = party1_store.take(&partial_sig.id).unwrap();
+        let signature = party1_presignature
+            .sign(&party1_private, &pubkey, &message, &partial_sig)
+            .expect("invalid signature");
+        let signature = party_one::Signature {
+            r: signature.r,
+            s: signature.s,
+        };
+        party_one::verify(&signature, &pubkey, &message).expect("Invalid signature");
+
+        // the same nonce is never handed out twice
+        assert!(party1_store.take(&partial_sig.id).is_err());
+    }
+    assert!(party2_store.take_next().is_none());
+
+    // a presignature restored from a stale copy of the store is refused
+    let stale = backup.take_next().unwrap();
+    assert!(party1_store.insert(stale).is_err());
+}
+
+#[test]
+fn test_adaptor_signature() {
+    use curv::elliptic::curves::p256::{FE, GE};
+
+    let (_party_one_private_share_gen, _comm_witness, ec_key_pair_party1) =
+        party_one::KeyGenFirstMsg::create_commitments();
+    let (party_two_private_share_gen, ec_key_pair_party2) = party_two::KeyGenFirstMsg::create();
+    let keypair =
+        party_one::PaillierKeyPair::generate_keypair_and_encrypted_share(&ec_key_pair_party1);
+    let party1_private = party_one::Party1Private::set_private_key(&ec_key_pair_party1, &keypair);
+    let party2_private = party_two::Party2Private::set_private_key(&ec_key_pair_party2);
+    let pubkey =
+        party_one::compute_pubkey(&party1_private, &party_two_private_share_gen.public_share);
+
+    // the secret whose revelation the swap hinges on
+    let adaptor_secret: FE = ECScalar::new_random();
+    let adaptor_point = GE::generator() * adaptor_secret;
+
+    let (eph_party_two_first_message, eph_comm_witness, eph_ec_key_pair_party2) =
+        party_two::EphKeyGenFirstMsg::create_commitments_with_adaptor(&adaptor_point);
+    let (eph_party_one_first_message, eph_ec_key_pair_party1) =
+        party_one::EphKeyGenFirstMsg::create_with_adaptor(&adaptor_point);
+    let eph_party_two_second_message =
+        party_two::EphKeyGenSecondMsg::verify_and_decommit_with_adaptor(
+            eph_comm_witness,
+            &eph_party_one_first_message,
+            &adaptor_point,
+        )
+        .expect("party1 DLog proof failed");
+    party_one::EphKeyGenSecondMsg::verify_commitments_and_dlog_proof_with_adaptor(
+        &eph_party_two_first_message,
+        &eph_party_two_second_message,
+        &adaptor_point,
+    )
+    .expect("failed to verify commitments and DLog proof");
+
+    let message = BigInt::from(1234);
+    let partial_sig = party_two::PartialSig::compute_adaptor(
+        &keypair.ek,
+        &keypair.encrypted_share,
+        &party2_private,
+        &eph_ec_key_pair_party2,
+        &eph_party_one_first_message,
+        &message,
+    );
+    let pre_signature = party_one::Signature::compute_adaptor(
+        &party1_private,
+        &partial_sig.c3,
+        &eph_ec_key_pair_party1,
+        &eph_party_two_second_message,
+    );
+    pre_signature
+        .verify(&pubkey, &message, &adaptor_point)
+        .expect("invalid pre-signature");
+    assert!(pre_signature
+        .verify(&pubkey, &BigInt::from(4321), &adaptor_point)
+        .is_err());
+    let other_secret: FE = ECScalar::new_random();
+    let other_point = GE::generator() * other_secret;
+    assert!(pre_signature
+        .verify(&pubkey, &message, &other_point)
+        .is_err());
+
+    // the pre-signature alone is not a signature
+    let unadapted = party_one::Signature {
+        r: pre_signature.r.clone(),
+        s: pre_signature.s.clone(),
+    };
+    assert!(party_one::verify(&unadapted, &pubkey, &message).is_err());
+
+    let signature = pre_signature.adapt(&adaptor_secret);
+    party_one::verify(&signature, &pubkey, &message).expect("Invalid signature");
+    let extracted = pre_signature
+        .extract(&signature, &adaptor_point)
+        .expect("failed to extract the adaptor secret");
+    assert_eq!(extracted, adaptor_secret);
+}
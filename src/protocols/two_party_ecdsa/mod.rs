@@ -22,3 +22,6 @@ pub mod lindell_2017;
 //Efficient Instantiations (https://eprint.iacr.org/2019/503.pdf)
 #[cfg(feature = "cclst")]
 pub mod cclst_2019;
+
+// Secure Two-party Threshold ECDSA from ECDSA Assumptions (https://eprint.iacr.org/2018/499.pdf)
+pub mod dkls;
@@ -0,0 +1,83 @@
+/*
+    Multi-party ECDSA
+
+    Copyright 2018 by Kzen Networks
+
+    This file is part of Multi-party ECDSA library
+    (https://github.com/KZen-networks/multi-party-ecdsa)
+
+    Multi-party ECDSA is free software: you can redistribute
+    it and/or modify it under the terms of the GNU General Public
+    License as published by the Free Software Foundation, either
+    version 3 of the License, or (at your option) any later version.
+
+    @license GPL-3.0+ <https://github.com/KZen-networks/multi-party-ecdsa/blob/master/LICENSE>
+*/
+
+//! Conversion of raw messages into the integer signed by ECDSA, as specified in FIPS 186-4,
+//! section 6.4: the message is hashed and the leftmost 256 bits of the digest are taken
+//! (bits2int). Signatures over the result verify with standard P-256 verifiers given the
+//! same message and hash function.
+
+use curv::arithmetic::traits::*;
+use curv::elliptic::curves::p256::FE;
+use curv::elliptic::curves::traits::*;
+use curv::BigInt;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256, Sha384};
+use sha3::Sha3_256;
+
+#[cfg(test)]
+mod test;
+
+// byte length of the P-256 group order
+const ORDER_BYTES: usize = 32;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum MessageDigest {
+    Sha256,
+    Sha384,
+    Sha3_256,
+    /// The message is already the output of a hash function.
+    Prehashed,
+}
+
+impl MessageDigest {
+    pub fn hash(self, message: &[u8]) -> Vec<u8> {
+        match self {
+            MessageDigest::Sha256 => Sha256::digest(message).to_vec(),
+            MessageDigest::Sha384 => Sha384::digest(message).to_vec(),
+            MessageDigest::Sha3_256 => Sha3_256::digest(message).to_vec(),
+            MessageDigest::Prehashed => message.to_vec(),
+        }
+    }
+
+    /// Hashes `message` and returns the integer to be passed as `message: &BigInt` to the
+    /// signing and verification functions.
+    pub fn to_message(self, message: &[u8]) -> BigInt {
+        bits2int(&self.hash(message)).mod_floor(&FE::q())
+    }
+}
+
+/// What signers compare before signing: a hash of the integer to be signed and of
+/// application metadata, such as a summary of the transaction, that they must also agree on.
+pub fn signing_intent(message: &BigInt, metadata: &[u8]) -> [u8; 32] {
+    let message = BigInt::to_bytes(&message.mod_floor(&FE::q()));
+    let mut intent = [0u8; 32];
+    let digest = Sha256::new()
+        .chain(b"signing intent")
+        .chain((message.len() as u64).to_be_bytes())
+        .chain(&message)
+        .chain((metadata.len() as u64).to_be_bytes())
+        .chain(metadata)
+        .finalize();
+    intent.copy_from_slice(&digest);
+    intent
+}
+
+/// Interprets the leftmost 256 bits of `digest` as a big endian integer.
+pub fn bits2int(digest: &[u8]) -> BigInt {
+    // the order is a whole number of bytes, so truncating bits is truncating bytes
+    let len = digest.len().min(ORDER_BYTES);
+    BigInt::from_bytes(&digest[..len])
+}
@@ -0,0 +1,78 @@
+use curv::arithmetic::traits::*;
+use curv::elliptic::curves::p256::{FE, GE};
+use curv::elliptic::curves::traits::*;
+use curv::BigInt;
+use p256::ecdsa::{signature::Verifier, VerifyKey};
+
+use crate::utilities::digest::*;
+use crate::utilities::encoding;
+
+fn hex(s: &str) -> BigInt {
+    BigInt::from_str_radix(s, 16).unwrap()
+}
+
+#[test]
+fn test_digest_vectors() {
+    let message = b"abc";
+    assert_eq!(
+        MessageDigest::Sha256.to_message(message),
+        hex("ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad")
+    );
+    // leftmost 256 bits of the 384 bit digest
+    assert_eq!(
+        MessageDigest::Sha384.to_message(message),
+        hex("cb00753f45a35e8bb5a03d699ac65007272c32ab0eded1631a8b605a43ff5bed")
+    );
+    assert_eq!(
+        MessageDigest::Sha3_256.to_message(message),
+        hex("3a985da74fe225b2045c172d6bd390bd855f086e3e9d525b46bfe24511431532")
+    );
+    assert_eq!(
+        MessageDigest::Prehashed.to_message(&MessageDigest::Sha256.hash(message)),
+        MessageDigest::Sha256.to_message(message)
+    );
+}
+
+#[test]
+fn test_bits2int() {
+    assert_eq!(bits2int(&[0x01, 0x02]), BigInt::from(0x0102));
+    let mut long = vec![0xff; 32];
+    long.extend_from_slice(&[0x12; 16]);
+    assert_eq!(bits2int(&long), bits2int(&[0xff; 32]));
+    // the result is reduced when used as a message
+    assert_eq!(
+        MessageDigest::Prehashed.to_message(&[0xff; 32]),
+        bits2int(&[0xff; 32]).mod_floor(&FE::q())
+    );
+}
+
+#[test]
+fn test_p256_verifies_sha256_digest() {
+    let q = FE::q();
+    let x: FE = ECScalar::new_random();
+    let y = GE::generator() * x;
+    let message = b"message to sign";
+
+    let k: FE = ECScalar::new_random();
+    let r: FE = ECScalar::from(&(GE::generator() * k).x_coor().unwrap().mod_floor(&q));
+    let m: FE = ECScalar::from(&MessageDigest::Sha256.to_message(message));
+    let s = k.invert() * (m + r * x);
+
+    let signature = encoding::to_p256(&r.to_big_int(), &s.to_big_int()).unwrap();
+    let verify_key: VerifyKey = y.get_element();
+    assert!(verify_key.verify(message, &signature).is_ok());
+}
+
+#[test]
+fn test_signing_intent() {
+    let message = MessageDigest::Sha256.to_message(b"abc");
+    let intent = signing_intent(&message, b"");
+    assert_eq!(intent, signing_intent(&(&message + &FE::q()), b""));
+    assert_ne!(intent, signing_intent(&(&message + &BigInt::one()), b""));
+    assert_ne!(intent, signing_intent(&message, b"pay 100 to A"));
+    // the lengths are part of the intent, so bytes cannot move between the fields
+    assert_ne!(
+        signing_intent(&BigInt::from(0x0102), &[0x03]),
+        signing_intent(&BigInt::from(0x01), &[0x02, 0x03])
+    );
+}
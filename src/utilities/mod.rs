@@ -1,3 +1,11 @@
+pub mod digest;
+pub mod encoding;
 pub mod mta;
+pub mod ot;
+pub mod paillier_context;
+pub mod parallel;
+pub mod presign;
+pub mod recovery;
+pub mod verification;
 pub mod zk_pdl;
 pub mod zk_pdl_with_slack;
@@ -0,0 +1,181 @@
+/*
+    Multi-party ECDSA
+
+    Copyright 2018 by Kzen Networks
+
+    This file is part of Multi-party ECDSA library
+    (https://github.com/KZen-networks/multi-party-ecdsa)
+
+    Multi-party ECDSA is free software: you can redistribute
+    it and/or modify it under the terms of the GNU General Public
+    License as published by the Free Software Foundation, either
+    version 3 of the License, or (at your option) any later version.
+
+    @license GPL-3.0+ <https://github.com/KZen-networks/multi-party-ecdsa/blob/master/LICENSE>
+*/
+
+//! MtA over CL encryption, in the style of Castagnos, Catalano, Laguillaumie, Savasta and
+//! Tucker, "Bandwidth-efficient threshold EC-DSA" (https://eprint.iacr.org/2020/084).
+//!
+//! The plaintexts live in Z/q for the curve order q, so Bob's answer never wraps around and
+//! no range proofs are needed. Instead Alice proves knowledge of the plaintext and the
+//! randomness of her ciphertext, which reveals nothing about her input. Each party draws a
+//! fresh key pair in a class group all parties share, so there is no Paillier key involved.
+
+use class_group::primitives::cl_dl_public_setup::{
+    decrypt, encrypt, encrypt_predefined_randomness, eval_scal, eval_sum, CLGroup, Ciphertext, PK,
+    SK,
+};
+use curv::arithmetic::traits::*;
+use curv::cryptographic_primitives::proofs::sigma_dlog::DLogProof;
+use curv::elliptic::curves::p256::{FE, GE};
+use curv::elliptic::curves::traits::*;
+use curv::BigInt;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+use super::{verify_b_proofs, MtaBackend};
+use crate::protocols::two_party_ecdsa::cclst_2019::setup::{with_pari, ClSetup, CL_GROUP_BITS};
+use crate::Error::{self, InvalidKey};
+
+const CHALLENGE_BITS: usize = 128;
+
+// the encryption randomness is below 2^CL_GROUP_BITS, so e * r is statistically hidden
+const MASK_BITS: usize = CL_GROUP_BITS + CHALLENGE_BITS + 40;
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ClMessageA {
+    pub pk: PK,
+    pub c: Ciphertext,
+    pub proof: ClEncProof,
+}
+
+/// Proof of knowledge of a and r with c = Enc(a; r): for the challenge e, Alice opens
+/// t + e * c as Enc(z_a; z_r). Sound under the low order assumption in the class group.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct ClEncProof {
+    t: Ciphertext,
+    z_a: FE,
+    z_r: BigInt,
+}
+
+impl ClEncProof {
+    fn prove(group: &CLGroup, pk: &PK, c: &Ciphertext, a: &FE, r: &SK) -> Self {
+        let a_mask: FE = ECScalar::new_random();
+        let r_mask = BigInt::sample(MASK_BITS);
+        let t = encrypt_predefined_randomness(group, pk, &a_mask, &SK::from(r_mask.clone()));
+        let e = challenge(pk, c, &t);
+        let e_fe: FE = ECScalar::from(&e);
+        ClEncProof {
+            t,
+            z_a: a_mask + e_fe * *a,
+            z_r: r_mask + e * r.to_big_int(),
+        }
+    }
+
+    fn verify(&self, group: &CLGroup, pk: &PK, c: &Ciphertext) -> Result<(), Error> {
+        if self.z_r >= BigInt::from(2).pow(MASK_BITS as u32 + 1) {
+            return Err(InvalidKey);
+        }
+        let e = challenge(pk, c, &self.t);
+        let opened =
+            encrypt_predefined_randomness(group, pk, &self.z_a, &SK::from(self.z_r.clone()));
+        let expected = eval_sum(&self.t, &eval_scal(c, &e));
+        if encode(&opened) == encode(&expected) {
+            Ok(())
+        } else {
+            Err(InvalidKey)
+        }
+    }
+}
+
+fn challenge(pk: &PK, c: &Ciphertext, t: &Ciphertext) -> BigInt {
+    let digest = Sha256::new()
+        .chain(b"cl plaintext proof")
+        .chain(encode(pk))
+        .chain(encode(c))
+        .chain(encode(t))
+        .finalize();
+    BigInt::from_bytes(&digest[..CHALLENGE_BITS / 8])
+}
+
+// class group elements are hashed and compared in serialized form, as reduced forms are unique
+fn encode<T: Serialize>(value: &T) -> Vec<u8> {
+    serde_json::to_vec(value).expect("class group elements always serialize")
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct ClMessageB {
+    pub c: Ciphertext,
+    pub b_proof: DLogProof<GE>,
+    pub beta_tag_proof: DLogProof<GE>,
+}
+
+pub struct ClMta {
+    setup: ClSetup,
+    pk: PK,
+    sk: SK,
+}
+
+impl ClMta {
+    /// A fresh key pair in the group of `setup`, which every party must share.
+    pub fn new(setup: &ClSetup) -> Self {
+        let (sk, pk) = with_pari(|| setup.group().keygen());
+        ClMta {
+            setup: setup.clone(),
+            pk,
+            sk,
+        }
+    }
+}
+
+impl MtaBackend for ClMta {
+    type MessageA = ClMessageA;
+    type MessageB = ClMessageB;
+
+    fn message_a(&self, a: &FE) -> ClMessageA {
+        let group = self.setup.group();
+        let (c, proof) = with_pari(|| {
+            let (c, r) = encrypt(group, &self.pk, a);
+            let proof = ClEncProof::prove(group, &self.pk, &c, a, &r);
+            (c, proof)
+        });
+        ClMessageA {
+            pk: self.pk.clone(),
+            c,
+            proof,
+        }
+    }
+
+    fn message_b(
+        &self,
+        _alice: usize,
+        b: &FE,
+        m_a: &ClMessageA,
+    ) -> Result<(ClMessageB, FE), Error> {
+        let group = self.setup.group();
+        let beta_tag: FE = ECScalar::new_random();
+        let c = with_pari(|| -> Result<_, Error> {
+            m_a.proof.verify(group, &m_a.pk, &m_a.c)?;
+            let (c_beta_tag, _) = encrypt(group, &m_a.pk, &beta_tag);
+            Ok(eval_sum(&eval_scal(&m_a.c, &b.to_big_int()), &c_beta_tag))
+        })?;
+        let beta = FE::zero().sub(&beta_tag.get_element());
+        let m_b = ClMessageB {
+            c,
+            b_proof: DLogProof::prove(b),
+            beta_tag_proof: DLogProof::prove(&beta_tag),
+        };
+        Ok((m_b, beta))
+    }
+
+    fn alpha(&self, _bob: usize, m_b: &ClMessageB, a: &FE) -> Result<FE, Error> {
+        let alpha = with_pari(|| decrypt(self.setup.group(), &self.sk, &m_b.c));
+        verify_b_proofs(&m_b.b_proof, &m_b.beta_tag_proof, a, &alpha)?;
+        Ok(alpha)
+    }
+
+    fn b_proof(m_b: &ClMessageB) -> &DLogProof<GE> {
+        &m_b.b_proof
+    }
+}
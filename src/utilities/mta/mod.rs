@@ -15,19 +15,27 @@
 */
 
 /// MtA is descrbied in https://eprint.iacr.org/2019/114.pdf section 3
+use std::fmt::Debug;
+
 use curv::arithmetic::traits::Samplable;
 use curv::cryptographic_primitives::proofs::sigma_dlog::DLogProof;
 use curv::elliptic::curves::p256::{FE, GE};
 use curv::elliptic::curves::traits::*;
 use curv::BigInt;
 use paillier::traits::EncryptWithChosenRandomness;
-use paillier::{Add, Decrypt, Mul};
+use paillier::{Add, Mul};
 use paillier::{DecryptionKey, EncryptionKey, Paillier, Randomness, RawCiphertext, RawPlaintext};
+use serde::de::DeserializeOwned;
 use serde::{Deserialize, Serialize};
 
 use crate::protocols::multi_party_ecdsa::gg_2018::party_i::PartyPrivate;
+use crate::utilities::paillier_context::PaillierContext;
 use crate::Error::{self, InvalidKey};
 
+#[cfg(feature = "cclst")]
+pub mod cl;
+pub mod ot;
+
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct MessageA {
     pub c: BigInt, // paillier encryption
@@ -42,18 +50,13 @@ pub struct MessageB {
 
 impl MessageA {
     pub fn a(a: &FE, alice_ek: &EncryptionKey) -> (Self, BigInt) {
-        let randomness = BigInt::sample_below(&alice_ek.n);
-        let c_a = Paillier::encrypt_with_chosen_randomness(
-            alice_ek,
-            RawPlaintext::from(a.to_big_int()),
-            &Randomness::from(randomness.clone()),
-        );
-        (
-            Self {
-                c: c_a.0.clone().into_owned(),
-            },
-            randomness,
-        )
+        Self::a_with_context(a, &PaillierContext::new(alice_ek))
+    }
+
+    /// Like `a`, taking the encryption randomness from the pool of `alice` if it has any.
+    pub fn a_with_context(a: &FE, alice: &PaillierContext) -> (Self, BigInt) {
+        let (c, randomness) = alice.encrypt(&a.to_big_int());
+        (Self { c }, randomness)
     }
 
     pub fn a_with_predefined_randomness(
@@ -75,29 +78,28 @@ impl MessageA {
 
 impl MessageB {
     pub fn b(b: &FE, alice_ek: &EncryptionKey, c_a: MessageA) -> (Self, FE, BigInt, BigInt) {
-        let beta_tag = BigInt::sample_below(&alice_ek.n);
+        Self::b_with_context(b, &PaillierContext::new(alice_ek), c_a)
+    }
+
+    /// Like `b`, with `alice` a context for her encryption key.
+    pub fn b_with_context(
+        b: &FE,
+        alice: &PaillierContext,
+        c_a: MessageA,
+    ) -> (Self, FE, BigInt, BigInt) {
+        let beta_tag = BigInt::sample_below(&alice.ek().n);
         let beta_tag_fe: FE = ECScalar::from(&beta_tag);
-        let randomness = BigInt::sample_below(&alice_ek.n);
-        let c_beta_tag = Paillier::encrypt_with_chosen_randomness(
-            alice_ek,
-            RawPlaintext::from(beta_tag.clone()),
-            &Randomness::from(randomness.clone()),
-        );
+        let (c_beta_tag, randomness) = alice.encrypt(&beta_tag);
 
-        let b_bn = b.to_big_int();
-        let b_c_a = Paillier::mul(
-            alice_ek,
-            RawCiphertext::from(c_a.c),
-            RawPlaintext::from(b_bn),
-        );
-        let c_b = Paillier::add(alice_ek, b_c_a, c_beta_tag);
+        let b_c_a = alice.mul(&c_a.c, &b.to_big_int());
+        let c_b = alice.add(&b_c_a, &c_beta_tag);
         let beta = FE::zero().sub(&beta_tag_fe.get_element());
         let dlog_proof_b = DLogProof::prove(b);
         let dlog_proof_beta_tag = DLogProof::prove(&beta_tag_fe);
 
         (
             Self {
-                c: c_b.0.clone().into_owned(),
+                c: c_b,
                 b_proof: dlog_proof_b,
                 beta_tag_proof: dlog_proof_beta_tag,
             },
@@ -147,9 +149,18 @@ impl MessageB {
         dk: &DecryptionKey,
         a: &FE,
     ) -> Result<(FE, BigInt), Error> {
-        let alice_share = Paillier::decrypt(dk, &RawCiphertext::from(self.c.clone()));
+        self.verify_proofs_get_alpha_with_context(&PaillierContext::with_decryption_key(dk), a)
+    }
+
+    /// Like `verify_proofs_get_alpha`, with `alice` the context of the local decryption key.
+    pub fn verify_proofs_get_alpha_with_context(
+        &self,
+        alice: &PaillierContext,
+        a: &FE,
+    ) -> Result<(FE, BigInt), Error> {
+        let alice_share = alice.decrypt(&self.c)?;
         let g: GE = ECPoint::generator();
-        let alpha: FE = ECScalar::from(&alice_share.0);
+        let alpha: FE = ECScalar::from(&alice_share);
         let g_alpha = g * alpha;
         let ba_btag = self.b_proof.pk * a + self.beta_tag_proof.pk;
         if DLogProof::verify(&self.b_proof).is_ok()
@@ -157,7 +168,7 @@ impl MessageB {
             // we prove the correctness of the ciphertext using this check and the proof of knowledge of dlog of beta_tag
             && ba_btag.get_element() == g_alpha.get_element()
         {
-            Ok((alpha, alice_share.0.into_owned()))
+            Ok((alpha, alice_share))
         } else {
             Err(InvalidKey)
         }
@@ -191,5 +202,90 @@ impl MessageB {
     }
 }
 
+/// The encryption scheme an MtA runs over. A backend holds the local decryption key and
+/// whatever it needs to answer the MtA of the other parties, so that a protocol runs the
+/// same rounds over Paillier or over class group encryption.
+pub trait MtaBackend: Sync {
+    type MessageA: Debug + Serialize + DeserializeOwned + Send + Sync;
+    type MessageB: Debug + Serialize + DeserializeOwned + Send + Sync;
+
+    /// Alice's encryption of `a`.
+    fn message_a(&self, a: &FE) -> Self::MessageA;
+
+    /// Bob's answer to the `m_a` of party `alice`, together with his share beta.
+    fn message_b(
+        &self,
+        alice: usize,
+        b: &FE,
+        m_a: &Self::MessageA,
+    ) -> Result<(Self::MessageB, FE), Error>;
+
+    /// Alice's share alpha from the answer of party `bob`, once his proofs check out
+    /// against her input `a`.
+    fn alpha(&self, bob: usize, m_b: &Self::MessageB, a: &FE) -> Result<FE, Error>;
+
+    /// Bob's proof of knowledge of b, whose public key is g^b.
+    fn b_proof(m_b: &Self::MessageB) -> &DLogProof<GE>;
+}
+
+// the check of `verify_proofs_get_alpha`: g^alpha = (g^b)^a * g^beta_tag
+fn verify_b_proofs(
+    b_proof: &DLogProof<GE>,
+    beta_tag_proof: &DLogProof<GE>,
+    a: &FE,
+    alpha: &FE,
+) -> Result<(), Error> {
+    let g_alpha = GE::generator() * alpha;
+    let ba_btag = b_proof.pk * a + beta_tag_proof.pk;
+    if DLogProof::verify(b_proof).is_ok()
+        && DLogProof::verify(beta_tag_proof).is_ok()
+        && ba_btag.get_element() == g_alpha.get_element()
+    {
+        Ok(())
+    } else {
+        Err(InvalidKey)
+    }
+}
+
+/// MtA over the Paillier keys of a GG18 keygen.
+pub struct PaillierMta {
+    local: PaillierContext,
+    keys: Vec<PaillierContext>,
+}
+
+impl PaillierMta {
+    /// `local` decrypts the answers to this party, `keys[j]` is the encryption key of party j.
+    pub fn new(local: PaillierContext, keys: &[EncryptionKey]) -> Self {
+        PaillierMta {
+            local,
+            keys: keys.iter().map(PaillierContext::new).collect(),
+        }
+    }
+}
+
+impl MtaBackend for PaillierMta {
+    type MessageA = MessageA;
+    type MessageB = MessageB;
+
+    fn message_a(&self, a: &FE) -> MessageA {
+        MessageA::a_with_context(a, &self.local).0
+    }
+
+    fn message_b(&self, alice: usize, b: &FE, m_a: &MessageA) -> Result<(MessageB, FE), Error> {
+        let alice = self.keys.get(alice).ok_or(InvalidKey)?;
+        let (m_b, beta, _, _) = MessageB::b_with_context(b, alice, m_a.clone());
+        Ok((m_b, beta))
+    }
+
+    fn alpha(&self, _bob: usize, m_b: &MessageB, a: &FE) -> Result<FE, Error> {
+        let (alpha, _) = m_b.verify_proofs_get_alpha_with_context(&self.local, a)?;
+        Ok(alpha)
+    }
+
+    fn b_proof(m_b: &MessageB) -> &DLogProof<GE> {
+        &m_b.b_proof
+    }
+}
+
 #[cfg(test)]
 mod test;
@@ -0,0 +1,390 @@
+/*
+    Multi-party ECDSA
+
+    Copyright 2018 by Kzen Networks
+
+    This file is part of Multi-party ECDSA library
+    (https://github.com/KZen-networks/multi-party-ecdsa)
+
+    Multi-party ECDSA is free software: you can redistribute
+    it and/or modify it under the terms of the GNU General Public
+    License as published by the Free Software Foundation, either
+    version 3 of the License, or (at your option) any later version.
+
+    @license GPL-3.0+ <https://github.com/KZen-networks/multi-party-ecdsa/blob/master/LICENSE>
+*/
+
+//! MtA over oblivious transfer, after Doerner, Kondi, Lee and shelat
+//! (https://eprint.iacr.org/2018/499).
+//!
+//! Alice's input a is encoded as `ENCODED_BITS` bits: `ENCODING_PADDING` random bits rho_i
+//! weighted by public random scalars g_i, followed by the binary digits of
+//! a - sum rho_i g_i. She receives one extended OT per bit, for which Bob offers the pair
+//! (x_i, x_i + b g_i), so the sum of what she receives is a * b + sum x_i. A Bob cheating on
+//! some OTs can at most make the GG18 check of Alice fail depending on a few random bits of
+//! the encoding, which tells him nothing about a.
+//!
+//! The base OTs between every pair of parties are set up once by [`OtMtaSetup`]. From then
+//! on an MtA takes no public key operations apart from the DLog proofs also used with
+//! Paillier, and the resulting [`OtMta`] can be cloned into any number of signing sessions.
+
+use curv::arithmetic::traits::*;
+use curv::cryptographic_primitives::proofs::sigma_dlog::DLogProof;
+use curv::elliptic::curves::p256::{FE, GE};
+use curv::elliptic::curves::traits::*;
+use curv::BigInt;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+use super::{verify_b_proofs, MtaBackend};
+use crate::protocols::threshold_signer::{self, broadcast, p2p, Msg, Session};
+use crate::utilities::ot::{
+    self, BaseOtReceiverMsg, BaseOtSender, BaseOtSenderMsg, ExtensionMsg, ExtensionReceiver,
+    ExtensionSender, Seed, KOS_PADDING,
+};
+use crate::Error::{self, InvalidKey, InvalidMessage};
+
+const SCALAR_BITS: usize = 256;
+
+/// Random bits in the encoding of Alice's input.
+pub const ENCODING_PADDING: usize = 256;
+
+/// Number of OTs, and of field elements in Bob's answer, per MtA.
+pub const ENCODED_BITS: usize = SCALAR_BITS + ENCODING_PADDING;
+
+const EXTENDED_OTS: usize = ENCODED_BITS + KOS_PADDING;
+
+/// Alice's extensions to every other party, all under the same nonce.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct OtMessageA {
+    pub nonce: Seed,
+    pub extensions: Vec<(u16, ExtensionMsg)>,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct OtMessageB {
+    pub nonce: Seed,
+    pub salt: Seed,
+    pub tau: Vec<FE>,
+    pub b_proof: DLogProof<GE>,
+    pub beta_tag_proof: DLogProof<GE>,
+}
+
+// the two directions of the OTs with one other party
+#[derive(Clone, Serialize, Deserialize)]
+struct PeerOt {
+    peer: u16,
+    receiver: ExtensionReceiver,
+    sender: ExtensionSender,
+}
+
+/// The OT setup of a party with every other party of a signing group.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct OtMta {
+    party: u16,
+    encoding_key: Seed,
+    peers: Vec<PeerOt>,
+}
+
+impl OtMta {
+    /// The setup of `party` with the single party `peer`, from base OTs run in both
+    /// directions by some other protocol.
+    pub(crate) fn with_peer(
+        party: u16,
+        peer: u16,
+        receiver: ExtensionReceiver,
+        sender: ExtensionSender,
+    ) -> Self {
+        OtMta {
+            party,
+            encoding_key: ot::random_seed(),
+            peers: vec![PeerOt {
+                peer,
+                receiver,
+                sender,
+            }],
+        }
+    }
+
+    pub fn party(&self) -> u16 {
+        self.party
+    }
+
+    fn peer(&self, j: usize) -> Result<&PeerOt, Error> {
+        self.peers
+            .iter()
+            .find(|p| p.peer as usize == j)
+            .ok_or(InvalidKey)
+    }
+
+    // the choices of Alice for the MtA with `peer`, padding included. The random bits are
+    // derived from the nonce so that they can be recomputed once Bob answers.
+    fn encode(&self, a: &FE, nonce: &Seed, peer: u16) -> Vec<bool> {
+        let mut peer_nonce = [0u8; 32];
+        peer_nonce.copy_from_slice(
+            &Sha256::new()
+                .chain(nonce)
+                .chain(peer.to_be_bytes())
+                .finalize(),
+        );
+        let random = ot::expand(
+            &self.encoding_key,
+            &peer_nonce,
+            (ENCODING_PADDING + KOS_PADDING) / 8,
+        );
+        let random: Vec<bool> = (0..ENCODING_PADDING + KOS_PADDING)
+            .map(|i| (random[i / 8] >> (i % 8)) & 1 == 1)
+            .collect();
+        let (rho, kos_padding) = random.split_at(ENCODING_PADDING);
+        let a_rest = gadget()[SCALAR_BITS..]
+            .iter()
+            .zip(rho)
+            .filter(|(_, rho_i)| **rho_i)
+            .fold(*a, |acc, (g_i, _)| acc.sub(&g_i.get_element()))
+            .to_big_int();
+        (0..SCALAR_BITS)
+            .map(|i| a_rest.test_bit(i))
+            .chain(rho.iter().cloned())
+            .chain(kos_padding.iter().cloned())
+            .collect()
+    }
+}
+
+impl MtaBackend for OtMta {
+    type MessageA = OtMessageA;
+    type MessageB = OtMessageB;
+
+    fn message_a(&self, a: &FE) -> OtMessageA {
+        let nonce = ot::random_seed();
+        let extensions = self
+            .peers
+            .iter()
+            .map(|p| {
+                let choices = self.encode(a, &nonce, p.peer);
+                let (extension, _) = p
+                    .receiver
+                    .extend(&choices, &nonce)
+                    .expect("EXTENDED_OTS is a multiple of 8");
+                (p.peer, extension)
+            })
+            .collect();
+        OtMessageA { nonce, extensions }
+    }
+
+    fn message_b(&self, alice: usize, b: &FE, m_a: &OtMessageA) -> Result<(OtMessageB, FE), Error> {
+        let peer = self.peer(alice)?;
+        let (_, extension) = m_a
+            .extensions
+            .iter()
+            .find(|(receiver, _)| *receiver == self.party)
+            .ok_or(InvalidMessage)?;
+        let q = peer.sender.receive(extension, &m_a.nonce, EXTENDED_OTS)?;
+        let delta = peer.sender.delta();
+        let salt = ot::random_seed();
+        let mut beta_tag = FE::zero();
+        let tau = gadget()
+            .iter()
+            .enumerate()
+            .map(|(i, g_i)| {
+                let x_0 = pad(&salt, i, q[i]);
+                let x_1 = x_0 + *b * *g_i;
+                beta_tag = beta_tag + x_0;
+                x_1.sub(&pad(&salt, i, q[i] ^ delta).get_element())
+            })
+            .collect();
+        let beta = FE::zero().sub(&beta_tag.get_element());
+        let m_b = OtMessageB {
+            nonce: m_a.nonce,
+            salt,
+            tau,
+            b_proof: DLogProof::prove(b),
+            beta_tag_proof: DLogProof::prove(&beta_tag),
+        };
+        Ok((m_b, beta))
+    }
+
+    fn alpha(&self, bob: usize, m_b: &OtMessageB, a: &FE) -> Result<FE, Error> {
+        let peer = self.peer(bob)?;
+        if m_b.tau.len() != ENCODED_BITS {
+            return Err(InvalidMessage);
+        }
+        let t = peer.receiver.rows(&m_b.nonce, EXTENDED_OTS);
+        let choices = self.encode(a, &m_b.nonce, peer.peer);
+        let alpha = m_b
+            .tau
+            .iter()
+            .enumerate()
+            .fold(FE::zero(), |acc, (i, tau_i)| {
+                let received = pad(&m_b.salt, i, t[i]);
+                if choices[i] {
+                    acc + received + *tau_i
+                } else {
+                    acc + received
+                }
+            });
+        verify_b_proofs(&m_b.b_proof, &m_b.beta_tag_proof, a, &alpha)?;
+        Ok(alpha)
+    }
+
+    fn b_proof(m_b: &OtMessageB) -> &DLogProof<GE> {
+        &m_b.b_proof
+    }
+}
+
+// 2^i for the binary digits, then the public random weights of the padding bits
+fn gadget() -> Vec<FE> {
+    (0..ENCODED_BITS)
+        .map(|i| {
+            if i < SCALAR_BITS {
+                ECScalar::from(&(BigInt::one() << i))
+            } else {
+                hash_to_scalar(&[&b"ot mta gadget"[..], &(i as u32).to_be_bytes()])
+            }
+        })
+        .collect()
+}
+
+// the value of an extended OT, from its row and the salt Bob picked for the answer
+fn pad(salt: &Seed, i: usize, row: u128) -> FE {
+    hash_to_scalar(&[&salt[..], &(i as u32).to_be_bytes(), &row.to_be_bytes()])
+}
+
+// 512 bits reduced mod q, so that the bias is negligible
+fn hash_to_scalar(parts: &[&[u8]]) -> FE {
+    let digest = |domain: u8| {
+        parts
+            .iter()
+            .fold(Sha256::new().chain([domain]), |hasher, part| {
+                hasher.chain(part)
+            })
+            .finalize()
+    };
+    let mut bytes = digest(0).to_vec();
+    bytes.extend_from_slice(&digest(1));
+    ECScalar::from(&BigInt::from_bytes(&bytes).mod_floor(&FE::q()))
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub enum OtSetupMessage {
+    BaseOt(BaseOtSenderMsg),
+    BaseOtReply(BaseOtReceiverMsg),
+}
+
+/// Sets up the base OTs between every pair of `parties`, in both directions. Every party
+/// broadcasts one base OT message, then answers each other party's message directly.
+pub struct OtMtaSetup {
+    party: u16,
+    others: Vec<u16>,
+    round: u16,
+    base_ot: BaseOtSender,
+    senders: Vec<ExtensionSender>,
+    output: Option<OtMta>,
+}
+
+impl OtMtaSetup {
+    pub fn new(party: u16, parties: &[u16]) -> Result<Self, Error> {
+        let mut sorted = parties.to_vec();
+        sorted.sort_unstable();
+        sorted.dedup();
+        if sorted.len() != parties.len() || !parties.contains(&party) {
+            return Err(InvalidKey);
+        }
+        Ok(OtMtaSetup {
+            party,
+            others: parties.iter().cloned().filter(|j| *j != party).collect(),
+            round: 0,
+            base_ot: BaseOtSender::new(),
+            senders: Vec::new(),
+            output: None,
+        })
+    }
+}
+
+impl Session for OtMtaSetup {
+    type Message = OtSetupMessage;
+    type Output = OtMta;
+
+    fn party(&self) -> u16 {
+        self.party
+    }
+
+    fn round(&self) -> u16 {
+        self.round
+    }
+
+    fn expected_senders(&self) -> Vec<u16> {
+        match self.round {
+            1 | 2 => self.others.clone(),
+            _ => Vec::new(),
+        }
+    }
+
+    fn proceed(
+        &mut self,
+        incoming: Vec<Msg<OtSetupMessage>>,
+    ) -> Result<Vec<Msg<OtSetupMessage>>, Error> {
+        let party = self.party;
+        let outgoing = match self.round {
+            0 => {
+                let body = OtSetupMessage::BaseOt(self.base_ot.msg.clone());
+                vec![broadcast(party, 1, body)]
+            }
+            1 => {
+                let base_ots =
+                    threshold_signer::collect(incoming, self.party(), 1, &self.others, |body| {
+                        match body {
+                            OtSetupMessage::BaseOt(msg) => Some(msg),
+                            _ => None,
+                        }
+                    })?;
+                let mut outgoing = Vec::new();
+                for (j, msg) in self.others.iter().zip(base_ots) {
+                    let (reply, sender) = ot::base_ot_receive(&msg, *j, party)?;
+                    self.senders.push(sender);
+                    outgoing.push(p2p(party, *j, 2, OtSetupMessage::BaseOtReply(reply)));
+                }
+                outgoing
+            }
+            2 => {
+                let replies =
+                    threshold_signer::collect(incoming, self.party(), 2, &self.others, |body| {
+                        match body {
+                            OtSetupMessage::BaseOtReply(reply) => Some(reply),
+                            _ => None,
+                        }
+                    })?;
+                let senders = std::mem::take(&mut self.senders);
+                let peers = self
+                    .others
+                    .iter()
+                    .zip(replies)
+                    .zip(senders)
+                    .map(|((j, reply), sender)| {
+                        Ok(PeerOt {
+                            peer: *j,
+                            receiver: self.base_ot.finalize(&reply, party, *j)?,
+                            sender,
+                        })
+                    })
+                    .collect::<Result<Vec<PeerOt>, Error>>()?;
+                self.output = Some(OtMta {
+                    party,
+                    encoding_key: ot::random_seed(),
+                    peers,
+                });
+                Vec::new()
+            }
+            _ => return Err(InvalidMessage),
+        };
+        self.round += 1;
+        Ok(outgoing)
+    }
+
+    fn is_finished(&self) -> bool {
+        self.round > 2
+    }
+
+    fn pick_output(&mut self) -> Option<OtMta> {
+        self.output.take()
+    }
+}
@@ -1,4 +1,6 @@
+use crate::utilities::mta::ot::{OtMta, OtMtaSetup};
 use crate::utilities::mta::*;
+use crate::utilities::paillier_context::PaillierContext;
 use curv::elliptic::curves::p256::FE;
 use curv::elliptic::curves::traits::ECScalar;
 use paillier::traits::KeyGeneration;
@@ -18,3 +20,133 @@ fn test_mta() {
     let right = alice_input * bob_input;
     assert_eq!(left.get_element(), right.get_element());
 }
+
+#[test]
+fn test_mta_with_context() {
+    let alice_input: FE = ECScalar::new_random();
+    let (ek_alice, dk_alice) = Paillier::keypair().keys();
+    let alice = PaillierContext::with_decryption_key(&dk_alice);
+    let alice_public = PaillierContext::new(&ek_alice);
+    alice.precompute(1);
+    alice_public.precompute(1);
+
+    let bob_input: FE = ECScalar::new_random();
+    let (m_a, _r) = MessageA::a_with_context(&alice_input, &alice);
+    let (m_b, beta, _, _) = MessageB::b_with_context(&bob_input, &alice_public, m_a);
+    assert_eq!(alice.pool_size() + alice_public.pool_size(), 0);
+    let alpha = m_b
+        .verify_proofs_get_alpha_with_context(&alice, &alice_input)
+        .expect("wrong dlog or m_b");
+    // the plain API decrypts to the same share
+    let alpha_plain = m_b
+        .verify_proofs_get_alpha(&dk_alice, &alice_input)
+        .expect("wrong dlog or m_b");
+    assert_eq!(alpha.1, alpha_plain.1);
+
+    let left = alpha.0 + beta;
+    let right = alice_input * bob_input;
+    assert_eq!(left.get_element(), right.get_element());
+}
+
+#[test]
+fn test_paillier_backend() {
+    let (ek_alice, dk_alice) = Paillier::keypair().keys();
+    let alice = PaillierMta::new(PaillierContext::with_decryption_key(&dk_alice), &[]);
+    let bob = PaillierMta::new(PaillierContext::new(&ek_alice), &[ek_alice.clone()]);
+
+    let alice_input: FE = ECScalar::new_random();
+    let bob_input: FE = ECScalar::new_random();
+    let m_a = alice.message_a(&alice_input);
+    let (m_b, beta) = bob.message_b(0, &bob_input, &m_a).unwrap();
+    assert!(bob.message_b(1, &bob_input, &m_a).is_err());
+    let alpha = alice
+        .alpha(1, &m_b, &alice_input)
+        .expect("wrong dlog or m_b");
+    let left = alpha + beta;
+    let right = alice_input * bob_input;
+    assert_eq!(left.get_element(), right.get_element());
+}
+
+#[cfg(feature = "cclst")]
+#[test]
+fn test_cl_mta() {
+    use crate::protocols::two_party_ecdsa::cclst_2019::setup::ClSetup;
+    use crate::utilities::mta::cl::ClMta;
+
+    let setup = ClSetup::generate();
+    let alice = ClMta::new(&setup);
+    let bob = ClMta::new(&setup);
+
+    let alice_input: FE = ECScalar::new_random();
+    let bob_input: FE = ECScalar::new_random();
+    let m_a = alice.message_a(&alice_input);
+    let (m_b, beta) = bob.message_b(0, &bob_input, &m_a).unwrap();
+    let alpha = alice
+        .alpha(1, &m_b, &alice_input)
+        .expect("wrong dlog or m_b");
+    let left = alpha + beta;
+    let right = alice_input * bob_input;
+    assert_eq!(left.get_element(), right.get_element());
+    // bob's answer only decrypts correctly under alice's key
+    assert!(bob.alpha(1, &m_b, &alice_input).is_err());
+
+    // the proof does not carry over to another ciphertext
+    let mut m_a = alice.message_a(&alice_input);
+    m_a.c = alice.message_a(&bob_input).c;
+    assert!(bob.message_b(0, &bob_input, &m_a).is_err());
+}
+
+fn ot_setup_pair() -> (OtMta, OtMta) {
+    use crate::protocols::threshold_signer::Session;
+
+    let mut alice = OtMtaSetup::new(0, &[0, 1]).unwrap();
+    let mut bob = OtMtaSetup::new(1, &[0, 1]).unwrap();
+    let mut to_bob = alice.proceed(Vec::new()).unwrap();
+    let mut to_alice = bob.proceed(Vec::new()).unwrap();
+    while !alice.is_finished() {
+        let from_bob = bob.proceed(to_bob).unwrap();
+        to_bob = alice.proceed(to_alice).unwrap();
+        to_alice = from_bob;
+    }
+    (alice.pick_output().unwrap(), bob.pick_output().unwrap())
+}
+
+#[test]
+fn test_ot_mta() {
+    let (alice, bob) = ot_setup_pair();
+    let alice_input: FE = ECScalar::new_random();
+    for _ in 0..2 {
+        let bob_input: FE = ECScalar::new_random();
+        let m_a = alice.message_a(&alice_input);
+        let (m_b, beta) = bob.message_b(0, &bob_input, &m_a).unwrap();
+        let alpha = alice
+            .alpha(1, &m_b, &alice_input)
+            .expect("wrong dlog or m_b");
+        let left = alpha + beta;
+        let right = alice_input * bob_input;
+        assert_eq!(left.get_element(), right.get_element());
+    }
+}
+
+#[test]
+fn test_ot_mta_rejects_tampering() {
+    let (alice, bob) = ot_setup_pair();
+    let alice_input: FE = ECScalar::new_random();
+    let bob_input: FE = ECScalar::new_random();
+
+    // an extension with inconsistent choices fails the consistency check
+    let mut m_a = alice.message_a(&alice_input);
+    m_a.extensions[0].1.u[0][0] ^= 1;
+    assert!(bob.message_b(0, &bob_input, &m_a).is_err());
+
+    // Bob offering wrong values in the OTs makes alpha fail the check
+    let m_a = alice.message_a(&alice_input);
+    let (mut m_b, _) = bob.message_b(0, &bob_input, &m_a).unwrap();
+    for tau_i in m_b.tau.iter_mut() {
+        *tau_i = *tau_i + bob_input;
+    }
+    assert!(alice.alpha(1, &m_b, &alice_input).is_err());
+
+    // answers go to the party that asked
+    assert!(alice.message_b(1, &bob_input, &m_a).is_err());
+}
@@ -0,0 +1,373 @@
+/*
+    Multi-party ECDSA
+
+    Copyright 2018 by Kzen Networks
+
+    This file is part of Multi-party ECDSA library
+    (https://github.com/KZen-networks/multi-party-ecdsa)
+
+    Multi-party ECDSA is free software: you can redistribute
+    it and/or modify it under the terms of the GNU General Public
+    License as published by the Free Software Foundation, either
+    version 3 of the License, or (at your option) any later version.
+
+    @license GPL-3.0+ <https://github.com/KZen-networks/multi-party-ecdsa/blob/master/LICENSE>
+*/
+
+//! Oblivious transfer on P-256, for the OT based MtA.
+//!
+//! The base OTs are the "simplest OT" of Chou and Orlandi (https://eprint.iacr.org/2015/267),
+//! with the sender proving knowledge of its secret. They are extended with IKNP: after a
+//! one-time setup of `KAPPA` base OTs per ordered pair of parties, every batch of extended
+//! OTs costs the receiver a single message and both sides only symmetric crypto.
+//!
+//! A malicious receiver could use inconsistent choices across the columns to learn the
+//! sender's correlation, so every extension carries the consistency check of Keller, Orsini
+//! and Scholl (https://eprint.iacr.org/2015/546). As in DKLs18 the challenge is derived by
+//! Fiat-Shamir, and the receiver appends `KOS_PADDING` random choices that hide its real
+//! ones behind the check.
+
+use curv::arithmetic::traits::*;
+use curv::cryptographic_primitives::proofs::sigma_dlog::DLogProof;
+use curv::elliptic::curves::p256::{FE, GE};
+use curv::elliptic::curves::traits::*;
+use curv::BigInt;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+use crate::utilities::encoding::public_key_to_sec1;
+use crate::utilities::recovery::to_bytes_32;
+use crate::Error::{self, InvalidKey, InvalidMessage};
+
+#[cfg(test)]
+mod test;
+
+/// Number of base OTs, and bit length of the sender's correlation.
+pub const KAPPA: usize = 128;
+
+/// Random choices appended to every extension for the consistency check.
+pub const KOS_PADDING: usize = KAPPA + 80;
+
+pub type Seed = [u8; 32];
+
+/// First message of the base OTs, shared by all of them.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct BaseOtSenderMsg {
+    pub a_public: GE,
+    pub proof: DLogProof<GE>,
+}
+
+/// The receiver's answer, one point per base OT.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct BaseOtReceiverMsg {
+    pub b_public: Vec<GE>,
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct BaseOtSender {
+    a: FE,
+    pub msg: BaseOtSenderMsg,
+}
+
+/// A batch of extended OTs: `KAPPA` columns with one bit per OT, and the receiver's answer
+/// to the consistency check.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct ExtensionMsg {
+    pub u: Vec<Vec<u8>>,
+    pub check_x: u128,
+    pub check_t: u128,
+}
+
+/// The receiver of extended OTs, which was the sender of the base OTs.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct ExtensionReceiver {
+    seeds: Vec<(Seed, Seed)>,
+}
+
+/// The sender of extended OTs, which received the base OTs with the bits of `delta` as
+/// choices. For every extended OT it gets a row q_i with q_i = t_i ^ choice_i * delta.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct ExtensionSender {
+    delta: u128,
+    seeds: Vec<Seed>,
+}
+
+impl BaseOtSender {
+    pub fn new() -> Self {
+        let a: FE = ECScalar::new_random();
+        let proof = DLogProof::prove(&a);
+        BaseOtSender {
+            a,
+            msg: BaseOtSenderMsg {
+                a_public: proof.pk,
+                proof,
+            },
+        }
+    }
+
+    /// Derives both seeds of every base OT from the receiver's answer. `sender` and
+    /// `receiver` are the party indexes, which are bound into the seeds.
+    pub fn finalize(
+        &self,
+        reply: &BaseOtReceiverMsg,
+        sender: u16,
+        receiver: u16,
+    ) -> Result<ExtensionReceiver, Error> {
+        if reply.b_public.len() != KAPPA {
+            return Err(InvalidMessage);
+        }
+        let a_public = self.msg.a_public;
+        let a_a = a_public * self.a;
+        let seeds = reply
+            .b_public
+            .iter()
+            .enumerate()
+            .map(|(j, b_public)| {
+                let a_b = b_public * &self.a;
+                let a_b_minus_a_a = a_b.sub_point(&a_a.get_element());
+                (
+                    base_ot_seed(j, sender, receiver, &a_public, b_public, &a_b),
+                    base_ot_seed(j, sender, receiver, &a_public, b_public, &a_b_minus_a_a),
+                )
+            })
+            .collect();
+        Ok(ExtensionReceiver { seeds })
+    }
+}
+
+impl Default for BaseOtSender {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Answers the base OTs of `sender` with a fresh random correlation as choices.
+pub fn base_ot_receive(
+    msg: &BaseOtSenderMsg,
+    sender: u16,
+    receiver: u16,
+) -> Result<(BaseOtReceiverMsg, ExtensionSender), Error> {
+    if DLogProof::verify(&msg.proof).is_err() || msg.proof.pk != msg.a_public {
+        return Err(InvalidKey);
+    }
+    let delta = random_u128();
+    let (b_public, seeds) = (0..KAPPA)
+        .map(|j| {
+            let b: FE = ECScalar::new_random();
+            let b_g = GE::generator() * b;
+            let b_public = if bit(delta, j) {
+                b_g + msg.a_public
+            } else {
+                b_g
+            };
+            let seed = base_ot_seed(
+                j,
+                sender,
+                receiver,
+                &msg.a_public,
+                &b_public,
+                &(msg.a_public * b),
+            );
+            (b_public, seed)
+        })
+        .unzip();
+    Ok((
+        BaseOtReceiverMsg { b_public },
+        ExtensionSender { delta, seeds },
+    ))
+}
+
+impl ExtensionReceiver {
+    /// Extends to one OT per choice, the number of choices being a multiple of 8, or fails
+    /// with `InvalidMessage`. The caller is responsible for appending the `KOS_PADDING`
+    /// random choices and for never reusing `nonce`. Returns the rows t_i along with the
+    /// message.
+    pub fn extend(
+        &self,
+        choices: &[bool],
+        nonce: &Seed,
+    ) -> Result<(ExtensionMsg, Vec<u128>), Error> {
+        let m = choices.len();
+        if m % 8 != 0 {
+            return Err(InvalidMessage);
+        }
+        let r = pack(choices);
+        let mut columns = Vec::with_capacity(KAPPA);
+        let mut u = Vec::with_capacity(KAPPA);
+        for (k0, k1) in &self.seeds {
+            let t0 = expand(k0, nonce, m / 8);
+            let t1 = expand(k1, nonce, m / 8);
+            let u_j = t0
+                .iter()
+                .zip(&t1)
+                .zip(&r)
+                .map(|((t0, t1), r)| t0 ^ t1 ^ r)
+                .collect();
+            columns.push(t0);
+            u.push(u_j);
+        }
+        let t = transpose(&columns, m);
+        let chi = challenges(nonce, &u, m);
+        let mut check_x = 0;
+        let mut check_t = 0;
+        for i in 0..m {
+            if choices[i] {
+                check_x ^= chi[i];
+            }
+            check_t ^= gf_mul(t[i], chi[i]);
+        }
+        let msg = ExtensionMsg {
+            u,
+            check_x,
+            check_t,
+        };
+        Ok((msg, t))
+    }
+
+    /// The rows t_i of an earlier extension of `m` OTs under `nonce`.
+    pub fn rows(&self, nonce: &Seed, m: usize) -> Vec<u128> {
+        let columns: Vec<Vec<u8>> = self
+            .seeds
+            .iter()
+            .map(|(k0, _)| expand(k0, nonce, m / 8))
+            .collect();
+        transpose(&columns, m)
+    }
+}
+
+impl ExtensionSender {
+    pub fn delta(&self) -> u128 {
+        self.delta
+    }
+
+    /// The rows q_i of an extension of `m` OTs, once it passes the consistency check.
+    pub fn receive(&self, msg: &ExtensionMsg, nonce: &Seed, m: usize) -> Result<Vec<u128>, Error> {
+        if m % 8 != 0 || msg.u.len() != KAPPA || msg.u.iter().any(|u_j| u_j.len() != m / 8) {
+            return Err(InvalidMessage);
+        }
+        let columns: Vec<Vec<u8>> = self
+            .seeds
+            .iter()
+            .zip(&msg.u)
+            .enumerate()
+            .map(|(j, (seed, u_j))| {
+                let mut q_j = expand(seed, nonce, m / 8);
+                if bit(self.delta, j) {
+                    q_j.iter_mut().zip(u_j).for_each(|(q, u)| *q ^= u);
+                }
+                q_j
+            })
+            .collect();
+        let q = transpose(&columns, m);
+        let chi = challenges(nonce, &msg.u, m);
+        let check = q
+            .iter()
+            .zip(&chi)
+            .fold(0, |acc, (q_i, chi_i)| acc ^ gf_mul(*q_i, *chi_i));
+        if check != msg.check_t ^ gf_mul(msg.check_x, self.delta) {
+            return Err(InvalidKey);
+        }
+        Ok(q)
+    }
+}
+
+pub fn random_seed() -> Seed {
+    to_bytes_32(&BigInt::sample(256))
+}
+
+fn random_u128() -> u128 {
+    let mut bytes = [0u8; 16];
+    bytes.copy_from_slice(&random_seed()[..16]);
+    u128::from_be_bytes(bytes)
+}
+
+fn bit(x: u128, j: usize) -> bool {
+    (x >> j) & 1 == 1
+}
+
+fn base_ot_seed(j: usize, sender: u16, receiver: u16, a: &GE, b: &GE, key: &GE) -> Seed {
+    let mut seed = [0u8; 32];
+    let digest = Sha256::new()
+        .chain((j as u32).to_be_bytes())
+        .chain(sender.to_be_bytes())
+        .chain(receiver.to_be_bytes())
+        .chain(public_key_to_sec1(a, true))
+        .chain(public_key_to_sec1(b, true))
+        .chain(public_key_to_sec1(key, true))
+        .finalize();
+    seed.copy_from_slice(&digest);
+    seed
+}
+
+// SHA-256 in counter mode
+pub(crate) fn expand(seed: &Seed, nonce: &Seed, len: usize) -> Vec<u8> {
+    let mut out = Vec::with_capacity(len + 32);
+    let mut counter = 0u32;
+    while out.len() < len {
+        let block = Sha256::new()
+            .chain(seed)
+            .chain(nonce)
+            .chain(counter.to_be_bytes())
+            .finalize();
+        out.extend_from_slice(&block);
+        counter += 1;
+    }
+    out.truncate(len);
+    out
+}
+
+fn pack(bits: &[bool]) -> Vec<u8> {
+    let mut bytes = vec![0u8; (bits.len() + 7) / 8];
+    for (i, b) in bits.iter().enumerate() {
+        if *b {
+            bytes[i / 8] |= 1 << (i % 8);
+        }
+    }
+    bytes
+}
+
+// row i of the result holds bit i of every column
+fn transpose(columns: &[Vec<u8>], m: usize) -> Vec<u128> {
+    let mut rows = vec![0u128; m];
+    for (j, column) in columns.iter().enumerate() {
+        for (i, row) in rows.iter_mut().enumerate() {
+            if (column[i / 8] >> (i % 8)) & 1 == 1 {
+                *row |= 1 << j;
+            }
+        }
+    }
+    rows
+}
+
+fn challenges(nonce: &Seed, u: &[Vec<u8>], m: usize) -> Vec<u128> {
+    let seed = u
+        .iter()
+        .fold(Sha256::new().chain(nonce), |hasher, u_j| hasher.chain(u_j))
+        .finalize();
+    (0..m as u32)
+        .map(|i| {
+            let digest = Sha256::new().chain(&seed).chain(i.to_be_bytes()).finalize();
+            let mut bytes = [0u8; 16];
+            bytes.copy_from_slice(&digest[..16]);
+            u128::from_be_bytes(bytes)
+        })
+        .collect()
+}
+
+// multiplication in GF(2^128) modulo x^128 + x^7 + x^2 + x + 1
+fn gf_mul(a: u128, b: u128) -> u128 {
+    let (mut a, mut b) = (a, b);
+    let mut product = 0;
+    while b != 0 {
+        if b & 1 == 1 {
+            product ^= a;
+        }
+        b >>= 1;
+        let carry = a >> 127;
+        a <<= 1;
+        if carry == 1 {
+            a ^= 0x87;
+        }
+    }
+    product
+}
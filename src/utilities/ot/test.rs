@@ -0,0 +1,94 @@
+use super::*;
+
+fn setup() -> (ExtensionReceiver, ExtensionSender) {
+    let sender = BaseOtSender::new();
+    let (reply, extension_sender) = base_ot_receive(&sender.msg, 0, 1).unwrap();
+    let extension_receiver = sender.finalize(&reply, 0, 1).unwrap();
+    (extension_receiver, extension_sender)
+}
+
+fn random_choices(m: usize) -> Vec<bool> {
+    let bits = BigInt::sample(m);
+    (0..m).map(|i| bits.test_bit(i)).collect()
+}
+
+#[test]
+fn test_base_ot() {
+    let (receiver, sender) = setup();
+    for (j, (k0, k1)) in receiver.seeds.iter().enumerate() {
+        let chosen = if bit(sender.delta, j) { k1 } else { k0 };
+        assert_eq!(&sender.seeds[j], chosen);
+        assert_ne!(k0, k1);
+    }
+}
+
+#[test]
+fn test_base_ot_binds_parties() {
+    let sender = BaseOtSender::new();
+    let (reply, extension_sender) = base_ot_receive(&sender.msg, 0, 1).unwrap();
+    let extension_receiver = sender.finalize(&reply, 0, 2).unwrap();
+    let (k0, k1) = &extension_receiver.seeds[0];
+    assert_ne!(&extension_sender.seeds[0], k0);
+    assert_ne!(&extension_sender.seeds[0], k1);
+}
+
+#[test]
+fn test_extension() {
+    let (receiver, sender) = setup();
+    let m = 256 + KOS_PADDING;
+    let choices = random_choices(m);
+    let nonce = random_seed();
+    let (msg, t) = receiver.extend(&choices, &nonce).unwrap();
+    assert_eq!(receiver.rows(&nonce, m), t);
+    let q = sender
+        .receive(&msg, &nonce, m)
+        .expect("consistency check failed");
+    for i in 0..m {
+        let expected = if choices[i] {
+            t[i] ^ sender.delta()
+        } else {
+            t[i]
+        };
+        assert_eq!(q[i], expected);
+    }
+}
+
+#[test]
+fn test_extension_consistency_check() {
+    let (receiver, sender) = setup();
+    let m = 256 + KOS_PADDING;
+    let nonce = random_seed();
+    let (mut msg, _) = receiver.extend(&random_choices(m), &nonce).unwrap();
+    // a different choice bit in a single column
+    msg.u[5][3] ^= 1;
+    assert_eq!(sender.receive(&msg, &nonce, m).err(), Some(InvalidKey));
+    msg.u[5][3] ^= 1;
+    assert!(sender.receive(&msg, &random_seed(), m).is_err());
+    assert_eq!(
+        sender.receive(&msg, &nonce, m + 8).err(),
+        Some(InvalidMessage)
+    );
+}
+
+#[test]
+fn test_extension_rejects_partial_bytes() {
+    let (receiver, _) = setup();
+    let choices = random_choices(256 + KOS_PADDING + 1);
+    assert_eq!(
+        receiver.extend(&choices, &random_seed()).err(),
+        Some(InvalidMessage)
+    );
+}
+
+#[test]
+fn test_gf_mul() {
+    let a = random_u128();
+    let b = random_u128();
+    let c = random_u128();
+    assert_eq!(gf_mul(a, 1), a);
+    assert_eq!(gf_mul(a, b), gf_mul(b, a));
+    assert_eq!(gf_mul(a, b ^ c), gf_mul(a, b) ^ gf_mul(a, c));
+    assert_eq!(gf_mul(gf_mul(a, b), c), gf_mul(a, gf_mul(b, c)));
+    // x^127 * x = x^7 + x^2 + x + 1
+    assert_eq!(gf_mul(1 << 127, 2), 0x87);
+}
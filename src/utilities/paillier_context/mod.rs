@@ -0,0 +1,200 @@
+/*
+    Multi-party ECDSA
+
+    Copyright 2018 by Kzen Networks
+
+    This file is part of Multi-party ECDSA library
+    (https://github.com/KZen-networks/multi-party-ecdsa)
+
+    Multi-party ECDSA is free software: you can redistribute
+    it and/or modify it under the terms of the GNU General Public
+    License as published by the Free Software Foundation, either
+    version 3 of the License, or (at your option) any later version.
+
+    @license GPL-3.0+ <https://github.com/KZen-networks/multi-party-ecdsa/blob/master/LICENSE>
+*/
+
+//! Paillier encryption and decryption under a fixed key, faster than the generic
+//! `paillier::Paillier` functions.
+//!
+//! For the local key the CRT parameters are computed once, which makes decryption and the
+//! r^N exponentiation about four times cheaper. For any key a pool of r^N mod N^2 values can
+//! be filled offline with [`PaillierContext::precompute`]; an encryption then costs a single
+//! multiplication. Every pooled value is used at most once, the pool is zeroized as it is
+//! consumed and when the context is dropped.
+
+use std::sync::Mutex;
+
+use curv::arithmetic::traits::*;
+use curv::BigInt;
+use paillier::{DecryptionKey, EncryptionKey};
+use zeroize::Zeroize;
+
+use crate::Error::{self, InvalidKey};
+
+#[cfg(test)]
+mod test;
+
+struct Crt {
+    p: BigInt,
+    q: BigInt,
+    pp: BigInt,
+    qq: BigInt,
+    // N reduced mod phi(p^2) = p(p - 1) and mod phi(q^2)
+    n_mod_phi_pp: BigInt,
+    n_mod_phi_qq: BigInt,
+    // L_p(g^(p-1) mod p^2)^-1 mod p for g = N + 1, and the same for q
+    hp: BigInt,
+    hq: BigInt,
+    p_inv_q: BigInt,
+    pp_inv_qq: BigInt,
+}
+
+pub struct PaillierContext {
+    ek: EncryptionKey,
+    crt: Option<Crt>,
+    // pairs (r, r^N mod N^2)
+    pool: Mutex<Vec<(BigInt, BigInt)>>,
+}
+
+impl PaillierContext {
+    /// Context for encrypting under someone else's key.
+    pub fn new(ek: &EncryptionKey) -> Self {
+        PaillierContext {
+            ek: ek.clone(),
+            crt: None,
+            pool: Mutex::new(Vec::new()),
+        }
+    }
+
+    /// Context for the local key, able to decrypt.
+    pub fn with_decryption_key(dk: &DecryptionKey) -> Self {
+        let (p, q) = (dk.p.clone(), dk.q.clone());
+        let n = &p * &q;
+        let ek = EncryptionKey {
+            nn: &n * &n,
+            n: n.clone(),
+        };
+        let pp = &p * &p;
+        let qq = &q * &q;
+        let one = BigInt::one();
+        let h = |prime: &BigInt, square: &BigInt| {
+            // g^(p-1) = 1 + (p - 1) N mod p^2
+            let g_p = (&one + (prime - &one) * &n).mod_floor(square);
+            let l = (g_p - &one).div_floor(prime);
+            BigInt::mod_inv(&l, prime).unwrap()
+        };
+        let crt = Crt {
+            n_mod_phi_pp: n.mod_floor(&(&pp - &p)),
+            n_mod_phi_qq: n.mod_floor(&(&qq - &q)),
+            hp: h(&p, &pp),
+            hq: h(&q, &qq),
+            p_inv_q: BigInt::mod_inv(&p, &q).unwrap(),
+            pp_inv_qq: BigInt::mod_inv(&pp, &qq).unwrap(),
+            p,
+            q,
+            pp,
+            qq,
+        };
+        PaillierContext {
+            ek,
+            crt: Some(crt),
+            pool: Mutex::new(Vec::new()),
+        }
+    }
+
+    pub fn ek(&self) -> &EncryptionKey {
+        &self.ek
+    }
+
+    /// Adds `count` fresh randomness values to the pool.
+    pub fn precompute(&self, count: usize) {
+        let values: Vec<(BigInt, BigInt)> = (0..count)
+            .map(|_| {
+                let r = BigInt::sample_below(&self.ek.n);
+                let rn = self.pow_n(&r);
+                (r, rn)
+            })
+            .collect();
+        self.pool.lock().unwrap().extend(values);
+    }
+
+    pub fn pool_size(&self) -> usize {
+        self.pool.lock().unwrap().len()
+    }
+
+    /// Encrypts `m` with fresh randomness, taken from the pool if it is not empty, and
+    /// returns the ciphertext and the randomness, which the caller has to zeroize.
+    pub fn encrypt(&self, m: &BigInt) -> (BigInt, BigInt) {
+        let pooled = self.pool.lock().unwrap().pop();
+        let (r, mut rn) = pooled.unwrap_or_else(|| {
+            let r = BigInt::sample_below(&self.ek.n);
+            let rn = self.pow_n(&r);
+            (r, rn)
+        });
+        let c = self.encrypt_with_rn(m, &rn);
+        rn.zeroize();
+        (c, r)
+    }
+
+    pub fn encrypt_with_chosen_randomness(&self, m: &BigInt, r: &BigInt) -> BigInt {
+        self.encrypt_with_rn(m, &self.pow_n(r))
+    }
+
+    /// Homomorphic c^k, i.e. an encryption of k times the plaintext.
+    pub fn mul(&self, c: &BigInt, k: &BigInt) -> BigInt {
+        BigInt::mod_pow(c, k, &self.ek.nn)
+    }
+
+    /// Homomorphic c1 c2, i.e. an encryption of the sum of the plaintexts.
+    pub fn add(&self, c1: &BigInt, c2: &BigInt) -> BigInt {
+        BigInt::mod_mul(c1, c2, &self.ek.nn)
+    }
+
+    pub fn decrypt(&self, c: &BigInt) -> Result<BigInt, Error> {
+        let crt = self.crt.as_ref().ok_or(InvalidKey)?;
+        let one = BigInt::one();
+        let m_p = {
+            let c_p = BigInt::mod_pow(&c.mod_floor(&crt.pp), &(&crt.p - &one), &crt.pp);
+            let l = (c_p - &one).div_floor(&crt.p);
+            BigInt::mod_mul(&l, &crt.hp, &crt.p)
+        };
+        let m_q = {
+            let c_q = BigInt::mod_pow(&c.mod_floor(&crt.qq), &(&crt.q - &one), &crt.qq);
+            let l = (c_q - &one).div_floor(&crt.q);
+            BigInt::mod_mul(&l, &crt.hq, &crt.q)
+        };
+        // m = m_p + p ((m_q - m_p) p^-1 mod q)
+        let t = BigInt::mod_mul(&(m_q - &m_p).mod_floor(&crt.q), &crt.p_inv_q, &crt.q);
+        Ok(m_p + &crt.p * t)
+    }
+
+    // (1 + mN) r^N mod N^2
+    fn encrypt_with_rn(&self, m: &BigInt, rn: &BigInt) -> BigInt {
+        let gm = (BigInt::one() + m * &self.ek.n).mod_floor(&self.ek.nn);
+        BigInt::mod_mul(&gm, rn, &self.ek.nn)
+    }
+
+    fn pow_n(&self, r: &BigInt) -> BigInt {
+        match &self.crt {
+            None => BigInt::mod_pow(r, &self.ek.n, &self.ek.nn),
+            Some(crt) => {
+                let a = BigInt::mod_pow(&r.mod_floor(&crt.pp), &crt.n_mod_phi_pp, &crt.pp);
+                let b = BigInt::mod_pow(&r.mod_floor(&crt.qq), &crt.n_mod_phi_qq, &crt.qq);
+                // x = a + p^2 ((b - a) (p^2)^-1 mod q^2)
+                let t = BigInt::mod_mul(&(b - &a).mod_floor(&crt.qq), &crt.pp_inv_qq, &crt.qq);
+                a + &crt.pp * t
+            }
+        }
+    }
+}
+
+impl Drop for PaillierContext {
+    fn drop(&mut self) {
+        let pool = self.pool.get_mut().unwrap_or_else(|e| e.into_inner());
+        for (r, rn) in pool.iter_mut() {
+            r.zeroize();
+            rn.zeroize();
+        }
+    }
+}
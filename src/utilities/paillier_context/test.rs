@@ -0,0 +1,67 @@
+use curv::arithmetic::traits::*;
+use curv::BigInt;
+use paillier::traits::{EncryptWithChosenRandomness, KeyGeneration};
+use paillier::{Decrypt, Paillier, Randomness, RawCiphertext, RawPlaintext};
+
+use crate::utilities::paillier_context::*;
+
+#[test]
+fn test_matches_paillier() {
+    let (ek, dk) = Paillier::keypair().keys();
+    let local = PaillierContext::with_decryption_key(&dk);
+    let remote = PaillierContext::new(&ek);
+    assert_eq!(local.ek().n, ek.n);
+    assert_eq!(local.ek().nn, ek.nn);
+
+    let m = BigInt::sample_below(&ek.n);
+    let r = BigInt::sample_below(&ek.n);
+    let expected = Paillier::encrypt_with_chosen_randomness(
+        &ek,
+        RawPlaintext::from(m.clone()),
+        &Randomness::from(r.clone()),
+    )
+    .0
+    .into_owned();
+    assert_eq!(local.encrypt_with_chosen_randomness(&m, &r), expected);
+    assert_eq!(remote.encrypt_with_chosen_randomness(&m, &r), expected);
+
+    let decrypted: RawPlaintext = Paillier::decrypt(&dk, &RawCiphertext::from(expected.clone()));
+    assert_eq!(local.decrypt(&expected).unwrap(), decrypted.0.into_owned());
+    assert_eq!(local.decrypt(&expected).unwrap(), m);
+    assert!(remote.decrypt(&expected).is_err());
+}
+
+#[test]
+fn test_pool() {
+    let (ek, dk) = Paillier::keypair().keys();
+    let local = PaillierContext::with_decryption_key(&dk);
+    let remote = PaillierContext::new(&ek);
+    remote.precompute(3);
+    assert_eq!(remote.pool_size(), 3);
+
+    let mut randomness = Vec::new();
+    for i in 0..4 {
+        let m = BigInt::from(i);
+        let (c, r) = remote.encrypt(&m);
+        assert_eq!(local.decrypt(&c).unwrap(), m);
+        assert_eq!(remote.encrypt_with_chosen_randomness(&m, &r), c);
+        randomness.push(r);
+    }
+    // the pool ran dry after three encryptions and no value was used twice
+    assert_eq!(remote.pool_size(), 0);
+    randomness.sort();
+    randomness.dedup();
+    assert_eq!(randomness.len(), 4);
+}
+
+#[test]
+fn test_homomorphic_operations() {
+    let (_, dk) = Paillier::keypair().keys();
+    let ctx = PaillierContext::with_decryption_key(&dk);
+    let (c1, _) = ctx.encrypt(&BigInt::from(20));
+    let (c2, _) = ctx.encrypt(&BigInt::from(22));
+    let sum = ctx.add(&c1, &c2);
+    assert_eq!(ctx.decrypt(&sum).unwrap(), BigInt::from(42));
+    let product = ctx.mul(&c1, &BigInt::from(3));
+    assert_eq!(ctx.decrypt(&product).unwrap(), BigInt::from(60));
+}
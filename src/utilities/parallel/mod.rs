@@ -0,0 +1,76 @@
+/*
+    Multi-party ECDSA
+
+    Copyright 2018 by Kzen Networks
+
+    This file is part of Multi-party ECDSA library
+    (https://github.com/KZen-networks/multi-party-ecdsa)
+
+    Multi-party ECDSA is free software: you can redistribute
+    it and/or modify it under the terms of the GNU General Public
+    License as published by the Free Software Foundation, either
+    version 3 of the License, or (at your option) any later version.
+
+    @license GPL-3.0+ <https://github.com/KZen-networks/multi-party-ecdsa/blob/master/LICENSE>
+*/
+
+//! Verification of independent per-party proofs. With the `rayon` feature the checks run on
+//! the rayon thread pool, otherwise sequentially. Either way the reported failure is the one
+//! with the lowest index, so errors do not depend on scheduling.
+
+use crate::Error;
+
+#[cfg(feature = "rayon")]
+use rayon::prelude::*;
+
+#[cfg(test)]
+mod test;
+
+/// Returns the lowest i in 0..n for which `check(i)` is false.
+pub fn first_failure<F>(n: usize, check: F) -> Option<usize>
+where
+    F: Fn(usize) -> bool + Sync + Send,
+{
+    #[cfg(feature = "rayon")]
+    {
+        (0..n).into_par_iter().filter(|i| !check(*i)).min()
+    }
+    #[cfg(not(feature = "rayon"))]
+    {
+        (0..n).find(|i| !check(*i))
+    }
+}
+
+/// Maps `f` over 0..n and returns the results in order.
+pub fn map<T, F>(n: usize, f: F) -> Vec<T>
+where
+    T: Send,
+    F: Fn(usize) -> T + Sync + Send,
+{
+    #[cfg(feature = "rayon")]
+    {
+        (0..n).into_par_iter().map(f).collect()
+    }
+    #[cfg(not(feature = "rayon"))]
+    {
+        (0..n).map(f).collect()
+    }
+}
+
+/// Maps `f` over 0..n and returns the results in order, or the error of the lowest failing
+/// index.
+pub fn try_map<T, F>(n: usize, f: F) -> Result<Vec<T>, Error>
+where
+    T: Send,
+    F: Fn(usize) -> Result<T, Error> + Sync + Send,
+{
+    #[cfg(feature = "rayon")]
+    {
+        let results: Vec<Result<T, Error>> = (0..n).into_par_iter().map(f).collect();
+        results.into_iter().collect()
+    }
+    #[cfg(not(feature = "rayon"))]
+    {
+        (0..n).map(f).collect()
+    }
+}
@@ -0,0 +1,24 @@
+use crate::utilities::parallel::*;
+use crate::Error::{InvalidKey, InvalidSig};
+
+#[test]
+fn test_first_failure() {
+    assert_eq!(first_failure(100, |_| true), None);
+    assert_eq!(first_failure(100, |i| i % 7 != 3), Some(3));
+    assert_eq!(first_failure(0, |_| false), None);
+}
+
+#[test]
+fn test_try_map() {
+    assert_eq!(
+        try_map(50, |i| Ok(i * 2)),
+        Ok((0..50).map(|i| i * 2).collect())
+    );
+    // the lowest failing index wins, however the work was scheduled
+    let result: Result<Vec<usize>, _> = try_map(50, |i| match i {
+        10 => Err(InvalidSig),
+        i if i > 20 => Err(InvalidKey),
+        i => Ok(i),
+    });
+    assert_eq!(result, Err(InvalidSig));
+}
@@ -0,0 +1,95 @@
+/*
+    Multi-party ECDSA
+
+    Copyright 2018 by Kzen Networks
+
+    This file is part of Multi-party ECDSA library
+    (https://github.com/KZen-networks/multi-party-ecdsa)
+
+    Multi-party ECDSA is free software: you can redistribute
+    it and/or modify it under the terms of the GNU General Public
+    License as published by the Free Software Foundation, either
+    version 3 of the License, or (at your option) any later version.
+
+    @license GPL-3.0+ <https://github.com/KZen-networks/multi-party-ecdsa/blob/master/LICENSE>
+*/
+
+//! Bookkeeping of presignatures, shared by the protocols that sign with precomputed nonces.
+//! A presignature must never be used twice, two signatures with the same nonce give away
+//! the key.
+
+use curv::BigInt;
+use serde::{Deserialize, Serialize};
+
+use crate::Error::{self, InvalidMessage};
+
+/// Identifies a presignature on all sides, as a hash of its public nonce shares.
+pub type PresignatureId = BigInt;
+
+pub trait Presignature {
+    fn id(&self) -> PresignatureId;
+}
+
+/// Unused presignatures of one party, together with the ids of all presignatures that were
+/// taken out. Persist the whole store, not single presignatures, so that a restored store
+/// still refuses the ids it already handed out.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct PresignatureStore<T> {
+    unused: Vec<T>,
+    used: Vec<PresignatureId>,
+}
+
+impl<T: Presignature> PresignatureStore<T> {
+    pub fn new() -> Self {
+        PresignatureStore {
+            unused: Vec::new(),
+            used: Vec::new(),
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.unused.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.unused.is_empty()
+    }
+
+    /// Fails if a presignature with the same id is stored or was used already.
+    pub fn insert(&mut self, presignature: T) -> Result<(), Error> {
+        let id = presignature.id();
+        if self.used.contains(&id) || self.unused.iter().any(|p| p.id() == id) {
+            return Err(InvalidMessage);
+        }
+        self.unused.push(presignature);
+        Ok(())
+    }
+
+    /// Takes out the presignature with the given id, e.g. for Lindell's party one answering
+    /// party two.
+    pub fn take(&mut self, id: &PresignatureId) -> Result<T, Error> {
+        let position = self
+            .unused
+            .iter()
+            .position(|p| p.id() == *id)
+            .ok_or(InvalidMessage)?;
+        self.used.push(id.clone());
+        Ok(self.unused.remove(position))
+    }
+
+    /// Takes out the oldest presignature, e.g. for Lindell's party two starting a signature.
+    pub fn take_next(&mut self) -> Option<T> {
+        if self.unused.is_empty() {
+            return None;
+        }
+        let presignature = self.unused.remove(0);
+        self.used.push(presignature.id());
+        Some(presignature)
+    }
+}
+
+impl<T: Presignature> Default for PresignatureStore<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -0,0 +1,164 @@
+#![allow(non_snake_case)]
+/*
+    Multi-party ECDSA
+
+    Copyright 2018 by Kzen Networks
+
+    This file is part of Multi-party ECDSA library
+    (https://github.com/KZen-networks/multi-party-ecdsa)
+
+    Multi-party ECDSA is free software: you can redistribute
+    it and/or modify it under the terms of the GNU General Public
+    License as published by the Free Software Foundation, either
+    version 3 of the License, or (at your option) any later version.
+
+    @license GPL-3.0+ <https://github.com/KZen-networks/multi-party-ecdsa/blob/master/LICENSE>
+*/
+
+//! Public key recovery from (r, s, recid) as described in SEC 1 v2, section 4.1.6, and the
+//! compact 65 bytes r || s || v encoding of recoverable signatures.
+//!
+//! The recovery id follows the convention used by the signing protocols of this crate:
+//! bit 0 is the parity of R.y (after low-s normalization), bit 1 is set if R.x >= q, i.e.
+//! r = R.x - q.
+
+use curv::arithmetic::traits::*;
+use curv::elliptic::curves::p256::{FE, GE};
+use curv::elliptic::curves::traits::*;
+use curv::BigInt;
+
+use crate::utilities::encoding;
+use crate::Error::{self, InvalidSig};
+
+pub const COMPACT_SIGNATURE_SIZE: usize = 65;
+
+// P-256 base field prime and curve coefficient b (a = -3)
+const FIELD_PRIME: &str = "ffffffff00000001000000000000000000000000ffffffffffffffffffffffff";
+const CURVE_B: &str = "5ac635d8aa3a93e7b3ebbd55769886bc651d06b0cc53b0f63bce3c3e27d2604b";
+
+pub fn field_prime() -> BigInt {
+    BigInt::from_str_radix(FIELD_PRIME, 16).unwrap()
+}
+
+/// Computes the recovery id of a signature whose nonce point is `R`. `s_was_high` tells
+/// whether s was replaced by q - s during low-s normalization.
+pub fn compute_recid(R: &GE, s_was_high: bool) -> u8 {
+    let mut recid = if R.y_coor().unwrap().test_bit(0) { 1 } else { 0 };
+    if s_was_high {
+        recid ^= 1;
+    }
+    if R.x_coor().unwrap() >= FE::q() {
+        recid |= 2;
+    }
+    recid
+}
+
+/// Rebuilds the nonce point R from r and the recovery id.
+pub fn recover_r_point(r: &BigInt, recid: u8) -> Result<GE, Error> {
+    if recid > 3 {
+        return Err(InvalidSig);
+    }
+    let p = field_prime();
+    let x = if recid & 2 == 2 {
+        r + FE::q()
+    } else {
+        r.clone()
+    };
+    if x >= p {
+        return Err(InvalidSig);
+    }
+
+    // y^2 = x^3 - 3x + b, and since p = 3 mod 4 a square root is rhs^((p + 1) / 4)
+    let b = BigInt::from_str_radix(CURVE_B, 16).unwrap();
+    let rhs = (BigInt::mod_pow(&x, &BigInt::from(3), &p) + &b + (&p - BigInt::from(3)) * &x)
+        .mod_floor(&p);
+    let exp = (&p + BigInt::one()).div_floor(&BigInt::from(4));
+    let mut y = BigInt::mod_pow(&rhs, &exp, &p);
+    if BigInt::mod_mul(&y, &y, &p) != rhs {
+        return Err(InvalidSig);
+    }
+    if y.test_bit(0) != (recid & 1 == 1) {
+        y = &p - &y;
+    }
+
+    let mut bytes = vec![4u8];
+    bytes.extend_from_slice(&to_bytes_32(&x));
+    bytes.extend_from_slice(&to_bytes_32(&y));
+    GE::from_bytes(&bytes).map_err(|_| InvalidSig)
+}
+
+/// Recovers the public key Q = r^-1 (sR - mG) of a signature on `message`.
+pub fn recover_public_key(
+    r: &BigInt,
+    s: &BigInt,
+    recid: u8,
+    message: &BigInt,
+) -> Result<GE, Error> {
+    let q = FE::q();
+    let zero = BigInt::zero();
+    if r == &zero || s == &zero || r >= &q || s >= &q {
+        return Err(InvalidSig);
+    }
+    let R = recover_r_point(r, recid)?;
+    let r_fe: FE = ECScalar::from(r);
+    let s_fe: FE = ECScalar::from(s);
+    let r_inv = r_fe.invert();
+    let m = message.mod_floor(&q);
+
+    let sR = R * s_fe;
+    let Q = if m == zero {
+        sR * r_inv
+    } else {
+        let m_fe: FE = ECScalar::from(&m);
+        let g: GE = ECPoint::generator();
+        sR.sub_point(&(g * m_fe).get_element()) * r_inv
+    };
+    Ok(Q)
+}
+
+/// r || s || v with r and s as 32 bytes big endian and v the raw recovery id (0..=3). s is
+/// normalized to the low half, which flips bit 0 of the recovery id.
+pub fn to_compact(
+    r: &BigInt,
+    s: &BigInt,
+    recid: u8,
+) -> Result<[u8; COMPACT_SIGNATURE_SIZE], Error> {
+    check_compact(r, s, recid)?;
+    let (s, s_was_high) = encoding::normalize_s(s);
+    let mut bytes = [0u8; COMPACT_SIGNATURE_SIZE];
+    bytes[..32].copy_from_slice(&to_bytes_32(r));
+    bytes[32..64].copy_from_slice(&to_bytes_32(&s));
+    bytes[64] = if s_was_high { recid ^ 1 } else { recid };
+    Ok(bytes)
+}
+
+pub fn from_compact(bytes: &[u8]) -> Result<(BigInt, BigInt, u8), Error> {
+    if bytes.len() != COMPACT_SIGNATURE_SIZE {
+        return Err(InvalidSig);
+    }
+    let r = BigInt::from_bytes(&bytes[..32]);
+    let s = BigInt::from_bytes(&bytes[32..64]);
+    check_compact(&r, &s, bytes[64])?;
+    let (s, s_was_high) = encoding::normalize_s(&s);
+    let recid = if s_was_high { bytes[64] ^ 1 } else { bytes[64] };
+    Ok((r, s, recid))
+}
+
+fn check_compact(r: &BigInt, s: &BigInt, recid: u8) -> Result<(), Error> {
+    let q = FE::q();
+    let zero = BigInt::zero();
+    if recid > 3 || r <= &zero || s <= &zero || r >= &q || s >= &q {
+        return Err(InvalidSig);
+    }
+    Ok(())
+}
+
+pub(crate) fn to_bytes_32(n: &BigInt) -> [u8; 32] {
+    let bytes = BigInt::to_bytes(n);
+    let mut out = [0u8; 32];
+    out[32 - bytes.len()..].copy_from_slice(&bytes);
+    out
+}
+
+#[cfg(test)]
+mod test;
@@ -0,0 +1,77 @@
+#![allow(non_snake_case)]
+
+use curv::arithmetic::traits::*;
+use curv::elliptic::curves::p256::{FE, GE};
+use curv::elliptic::curves::traits::*;
+use curv::BigInt;
+
+use crate::utilities::recovery::*;
+
+// plain single party ECDSA with low-s normalization
+fn sign(x: &FE, message: &BigInt) -> (BigInt, BigInt, u8) {
+    let q = FE::q();
+    let k: FE = ECScalar::new_random();
+    let R = GE::generator() * k;
+    let r: FE = ECScalar::from(&R.x_coor().unwrap().mod_floor(&q));
+    let m: FE = ECScalar::from(&message.mod_floor(&q));
+    let s = k.invert() * (m + r * x);
+    let s_bn = s.to_big_int();
+    let s_high = s_bn > &q - &s_bn;
+    let s_bn = if s_high { &q - &s_bn } else { s_bn };
+    (r.to_big_int(), s_bn, compute_recid(&R, s_high))
+}
+
+#[test]
+fn test_recover_public_key() {
+    for _ in 0..10 {
+        let x: FE = ECScalar::new_random();
+        let y = GE::generator() * x;
+        let message = BigInt::sample(256);
+        let (r, s, recid) = sign(&x, &message);
+        let recovered = recover_public_key(&r, &s, recid, &message).expect("recovery failed");
+        assert_eq!(recovered, y);
+    }
+}
+
+#[test]
+fn test_recover_with_wrong_recid_gives_other_key() {
+    let x: FE = ECScalar::new_random();
+    let y = GE::generator() * x;
+    let message = BigInt::from(1234);
+    let (r, s, recid) = sign(&x, &message);
+    let recovered = recover_public_key(&r, &s, recid ^ 1, &message).expect("recovery failed");
+    assert_ne!(recovered, y);
+}
+
+#[test]
+fn test_recover_r_point_rejects_x_overflow() {
+    // r + q exceeds the field prime for all but a negligible fraction of r values
+    let r = &field_prime() - &FE::q();
+    assert!(recover_r_point(&r, 2).is_err());
+    assert!(recover_r_point(&r, 4).is_err());
+}
+
+#[test]
+fn test_compact_encoding() {
+    let x: FE = ECScalar::new_random();
+    let message = BigInt::from(1234);
+    let (r, s, recid) = sign(&x, &message);
+    let bytes = to_compact(&r, &s, recid).expect("bad signature");
+    assert_eq!(bytes.len(), COMPACT_SIGNATURE_SIZE);
+    let (r2, s2, recid2) = from_compact(&bytes).expect("bad encoding");
+    assert_eq!((&r, &s, recid), (&r2, &s2, recid2));
+
+    let mut bad = bytes;
+    bad[64] = 4;
+    assert!(from_compact(&bad).is_err());
+    assert!(from_compact(&bytes[..64]).is_err());
+
+    // the high s form of the same signature is normalized, with the parity of R flipped
+    let q = FE::q();
+    let high = to_compact(&r, &(&q - &s), recid ^ 1).expect("bad signature");
+    assert_eq!(high, bytes);
+
+    assert!(to_compact(&r, &q, recid).is_err());
+    assert!(to_compact(&(BigInt::one() << 256), &s, recid).is_err());
+    assert!(to_compact(&r, &s, 4).is_err());
+}
@@ -0,0 +1,224 @@
+/*
+    Multi-party ECDSA
+
+    Copyright 2018 by Kzen Networks
+
+    This file is part of Multi-party ECDSA library
+    (https://github.com/KZen-networks/multi-party-ecdsa)
+
+    Multi-party ECDSA is free software: you can redistribute
+    it and/or modify it under the terms of the GNU General Public
+    License as published by the Free Software Foundation, either
+    version 3 of the License, or (at your option) any later version.
+
+    @license GPL-3.0+ <https://github.com/KZen-networks/multi-party-ecdsa/blob/master/LICENSE>
+*/
+
+//! ECDSA verification with a single multi-scalar multiplication, and batch verification of
+//! signatures carrying a recovery id.
+//!
+//! A batch of signatures (r_i, s_i) with nonce points R_i, rebuilt from the recovery ids,
+//! is valid iff sum z_i s_i R_i - (sum z_i m_i) G - sum (z_i r_i) Y_i = 0 for random z_i,
+//! except with negligible probability. The terms of signatures under the same key are
+//! merged, so verifying n signatures of one key costs a single MSM of size n + 2.
+
+use std::collections::HashMap;
+
+use curv::arithmetic::traits::*;
+use curv::elliptic::curves::p256::{FE, GE};
+use curv::elliptic::curves::traits::*;
+use curv::BigInt;
+
+use crate::utilities::encoding;
+use crate::utilities::recovery::{self, to_bytes_32};
+use crate::Error::{self, InvalidSig};
+
+#[cfg(test)]
+mod test;
+
+// below this many points interleaved fixed windows beat buckets
+const PIPPENGER_THRESHOLD: usize = 32;
+const STRAUS_WINDOW: usize = 4;
+const SCALAR_BITS: usize = 256;
+
+pub struct BatchItem {
+    pub r: BigInt,
+    pub s: BigInt,
+    pub recid: u8,
+    pub message: BigInt,
+    pub public_key: GE,
+}
+
+/// Computes sum k_i P_i, `None` being the point at infinity.
+pub fn multi_scalar_mul(scalars: &[FE], points: &[GE]) -> Option<GE> {
+    assert_eq!(scalars.len(), points.len());
+    let scalars: Vec<[u8; 32]> = scalars
+        .iter()
+        .map(|k| to_bytes_32(&k.to_big_int()))
+        .collect();
+    if points.len() < PIPPENGER_THRESHOLD {
+        straus(&scalars, points)
+    } else {
+        pippenger(&scalars, points)
+    }
+}
+
+/// Checks that (r, s) is a signature on `message` under `y` by comparing r with the x
+/// coordinate of s^-1 m G + s^-1 r Y.
+pub fn verify(r: &BigInt, s: &BigInt, message: &BigInt, y: &GE) -> Result<(), Error> {
+    let q = FE::q();
+    check_range(r, s)?;
+    let s_fe: FE = ECScalar::from(s);
+    let s_inv = s_fe.invert();
+    let m: FE = ECScalar::from(&message.mod_floor(&q));
+    let r_fe: FE = ECScalar::from(r);
+    let g: GE = ECPoint::generator();
+    match multi_scalar_mul(&[m * s_inv, r_fe * s_inv], &[g, *y]) {
+        Some(R) if &R.x_coor().unwrap().mod_floor(&q) == r => Ok(()),
+        _ => Err(InvalidSig),
+    }
+}
+
+/// Verifies all the signatures at once. An error does not tell which of them is invalid.
+pub fn batch_verify(items: &[BatchItem]) -> Result<(), Error> {
+    let g: GE = ECPoint::generator();
+    let mut scalars = Vec::with_capacity(items.len() + 1);
+    let mut points = Vec::with_capacity(items.len() + 1);
+    let mut g_scalar: FE = ECScalar::zero();
+    let mut keys: Vec<(GE, FE)> = Vec::new();
+    let mut key_index: HashMap<Vec<u8>, usize> = HashMap::new();
+    for item in items {
+        check_range(&item.r, &item.s)?;
+        let R = recovery::recover_r_point(&item.r, item.recid)?;
+        let z: FE = ECScalar::new_random();
+        let s: FE = ECScalar::from(&item.s);
+        let r: FE = ECScalar::from(&item.r);
+        let m: FE = ECScalar::from(&item.message.mod_floor(&FE::q()));
+        scalars.push(z * s);
+        points.push(R);
+        g_scalar = g_scalar + z * m;
+
+        let encoded = encoding::public_key_to_sec1(&item.public_key, true);
+        match key_index.get(&encoded) {
+            Some(i) => keys[*i].1 = keys[*i].1 + z * r,
+            None => {
+                key_index.insert(encoded, keys.len());
+                keys.push((item.public_key, z * r));
+            }
+        }
+    }
+    scalars.push(negate(&g_scalar));
+    points.push(g);
+    for (key, scalar) in keys {
+        scalars.push(negate(&scalar));
+        points.push(key);
+    }
+    match multi_scalar_mul(&scalars, &points) {
+        None => Ok(()),
+        Some(_) => Err(InvalidSig),
+    }
+}
+
+fn check_range(r: &BigInt, s: &BigInt) -> Result<(), Error> {
+    let q = FE::q();
+    let zero = BigInt::zero();
+    if r == &zero || s == &zero || r >= &q || s >= &q {
+        return Err(InvalidSig);
+    }
+    Ok(())
+}
+
+fn negate(x: &FE) -> FE {
+    let q = FE::q();
+    ECScalar::from(&(&q - x.to_big_int()).mod_floor(&q))
+}
+
+// the c bits of `scalar` starting at bit c * window, least significant first
+fn digit(scalar: &[u8; 32], window: usize, c: usize) -> usize {
+    let mut digit = 0;
+    for bit in 0..c {
+        let i = window * c + bit;
+        if i >= SCALAR_BITS {
+            break;
+        }
+        let byte = scalar[31 - i / 8];
+        digit |= (((byte >> (i % 8)) & 1) as usize) << bit;
+    }
+    digit
+}
+
+// curv points cannot represent infinity, which is tracked as `None` instead
+fn add(acc: Option<GE>, point: &GE) -> Option<GE> {
+    match acc {
+        None => Some(*point),
+        Some(acc) if acc.x_coor() == point.x_coor() && acc.y_coor() != point.y_coor() => None,
+        Some(acc) => Some(acc + *point),
+    }
+}
+
+fn double(acc: Option<GE>) -> Option<GE> {
+    acc.map(|acc| acc + acc)
+}
+
+fn straus(scalars: &[[u8; 32]], points: &[GE]) -> Option<GE> {
+    // tables[i][d - 1] = d P_i
+    let tables: Vec<Vec<GE>> = points
+        .iter()
+        .map(|point| {
+            let mut table = vec![*point];
+            for _ in 2..(1 << STRAUS_WINDOW) {
+                let last = *table.last().unwrap();
+                table.push(last + *point);
+            }
+            table
+        })
+        .collect();
+
+    let mut acc = None;
+    for window in (0..SCALAR_BITS / STRAUS_WINDOW).rev() {
+        for _ in 0..STRAUS_WINDOW {
+            acc = double(acc);
+        }
+        for (table, scalar) in tables.iter().zip(scalars) {
+            let d = digit(scalar, window, STRAUS_WINDOW);
+            if d != 0 {
+                acc = add(acc, &table[d - 1]);
+            }
+        }
+    }
+    acc
+}
+
+fn pippenger(scalars: &[[u8; 32]], points: &[GE]) -> Option<GE> {
+    let c = (points.len() as f64).ln().ceil() as usize;
+    let windows = (SCALAR_BITS + c - 1) / c;
+
+    let mut acc = None;
+    for window in (0..windows).rev() {
+        for _ in 0..c {
+            acc = double(acc);
+        }
+        let mut buckets: Vec<Option<GE>> = vec![None; (1 << c) - 1];
+        for (scalar, point) in scalars.iter().zip(points) {
+            let d = digit(scalar, window, c);
+            if d != 0 {
+                buckets[d - 1] = add(buckets[d - 1], point);
+            }
+        }
+        // sum d * bucket_d as a sum of running sums, starting from the highest bucket
+        let mut running = None;
+        let mut sum = None;
+        for bucket in buckets.iter().rev() {
+            if let Some(bucket) = bucket {
+                running = add(running, bucket);
+            }
+            if let Some(running) = running {
+                sum = add(sum, &running);
+            }
+        }
+        if let Some(sum) = sum {
+            acc = add(acc, &sum);
+        }
+    }
+    acc
+}
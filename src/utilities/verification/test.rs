@@ -0,0 +1,90 @@
+#![allow(non_snake_case)]
+
+use curv::arithmetic::traits::*;
+use curv::elliptic::curves::p256::{FE, GE};
+use curv::elliptic::curves::traits::*;
+use curv::BigInt;
+
+use crate::utilities::encoding::normalize_s;
+use crate::utilities::recovery::compute_recid;
+use crate::utilities::verification::*;
+
+fn sign(x: &FE, message: &BigInt) -> BatchItem {
+    let q = FE::q();
+    let k: FE = ECScalar::new_random();
+    let R = GE::generator() * k;
+    let r: FE = ECScalar::from(&R.x_coor().unwrap().mod_floor(&q));
+    let m: FE = ECScalar::from(&message.mod_floor(&q));
+    let s = k.invert() * (m + r * x);
+    let (s, s_was_high) = normalize_s(&s.to_big_int());
+    BatchItem {
+        r: r.to_big_int(),
+        s,
+        recid: compute_recid(&R, s_was_high),
+        message: message.clone(),
+        public_key: GE::generator() * x,
+    }
+}
+
+fn naive_mul(scalars: &[FE], points: &[GE]) -> GE {
+    let mut sum = points[0] * scalars[0];
+    for (k, p) in scalars.iter().zip(points).skip(1) {
+        sum = sum + *p * *k;
+    }
+    sum
+}
+
+#[test]
+fn test_multi_scalar_mul() {
+    // 40 points go through the bucket method
+    for n in &[1, 2, 5, 40] {
+        let scalars: Vec<FE> = (0..*n).map(|_| ECScalar::new_random()).collect();
+        let points: Vec<GE> = (0..*n)
+            .map(|_| {
+                let x: FE = ECScalar::new_random();
+                GE::generator() * x
+            })
+            .collect();
+        assert_eq!(
+            multi_scalar_mul(&scalars, &points),
+            Some(naive_mul(&scalars, &points))
+        );
+    }
+
+    let k: FE = ECScalar::new_random();
+    let minus_k: FE = ECScalar::from(&(FE::q() - k.to_big_int()));
+    let g = GE::generator();
+    assert_eq!(multi_scalar_mul(&[k, minus_k], &[g, g]), None);
+}
+
+#[test]
+fn test_verify() {
+    let x: FE = ECScalar::new_random();
+    let item = sign(&x, &BigInt::from(1234));
+    assert!(verify(&item.r, &item.s, &item.message, &item.public_key).is_ok());
+    assert!(verify(&item.r, &item.s, &BigInt::from(4321), &item.public_key).is_err());
+    assert!(verify(&item.r, &BigInt::zero(), &item.message, &item.public_key).is_err());
+    assert!(verify(&FE::q(), &item.s, &item.message, &item.public_key).is_err());
+}
+
+#[test]
+fn test_batch_verify() {
+    let keys: Vec<FE> = (0..3).map(|_| ECScalar::new_random()).collect();
+    let mut items: Vec<BatchItem> = (0..40)
+        .map(|i| sign(&keys[i % 3], &BigInt::from(i as u32)))
+        .collect();
+    assert!(batch_verify(&items).is_ok());
+    assert!(batch_verify(&[]).is_ok());
+
+    items[7].message = BigInt::from(1000);
+    assert!(batch_verify(&items).is_err());
+    items[7].message = BigInt::from(7);
+
+    // the wrong parity of R
+    items[3].recid ^= 1;
+    assert!(batch_verify(&items).is_err());
+    items[3].recid ^= 1;
+
+    items[5].public_key = items[6].public_key;
+    assert!(batch_verify(&items).is_err());
+}